@@ -0,0 +1,171 @@
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+    time::Instant,
+};
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    HttpMessage,
+};
+use serde_json::json;
+
+use crate::request_id::RequestId;
+
+/// アクセスログを出力するログのターゲット。
+pub const ACCESS_LOG_TARGET: &str = "access_log";
+
+/// アクセスログミドルウェア
+///
+/// リクエストごとに、メソッド、パス、ステータスコード、処理時間(ミリ秒)及びリクエストIDを含む
+/// 1行のJSONをINFOレベルでログに出力する。
+/// リクエストIDは`RequestIdMiddleware`がリクエストに割り当てたものを出力するため、
+/// このミドルウェアは`RequestIdMiddleware`の内側に登録する必要がある。
+pub struct AccessLogMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for AccessLogMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = AccessLogService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AccessLogService {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+/// アクセスログサービス
+pub struct AccessLogService<S> {
+    /// 後続のサービス。
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for AccessLogService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let started_at = Instant::now();
+        let method = req.method().to_string();
+        let path = req.path().to_owned();
+        let request_id = req.extensions().get::<RequestId>().map(|id| id.0.clone());
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            let result = service.call(req).await;
+            let status = match &result {
+                Ok(res) => res.status(),
+                Err(err) => err.as_response_error().status_code(),
+            };
+            let latency = started_at.elapsed().as_secs_f64() * 1000.0;
+            log::info!(
+                target: ACCESS_LOG_TARGET,
+                "{}",
+                json!({
+                    "method": method,
+                    "path": path,
+                    "status": status.as_u16(),
+                    "latencyMs": latency,
+                    "requestId": request_id,
+                })
+            );
+
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod access_log_tests {
+    use std::sync::Mutex;
+
+    use actix_web::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+    use log::{LevelFilter, Log, Metadata, Record};
+
+    use super::*;
+    use crate::request_id::{RequestIdMiddleware, X_REQUEST_ID};
+
+    /// 出力されたアクセスログを記録するロガー
+    struct CaptureLogger {
+        lines: Mutex<Vec<String>>,
+    }
+
+    impl Log for CaptureLogger {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.target() == ACCESS_LOG_TARGET
+        }
+
+        fn log(&self, record: &Record) {
+            if self.enabled(record.metadata()) {
+                self.lines.lock().unwrap().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CaptureLogger = CaptureLogger {
+        lines: Mutex::new(Vec::new()),
+    };
+
+    /// 指定されたリクエストIDのアクセスログを返却する。
+    fn access_log(request_id: &str) -> serde_json::Value {
+        let lines = LOGGER.lines.lock().unwrap();
+        lines
+            .iter()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .find(|log| log["requestId"] == request_id)
+            .unwrap()
+    }
+
+    /// 既知のルートへのリクエストについて、ステータスコードとリクエストIDを含むアクセスログを出力することを確認する。
+    #[actix_web::test]
+    async fn test_access_log() {
+        log::set_logger(&LOGGER).ok();
+        log::set_max_level(LevelFilter::Info);
+        let app = test::init_service(
+            App::new()
+                .wrap(AccessLogMiddleware)
+                .wrap(RequestIdMiddleware)
+                .route("/created", web::post().to(HttpResponse::Created)),
+        )
+        .await;
+        for (method, uri, status) in [
+            ("POST", "/created", StatusCode::CREATED),
+            ("GET", "/unknown", StatusCode::NOT_FOUND),
+        ] {
+            let req = TestRequest::default()
+                .method(method.parse().unwrap())
+                .uri(uri)
+                .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), status);
+            let request_id = res.headers().get(X_REQUEST_ID).unwrap().to_str().unwrap();
+            let log = access_log(request_id);
+            assert_eq!(log["method"], method);
+            assert_eq!(log["path"], uri);
+            assert_eq!(log["status"], status.as_u16());
+            assert!(log["latencyMs"].as_f64().unwrap() >= 0.0);
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use std::{borrow::Cow, fmt};
+
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use serde_json::json;
+
+use usecases::{
+    accounts::{self, BulkInsertError, ImportError},
+    auth,
+};
+
+/// Web APIエラー
+///
+/// ユースケースのエラーを、エラー区分に対応するステータスコードと、`message`を含むJSONのレスポンスに変換する。
+/// ハンドラーは`Result<HttpResponse, AppError>`を返却して、ユースケースのエラーを`?`で伝播する。
+#[derive(Debug)]
+pub enum AppError {
+    /// リクエストが不正
+    BadRequest(Cow<'static, str>),
+    /// 操作が許可されていない
+    Forbidden(Cow<'static, str>),
+    /// アカウントユースケースエラー
+    Account(accounts::Error),
+    /// アカウント一括登録エラー
+    BulkInsert(BulkInsertError),
+    /// アカウントインポートエラー
+    Import(ImportError),
+    /// 認証ユースケースエラー
+    Auth(auth::Error),
+}
+
+impl AppError {
+    /// エラーメッセージを返却する。
+    ///
+    /// # Returns
+    ///
+    /// エラーメッセージ。
+    fn message(&self) -> &str {
+        match self {
+            Self::BadRequest(message) | Self::Forbidden(message) => message,
+            Self::Account(err) => &err.message,
+            Self::BulkInsert(err) => &err.error.message,
+            Self::Import(err) => &err.error.message,
+            Self::Auth(err) => &err.message,
+        }
+    }
+}
+
+/// アカウントユースケースエラー区分に対応するステータスコードを返却する。
+///
+/// # Arguments
+///
+/// * `kind` - アカウントユースケースエラー区分。
+///
+/// # Returns
+///
+/// ステータスコード。
+fn account_status_code(kind: &accounts::ErrorKind) -> StatusCode {
+    use accounts::ErrorKind;
+
+    match kind {
+        ErrorKind::InternalServerError | ErrorKind::TransactionAborted => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+        ErrorKind::NotFound | ErrorKind::PrefectureNotFound => StatusCode::NOT_FOUND,
+        ErrorKind::EmailAddressAlreadyExists | ErrorKind::Conflict => StatusCode::CONFLICT,
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::Account(err) => account_status_code(&err.code),
+            Self::BulkInsert(err) => account_status_code(&err.error.code),
+            Self::Import(err) => account_status_code(&err.error.code),
+            Self::Auth(err) => match err.code {
+                auth::ErrorKind::InternalServerError | auth::ErrorKind::TransactionAborted => {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+                _ => StatusCode::BAD_REQUEST,
+            },
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut body = json!({ "message": self.message() });
+        match self {
+            // 複数のフィールドの検証に失敗した場合は、すべてのフィールドのエラーを返却
+            Self::Account(err) if !err.field_errors.is_empty() => {
+                body["errors"] = json!(err.field_errors);
+            }
+            // 一括登録に失敗したアカウントのインデックスを返却
+            Self::BulkInsert(err) => {
+                body["index"] = json!(err.index);
+            }
+            // インポートに失敗したすべての行のエラーを返却
+            Self::Import(err) if !err.rows.is_empty() => {
+                body["errors"] = json!(err.rows);
+            }
+            _ => {}
+        }
+
+        HttpResponse::build(self.status_code()).json(body)
+    }
+}
+
+impl From<accounts::Error> for AppError {
+    fn from(err: accounts::Error) -> Self {
+        Self::Account(err)
+    }
+}
+
+impl From<BulkInsertError> for AppError {
+    fn from(err: BulkInsertError) -> Self {
+        Self::BulkInsert(err)
+    }
+}
+
+impl From<ImportError> for AppError {
+    fn from(err: ImportError) -> Self {
+        Self::Import(err)
+    }
+}
+
+impl From<auth::Error> for AppError {
+    fn from(err: auth::Error) -> Self {
+        Self::Auth(err)
+    }
+}
+
+#[cfg(test)]
+mod app_error_tests {
+    use actix_web::body::to_bytes;
+
+    use usecases::accounts::{FieldError, ImportRowError};
+
+    use super::*;
+
+    /// エラーのレスポンスのステータスコードとJSONのボディを返却する。
+    async fn response(err: AppError) -> (StatusCode, serde_json::Value) {
+        let res = err.error_response();
+        let status = res.status();
+        let body = to_bytes(res.into_body()).await.unwrap();
+
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn account_error(code: accounts::ErrorKind) -> accounts::Error {
+        accounts::Error {
+            code,
+            message: "message".into(),
+            field_errors: vec![],
+        }
+    }
+
+    /// アカウントユースケースエラー区分に対応するステータスコードと、メッセージを返却することを確認する。
+    #[actix_web::test]
+    async fn test_account_error() {
+        use accounts::ErrorKind;
+
+        for (code, expected) in [
+            (
+                ErrorKind::InternalServerError,
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                ErrorKind::TransactionAborted,
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (ErrorKind::NotFound, StatusCode::NOT_FOUND),
+            (ErrorKind::PrefectureNotFound, StatusCode::NOT_FOUND),
+            (ErrorKind::EmailAddressAlreadyExists, StatusCode::CONFLICT),
+            (ErrorKind::Conflict, StatusCode::CONFLICT),
+            (ErrorKind::WrongPassword, StatusCode::BAD_REQUEST),
+            (ErrorKind::InvalidPostalCode, StatusCode::BAD_REQUEST),
+        ] {
+            let name = format!("{:?}", code);
+            let (status, body) = response(account_error(code).into()).await;
+            assert_eq!(status, expected, "{}", name);
+            assert_eq!(body, json!({ "message": "message" }), "{}", name);
+        }
+    }
+
+    /// フィールドの検証エラーがある場合に、すべてのフィールドのエラーを返却することを確認する。
+    #[actix_web::test]
+    async fn test_account_error_with_field_errors() {
+        let err = accounts::Error {
+            field_errors: vec![FieldError {
+                field: "name".to_owned(),
+                message: "name".to_owned(),
+            }],
+            ..account_error(accounts::ErrorKind::InvalidName)
+        };
+        let (status, body) = response(err.into()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["message"], "message");
+        assert_eq!(body["errors"][0]["field"], "name");
+    }
+
+    /// 一括登録エラーの場合に、登録に失敗したアカウントのインデックスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_bulk_insert_error() {
+        let err = BulkInsertError {
+            index: Some(2),
+            error: account_error(accounts::ErrorKind::EmailAddressAlreadyExists),
+        };
+        let (status, body) = response(err.into()).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body, json!({ "message": "message", "index": 2 }));
+    }
+
+    /// インポートエラーの場合に、インポートに失敗したすべての行のエラーを返却することを確認する。
+    #[actix_web::test]
+    async fn test_import_error() {
+        let err = ImportError {
+            error: account_error(accounts::ErrorKind::InvalidCsv),
+            rows: vec![ImportRowError {
+                line: 3,
+                field: Some("postalCode".to_owned()),
+                message: "message".to_owned(),
+            }],
+        };
+        let (status, body) = response(err.into()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            json!({
+                "message": "message",
+                "errors": [{ "line": 3, "field": "postalCode", "message": "message" }],
+            })
+        );
+    }
+
+    /// 認証ユースケースエラー、不正なリクエスト及び許可されていない操作のステータスコードを確認する。
+    #[actix_web::test]
+    async fn test_auth_error_and_bad_request() {
+        for (err, expected) in [
+            (
+                AppError::from(auth::Error {
+                    code: auth::ErrorKind::InternalServerError,
+                    message: "message".into(),
+                }),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                AppError::from(auth::Error {
+                    code: auth::ErrorKind::InvalidCredential,
+                    message: "message".into(),
+                }),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                AppError::BadRequest("message".into()),
+                StatusCode::BAD_REQUEST,
+            ),
+            (AppError::Forbidden("message".into()), StatusCode::FORBIDDEN),
+        ] {
+            let (status, body) = response(err).await;
+            assert_eq!(status, expected);
+            assert_eq!(body, json!({ "message": "message" }));
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::{future::Future, pin::Pin, str::FromStr};
+
+use actix_web::{dev::Payload, error::InternalError, FromRequest, HttpRequest, HttpResponse};
+use serde_json::json;
+
+use common::jwt_token::Claims;
+use domains::models::accounts::Role;
+
+/// クレイムに管理者の権限が記録されているか確認する。
+///
+/// 権限が記録されていないクレイムは、一般ユーザーのクレイムとして扱う。
+///
+/// # Arguments
+///
+/// * `claims` - アクセストークンのクレイム。
+///
+/// # Returns
+///
+/// 管理者の権限が記録されている場合は`true`。
+pub fn is_admin(claims: &Claims) -> bool {
+    matches!(
+        claims.role.as_deref().map(Role::from_str),
+        Some(Ok(Role::Admin))
+    )
+}
+
+/// 管理者の権限を要求するエクストラクター
+///
+/// アクセストークンのクレイムに管理者の権限が記録されていない場合は、FORBIDDENレスポンスを返却する。
+/// アクセストークンが指定されていない場合や不正な場合は、`Claims`と同様にUNAUTHORIZEDレスポンスを返却する。
+/// 権限が記録されていないトークンは、一般ユーザーのトークンとして扱う。
+pub struct RequireAdmin(pub Claims);
+
+impl FromRequest for RequireAdmin {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let claims = Claims::from_request(req, payload);
+        Box::pin(async move {
+            let claims = claims.await?;
+            if is_admin(&claims) {
+                return Ok(Self(claims));
+            }
+            let message = "この操作には管理者の権限が必要です。";
+            let response = HttpResponse::Forbidden().json(json!({ "message": message }));
+
+            Err(InternalError::from_response(message, response).into())
+        })
+    }
+}
+
+/// テストで使用する、指定された権限を記録したアクセストークンの`Authorization`ヘッダの値を返却する。
+///
+/// # Arguments
+///
+/// * `sub` - アカウントID。
+/// * `role` - 権限。
+///
+/// # Returns
+///
+/// `Bearer`で始まる`Authorization`ヘッダの値。
+#[cfg(test)]
+pub fn bearer_token(sub: &str, role: Role) -> String {
+    let token = common::jwt_token::gen_jwt_token(&Claims {
+        sub: sub.to_owned(),
+        exp: (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp(),
+        role: Some(role.to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+
+    format!("Bearer {}", token)
+}
+
+#[cfg(test)]
+mod authorization_tests {
+    use actix_web::{
+        http::{header, StatusCode},
+        test::{self, TestRequest},
+        web, App,
+    };
+    use chrono::{Duration, Utc};
+    use common::jwt_token::gen_jwt_token;
+
+    use super::*;
+
+    /// 管理者のみが呼び出せるハンドラー。
+    async fn admin_only(admin: RequireAdmin) -> HttpResponse {
+        HttpResponse::Ok().json(json!({ "sub": admin.0.sub }))
+    }
+
+    /// 管理者の権限を記録したトークンのみ受け付けて、一般ユーザーのトークンはFORBIDDENレスポンス、
+    /// トークンが指定されていない場合はUNAUTHORIZEDレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_require_admin() {
+        let app = test::init_service(App::new().route("/admin", web::post().to(admin_only))).await;
+        let token = |role: Option<&str>| {
+            gen_jwt_token(&Claims {
+                sub: "01FVH5SD9H9Y4HNZ4J1Q3E3PFD".to_owned(),
+                exp: (Utc::now() + Duration::hours(1)).timestamp(),
+                role: role.map(str::to_owned),
+                ..Default::default()
+            })
+            .unwrap()
+        };
+        for (token, expected) in [
+            (Some(token(Some("admin"))), StatusCode::OK),
+            (Some(token(Some("user"))), StatusCode::FORBIDDEN),
+            (Some(token(Some("Admin"))), StatusCode::FORBIDDEN),
+            (Some(token(None)), StatusCode::FORBIDDEN),
+            (None, StatusCode::UNAUTHORIZED),
+        ] {
+            let mut req = TestRequest::post().uri("/admin");
+            if let Some(token) = &token {
+                req = req.insert_header((header::AUTHORIZATION, format!("Bearer {}", token)));
+            }
+            let res = test::call_service(&app, req.to_request()).await;
+            assert_eq!(res.status(), expected, "{:?}", token);
+            if expected == StatusCode::FORBIDDEN {
+                let body: serde_json::Value = test::read_body_json(res).await;
+                assert_eq!(body["message"], "この操作には管理者の権限が必要です。");
+            }
+        }
+    }
+}
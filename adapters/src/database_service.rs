@@ -2,12 +2,17 @@ use derive_new::new;
 use sea_orm::{DatabaseConnection, DatabaseTransaction};
 
 use domains::repositories::{
-    accounts::AccountRepository, auth::JwtTokensRepository, common::PrefectureRepository,
+    accounts::{AccountRepository, PasswordHistoryRepository},
+    auth::JwtTokensRepository,
+    common::PrefectureRepository,
+};
+use usecases::{
+    database_service::DatabaseService,
+    queries::{AccountQueryService, PrefectureStatsQueryService},
 };
-use usecases::{database_service::DatabaseService, queries::AccountQueryService};
 
 /// 具象型データベースサービス
-#[derive(Clone, new)]
+#[derive(new)]
 pub struct DatabaseServiceImpl {
     /// データベースコネクション。
     pub conn: DatabaseConnection,
@@ -19,8 +24,8 @@ impl DatabaseService for DatabaseServiceImpl {
     /// # Returns
     ///
     /// データベースコネクション。
-    fn connection(&self) -> DatabaseConnection {
-        self.conn.clone()
+    fn connection(&self) -> &DatabaseConnection {
+        &self.conn
     }
 
     /// 都道府県リポジトリを返却する。
@@ -45,6 +50,42 @@ impl DatabaseService for DatabaseServiceImpl {
         Box::new(PgAccountRepository::new(txn))
     }
 
+    /// トランザクションを開始せずに、データベースコネクションでクエリを実行する都道府県リポジトリを返却する。
+    ///
+    /// # Returns
+    ///
+    /// 都道府県リポジトリ。
+    fn prefecture_reader(&self) -> Box<dyn PrefectureRepository + '_> {
+        use infra::postgres::repositories::prefectures::PgPrefectureRepository;
+
+        Box::new(PgPrefectureRepository::new(&self.conn))
+    }
+
+    /// トランザクションを開始せずに、データベースコネクションでクエリを実行するアカウントリポジトリを返却する。
+    ///
+    /// # Returns
+    ///
+    /// アカウントリポジトリ。
+    fn account_reader(&self) -> Box<dyn AccountRepository + '_> {
+        use infra::postgres::repositories::accounts::PgAccountRepository;
+
+        Box::new(PgAccountRepository::new(&self.conn))
+    }
+
+    /// パスワード履歴リポジトリを返却する。
+    ///
+    /// # Returns
+    ///
+    /// パスワード履歴リポジトリ。
+    fn password_history<'a>(
+        &self,
+        txn: &'a DatabaseTransaction,
+    ) -> Box<dyn PasswordHistoryRepository + 'a> {
+        use infra::postgres::repositories::password_history::PgPasswordHistoryRepository;
+
+        Box::new(PgPasswordHistoryRepository::new(txn))
+    }
+
     /// JWTトークンリポジトリを返却する。
     ///
     /// # Returns
@@ -69,4 +110,753 @@ impl DatabaseService for DatabaseServiceImpl {
 
         Box::new(PgAccountQueryService::new(txn))
     }
+
+    /// 都道府県統計クエリサービスを返却する。
+    ///
+    /// # Returns
+    ///
+    /// 都道府県統計クエリサービス。
+    fn prefecture_stats_service<'a>(
+        &self,
+        txn: &'a DatabaseTransaction,
+    ) -> Box<dyn PrefectureStatsQueryService + 'a> {
+        use infra::postgres::queries::PgPrefectureStatsQueryService;
+
+        Box::new(PgPrefectureStatsQueryService::new(txn))
+    }
+}
+
+/// SQLiteのインメモリデータベースに接続して、テーブルを作成したデータベースサービスを返却する。
+///
+/// インメモリデータベースはコネクションごとに作成されるため、コネクションプールのコネクションは1つのみとする。
+#[cfg(test)]
+pub(crate) async fn sqlite_db_service() -> DatabaseServiceImpl {
+    let mut options = sea_orm::ConnectOptions::new("sqlite::memory:".to_owned());
+    options.max_connections(1).sqlx_logging(false);
+    let conn = sea_orm::Database::connect(options).await.unwrap();
+    let db_service = DatabaseServiceImpl::new(conn);
+    crate::migrations::run_migrations(&db_service)
+        .await
+        .unwrap();
+
+    db_service
+}
+
+/// SQLiteのデータベースに、指定されたEメールアドレスと`TEST_PASSWORD`のパスワードで、
+/// 有効な一般ユーザーのアカウントを登録する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `email` - Eメールアドレス。
+///
+/// # Returns
+///
+/// 登録したアカウント。
+#[cfg(test)]
+pub(crate) async fn insert_sqlite_account(
+    db_service: &DatabaseServiceImpl,
+    email: &str,
+) -> usecases::accounts::AccountDto {
+    use common::hashing::HashingConfig;
+    use domains::services::{clock::SystemClock, hashers::PasswordHasherImpl};
+    use usecases::{accounts::NewAccount, events::NoopAccountEventSink};
+
+    use crate::mock_database_service::TEST_PASSWORD;
+
+    usecases::accounts::insert(
+        db_service,
+        &SystemClock,
+        &PasswordHasherImpl::new(HashingConfig::from_env()),
+        &NoopAccountEventSink,
+        NewAccount {
+            email: email.to_owned(),
+            name: "foo".to_owned(),
+            password: TEST_PASSWORD.to_owned(),
+            is_active: true,
+            fixed_number: None,
+            mobile_number: Some("090-1234-5678".to_owned()),
+            postal_code: "100-0014".to_owned(),
+            prefecture_code: 13,
+            address_details: "千代田区永田町1-7-1".to_owned(),
+        },
+    )
+    .await
+    .unwrap()
+}
+
+/// SQLiteのデータベースに、指定されたアクセストークンとリフレッシュトークンを登録してコミットする。
+///
+/// アクセストークンの有効期限は1時間後、リフレッシュトークンの有効期限は2時間後とする。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `account_id` - トークンを発行したアカウントのアカウントID。
+/// * `access` - アクセストークン。
+/// * `refresh` - リフレッシュトークン。
+///
+/// # Returns
+///
+/// 登録したトークン。
+#[cfg(test)]
+pub(crate) async fn insert_sqlite_tokens(
+    db_service: &DatabaseServiceImpl,
+    account_id: domains::models::accounts::AccountId,
+    access: &str,
+    refresh: &str,
+) -> domains::models::auth::JwtTokens {
+    use chrono::Duration;
+    use domains::models::{
+        auth::{JwtToken, JwtTokenWithExpiredAt, JwtTokens, JwtTokensId},
+        common::local_now,
+    };
+    use sea_orm::TransactionTrait;
+
+    let now = local_now(None);
+    let tokens = JwtTokens::new(
+        JwtTokensId::gen(),
+        account_id,
+        JwtTokenWithExpiredAt {
+            token: JwtToken::new(access).unwrap(),
+            expired_at: now + Duration::hours(1),
+        },
+        JwtTokenWithExpiredAt {
+            token: JwtToken::new(refresh).unwrap(),
+            expired_at: now + Duration::hours(2),
+        },
+    );
+    let txn = db_service.conn.begin().await.unwrap();
+    db_service.jwt_tokens(&txn).insert(&tokens).await.unwrap();
+    txn.commit().await.unwrap();
+
+    tokens
+}
+
+#[cfg(test)]
+mod database_service_impl_tests {
+    use std::sync::Mutex;
+
+    use sea_orm::{
+        ConnectOptions, ConnectionTrait, Database, DbBackend, SqlxSqliteConnector, Statement,
+        TransactionTrait,
+    };
+
+    use chrono::Duration;
+    use common::hashing::HashingConfig;
+    use domains::{
+        models::{
+            accounts::AccountId,
+            auth::{JwtToken, JwtTokenWithExpiredAt, JwtTokens, JwtTokensId},
+            common::{local_now, EmailAddress},
+        },
+        services::{clock::SystemClock, hashers::PasswordHasherImpl},
+    };
+    use usecases::{
+        accounts::{AccountDto, ErrorKind, NewAccount, UpdateAccount},
+        events::{AccountEventSink, NoopAccountEventSink},
+        transaction::{is_retryable_error, is_unique_violation},
+    };
+
+    use super::*;
+    use crate::migrations::run_migrations;
+
+    /// SQLiteのインメモリデータベースで、アカウントを登録、取得、更新及び削除できることを確認する。
+    #[actix_web::test]
+    async fn test_account_crud_on_sqlite() {
+        dotenv::from_filename(".env").ok();
+        let db_service = sqlite_db_service().await;
+        let hasher = PasswordHasherImpl::new(HashingConfig::from_env());
+
+        // 登録
+        let inserted = usecases::accounts::insert(
+            &db_service,
+            &SystemClock,
+            &hasher,
+            &NoopAccountEventSink,
+            NewAccount {
+                email: "foo@example.com".to_owned(),
+                name: "foo".to_owned(),
+                password: "012abcEFG=+".to_owned(),
+                is_active: true,
+                fixed_number: None,
+                mobile_number: Some("090-1234-5678".to_owned()),
+                postal_code: "100-0014".to_owned(),
+                prefecture_code: 13,
+                address_details: "千代田区永田町1-7-1".to_owned(),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(inserted.prefecture_name, "東京都");
+
+        // 取得
+        let found = usecases::accounts::find_by_id(&db_service, inserted.id.clone())
+            .await
+            .unwrap();
+        assert_eq!(found.email.value(), "foo@example.com");
+        assert_eq!(found.created_at, inserted.created_at);
+
+        // 更新
+        let updated = usecases::accounts::update(
+            &db_service,
+            &SystemClock,
+            UpdateAccount {
+                id: inserted.id.clone(),
+                name: "bar".to_owned(),
+                is_active: false,
+                fixed_number: Some("03-3581-5111".to_owned()),
+                mobile_number: None,
+                postal_code: "530-0001".to_owned(),
+                prefecture_code: 27,
+                address_details: "大阪市北区梅田1-1-1".to_owned(),
+                updated_at: found.updated_at,
+            },
+            inserted.id.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.name.value(), "bar");
+        assert!(!updated.is_active);
+        assert_eq!(updated.prefecture_name, "大阪府");
+        assert_eq!(updated.updated_by, Some(inserted.id.clone()));
+
+        // 削除
+        usecases::accounts::delete(
+            &db_service,
+            &hasher,
+            &NoopAccountEventSink,
+            inserted.id.clone(),
+            Some("012abcEFG=+"),
+        )
+        .await
+        .unwrap();
+        let err = usecases::accounts::find_by_id(&db_service, inserted.id)
+            .await
+            .unwrap_err();
+        assert!(matches!(err.code, ErrorKind::NotFound));
+    }
+
+    /// トランザクションを開始せずに、都道府県とアカウントを正しく取得できることを確認する。
+    #[actix_web::test]
+    async fn test_reads_without_transaction_on_sqlite() {
+        dotenv::from_filename(".env").ok();
+        let db_service = sqlite_db_service().await;
+
+        // 都道府県
+        let prefectures = usecases::prefectures::list(&db_service).await.unwrap();
+        assert_eq!(prefectures.len(), 47);
+        assert_eq!(prefectures[0].code(), 1);
+        assert_eq!(prefectures[46].code(), 47);
+        let found = usecases::prefectures::find_by_code(&db_service, 13)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.name(), "東京都");
+        assert!(usecases::prefectures::find_by_code(&db_service, 48)
+            .await
+            .unwrap()
+            .is_none());
+        let found = usecases::prefectures::find_by_codes(&db_service, &[27, 48, 13, 27])
+            .await
+            .unwrap();
+        let names: Vec<Option<String>> =
+            found.iter().map(|p| p.as_ref().map(|p| p.name())).collect();
+        assert_eq!(
+            names,
+            vec![
+                Some("大阪府".to_owned()),
+                None,
+                Some("東京都".to_owned()),
+                Some("大阪府".to_owned()),
+            ]
+        );
+        assert!(usecases::prefectures::find_by_codes(&db_service, &[])
+            .await
+            .unwrap()
+            .is_empty());
+
+        // トランザクションでコミットしたアカウント
+        let inserted = usecases::accounts::insert(
+            &db_service,
+            &SystemClock,
+            &PasswordHasherImpl::new(HashingConfig::from_env()),
+            &NoopAccountEventSink,
+            NewAccount {
+                email: "foo@example.com".to_owned(),
+                name: "foo".to_owned(),
+                password: "012abcEFG=+".to_owned(),
+                is_active: true,
+                fixed_number: None,
+                mobile_number: Some("090-1234-5678".to_owned()),
+                postal_code: "100-0014".to_owned(),
+                prefecture_code: 13,
+                address_details: "千代田区永田町1-7-1".to_owned(),
+            },
+        )
+        .await
+        .unwrap();
+        let found = usecases::accounts::find_by_id(&db_service, inserted.id.clone())
+            .await
+            .unwrap();
+        assert_eq!(found.name.value(), "foo");
+        assert_eq!(found.prefecture_name, "東京都");
+        let found = db_service
+            .account_reader()
+            .find_by_email(EmailAddress::new("foo@example.com").unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id(), inserted.id);
+    }
+
+    /// 同じアカウントIDのアカウントを登録した場合に、一意制約違反のエラーと判定できることを確認する。
+    #[actix_web::test]
+    async fn test_unique_violation_on_sqlite() {
+        dotenv::from_filename(".env").ok();
+        let db_service = sqlite_db_service().await;
+        let inserted = usecases::accounts::insert(
+            &db_service,
+            &SystemClock,
+            &PasswordHasherImpl::new(HashingConfig::from_env()),
+            &NoopAccountEventSink,
+            NewAccount {
+                email: "foo@example.com".to_owned(),
+                name: "foo".to_owned(),
+                password: "012abcEFG=+".to_owned(),
+                is_active: true,
+                fixed_number: None,
+                mobile_number: Some("090-1234-5678".to_owned()),
+                postal_code: "100-0014".to_owned(),
+                prefecture_code: 13,
+                address_details: "千代田区永田町1-7-1".to_owned(),
+            },
+        )
+        .await
+        .unwrap();
+        let account = db_service
+            .account_reader()
+            .find_by_id(inserted.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let txn = db_service.conn.begin().await.unwrap();
+        let err = db_service.account(&txn).insert(&account).await.unwrap_err();
+        txn.rollback().await.unwrap();
+        let err: Box<dyn std::error::Error> = err.into();
+        assert!(is_unique_violation(err.as_ref()), "{}", err);
+        assert!(!is_retryable_error(err.as_ref()), "{}", err);
+    }
+
+    /// Eメールアドレスの大文字と小文字を区別せずにアカウントを検索して、
+    /// 正規化する前に記録された大文字を含むEメールアドレスを正規化できることを確認する。
+    #[actix_web::test]
+    async fn test_email_address_case_on_sqlite() {
+        dotenv::from_filename(".env").ok();
+        let db_service = sqlite_db_service().await;
+        let clock = SystemClock;
+        let hasher = PasswordHasherImpl::new(HashingConfig::from_env());
+        let new_account = |email: &str, name: &str| NewAccount {
+            email: email.to_owned(),
+            name: name.to_owned(),
+            password: "012abcEFG=+".to_owned(),
+            is_active: true,
+            fixed_number: None,
+            mobile_number: Some("090-1234-5678".to_owned()),
+            postal_code: "100-0014".to_owned(),
+            prefecture_code: 13,
+            address_details: "千代田区永田町1-7-1".to_owned(),
+        };
+        let insert = |account: NewAccount| {
+            usecases::accounts::insert(&db_service, &clock, &hasher, &NoopAccountEventSink, account)
+        };
+
+        // 大文字を含むEメールアドレスは、小文字に変換して登録
+        let foo = insert(new_account("Foo@Example.com", "foo")).await.unwrap();
+        assert_eq!(foo.email.value(), "foo@example.com");
+        // 大文字と小文字のみが異なるEメールアドレスは、登録済みとする
+        let err = insert(new_account("FOO@example.COM", "foo2"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err.code, ErrorKind::EmailAddressAlreadyExists));
+
+        // 正規化する前に記録された大文字を含むEメールアドレス
+        let bar = insert(new_account("bar@example.com", "bar")).await.unwrap();
+        db_service
+            .conn
+            .execute_unprepared(&format!(
+                "UPDATE accounts SET email = 'Bar@Example.com' WHERE id = '{}'",
+                bar.id
+            ))
+            .await
+            .unwrap();
+        let found = db_service
+            .account_reader()
+            .find_by_email(EmailAddress::new("bar@example.com").unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id(), bar.id);
+        // 正規化したEメールアドレスが他のアカウントに記録されている場合はスキップ
+        db_service
+            .conn
+            .execute_unprepared(&format!(
+                "UPDATE accounts SET email = 'BAR@example.com' WHERE id = '{}'",
+                foo.id
+            ))
+            .await
+            .unwrap();
+        db_service
+            .conn
+            .execute_unprepared(&format!(
+                "UPDATE accounts SET email = 'bar@example.com' WHERE id = '{}'",
+                bar.id
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            usecases::accounts::normalize_email_addresses(&db_service)
+                .await
+                .unwrap(),
+            0
+        );
+        db_service
+            .conn
+            .execute_unprepared(&format!(
+                "UPDATE accounts SET email = 'Foo@Example.com' WHERE id = '{}'",
+                foo.id
+            ))
+            .await
+            .unwrap();
+        // 不正なEメールアドレスが記録されている場合は、スキップして他のアカウントを正規化
+        let baz = insert(new_account("baz@example.com", "baz")).await.unwrap();
+        db_service
+            .conn
+            .execute_unprepared(&format!(
+                "UPDATE accounts SET email = 'INVALID' WHERE id = '{}'",
+                baz.id
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            usecases::accounts::normalize_email_addresses(&db_service)
+                .await
+                .unwrap(),
+            1
+        );
+        let row = db_service
+            .conn
+            .query_one(Statement::from_string(
+                DbBackend::Sqlite,
+                format!("SELECT email FROM accounts WHERE id = '{}'", foo.id),
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            row.try_get::<String>("", "email").unwrap(),
+            "foo@example.com"
+        );
+        // 繰り返し実行しても更新しない
+        assert_eq!(
+            usecases::accounts::normalize_email_addresses(&db_service)
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    /// アカウントに複数のトークンが関連付けられている場合に、有効期限が切れていない最も新しいトークンを取得することを確認する。
+    #[actix_web::test]
+    async fn test_find_account_by_id_returns_latest_tokens_on_sqlite() {
+        dotenv::from_filename(".env").ok();
+        let db_service = sqlite_db_service().await;
+        let hasher = PasswordHasherImpl::new(HashingConfig::from_env());
+        let inserted = usecases::accounts::insert(
+            &db_service,
+            &SystemClock,
+            &hasher,
+            &NoopAccountEventSink,
+            NewAccount {
+                email: "foo@example.com".to_owned(),
+                name: "foo".to_owned(),
+                password: "012abcEFG=+".to_owned(),
+                is_active: true,
+                fixed_number: None,
+                mobile_number: Some("090-1234-5678".to_owned()),
+                postal_code: "100-0014".to_owned(),
+                prefecture_code: 13,
+                address_details: "千代田区永田町1-7-1".to_owned(),
+            },
+        )
+        .await
+        .unwrap();
+        let account_id = inserted.id.clone();
+        let now = local_now(None);
+        let tokens = |name: &str, access_minutes: i64, refresh_minutes: i64| {
+            JwtTokens::new(
+                JwtTokensId::gen(),
+                account_id.clone(),
+                JwtTokenWithExpiredAt {
+                    token: JwtToken::new(&format!("{}-access", name)).unwrap(),
+                    expired_at: now + Duration::minutes(access_minutes),
+                },
+                JwtTokenWithExpiredAt {
+                    token: JwtToken::new(&format!("{}-refresh", name)).unwrap(),
+                    expired_at: now + Duration::minutes(refresh_minutes),
+                },
+            )
+        };
+        let txn = db_service.conn.begin().await.unwrap();
+        for tokens in [
+            tokens("older", 5, 60),
+            tokens("newer", 10, 65),
+            tokens("expired", -10, -5),
+        ] {
+            db_service.jwt_tokens(&txn).insert(&tokens).await.unwrap();
+        }
+
+        for active in [true, false] {
+            let service = db_service.account_service(&txn);
+            let found = if active {
+                service.find_active_account_by_id(account_id.clone()).await
+            } else {
+                service.find_account_by_id(account_id.clone()).await
+            };
+            let tokens = found.unwrap().unwrap().tokens.unwrap();
+            assert_eq!(tokens.access().token.value(), "newer-access");
+            assert_eq!(tokens.refresh().token.value(), "newer-refresh");
+        }
+        txn.commit().await.unwrap();
+    }
+
+    /// CSVからアカウントをインポートして、いずれかの行に失敗した場合はすべての行を登録しないことを確認する。
+    #[actix_web::test]
+    async fn test_import_csv_on_sqlite() {
+        dotenv::from_filename(".env").ok();
+        let db_service = sqlite_db_service().await;
+        let hasher = PasswordHasherImpl::new(HashingConfig::from_env());
+        let import = |csv: String| {
+            let db_service = &db_service;
+            let hasher = &hasher;
+            async move {
+                usecases::accounts::import_csv(
+                    db_service,
+                    &SystemClock,
+                    hasher,
+                    &NoopAccountEventSink,
+                    csv.as_bytes(),
+                )
+                .await
+            }
+        };
+        let header = "email,name,password,isActive,fixedNumber,mobileNumber,postalCode,prefectureCode,addressDetails";
+        let row = |email: &str, postal_code: &str| {
+            format!(
+                "{},foo,012abcEFG=+,true,,090-1234-5678,{},13,千代田区永田町1-7-1",
+                email, postal_code
+            )
+        };
+        let csv = |rows: Vec<String>| format!("{}\n{}\n", header, rows.join("\n"));
+
+        // すべての行を登録
+        let inserted = import(csv(vec![
+            row("foo@example.com", "100-0014"),
+            row("bar@example.com", "100-0014"),
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(inserted.inserted, 2);
+
+        // 検証に失敗したすべての行の行番号とフィールド名を返却
+        let err = import(csv(vec![
+            row("baz@example.com", "100-0014"),
+            row("qux@example.com", "abc"),
+            row("not-email", "100-0014"),
+        ]))
+        .await
+        .unwrap_err();
+        assert!(matches!(err.error.code, ErrorKind::InvalidCsv));
+        let rows: Vec<_> = err
+            .rows
+            .iter()
+            .map(|row| (row.line, row.field.as_deref()))
+            .collect();
+        assert_eq!(rows, vec![(3, Some("postalCode")), (4, Some("email"))]);
+
+        // 登録済みのEメールアドレスの行がある場合は、他の行も登録しない
+        let err = import(csv(vec![
+            row("baz@example.com", "100-0014"),
+            row("foo@example.com", "100-0014"),
+        ]))
+        .await
+        .unwrap_err();
+        assert_eq!(err.rows[0].line, 3);
+        assert_eq!(err.rows[0].field.as_deref(), Some("email"));
+        let txn = db_service.conn.begin().await.unwrap();
+        let found = db_service
+            .account(&txn)
+            .find_by_email(EmailAddress::new("baz@example.com").unwrap())
+            .await
+            .unwrap();
+        assert!(found.is_none());
+    }
+
+    /// SQLiteのデータベースでマイグレーションを繰り返し実行しても、47都道府県が登録されていることを確認する。
+    #[actix_web::test]
+    async fn test_run_migrations_on_sqlite_is_idempotent() {
+        let db_service = sqlite_db_service().await;
+        run_migrations(&db_service).await.unwrap();
+        let txn = db_service.conn.begin().await.unwrap();
+        let prefectures = db_service.prefecture(&txn).list().await.unwrap();
+        assert_eq!(prefectures.len(), 47);
+    }
+
+    /// アカウントイベント
+    #[derive(Debug, PartialEq)]
+    enum AccountEvent {
+        /// 登録されたアカウントのアカウントIDと、通知されたときに登録がコミットされていたか。
+        Created(AccountId, bool),
+        /// 削除されたアカウントのアカウントIDと、通知されたときに削除がコミットされていたか。
+        Deleted(AccountId, bool),
+    }
+
+    /// 通知されたイベントと、通知されたときにコミットされていた状態を記録するアカウントイベントシンク
+    ///
+    /// コネクションプールのコネクションは1つのみであるため、トランザクションをコミットする前に
+    /// 通知された場合は、コネクションを取得できずにアカウントを確認できない。
+    struct RecordingAccountEventSink {
+        db_service: DatabaseServiceImpl,
+        events: Mutex<Vec<AccountEvent>>,
+    }
+
+    impl RecordingAccountEventSink {
+        fn new(conn: DatabaseConnection) -> Self {
+            Self {
+                db_service: DatabaseServiceImpl::new(conn),
+                events: Mutex::new(vec![]),
+            }
+        }
+
+        /// トランザクションを開始せずに、アカウントが存在するか確認する。
+        ///
+        /// # Returns
+        ///
+        /// アカウントが存在する場合は`Some(true)`、存在しない場合は`Some(false)`、
+        /// コネクションを取得できなかった場合は`None`。
+        fn account_exists(&self, id: &AccountId) -> Option<bool> {
+            tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current().block_on(async {
+                    self.db_service
+                        .account_reader()
+                        .find_by_id(id.clone())
+                        .await
+                        .ok()
+                        .map(|account| account.is_some())
+                })
+            })
+        }
+
+        fn events(&self) -> Vec<AccountEvent> {
+            std::mem::take(&mut *self.events.lock().unwrap())
+        }
+    }
+
+    impl AccountEventSink for RecordingAccountEventSink {
+        fn on_account_created(&self, account: &AccountDto) {
+            let committed = self.account_exists(&account.id) == Some(true);
+            self.events
+                .lock()
+                .unwrap()
+                .push(AccountEvent::Created(account.id.clone(), committed));
+        }
+
+        fn on_account_deleted(&self, id: &AccountId) {
+            let committed = self.account_exists(id) == Some(false);
+            self.events
+                .lock()
+                .unwrap()
+                .push(AccountEvent::Deleted(id.clone(), committed));
+        }
+    }
+
+    /// アカウントの登録と削除のイベントが、トランザクションをコミットした後にのみ通知され、
+    /// トランザクションをロールバックした場合は通知されないことを確認する。
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_account_events_are_notified_after_commit_on_sqlite() {
+        dotenv::from_filename(".env").ok();
+        let mut options = ConnectOptions::new("sqlite::memory:".to_owned());
+        options
+            .max_connections(1)
+            .acquire_timeout(std::time::Duration::from_secs(1))
+            .sqlx_logging(false);
+        let db_service = DatabaseServiceImpl::new(Database::connect(options).await.unwrap());
+        run_migrations(&db_service).await.unwrap();
+        let hasher = PasswordHasherImpl::new(HashingConfig::from_env());
+        // 同じコネクションプールを使用して、イベントシンクがアカウントを確認できるようにする
+        let pool = db_service.conn.get_sqlite_connection_pool().clone();
+        let sink = RecordingAccountEventSink::new(SqlxSqliteConnector::from_sqlx_sqlite_pool(pool));
+        let new_account = |prefecture_code: u8| NewAccount {
+            email: "foo@example.com".to_owned(),
+            name: "foo".to_owned(),
+            password: "012abcEFG=+".to_owned(),
+            is_active: true,
+            fixed_number: None,
+            mobile_number: Some("090-1234-5678".to_owned()),
+            postal_code: "100-0014".to_owned(),
+            prefecture_code,
+            address_details: "千代田区永田町1-7-1".to_owned(),
+        };
+
+        // 存在しない都道府県を指定して、登録をロールバック
+        let err =
+            usecases::accounts::insert(&db_service, &SystemClock, &hasher, &sink, new_account(48))
+                .await
+                .unwrap_err();
+        assert!(
+            matches!(err.code, ErrorKind::PrefectureNotFound),
+            "{:?}",
+            err
+        );
+        assert!(sink.events().is_empty());
+
+        // 登録をコミット
+        let inserted =
+            usecases::accounts::insert(&db_service, &SystemClock, &hasher, &sink, new_account(13))
+                .await
+                .unwrap();
+        assert_eq!(
+            sink.events(),
+            vec![AccountEvent::Created(inserted.id.clone(), true)]
+        );
+
+        // パスワードが間違っているため、削除をロールバック
+        let err = usecases::accounts::delete(
+            &db_service,
+            &hasher,
+            &sink,
+            inserted.id.clone(),
+            Some("wrong-password"),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err.code, ErrorKind::WrongPassword), "{:?}", err);
+        assert!(sink.events().is_empty());
+        assert_eq!(sink.account_exists(&inserted.id), Some(true));
+
+        // 削除をコミット
+        usecases::accounts::delete(
+            &db_service,
+            &hasher,
+            &sink,
+            inserted.id.clone(),
+            Some("012abcEFG=+"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            sink.events(),
+            vec![AccountEvent::Deleted(inserted.id, true)]
+        );
+    }
 }
@@ -1,11 +1,33 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{
+    http::header::{self, EntityTag, IfNoneMatch},
+    web, HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use futures_util::stream;
 use serde_json::json;
 
 use common::jwt_token::Claims;
-use domains::models::accounts::AccountId;
+use domains::{
+    models::{accounts::AccountId, common::EmailAddress},
+    services::{clock::Clock, hashers::PasswordHasher},
+};
 use usecases::{
-    accounts::{ChangePassword, ErrorKind, NewAccount, UpdateAccount},
+    accounts::{
+        AccountDto, AccountSessionsDto, BulkInsertedAccounts, ChangePassword, ChangeRole,
+        ConfirmEmailChange, DeleteAccount, EmailChangeToken, ErrorKind, ImportRowError, NewAccount,
+        RequestEmailChange, ResetPassword, UpdateAccount,
+    },
     database_service::DatabaseService,
+    events::AccountEventSink,
+    queries::{AccountSearch, EmailAddressSearch, Page, Pagination},
+};
+
+use crate::{
+    app_error::AppError,
+    authorization::{is_admin, RequireAdmin},
+    idempotency::{
+        fingerprint, idempotency_key, IdempotencyGuard, IdempotencyStatus, IdempotencyStore,
+    },
+    openapi::ErrorMessage,
 };
 
 /// アカウントIDを検証する。
@@ -19,170 +41,935 @@ use usecases::{
 /// `Result`。返却される`Result`の内容は以下の通り。
 ///
 /// * `Ok`: アカウントID。
-/// * `Err`: BAD_REQUESTレスポンス。
-fn validate_account_id(id: &str) -> Result<AccountId, HttpResponse> {
-    let account_id = AccountId::try_from(id);
-    if account_id.is_err() {
-        return Err(HttpResponse::BadRequest().json(json!({
-            "message":
-                format!(
-                    "URLで指定されたアカウントID({})が、ULIDの書式と異なります。",
-                    id
-                )
-        })));
+/// * `Err`: 不正なリクエストを示すエラー。
+fn validate_account_id(id: &str) -> Result<AccountId, AppError> {
+    AccountId::try_from(id).map_err(|_| {
+        AppError::BadRequest(
+            format!(
+                "URLで指定されたアカウントID({})が、ULIDの書式と異なります。",
+                id
+            )
+            .into(),
+        )
+    })
+}
+
+/// アクセストークンのクレイムから、操作するアカウントのアカウントIDを取得する。
+///
+/// # Arguments
+///
+/// * `claims` - アクセストークンのクレイム。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 操作するアカウントのアカウントID。
+/// * `Err`: クレイムに記録されたアカウントIDが、ULIDの書式と異なる場合のエラー。
+fn acting_account_id(claims: &Claims) -> Result<AccountId, AppError> {
+    AccountId::try_from(claims.sub.as_str()).map_err(|_| {
+        AppError::BadRequest(
+            format!(
+                "JWTトークンに指定されたアカウントID({})が、ULIDの書式と異なります。",
+                claims.sub
+            )
+            .into(),
+        )
+    })
+}
+
+/// アカウントの弱いETagを生成する。
+///
+/// アカウントの更新日時から生成するため、アカウントが更新されるとETagも変わる。
+///
+/// # Arguments
+///
+/// * `account` - アカウント。
+///
+/// # Returns
+///
+/// 弱いETag。
+fn account_etag(account: &AccountDto) -> EntityTag {
+    EntityTag::new_weak(format!("{:x}", account.updated_at.timestamp_micros()))
+}
+
+/// アカウント検索APIのレスポンスを生成する。
+///
+/// `If-None-Match`ヘッダに指定されたETagとアカウントのETagが一致する場合は、NOT_MODIFIEDレスポンスを返却する。
+///
+/// # Arguments
+///
+/// * `account` - アカウント。
+/// * `if_none_match` - リクエストの`If-None-Match`ヘッダ。
+///
+/// # Returns
+///
+/// `ETag`ヘッダを設定したOKまたはNOT_MODIFIEDレスポンス。
+fn find_by_id_response(account: &AccountDto, if_none_match: Option<&IfNoneMatch>) -> HttpResponse {
+    let etag = account_etag(account);
+    let not_modified = match if_none_match {
+        Some(IfNoneMatch::Any) => true,
+        Some(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&etag)),
+        None => false,
+    };
+    if not_modified {
+        HttpResponse::NotModified()
+            .insert_header(header::ETag(etag))
+            .finish()
+    } else {
+        HttpResponse::Ok()
+            .insert_header(header::ETag(etag))
+            .json(account)
     }
+}
 
-    Ok(account_id.unwrap())
+/// アカウント一覧API。
+///
+/// 検索条件に一致するアカウントを、登録日時順にページ単位でJSONで返却する。
+/// `q`を指定した場合は、アカウント名またはEメールアドレスに`q`を含む(大文字と小文字を区別しない)アカウントを返却する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `search` - 検索条件。
+/// * `pagination` - ページ指定。
+/// * `_admin` - 管理者のクレイム。管理者のみアカウントを検索できる。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    get,
+    path = "/accounts",
+    tag = "accounts",
+    params(AccountSearch, Pagination),
+    responses(
+        (status = 200, description = "アカウントのページ", body = Page<AccountDto>),
+        (status = 400, description = "検索条件またはページ指定が不正", body = ErrorMessage),
+        (status = 401, description = "認証されていない"),
+        (status = 403, description = "管理者ではない", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn search(
+    db_service: web::Data<dyn DatabaseService>,
+    search: Result<web::Query<AccountSearch>, actix_web::Error>,
+    pagination: web::Query<Pagination>,
+    _admin: RequireAdmin,
+) -> impl Responder {
+    let search = match search {
+        Ok(search) => search.into_inner(),
+        Err(_) => {
+            return HttpResponse::BadRequest().json(json!({
+                "message": "都道府県コード(prefectureCode)は数値、アクティブフラグ(isActive)はtrueまたはfalseで指定してください。"
+            }))
+        }
+    };
+    let pagination = pagination.into_inner();
+    if let Err(err) = pagination.validate() {
+        return HttpResponse::BadRequest().json(json!({ "message": format!("{}", err) }));
+    }
+    match usecases::accounts::search(db_service.as_ref(), search, pagination).await {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(err) => {
+            HttpResponse::InternalServerError().json(json!({ "message": format!("{}", err) }))
+        }
+    }
 }
 
 /// アカウント検索API。
 ///
 /// 指定されたアカウントIDと一致するアカウントをJSONで返却する。
+/// レスポンスにはアカウントの更新日時から生成した弱いETagを設定して、
+/// `If-None-Match`ヘッダに指定されたETagと一致する場合は、ボディを含まないNOT_MODIFIEDレスポンスを返却する。
 ///
 /// # Arguments
 ///
 /// * `db_service` - データベースサービス。
 /// * `path` - 引数で指定されたデータを格納するタプル。
+/// * `if_none_match` - リクエストの`If-None-Match`ヘッダ。
 ///
 /// # Returns
 ///
 /// レスポンス。
+#[utoipa::path(
+    get,
+    path = "/accounts/{id}",
+    tag = "accounts",
+    params(
+        ("id" = String, Path, description = "アカウントID"),
+        ("If-None-Match" = Option<String>, Header, description = "以前のレスポンスで返却されたETag"),
+    ),
+    responses(
+        (status = 200, description = "アカウント", body = AccountDto,
+            headers(("ETag" = String, description = "アカウントの更新日時から生成した弱いETag"))),
+        (status = 304, description = "アカウントが更新されていない"),
+        (status = 400, description = "アカウントIDが不正", body = ErrorMessage),
+        (status = 404, description = "アカウントが見つからない", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    )
+)]
 pub async fn find_by_id(
     db_service: web::Data<dyn DatabaseService>,
     path: web::Path<(String,)>,
-) -> impl Responder {
+    if_none_match: Option<web::Header<IfNoneMatch>>,
+) -> Result<HttpResponse, AppError> {
     // アカウントIDを検証
-    let result = validate_account_id(&path.into_inner().0);
-    if let Err(err) = result {
-        return err;
-    }
-    let account_id = result.unwrap();
+    let account_id = validate_account_id(&path.into_inner().0)?;
     // アカウントの取得を試行
-    match usecases::accounts::find_by_id(db_service.as_ref(), account_id).await {
-        Ok(account) => HttpResponse::Ok().json(account),
-        Err(err) => {
-            let mut response = match err.code {
-                ErrorKind::InternalServerError => HttpResponse::InternalServerError(),
-                ErrorKind::NotFound => HttpResponse::NotFound(),
-                _ => HttpResponse::BadRequest(),
-            };
-            response.json(json!({"message": err.message }))
-        }
+    let account = usecases::accounts::find_by_id(db_service.as_ref(), account_id).await?;
+
+    Ok(find_by_id_response(&account, if_none_match.as_deref()))
+}
+
+/// Eメールアドレスによるアカウント検索API。
+///
+/// 指定されたEメールアドレスと一致するアカウントをJSONで返却する。
+/// 管理者はすべてのアカウントを検索できるが、管理者以外はアカウントの存在を知られないように、
+/// 自分のEメールアドレスのみ検索できる。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `search` - 検索条件。
+/// * `claims` - アクセストークンのクレイム。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    get,
+    path = "/accounts/search",
+    tag = "accounts",
+    params(EmailAddressSearch),
+    responses(
+        (status = 200, description = "アカウント", body = AccountDto),
+        (status = 400, description = "Eメールアドレスが不正", body = ErrorMessage),
+        (status = 401, description = "認証されていない"),
+        (status = 403, description = "管理者ではなく、自分のEメールアドレスでもない", body = ErrorMessage),
+        (status = 404, description = "アカウントが見つからない", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn find_by_email(
+    db_service: web::Data<dyn DatabaseService>,
+    search: web::Query<EmailAddressSearch>,
+    claims: Claims,
+) -> Result<HttpResponse, AppError> {
+    let email = search.into_inner().email;
+    if is_admin(&claims) {
+        let account = usecases::accounts::find_by_email(db_service.as_ref(), &email).await?;
+        return Ok(HttpResponse::Ok().json(account));
     }
+    // 管理者以外は、他のアカウントを検索せずに、自分のEメールアドレスと一致するか確認
+    let email = EmailAddress::normalized(&email, usecases::accounts::email_normalization())
+        .map_err(|err| AppError::BadRequest(format!("{}", err).into()))?;
+    let account_id = acting_account_id(&claims)?;
+    let account = usecases::accounts::find_by_id(db_service.as_ref(), account_id).await?;
+    if account.email != email {
+        return Err(AppError::Forbidden(
+            "管理者以外は、自分のEメールアドレスのみ検索できます。".into(),
+        ));
+    }
+
+    Ok(HttpResponse::Ok().json(account))
+}
+
+/// アカウントセッション取得API。
+///
+/// URLで指定されたアカウントと、アカウントに発行した最新のトークンのIDと有効期限をJSONで返却する。
+/// トークンの文字列は返却しない。
+/// アカウント本人または管理者のみ呼び出せて、管理者は非アクティブなアカウントも取得できる。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `path` - アカウントIDを格納したタプル。
+/// * `claims` - アクセストークンのクレイム。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    get,
+    path = "/accounts/{id}/sessions",
+    tag = "accounts",
+    params(("id" = String, Path, description = "アカウントID")),
+    responses(
+        (status = 200, description = "アカウントとセッション", body = AccountSessionsDto),
+        (status = 400, description = "アカウントIDが不正", body = ErrorMessage),
+        (status = 401, description = "認証されていない"),
+        (status = 403, description = "アカウント本人または管理者ではない", body = ErrorMessage),
+        (status = 404, description = "アカウントが見つからない", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn sessions(
+    db_service: web::Data<dyn DatabaseService>,
+    path: web::Path<(String,)>,
+    claims: Claims,
+) -> Result<HttpResponse, AppError> {
+    // アカウントIDを検証
+    let account_id = validate_account_id(&path.into_inner().0)?;
+    // アカウント本人または管理者であることを確認
+    let admin = is_admin(&claims);
+    if !admin && account_id.to_string() != claims.sub {
+        return Err(AppError::Forbidden(
+            "アカウント本人または管理者のみ、セッションを取得できます。".into(),
+        ));
+    }
+    // アカウントとセッションの取得を試行
+    let sessions =
+        usecases::accounts::find_with_tokens(db_service.as_ref(), account_id, admin).await?;
+
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+/// アカウントを登録したときのレスポンスを生成する。
+///
+/// レスポンスの`Location`ヘッダに、登録したアカウントのURLを設定する。
+///
+/// # Arguments
+///
+/// * `account` - 登録したアカウント。
+///
+/// # Returns
+///
+/// CREATEDレスポンス。
+fn created_response(account: &AccountDto) -> HttpResponse {
+    HttpResponse::Created()
+        .insert_header((header::LOCATION, format!("/accounts/{}", account.id)))
+        .json(account)
 }
 
 /// アカウント登録API
 ///
+/// `Idempotency-Key`ヘッダが指定された場合は、同じ冪等キーで再送されたリクエストでアカウントを重複して登録せずに、
+/// 最初に登録したアカウントのレスポンスを返却する。
+///
 /// # Arguments
 ///
+/// * `req` - リクエスト。
 /// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `event_sink` - アカウントイベントシンク。
+/// * `idempotency` - 冪等キーストア。
 /// * `new_account` - 登録するアカウント。
 ///
 /// # Returns
 ///
 /// レスポンス。
+#[utoipa::path(
+    post,
+    path = "/accounts",
+    tag = "accounts",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "再送されたリクエストでアカウントを重複して登録しないための冪等キー"),
+    ),
+    request_body = NewAccount,
+    responses(
+        (status = 201, description = "登録したアカウント", body = AccountDto,
+            headers(("Location" = String, description = "登録したアカウントのURL"))),
+        (status = 400, description = "リクエストボディまたは冪等キーが不正", body = ErrorMessage),
+        (status = 404, description = "都道府県が見つからない", body = ErrorMessage),
+        (status = 409, description = "Eメールアドレスが既に登録されている、または同じ冪等キーのリクエストを処理中", body = ErrorMessage),
+        (status = 422, description = "リクエストボディのフィールドが不足しているか、型が不正、または同じ冪等キーで異なるリクエストボディが指定された"),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    )
+)]
 pub async fn insert(
+    req: HttpRequest,
     db_service: web::Data<dyn DatabaseService>,
+    clock: web::Data<dyn Clock>,
+    hasher: web::Data<dyn PasswordHasher>,
+    event_sink: web::Data<dyn AccountEventSink>,
+    idempotency: web::Data<IdempotencyStore>,
     new_account: web::Json<NewAccount>,
-) -> impl Responder {
-    // アカウントの登録を試行
-    match usecases::accounts::insert(db_service.as_ref(), new_account.into_inner()).await {
-        Ok(account) => HttpResponse::Created().json(account),
-        Err(err) => {
-            let mut response = match err.code {
-                ErrorKind::InternalServerError => HttpResponse::InternalServerError(),
-                ErrorKind::PrefectureNotFound => HttpResponse::NotFound(),
-                _ => HttpResponse::BadRequest(),
-            };
-            response.json(json!({"message": err.message}))
+) -> Result<HttpResponse, AppError> {
+    let new_account = new_account.into_inner();
+    // 冪等キーが指定された場合は、同じ冪等キーで受け付けたリクエストを確認
+    let key = match idempotency_key(&req) {
+        Ok(key) => key,
+        Err(response) => return Ok(response),
+    };
+    let mut guard = None;
+    if let Some(key) = key {
+        match idempotency.begin(&key, fingerprint(&new_account)) {
+            IdempotencyStatus::New => guard = Some(IdempotencyGuard::new(idempotency.clone(), key)),
+            IdempotencyStatus::Replay(account) => return Ok(created_response(&account)),
+            IdempotencyStatus::Mismatch => {
+                return Ok(HttpResponse::UnprocessableEntity().json(json!({
+                    "message":
+                        format!(
+                            "冪等キー({})は、異なるリクエストボディのリクエストで使用されています。",
+                            key
+                        )
+                })))
+            }
+            IdempotencyStatus::InProgress => {
+                return Ok(HttpResponse::Conflict().json(json!({
+                    "message": format!("冪等キー({})のリクエストを処理中です。", key)
+                })))
+            }
         }
     }
+    // アカウントの登録を試行して、失敗した場合は同じ冪等キーで再試行できるように、ガードのドロップで冪等キーの記録を破棄
+    let account = usecases::accounts::insert(
+        db_service.as_ref(),
+        clock.as_ref(),
+        hasher.as_ref(),
+        event_sink.as_ref(),
+        new_account,
+    )
+    .await?;
+    if let Some(guard) = guard {
+        guard.complete(&account);
+    }
+
+    Ok(created_response(&account))
+}
+
+/// アカウント一括登録API
+///
+/// JSON配列で指定された複数のアカウントを、単一のトランザクションで一括登録する。
+/// いずれかのアカウントの登録に失敗した場合は、すべてのアカウントを登録せずに、
+/// 登録に失敗したアカウントのインデックスを返却する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `event_sink` - アカウントイベントシンク。
+/// * `new_accounts` - 登録するアカウントのリスト。
+/// * `_admin` - 管理者のクレイム。管理者のみアカウントを一括登録できる。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    post,
+    path = "/accounts/bulk",
+    tag = "accounts",
+    request_body = Vec<NewAccount>,
+    responses(
+        (status = 201, description = "登録したアカウントの数とアカウントIDのリスト", body = BulkInsertedAccounts),
+        (status = 400, description = "リクエストボディが不正", body = ErrorMessage),
+        (status = 401, description = "認証されていない"),
+        (status = 403, description = "管理者ではない", body = ErrorMessage),
+        (status = 404, description = "都道府県が見つからない", body = ErrorMessage),
+        (status = 409, description = "Eメールアドレスが既に登録されている", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn bulk_insert(
+    db_service: web::Data<dyn DatabaseService>,
+    clock: web::Data<dyn Clock>,
+    hasher: web::Data<dyn PasswordHasher>,
+    event_sink: web::Data<dyn AccountEventSink>,
+    new_accounts: web::Json<Vec<NewAccount>>,
+    _admin: RequireAdmin,
+) -> Result<HttpResponse, AppError> {
+    // アカウントの一括登録を試行
+    let inserted = usecases::accounts::bulk_insert(
+        db_service.as_ref(),
+        clock.as_ref(),
+        hasher.as_ref(),
+        event_sink.as_ref(),
+        new_accounts.into_inner(),
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(inserted))
+}
+
+/// アカウントインポートAPI
+///
+/// CSVのすべての行のアカウントを単一のトランザクションで登録する。
+/// いずれかの行の検証または登録に失敗した場合は、すべてのアカウントを登録せずに、
+/// エラーが発生したすべての行の行番号、フィールド名及びエラーメッセージを返却する。
+///
+/// # Arguments
+///
+/// * `request` - HTTPリクエスト。
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `event_sink` - アカウントイベントシンク。
+/// * `body` - インポートするCSV。
+/// * `_admin` - 管理者のクレイム。管理者のみアカウントをインポートできる。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    post,
+    path = "/accounts/import",
+    tag = "accounts",
+    request_body(content = String, content_type = "text/csv", description = "1行目に新規アカウントのフィールド名を記述したCSV"),
+    responses(
+        (status = 201, description = "登録したアカウントの数とアカウントIDのリスト", body = BulkInsertedAccounts),
+        (status = 400, description = "CSVが不正、またはエラーが発生した行のリスト", body = Vec<ImportRowError>),
+        (status = 401, description = "認証されていない"),
+        (status = 403, description = "管理者ではない", body = ErrorMessage),
+        (status = 415, description = "Content-Typeがtext/csvではない", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn import(
+    request: HttpRequest,
+    db_service: web::Data<dyn DatabaseService>,
+    clock: web::Data<dyn Clock>,
+    hasher: web::Data<dyn PasswordHasher>,
+    event_sink: web::Data<dyn AccountEventSink>,
+    body: web::Bytes,
+    _admin: RequireAdmin,
+) -> Result<HttpResponse, AppError> {
+    if request.content_type() != "text/csv" {
+        return Ok(HttpResponse::UnsupportedMediaType()
+            .json(json!({ "message": "Content-Typeにtext/csvを指定してください。" })));
+    }
+    // アカウントのインポートを試行
+    let inserted = usecases::accounts::import_csv(
+        db_service.as_ref(),
+        clock.as_ref(),
+        hasher.as_ref(),
+        event_sink.as_ref(),
+        &body,
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(inserted))
+}
+
+/// アカウントエクスポートAPIで、1回のクエリで取得するアカウントの数。
+const EXPORT_CHUNK_SIZE: u64 = 500;
+
+/// アカウントエクスポートAPIのストリームの状態
+enum ExportState {
+    /// ヘッダ行を出力していない
+    Header,
+    /// 指定されたアカウントIDより後のアカウントを出力する
+    After(Option<AccountId>),
+    /// すべてのアカウントを出力した
+    Done,
+}
+
+/// アカウントエクスポートAPI
+///
+/// すべてのアカウントをCSVで返却する。
+/// アカウントを分割して取得しながらレスポンスボディに書き込むため、アカウントの数に関わらずメモリ使用量は一定である。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `_admin` - 管理者のクレイム。管理者のみアカウントをエクスポートできる。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    get,
+    path = "/accounts/export",
+    tag = "accounts",
+    responses(
+        (status = 200, description = "すべてのアカウントのCSV", content_type = "text/csv", body = String),
+        (status = 401, description = "認証されていない"),
+        (status = 403, description = "管理者ではない", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn export(
+    db_service: web::Data<dyn DatabaseService>,
+    _admin: RequireAdmin,
+) -> HttpResponse {
+    let body = stream::unfold(ExportState::Header, move |state| {
+        let db_service = db_service.clone();
+        async move {
+            let (accounts, with_header, after) = match state {
+                ExportState::Done => return None,
+                ExportState::Header => (vec![], true, None),
+                ExportState::After(after) => {
+                    let accounts = usecases::accounts::list_paged(
+                        db_service.as_ref(),
+                        after,
+                        EXPORT_CHUNK_SIZE,
+                    )
+                    .await;
+                    match accounts {
+                        // アカウントを取得できなくなるまで、アカウントを取得
+                        Ok(accounts) if accounts.is_empty() => return None,
+                        Ok(accounts) => {
+                            let after = accounts.last().map(|a| a.id.clone());
+                            (accounts, false, after)
+                        }
+                        // レスポンスヘッダを送信した後のため、レスポンスを中断
+                        Err(err) => {
+                            return Some((Err(AppError::from(err).into()), ExportState::Done));
+                        }
+                    }
+                }
+            };
+            let chunk = usecases::accounts::accounts_to_csv(&accounts, with_header)
+                .map(web::Bytes::from)
+                .map_err(|err| actix_web::Error::from(AppError::from(err)));
+            Some((chunk, ExportState::After(after)))
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header(header::ContentDisposition {
+            disposition: header::DispositionType::Attachment,
+            parameters: vec![header::DispositionParam::Filename(
+                "accounts.csv".to_owned(),
+            )],
+        })
+        .streaming(body)
 }
 
 /// アカウント更新API
 ///
+/// アカウント本人または管理者のみ呼び出せる。
+/// アクセストークンのアカウントを、アカウントを更新したアカウントとして記録する。
+/// アカウントを無効化した場合は、アカウント無効化APIと同様にアカウントのトークンを削除する。
+///
 /// # Arguments
 ///
 /// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
 /// * `update_account` - 更新するアカウント。
+/// * `claims` - アクセストークンのクレイム。
 ///
 /// # Returns
 ///
 /// レスポンス。
+#[utoipa::path(
+    put,
+    path = "/accounts/{id}",
+    tag = "accounts",
+    params(("id" = String, Path, description = "アカウントID")),
+    request_body = UpdateAccount,
+    responses(
+        (status = 200, description = "更新後のアカウント", body = AccountDto),
+        (status = 400, description = "リクエストボディが不正", body = ErrorMessage),
+        (status = 401, description = "認証されていない"),
+        (status = 403, description = "アカウント本人または管理者ではない", body = ErrorMessage),
+        (status = 404, description = "アカウントまたは都道府県が見つからない", body = ErrorMessage),
+        (status = 409, description = "アカウントが他のリクエストで更新されている", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn update(
     db_service: web::Data<dyn DatabaseService>,
+    clock: web::Data<dyn Clock>,
     path: web::Path<(String,)>,
     update_account: web::Json<UpdateAccount>,
-) -> impl Responder {
+    claims: Claims,
+) -> Result<HttpResponse, AppError> {
     // アカウントIDを検証
-    let result = validate_account_id(&path.into_inner().0);
-    if let Err(err) = result {
-        return err;
-    }
-    let account_id = result.unwrap();
+    let account_id = validate_account_id(&path.into_inner().0)?;
     // 更新するアカウントアカウントIDを検証
-    if account_id.value.to_string() != update_account.id {
-        return HttpResponse::BadRequest().json(json!({
-            "message":
-                format!(
-                    "URLで指定されたアカウントID({})とリクエストボディに指定されたアカウントID({})が異なります。",
-                    account_id.value, update_account.id,
-                )
-        }));
+    if account_id != update_account.id {
+        return Err(AppError::BadRequest(
+            format!(
+                "URLで指定されたアカウントID({})とリクエストボディに指定されたアカウントID({})が異なります。",
+                account_id, update_account.id,
+            )
+            .into(),
+        ));
     }
-    // アカウントの更新を試行
-    match usecases::accounts::update(db_service.as_ref(), update_account.into_inner()).await {
-        Ok(account) => HttpResponse::Ok().json(account),
-        Err(err) => {
-            let mut response = match err.code {
-                ErrorKind::InternalServerError => HttpResponse::InternalServerError(),
-                ErrorKind::NotFound => HttpResponse::NotFound(),
-                ErrorKind::PrefectureNotFound => HttpResponse::NotFound(),
-                _ => HttpResponse::BadRequest(),
-            };
-            response.json(json!({"message": err.message}))
-        }
+    // アカウント本人または管理者であることを確認
+    if !is_admin(&claims) && account_id.to_string() != claims.sub {
+        return Err(AppError::Forbidden(
+            "アカウント本人または管理者のみ、アカウントを更新できます。".into(),
+        ));
     }
+    // アカウントの更新を試行
+    let account = usecases::accounts::update(
+        db_service.as_ref(),
+        clock.as_ref(),
+        update_account.into_inner(),
+        acting_account_id(&claims)?,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(account))
 }
 
 /// アカウント削除API
 ///
+/// アカウント本人または管理者のみ呼び出せる。
+/// 盗まれたトークンでアカウントを削除されないように、アカウント本人はリクエストボディにパスワードを指定して、
+/// パスワードが一致する場合のみ削除できる。管理者はパスワードを指定せずに削除できる。
 /// URLで指定されたアカウントIDと一致するアカウントが存在しない場合は、
 /// 削除に成功したと判断して`NO CONTENT`を返却する。
 ///
 /// # Arguments
 ///
 /// * `db_service` - データベースサービス。
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `event_sink` - アカウントイベントシンク。
 /// * `path` - 削除するアカウントのアカウントIDを格納したタプル。
+/// * `data` - 削除するアカウントのパスワード。管理者は指定しなくてもよい。
+/// * `claims` - アクセストークンのクレイム。
 ///
 /// # Returns
 ///
 /// レスポンス。
+#[utoipa::path(
+    delete,
+    path = "/accounts/{id}",
+    tag = "accounts",
+    params(("id" = String, Path, description = "アカウントID")),
+    request_body(content = Option<DeleteAccount>, description = "アカウント本人が削除する場合のパスワード"),
+    responses(
+        (status = 204, description = "アカウントを削除した"),
+        (status = 400, description = "アカウントIDが不正、またはパスワードが指定されていないか間違っている", body = ErrorMessage),
+        (status = 401, description = "認証されていない"),
+        (status = 403, description = "アカウント本人または管理者ではない", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn delete(
     db_service: web::Data<dyn DatabaseService>,
+    hasher: web::Data<dyn PasswordHasher>,
+    event_sink: web::Data<dyn AccountEventSink>,
     path: web::Path<(String,)>,
-) -> impl Responder {
+    data: Option<web::Json<DeleteAccount>>,
+    claims: Claims,
+) -> Result<HttpResponse, AppError> {
     // アカウントIDを検証
-    let result = validate_account_id(&path.into_inner().0);
-    if let Err(err) = result {
-        return err;
-    }
-    let account_id = result.unwrap();
+    let account_id = validate_account_id(&path.into_inner().0)?;
+    // 管理者はパスワードを確認せず、アカウント本人はパスワードを確認
+    let password = if is_admin(&claims) {
+        None
+    } else if account_id.to_string() == claims.sub {
+        match data {
+            Some(data) => Some(data.into_inner().password),
+            None => {
+                return Err(AppError::BadRequest(
+                    "アカウントを削除するときは、リクエストボディにパスワード(password)を指定してください。"
+                        .into(),
+                ))
+            }
+        }
+    } else {
+        return Err(AppError::Forbidden(
+            "アカウント本人または管理者のみ、アカウントを削除できます。".into(),
+        ));
+    };
     // アカウントの削除を試行
-    match usecases::accounts::delete(db_service.as_ref(), account_id.clone()).await {
-        Ok(_) => HttpResponse::NoContent().json(json!({
-            "message": format!("アカウント({})を削除しました。", account_id.value)
-        })),
-        Err(err) => {
-            let mut response = match err.code {
-                ErrorKind::InternalServerError => HttpResponse::InternalServerError(),
-                _ => HttpResponse::BadRequest(),
-            };
-            response.json(json!({"message": err.message }))
+    match usecases::accounts::delete(
+        db_service.as_ref(),
+        hasher.as_ref(),
+        event_sink.as_ref(),
+        account_id.clone(),
+        password.as_deref(),
+    )
+    .await
+    {
+        Ok(_) => Ok(HttpResponse::NoContent().json(json!({
+            "message": format!("アカウント({})を削除しました。", account_id)
+        }))),
+        // アカウントが存在しない場合は、削除に成功したと判断
+        Err(err) if matches!(err.code, ErrorKind::NotFound) => {
+            Ok(HttpResponse::NoContent().finish())
         }
+        Err(err) => Err(err.into()),
     }
 }
 
+/// アカウント有効化API
+///
+/// URLで指定されたアカウントIDと一致するアカウントを有効化して、更新後のアカウントをJSONで返却する。
+/// アカウント本人または管理者のみ呼び出せる。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `path` - 有効化するアカウントのアカウントIDを格納したタプル。
+/// * `claims` - 認証済みアカウントのクレーム。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    post,
+    path = "/accounts/{id}/activate",
+    tag = "accounts",
+    params(("id" = String, Path, description = "アカウントID")),
+    responses(
+        (status = 200, description = "更新後のアカウント", body = AccountDto),
+        (status = 400, description = "アカウントIDが不正", body = ErrorMessage),
+        (status = 401, description = "認証されていない"),
+        (status = 403, description = "アカウント本人または管理者ではない", body = ErrorMessage),
+        (status = 404, description = "アカウントが見つからない", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn activate(
+    db_service: web::Data<dyn DatabaseService>,
+    clock: web::Data<dyn Clock>,
+    path: web::Path<(String,)>,
+    claims: Claims,
+) -> Result<HttpResponse, AppError> {
+    set_active(db_service, clock, path, true, claims).await
+}
+
+/// アカウント無効化API
+///
+/// URLで指定されたアカウントIDと一致するアカウントを無効化して、更新後のアカウントをJSONで返却する。
+/// アカウント本人または管理者のみ呼び出せる。
+/// 無効化したアカウントのトークンは削除する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `path` - 無効化するアカウントのアカウントIDを格納したタプル。
+/// * `claims` - 認証済みアカウントのクレーム。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    post,
+    path = "/accounts/{id}/deactivate",
+    tag = "accounts",
+    params(("id" = String, Path, description = "アカウントID")),
+    responses(
+        (status = 200, description = "更新後のアカウント", body = AccountDto),
+        (status = 400, description = "アカウントIDが不正", body = ErrorMessage),
+        (status = 401, description = "認証されていない"),
+        (status = 403, description = "アカウント本人または管理者ではない", body = ErrorMessage),
+        (status = 404, description = "アカウントが見つからない", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn deactivate(
+    db_service: web::Data<dyn DatabaseService>,
+    clock: web::Data<dyn Clock>,
+    path: web::Path<(String,)>,
+    claims: Claims,
+) -> Result<HttpResponse, AppError> {
+    set_active(db_service, clock, path, false, claims).await
+}
+
+/// アカウントを有効化または無効化する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `path` - アカウントIDを格納したタプル。
+/// * `is_active` - アカウントを有効化する場合は`true`、無効化する場合は`false`。
+/// * `claims` - 認証済みアカウントのクレーム。
+///
+/// # Returns
+///
+/// レスポンス。
+async fn set_active(
+    db_service: web::Data<dyn DatabaseService>,
+    clock: web::Data<dyn Clock>,
+    path: web::Path<(String,)>,
+    is_active: bool,
+    claims: Claims,
+) -> Result<HttpResponse, AppError> {
+    // アカウントIDを検証
+    let account_id = validate_account_id(&path.into_inner().0)?;
+    // アカウント本人または管理者であることを確認
+    if !is_admin(&claims) && account_id.to_string() != claims.sub {
+        return Err(AppError::Forbidden(
+            "アカウント本人または管理者のみ、アカウントを有効化または無効化できます。".into(),
+        ));
+    }
+    // アカウントの有効化または無効化を試行
+    let account = usecases::accounts::set_active(
+        db_service.as_ref(),
+        clock.as_ref(),
+        account_id,
+        is_active,
+        acting_account_id(&claims)?,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(account))
+}
+
+/// アカウント権限変更API
+///
+/// URLで指定されたアカウントを管理者に昇格、または一般ユーザーに降格する。
+/// 権限を変更したアカウントのトークンは削除するため、`VALIDATE_TOKEN_IN_DB`が`true`の場合は、
+/// 変更前の権限を記録したトークンを使用できなくなる。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `path` - 権限を変更するアカウントのアカウントIDを格納したタプル。
+/// * `data` - 権限変更データ。
+/// * `admin` - 管理者のクレイム。管理者のみ権限を変更できる。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    put,
+    path = "/accounts/{id}/role",
+    tag = "accounts",
+    params(("id" = String, Path, description = "アカウントID")),
+    request_body = ChangeRole,
+    responses(
+        (status = 200, description = "更新後のアカウント", body = AccountDto),
+        (status = 400, description = "アカウントIDが不正", body = ErrorMessage),
+        (status = 401, description = "認証されていない"),
+        (status = 403, description = "管理者ではない", body = ErrorMessage),
+        (status = 404, description = "アカウントが見つからない", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn change_role(
+    db_service: web::Data<dyn DatabaseService>,
+    clock: web::Data<dyn Clock>,
+    path: web::Path<(String,)>,
+    data: web::Json<ChangeRole>,
+    admin: RequireAdmin,
+) -> Result<HttpResponse, AppError> {
+    // アカウントIDを検証
+    let account_id = validate_account_id(&path.into_inner().0)?;
+    // アカウントの権限の変更を試行
+    let account = usecases::accounts::change_role(
+        db_service.as_ref(),
+        clock.as_ref(),
+        account_id,
+        data.into_inner().role,
+        acting_account_id(&admin.0)?,
+    )
+    .await?;
+    // 権限を変更した管理者を記録
+    log::info!(
+        "アカウント({})の権限を{}に変更しました(管理者: {})。",
+        account.id,
+        account.role,
+        admin.0.sub
+    );
+
+    Ok(HttpResponse::Ok().json(account))
+}
+
 /// パスワードを変更する。
 ///
 /// #Arguments
@@ -195,51 +982,1786 @@ pub async fn delete(
 /// --data '{"id": "<account-id>", "oldPassword": "<old-password>", "newPassword": "<new-password>"}' \
 /// http://127.0.0.1:8000/accounts/change_password/<account-id>
 /// ```
+#[utoipa::path(
+    post,
+    path = "/accounts/{id}/change_password",
+    tag = "accounts",
+    params(("id" = String, Path, description = "アカウントID")),
+    request_body = ChangePassword,
+    responses(
+        (status = 200, description = "パスワードを変更した", body = ErrorMessage),
+        (status = 400, description = "パスワードが不正、または再利用されている", body = ErrorMessage),
+        (status = 401, description = "認証されていない"),
+        (status = 404, description = "アカウントが見つからない", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn change_password(
     db_service: web::Data<dyn DatabaseService>,
+    clock: web::Data<dyn Clock>,
+    hasher: web::Data<dyn PasswordHasher>,
     path: web::Path<(String,)>,
     data: web::Json<ChangePassword>,
     claims: Claims,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     // アカウントIDを検証
-    let result = validate_account_id(&path.into_inner().0);
-    if let Err(err) = result {
-        return err;
-    }
-    let account_id = result.unwrap();
+    let account_id = validate_account_id(&path.into_inner().0)?;
     // URLで指定されたアカウントIDとJSONデータに記録されているアカウントIDが異なる場合はエラー
     let data = data.into_inner();
-    if account_id.value.to_string() != data.id {
-        let body = json!({
-            "message": "URLで指定されたアカウントIDとリクエストボディに指定されたアカウントIDが異なります。"
-        });
-        return HttpResponse::BadRequest().json(json!(body));
+    if account_id != data.id {
+        return Err(AppError::BadRequest(
+            "URLで指定されたアカウントIDとリクエストボディに指定されたアカウントIDが異なります。"
+                .into(),
+        ));
     }
     // URLで指定されたアカウントIDとJWTトークンに指定されたアカウントIDが異なる場合はエラー
-    if account_id.value.to_string() != claims.sub {
-        let body = json!({
-            "message": "URLで指定されたアカウントIDとJWTトークンに指定されたアカウントIDが異なります。"
-        });
-        return HttpResponse::BadRequest().json(json!(body));
+    if account_id.to_string() != claims.sub {
+        return Err(AppError::BadRequest(
+            "URLで指定されたアカウントIDとJWTトークンに指定されたアカウントIDが異なります。".into(),
+        ));
     }
     // アカウントのパスワードの変更を試行
-    match usecases::accounts::change_password(
+    usecases::accounts::change_password(
         db_service.as_ref(),
+        clock.as_ref(),
+        hasher.as_ref(),
         account_id,
         &data.old_password,
         &data.new_password,
     )
-    .await
-    {
-        Ok(_) => HttpResponse::Ok().json(json!({"message": "パスワードを変更しました。"})),
-        Err(err) => {
-            let mut response = match err.code {
-                ErrorKind::InvalidOldPassword => HttpResponse::BadRequest(),
-                ErrorKind::InvalidNewPassword => HttpResponse::BadRequest(),
-                ErrorKind::WrongPassword => HttpResponse::BadRequest(),
-                _ => HttpResponse::InternalServerError(),
-            };
-            response.json(json!({"message": err.message}))
+    .await?;
+
+    Ok(HttpResponse::Ok().json(json!({"message": "パスワードを変更しました。"})))
+}
+
+/// パスワード再設定API
+///
+/// URLで指定されたアカウントのパスワードを、古いパスワードを要求せずに再設定する。
+/// パスワードを再設定したアカウントのトークンは削除するため、`VALIDATE_TOKEN_IN_DB`が`true`の場合は、
+/// 再設定する前に取得したトークンを使用できなくなる。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `path` - パスワードを再設定するアカウントのアカウントIDを格納したタプル。
+/// * `data` - パスワード再設定データ。
+/// * `admin` - 管理者のクレイム。管理者のみパスワードを再設定できる。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    post,
+    path = "/accounts/{id}/reset_password",
+    tag = "accounts",
+    params(("id" = String, Path, description = "アカウントID")),
+    request_body = ResetPassword,
+    responses(
+        (status = 200, description = "パスワードを再設定した", body = ErrorMessage),
+        (status = 400, description = "アカウントIDまたはパスワードが不正", body = ErrorMessage),
+        (status = 401, description = "認証されていない"),
+        (status = 403, description = "管理者ではない", body = ErrorMessage),
+        (status = 404, description = "アカウントが見つからない", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn reset_password(
+    db_service: web::Data<dyn DatabaseService>,
+    clock: web::Data<dyn Clock>,
+    hasher: web::Data<dyn PasswordHasher>,
+    path: web::Path<(String,)>,
+    data: web::Json<ResetPassword>,
+    admin: RequireAdmin,
+) -> Result<HttpResponse, AppError> {
+    // アカウントIDを検証
+    let account_id = validate_account_id(&path.into_inner().0)?;
+    // アカウントのパスワードの再設定を試行
+    usecases::accounts::reset_password(
+        db_service.as_ref(),
+        clock.as_ref(),
+        hasher.as_ref(),
+        account_id.clone(),
+        &data.new_password,
+        acting_account_id(&admin.0)?,
+    )
+    .await?;
+    // パスワードを再設定した管理者を記録
+    log::info!(
+        "アカウント({})のパスワードを再設定しました(管理者: {})。",
+        account_id,
+        admin.0.sub
+    );
+
+    Ok(HttpResponse::Ok().json(json!({"message": "パスワードを再設定しました。"})))
+}
+
+/// Eメールアドレス変更リクエストAPI
+///
+/// 変更後のEメールアドレスを記録したEメールアドレス変更トークンを発行する。
+/// Eメールアドレスは、発行したトークンをEメールアドレス変更確認APIに送信するまで変更しない。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `path` - アカウントID。
+/// * `data` - Eメールアドレス変更リクエスト。
+/// * `claims` - アクセストークンのクレイム。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    post,
+    path = "/accounts/{id}/request_email_change",
+    tag = "accounts",
+    params(("id" = String, Path, description = "アカウントID")),
+    request_body = RequestEmailChange,
+    responses(
+        (status = 200, description = "Eメールアドレス変更トークンを発行した", body = EmailChangeToken),
+        (status = 400, description = "Eメールアドレスが不正", body = ErrorMessage),
+        (status = 401, description = "認証されていない"),
+        (status = 404, description = "アカウントが見つからない", body = ErrorMessage),
+        (status = 409, description = "Eメールアドレスが既に登録されている", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn request_email_change(
+    db_service: web::Data<dyn DatabaseService>,
+    clock: web::Data<dyn Clock>,
+    path: web::Path<(String,)>,
+    data: web::Json<RequestEmailChange>,
+    claims: Claims,
+) -> Result<HttpResponse, AppError> {
+    // アカウントIDを検証
+    let account_id = validate_account_id(&path.into_inner().0)?;
+    // URLで指定されたアカウントIDとJWTトークンに指定されたアカウントIDが異なる場合はエラー
+    if account_id.to_string() != claims.sub {
+        return Err(AppError::BadRequest(
+            "URLで指定されたアカウントIDとJWTトークンに指定されたアカウントIDが異なります。".into(),
+        ));
+    }
+    // Eメールアドレス変更トークンの発行を試行
+    let token = usecases::accounts::request_email_change(
+        db_service.as_ref(),
+        clock.as_ref(),
+        account_id,
+        &data.new_email,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(token))
+}
+
+/// Eメールアドレス変更確認API
+///
+/// Eメールアドレス変更トークンを検証して、アカウントのEメールアドレスを変更する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `data` - Eメールアドレス変更確認。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    post,
+    path = "/accounts/confirm_email",
+    tag = "accounts",
+    request_body = ConfirmEmailChange,
+    responses(
+        (status = 200, description = "Eメールアドレスを変更したアカウント", body = AccountDto),
+        (status = 400, description = "トークンが不正、有効期限切れまたは使用済み", body = ErrorMessage),
+        (status = 404, description = "アカウントが見つからない", body = ErrorMessage),
+        (status = 409, description = "Eメールアドレスが既に登録されている", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    )
+)]
+pub async fn confirm_email(
+    db_service: web::Data<dyn DatabaseService>,
+    clock: web::Data<dyn Clock>,
+    data: web::Json<ConfirmEmailChange>,
+) -> Result<HttpResponse, AppError> {
+    // Eメールアドレスの変更を試行
+    let account =
+        usecases::accounts::confirm_email_change(db_service.as_ref(), clock.as_ref(), &data.token)
+            .await?;
+
+    Ok(HttpResponse::Ok().json(account))
+}
+
+#[cfg(test)]
+mod accounts_handler_tests {
+    use std::sync::Arc;
+
+    use actix_web::{
+        dev::{ServiceFactory, ServiceRequest, ServiceResponse},
+        http::StatusCode,
+        test::{self, TestRequest},
+        App,
+    };
+    use chrono::{DateTime, Duration, FixedOffset, TimeZone};
+    use common::{
+        hashing::HashingConfig,
+        jwt_token::{gen_jwt_token, EmailChangeClaims},
+    };
+    use domains::{
+        models::{
+            accounts::{Account, AccountName, Role},
+            auth::{JwtToken, JwtTokenWithExpiredAt, JwtTokens, JwtTokensId},
+            common::{Address, AddressDetails, EmailAddress, PhoneNumber, PostalCode, Prefecture},
+        },
+        services::{clock::SystemClock, hashers::PasswordHasherImpl},
+    };
+    use sea_orm::{DatabaseConnection, TransactionTrait};
+    use usecases::{events::NoopAccountEventSink, queries::AccountTokens};
+
+    use super::*;
+    use crate::{
+        authorization::bearer_token,
+        database_service::{
+            insert_sqlite_account, insert_sqlite_tokens, sqlite_db_service, DatabaseServiceImpl,
+        },
+        idempotency::IDEMPOTENCY_KEY,
+        json_config::json_config,
+        mock_database_service::{test_account, test_datetime, MockDatabaseService, TEST_PASSWORD},
+    };
+
+    /// データベースに接続していないアカウント登録APIのアプリケーションを構築する。
+    fn insert_app(
+        hasher: PasswordHasherImpl,
+    ) -> App<
+        impl ServiceFactory<
+            ServiceRequest,
+            Config = (),
+            Response = ServiceResponse,
+            Error = actix_web::Error,
+            InitError = (),
+        >,
+    > {
+        idempotent_insert_app(
+            hasher,
+            web::Data::new(IdempotencyStore::new(std::time::Duration::from_secs(60))),
+        )
+    }
+
+    /// 指定された冪等キーストアを使用する、データベースに接続していないアカウント登録APIのアプリケーションを構築する。
+    fn idempotent_insert_app(
+        hasher: PasswordHasherImpl,
+        idempotency: web::Data<IdempotencyStore>,
+    ) -> App<
+        impl ServiceFactory<
+            ServiceRequest,
+            Config = (),
+            Response = ServiceResponse,
+            Error = actix_web::Error,
+            InitError = (),
+        >,
+    > {
+        let db_service: Arc<dyn DatabaseService> =
+            Arc::new(DatabaseServiceImpl::new(DatabaseConnection::Disconnected));
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let hasher: Arc<dyn PasswordHasher> = Arc::new(hasher);
+        let event_sink: Arc<dyn AccountEventSink> = Arc::new(NoopAccountEventSink);
+        App::new()
+            .app_data(web::Data::from(db_service))
+            .app_data(web::Data::from(clock))
+            .app_data(web::Data::from(hasher))
+            .app_data(web::Data::from(event_sink))
+            .app_data(idempotency)
+            .app_data(json_config(1024 * 1024))
+            .route("/accounts", web::post().to(insert))
+    }
+
+    /// モックのデータベースサービスを使用するアカウントAPIのアプリケーションを構築する。
+    fn mock_app(
+        db_service: MockDatabaseService,
+    ) -> App<
+        impl ServiceFactory<
+            ServiceRequest,
+            Config = (),
+            Response = ServiceResponse,
+            Error = actix_web::Error,
+            InitError = (),
+        >,
+    > {
+        accounts_app(Arc::new(db_service))
+    }
+
+    /// 指定されたデータベースサービスを使用するアカウントAPIのアプリケーションを構築する。
+    fn accounts_app(
+        db_service: Arc<dyn DatabaseService>,
+    ) -> App<
+        impl ServiceFactory<
+            ServiceRequest,
+            Config = (),
+            Response = ServiceResponse,
+            Error = actix_web::Error,
+            InitError = (),
+        >,
+    > {
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let hasher: Arc<dyn PasswordHasher> =
+            Arc::new(PasswordHasherImpl::new(HashingConfig::from_env()));
+        let event_sink: Arc<dyn AccountEventSink> = Arc::new(NoopAccountEventSink);
+        App::new()
+            .app_data(web::Data::from(db_service))
+            .app_data(web::Data::from(clock))
+            .app_data(web::Data::from(hasher))
+            .app_data(web::Data::from(event_sink))
+            .app_data(web::Data::new(IdempotencyStore::new(
+                std::time::Duration::from_secs(60),
+            )))
+            .route("/accounts", web::get().to(search))
+            .route("/accounts", web::post().to(insert))
+            .route("/accounts/export", web::get().to(export))
+            .route("/accounts/search", web::get().to(find_by_email))
+            .route("/accounts/{id}", web::get().to(find_by_id))
+            .route("/accounts/{id}", web::put().to(update))
+            .route("/accounts/{id}", web::delete().to(delete))
+            .route("/accounts/{id}/sessions", web::get().to(sessions))
+            .route("/accounts/{id}/activate", web::post().to(activate))
+            .route("/accounts/{id}/deactivate", web::post().to(deactivate))
+            .route("/accounts/bulk", web::post().to(bulk_insert))
+            .route("/accounts/import", web::post().to(import))
+            .route(
+                "/accounts/{id}/change_password",
+                web::post().to(change_password),
+            )
+            .route("/accounts/{id}/role", web::put().to(change_role))
+            .route(
+                "/accounts/{id}/reset_password",
+                web::post().to(reset_password),
+            )
+    }
+
+    /// モックのデータベースサービスを使用するアプリケーションでリクエストを処理して、レスポンスのステータスコードを返却する。
+    async fn mock_call(db_service: MockDatabaseService, req: TestRequest) -> StatusCode {
+        let app = test::init_service(mock_app(db_service)).await;
+
+        test::call_service(&app, req.to_request()).await.status()
+    }
+
+    /// 指定された更新日時のアカウントを構築する。
+    fn account_dto(id: AccountId, updated_at: DateTime<FixedOffset>) -> AccountDto {
+        AccountDto {
+            id,
+            email: EmailAddress::new("foo@example.com").unwrap(),
+            name: AccountName::new("foo").unwrap(),
+            is_active: true,
+            role: Role::User,
+            fixed_number: None,
+            mobile_number: Some(PhoneNumber::new("090-1234-5678").unwrap()),
+            postal_code: PostalCode::new("100-0014").unwrap(),
+            prefecture_code: 13,
+            prefecture_name: "東京都".to_owned(),
+            address_details: AddressDetails::new("千代田区永田町1-7-1").unwrap(),
+            logged_in_at: None,
+            created_at: updated_at,
+            updated_at,
+            updated_by: None,
+        }
+    }
+
+    /// アカウントを登録したときのレスポンスに、`Location`ヘッダが設定されていることを確認する。
+    #[test]
+    fn test_created_response_location() {
+        let id = AccountId::gen();
+        let dt = FixedOffset::east_opt(9 * 60 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2022, 4, 1, 9, 0, 0)
+            .unwrap();
+        let account = AccountDto {
+            id: id.clone(),
+            email: EmailAddress::new("foo@example.com").unwrap(),
+            name: AccountName::new("foo").unwrap(),
+            is_active: true,
+            role: Role::User,
+            fixed_number: None,
+            mobile_number: Some(PhoneNumber::new("090-1234-5678").unwrap()),
+            postal_code: PostalCode::new("100-0014").unwrap(),
+            prefecture_code: 13,
+            prefecture_name: "東京都".to_owned(),
+            address_details: AddressDetails::new("千代田区永田町1-7-1").unwrap(),
+            logged_in_at: None,
+            created_at: dt,
+            updated_at: dt,
+            updated_by: None,
+        };
+        let response = created_response(&account);
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert_eq!(location, format!("/accounts/{}", id));
+        // LocationヘッダのアカウントIDがULIDとして解釈できることを確認
+        let location_id = location.strip_prefix("/accounts/").unwrap();
+        assert!(AccountId::try_from(location_id).is_ok());
+    }
+
+    /// 強度が不足しているパスワードを指定したアカウントの登録を、データベースにアクセスせずに拒否することを確認する。
+    #[actix_web::test]
+    async fn test_insert_weak_password() {
+        let app = test::init_service(insert_app(PasswordHasherImpl::new(
+            HashingConfig::from_env(),
+        )))
+        .await;
+        for (password, message) in [
+            ("Password1!", "よく使用されるパスワードは指定できません。"),
+            (
+                "01#Taro.Yamada",
+                "パスワードにEメールアドレスを含めることはできません。",
+            ),
+            (
+                "01#yamaDA#x",
+                "パスワードにアカウント名を含めることはできません。",
+            ),
+            (
+                "01abCD####",
+                "パスワードに同じ文字を3文字より多く連続して含めることはできません。",
+            ),
+        ] {
+            let req = TestRequest::post()
+                .uri("/accounts")
+                .set_json(json!({
+                    "email": "taro.yamada@example.com",
+                    "name": "yamada",
+                    "password": password,
+                    "isActive": true,
+                    "mobileNumber": "090-1234-5678",
+                    "postalCode": "100-0014",
+                    "prefectureCode": 13,
+                    "addressDetails": "千代田区永田町1-7-1",
+                }))
+                .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{}", password);
+            let body: serde_json::Value = test::read_body_json(res).await;
+            assert_eq!(body["message"], message, "{}", password);
         }
     }
+
+    /// 長すぎるパスワードを指定したアカウントの登録を、パスワードをハッシュ化せずにすぐに拒否することを確認する。
+    #[actix_web::test]
+    async fn test_insert_too_long_password() {
+        let app = test::init_service(insert_app(PasswordHasherImpl::new(
+            HashingConfig::from_env(),
+        )))
+        .await;
+        let password = format!("01abCD#${}", "x".repeat(512 * 1024));
+        let req = TestRequest::post()
+            .uri("/accounts")
+            .set_json(json!({
+                "email": "foo@example.com",
+                "name": "foo",
+                "password": password,
+                "isActive": true,
+                "mobileNumber": "090-1234-5678",
+                "postalCode": "100-0014",
+                "prefectureCode": 13,
+                "addressDetails": "千代田区永田町1-7-1",
+            }))
+            .to_request();
+        let started_at = std::time::Instant::now();
+        let res = test::call_service(&app, req).await;
+        assert!(started_at.elapsed() < std::time::Duration::from_secs(1));
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(
+            body["message"],
+            "パスワードは128文字以下の文字列で指定してください。"
+        );
+    }
+
+    /// パスワードのハッシュ化に失敗した場合に、パニックせずにサーバー内部エラーのレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_insert_invalid_hash_func() {
+        let hasher = PasswordHasherImpl::new(HashingConfig {
+            func: "MD5".to_owned(),
+            ..HashingConfig::from_env()
+        });
+        let app = test::init_service(insert_app(hasher)).await;
+        let req = TestRequest::post()
+            .uri("/accounts")
+            .set_json(json!({
+                "email": "foo@example.com",
+                "name": "foo",
+                "password": "012abcEFG=+",
+                "isActive": true,
+                "mobileNumber": "090-1234-5678",
+                "postalCode": "100-0014",
+                "prefectureCode": 13,
+                "addressDetails": "千代田区永田町1-7-1",
+            }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(
+            body["message"],
+            "パスワードをハッシュ化する関数を指定する環境変数PASSWORD_HASH_FUNCの値が不正です。"
+        );
+    }
+
+    /// `If-None-Match`ヘッダに指定されたETagが一致する場合に、NOT_MODIFIEDレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_find_by_id_response_not_modified() {
+        let updated_at = FixedOffset::east_opt(9 * 60 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2022, 4, 1, 9, 0, 0)
+            .unwrap();
+        let account = account_dto(AccountId::gen(), updated_at);
+        let response = find_by_id_response(&account, None);
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response
+            .headers()
+            .get(header::ETAG)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(etag.starts_with("W/"), "{}", etag);
+        let if_none_match = IfNoneMatch::Items(vec![etag.parse().unwrap()]);
+        let response = find_by_id_response(&account, Some(&if_none_match));
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers().get(header::ETAG).unwrap(), etag);
+        let body = actix_web::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+    }
+
+    /// `If-None-Match`ヘッダに指定されたETagが古い場合に、更新されたアカウントを返却することを確認する。
+    #[actix_web::test]
+    async fn test_find_by_id_response_stale_etag() {
+        let id = AccountId::gen();
+        let updated_at = FixedOffset::east_opt(9 * 60 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2022, 4, 1, 9, 0, 0)
+            .unwrap();
+        let stale = account_etag(&account_dto(id.clone(), updated_at));
+        let account = account_dto(id.clone(), updated_at + Duration::seconds(1));
+        let if_none_match = IfNoneMatch::Items(vec![stale.clone()]);
+        let response = find_by_id_response(&account, Some(&if_none_match));
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response
+            .headers()
+            .get(header::ETAG)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert_ne!(etag, stale.to_string());
+        let body = actix_web::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["id"], id.to_string());
+        assert_eq!(body["updatedAt"], account.updated_at.to_rfc3339());
+    }
+
+    /// 必須フィールドが不足しているアカウントを登録しようとした場合に、
+    /// 不足しているフィールドを含むJSONのエラーレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_insert_missing_fields() {
+        let app = test::init_service(insert_app(PasswordHasherImpl::new(
+            HashingConfig::from_env(),
+        )))
+        .await;
+        let req = TestRequest::post()
+            .uri("/accounts")
+            .set_json(json!({"name": "x"}))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["code"], "VALIDATION_ERROR");
+        assert_eq!(body["field"], "email");
+        assert!(body["message"].as_str().unwrap().contains("email"));
+    }
+    /// 有効期限が切れたEメールアドレス変更トークンで、Eメールアドレスを変更できないことを確認する。
+    #[actix_web::test]
+    async fn test_confirm_email_expired_token() {
+        let db_service: Arc<dyn DatabaseService> =
+            Arc::new(DatabaseServiceImpl::new(DatabaseConnection::Disconnected));
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(db_service))
+                .app_data(web::Data::from(clock))
+                .route("/accounts/confirm_email", web::post().to(confirm_email)),
+        )
+        .await;
+        let token = gen_jwt_token(&EmailChangeClaims {
+            sub: AccountId::gen().to_string(),
+            current_email: "foo@example.com".to_owned(),
+            new_email: "bar@example.com".to_owned(),
+            exp: (chrono::Utc::now() - Duration::minutes(5)).timestamp(),
+            ..Default::default()
+        })
+        .unwrap();
+        let req = TestRequest::post()
+            .uri("/accounts/confirm_email")
+            .set_json(json!({ "token": token }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert!(
+            body["message"].as_str().unwrap().contains("有効期限"),
+            "{}",
+            body
+        );
+    }
+
+    /// アカウントを削除した後に、アカウントに発行したトークンが残っていないことを確認する。
+    #[actix_web::test]
+    async fn test_delete_removes_tokens() {
+        dotenv::from_filename(".env").ok();
+        let db_service = Arc::new(sqlite_db_service().await);
+        let account = insert_sqlite_account(&db_service, "foo@example.com").await;
+        let admin = insert_sqlite_account(&db_service, "admin@example.com").await;
+        let tokens =
+            insert_sqlite_tokens(&db_service, account.id.clone(), "access", "refresh").await;
+        let app = test::init_service(accounts_app(db_service.clone())).await;
+
+        let req = TestRequest::delete()
+            .uri(&format!("/accounts/{}", account.id))
+            .insert_header((
+                header::AUTHORIZATION,
+                bearer_token(&admin.id.to_string(), Role::Admin),
+            ))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        let txn = db_service.conn.begin().await.unwrap();
+        let found = db_service
+            .jwt_tokens(&txn)
+            .find_by_access_token(&tokens.access().token.value())
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert!(found.is_none());
+    }
+
+    /// 登録に成功したリクエストと同じ冪等キーとリクエストボディで再送した場合に、
+    /// アカウントを登録せずに最初に登録したアカウントのレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_insert_idempotent_retry() {
+        let body = json!({
+            "email": "foo@example.com",
+            "name": "foo",
+            "password": "012abcEFG=+",
+            "isActive": true,
+            "mobileNumber": "090-1234-5678",
+            "postalCode": "100-0014",
+            "prefectureCode": 13,
+            "addressDetails": "千代田区永田町1-7-1",
+        });
+        // 最初のリクエストで登録したアカウントを記録
+        let idempotency = web::Data::new(IdempotencyStore::new(std::time::Duration::from_secs(60)));
+        let new_account: NewAccount = serde_json::from_value(body.clone()).unwrap();
+        idempotency.begin("key", fingerprint(&new_account));
+        let account = account_dto(AccountId::gen(), chrono::Local::now().into());
+        idempotency.complete("key", &account);
+        // データベースに接続していないため、アカウントを登録しようとした場合はサーバー内部エラーになる
+        let app = test::init_service(idempotent_insert_app(
+            PasswordHasherImpl::new(HashingConfig::from_env()),
+            idempotency,
+        ))
+        .await;
+        let req = TestRequest::post()
+            .uri("/accounts")
+            .insert_header((IDEMPOTENCY_KEY, "key"))
+            .set_json(&body)
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(
+            res.headers().get(header::LOCATION).unwrap(),
+            format!("/accounts/{}", account.id).as_str()
+        );
+        let returned: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(returned["id"], account.id.to_string());
+    }
+
+    /// 同じ冪等キーで異なるリクエストボディを指定した場合に、UNPROCESSABLE_ENTITYレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_insert_idempotency_key_mismatch() {
+        let body = |email: &str| {
+            json!({
+                "email": email,
+                "name": "foo",
+                "password": "012abcEFG=+",
+                "isActive": true,
+                "mobileNumber": "090-1234-5678",
+                "postalCode": "100-0014",
+                "prefectureCode": 13,
+                "addressDetails": "千代田区永田町1-7-1",
+            })
+        };
+        let idempotency = web::Data::new(IdempotencyStore::new(std::time::Duration::from_secs(60)));
+        let new_account: NewAccount = serde_json::from_value(body("foo@example.com")).unwrap();
+        idempotency.begin("key", fingerprint(&new_account));
+        idempotency.complete(
+            "key",
+            &account_dto(AccountId::gen(), chrono::Local::now().into()),
+        );
+        let app = test::init_service(idempotent_insert_app(
+            PasswordHasherImpl::new(HashingConfig::from_env()),
+            idempotency,
+        ))
+        .await;
+        let req = TestRequest::post()
+            .uri("/accounts")
+            .insert_header((IDEMPOTENCY_KEY, "key"))
+            .set_json(body("bar@example.com"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let returned: serde_json::Value = test::read_body_json(res).await;
+        assert!(returned["message"].as_str().unwrap().contains("key"));
+    }
+
+    /// アカウント取得APIが、アカウントの有無とデータベースの状態に応じたレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_find_by_id_with_mock() {
+        let account = test_account(test_datetime());
+        let uri = format!("/accounts/{}", account.id());
+        for (db_service, uri, expected) in [
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                uri.as_str(),
+                StatusCode::OK,
+            ),
+            (
+                MockDatabaseService::account_missing().await,
+                uri.as_str(),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                MockDatabaseService::db_error().await,
+                uri.as_str(),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                "/accounts/invalid-id",
+                StatusCode::BAD_REQUEST,
+            ),
+        ] {
+            let req = TestRequest::get().uri(uri);
+            assert_eq!(mock_call(db_service, req).await, expected, "{}", uri);
+        }
+    }
+
+    /// Eメールアドレスによるアカウント検索APIが、権限、Eメールアドレス及びデータベースの状態に応じたレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_find_by_email_with_mock() {
+        let account = test_account(test_datetime());
+        let id = account.id().to_string();
+        let other = AccountId::gen().to_string();
+        let own = "/accounts/search?email=foo@example.com";
+        let another = "/accounts/search?email=bar@example.com";
+        let invalid = "/accounts/search?email=invalid";
+        for (db_service, uri, token, expected) in [
+            // 管理者は、すべてのアカウントを検索できる
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                another,
+                bearer_token(&other, Role::Admin),
+                StatusCode::OK,
+            ),
+            (
+                MockDatabaseService::account_missing().await,
+                another,
+                bearer_token(&other, Role::Admin),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                invalid,
+                bearer_token(&other, Role::Admin),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                MockDatabaseService::db_error().await,
+                another,
+                bearer_token(&other, Role::Admin),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            // 管理者以外は、自分のEメールアドレスのみ検索できる
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                own,
+                bearer_token(&id, Role::User),
+                StatusCode::OK,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                another,
+                bearer_token(&id, Role::User),
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                invalid,
+                bearer_token(&id, Role::User),
+                StatusCode::BAD_REQUEST,
+            ),
+        ] {
+            let req = TestRequest::get()
+                .uri(uri)
+                .insert_header((header::AUTHORIZATION, token));
+            assert_eq!(mock_call(db_service, req).await, expected, "{}", uri);
+        }
+        // 認証されていない場合
+        let req = TestRequest::get().uri(own);
+        assert_eq!(
+            mock_call(
+                MockDatabaseService::account_exists(account.clone()).await,
+                req
+            )
+            .await,
+            StatusCode::UNAUTHORIZED
+        );
+        // `search`をアカウントIDとして扱わない
+        let db_service = MockDatabaseService::account_exists(account.clone()).await;
+        let app = test::init_service(mock_app(db_service)).await;
+        let req = TestRequest::get()
+            .uri(own)
+            .insert_header((header::AUTHORIZATION, bearer_token(&id, Role::User)))
+            .to_request();
+        let returned: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(returned["id"], id);
+    }
+
+    /// アカウント登録APIが、リクエストボディと都道府県の有無及びデータベースの状態に応じたレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_insert_with_mock() {
+        let body = |postal_code: &str, prefecture_code: u8| {
+            json!({
+                "email": "foo@example.com",
+                "name": "foo",
+                "password": TEST_PASSWORD,
+                "isActive": true,
+                "mobileNumber": "090-1234-5678",
+                "postalCode": postal_code,
+                "prefectureCode": prefecture_code,
+                "addressDetails": "千代田区永田町1-7-1",
+            })
+        };
+        for (db_service, body, expected) in [
+            (
+                MockDatabaseService::account_missing().await,
+                body("100-0014", 13),
+                StatusCode::CREATED,
+            ),
+            (
+                MockDatabaseService::account_missing().await,
+                body("100-001", 13),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                MockDatabaseService::account_missing().await,
+                body("100-0014", 99),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                MockDatabaseService::db_error().await,
+                body("100-0014", 13),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        ] {
+            let req = TestRequest::post().uri("/accounts").set_json(&body);
+            assert_eq!(mock_call(db_service, req).await, expected, "{}", body);
+        }
+    }
+
+    /// アカウントの登録が一意制約に違反した場合に、サーバー内部エラーではなく競合を返却することを確認する。
+    #[actix_web::test]
+    async fn test_insert_unique_violation_with_mock() {
+        use usecases::transaction::test_db_err;
+
+        // Eメールアドレスの確認後に、他のリクエストが同じEメールアドレスのアカウントを登録した場合
+        let db_service = MockDatabaseService::account_missing()
+            .await
+            .with_account(|mock| {
+                mock.expect_find_by_email().returning(|_| Ok(None));
+                mock.expect_insert()
+                    .returning(|_| Err(test_db_err("23505").into()));
+            });
+        let app = test::init_service(mock_app(db_service)).await;
+        let req = TestRequest::post()
+            .uri("/accounts")
+            .set_json(json!({
+                "email": "foo@example.com",
+                "name": "foo",
+                "password": TEST_PASSWORD,
+                "isActive": true,
+                "mobileNumber": "090-1234-5678",
+                "postalCode": "100-0014",
+                "prefectureCode": 13,
+                "addressDetails": "千代田区永田町1-7-1",
+            }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        let message = body["message"].as_str().unwrap();
+        assert!(message.contains("既に登録されています"), "{}", message);
+        assert!(!message.contains("23505"), "{}", message);
+    }
+
+    /// アカウント更新APIが、アカウントの有無と更新日時及びデータベースの状態に応じたレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_update_with_mock() {
+        let account = test_account(test_datetime());
+        let uri = format!("/accounts/{}", account.id());
+        let body = |id: &str| {
+            json!({
+                "id": id,
+                "name": "bar",
+                "isActive": true,
+                "mobileNumber": "090-1234-5678",
+                "postalCode": "100-0014",
+                "prefectureCode": 13,
+                "addressDetails": "千代田区永田町1-7-1",
+                "updatedAt": account.updated_at().to_rfc3339(),
+            })
+        };
+        let id = account.id().to_string();
+        let token = bearer_token(&id, Role::User);
+        let stale = account.clone();
+        for (db_service, body, expected) in [
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                body(&id),
+                StatusCode::OK,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                body(&AccountId::gen().to_string()),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                MockDatabaseService::account_missing().await,
+                body(&id),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                MockDatabaseService::db_error().await,
+                body(&id),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                // 他のリクエストで更新されていて、更新日時が一致する行がない
+                MockDatabaseService::account_exists(account.clone())
+                    .await
+                    .with_account(move |mock| {
+                        let found = stale.clone();
+                        mock.expect_find_by_id()
+                            .returning(move |_| Ok(Some(found.clone())));
+                        mock.expect_update_if_unmodified()
+                            .returning(|_, _| Ok(None));
+                    }),
+                body(&id),
+                StatusCode::CONFLICT,
+            ),
+        ] {
+            let req = TestRequest::put()
+                .uri(&uri)
+                .insert_header((header::AUTHORIZATION, token.as_str()))
+                .set_json(&body);
+            assert_eq!(mock_call(db_service, req).await, expected, "{}", body);
+        }
+        // トークンを指定しない場合は、アカウントを更新しない
+        let req = TestRequest::put().uri(&uri).set_json(body(&id));
+        assert_eq!(
+            mock_call(MockDatabaseService::new().await, req).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    /// アカウント更新APIが、トークンのアカウントを更新したアカウントとして記録することを確認する。
+    #[actix_web::test]
+    async fn test_update_records_updated_by_with_mock() {
+        let account = test_account(test_datetime());
+        let id = account.id().to_string();
+        let admin_id = AccountId::gen().to_string();
+        // アカウント本人が更新した場合は本人、管理者が更新した場合は管理者を記録
+        for (sub, role) in [(id.as_str(), Role::User), (admin_id.as_str(), Role::Admin)] {
+            let app = test::init_service(mock_app(
+                MockDatabaseService::account_exists(account.clone()).await,
+            ))
+            .await;
+            let req = TestRequest::put()
+                .uri(&format!("/accounts/{}", id))
+                .insert_header((header::AUTHORIZATION, bearer_token(sub, role)))
+                .set_json(json!({
+                    "id": id,
+                    "name": "bar",
+                    "isActive": true,
+                    "mobileNumber": "090-1234-5678",
+                    "postalCode": "100-0014",
+                    "prefectureCode": 13,
+                    "addressDetails": "千代田区永田町1-7-1",
+                    "updatedAt": account.updated_at().to_rfc3339(),
+                }))
+                .to_request();
+            let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+            assert_eq!(body["name"], "bar");
+            assert_eq!(body["updatedBy"], sub);
+        }
+    }
+
+    /// アカウント削除APIが、アカウント本人のパスワードの一致、権限、アカウントの有無及びデータベースの状態に応じたレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_delete_with_mock() {
+        let account = test_account(test_datetime());
+        let id = account.id().to_string();
+        let uri = format!("/accounts/{}", id);
+        let owner = Some(bearer_token(&id, Role::User));
+        let admin = Some(bearer_token(&AccountId::gen().to_string(), Role::Admin));
+        let other = Some(bearer_token(&AccountId::gen().to_string(), Role::User));
+        let password = Some(json!({ "password": TEST_PASSWORD }));
+        let wrong_password = Some(json!({ "password": "987zyxWVU#-" }));
+        for (db_service, uri, token, body, expected) in [
+            // アカウント本人は、パスワードが一致する場合のみ削除できる
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                uri.as_str(),
+                owner.clone(),
+                password.clone(),
+                StatusCode::NO_CONTENT,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                uri.as_str(),
+                owner.clone(),
+                wrong_password.clone(),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                uri.as_str(),
+                owner.clone(),
+                None,
+                StatusCode::BAD_REQUEST,
+            ),
+            // 管理者は、パスワードを指定せずに削除できる
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                uri.as_str(),
+                admin.clone(),
+                None,
+                StatusCode::NO_CONTENT,
+            ),
+            (
+                MockDatabaseService::account_missing().await,
+                uri.as_str(),
+                admin.clone(),
+                None,
+                StatusCode::NO_CONTENT,
+            ),
+            (
+                MockDatabaseService::db_error().await,
+                uri.as_str(),
+                admin.clone(),
+                None,
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                "/accounts/invalid-id",
+                admin.clone(),
+                None,
+                StatusCode::BAD_REQUEST,
+            ),
+            // アカウント本人または管理者ではない場合
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                uri.as_str(),
+                other,
+                password.clone(),
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                uri.as_str(),
+                None,
+                password.clone(),
+                StatusCode::UNAUTHORIZED,
+            ),
+        ] {
+            let mut req = TestRequest::delete().uri(uri);
+            if let Some(token) = token {
+                req = req.insert_header((header::AUTHORIZATION, token));
+            }
+            if let Some(body) = body {
+                req = req.set_json(body);
+            }
+            assert_eq!(mock_call(db_service, req).await, expected, "{}", uri);
+        }
+    }
+
+    /// アカウント本人のパスワードが間違っている場合に、アカウントとトークンを削除しないことを確認する。
+    #[actix_web::test]
+    async fn test_delete_wrong_password_keeps_account_with_mock() {
+        let account = test_account(test_datetime());
+        let id = account.id().to_string();
+        let db_service = MockDatabaseService::new()
+            .await
+            .with_account(move |mock| {
+                let found = account.clone();
+                mock.expect_find_by_id()
+                    .returning(move |_| Ok(Some(found.clone())));
+                mock.expect_delete().never();
+            })
+            .with_jwt_tokens(|mock| {
+                mock.expect_delete_by_account_id().never();
+            });
+        let app = test::init_service(mock_app(db_service)).await;
+        let req = TestRequest::delete()
+            .uri(&format!("/accounts/{}", id))
+            .insert_header((header::AUTHORIZATION, bearer_token(&id, Role::User)))
+            .set_json(json!({ "password": "987zyxWVU#-" }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let returned: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(returned["message"], "パスワードが間違っています。");
+    }
+
+    /// パスワード変更APIが、古いパスワードの一致とアカウントの有無及びデータベースの状態に応じたレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_change_password_with_mock() {
+        let account = test_account(test_datetime());
+        let id = account.id().to_string();
+        let token = gen_jwt_token(&Claims {
+            sub: id.clone(),
+            exp: (chrono::Utc::now() + Duration::hours(1)).timestamp(),
+            ..Default::default()
+        })
+        .unwrap();
+        for (db_service, old_password, expected) in [
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                TEST_PASSWORD,
+                StatusCode::OK,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                "987zyxWVU#-",
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                MockDatabaseService::account_missing().await,
+                TEST_PASSWORD,
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                MockDatabaseService::db_error().await,
+                TEST_PASSWORD,
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        ] {
+            let req = TestRequest::post()
+                .uri(&format!("/accounts/{}/change_password", id))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .set_json(json!({
+                    "id": id,
+                    "oldPassword": old_password,
+                    "newPassword": "345xyzKLM#-",
+                }));
+            assert_eq!(
+                mock_call(db_service, req).await,
+                expected,
+                "{}",
+                old_password
+            );
+        }
+    }
+
+    /// アカウント権限変更APIが、管理者のトークンのみ受け付けて、アカウントの有無及びデータベースの状態に応じたレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_change_role_with_mock() {
+        let account = test_account(test_datetime());
+        let uri = format!("/accounts/{}/role", account.id());
+        let admin = Some(bearer_token("01FVH5SD9H9Y4HNZ4J1Q3E3PFD", Role::Admin));
+        let user = Some(bearer_token("01FVH5SD9H9Y4HNZ4J1Q3E3PFD", Role::User));
+        for (db_service, token, uri, expected) in [
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                admin.clone(),
+                uri.as_str(),
+                StatusCode::OK,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                user,
+                uri.as_str(),
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                None,
+                uri.as_str(),
+                StatusCode::UNAUTHORIZED,
+            ),
+            (
+                MockDatabaseService::account_missing().await,
+                admin.clone(),
+                uri.as_str(),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                MockDatabaseService::db_error().await,
+                admin.clone(),
+                uri.as_str(),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                admin.clone(),
+                "/accounts/invalid-id/role",
+                StatusCode::BAD_REQUEST,
+            ),
+        ] {
+            let mut req = TestRequest::put()
+                .uri(uri)
+                .set_json(json!({ "role": "admin" }));
+            if let Some(token) = &token {
+                req = req.insert_header((header::AUTHORIZATION, token.as_str()));
+            }
+            assert_eq!(
+                mock_call(db_service, req).await,
+                expected,
+                "{} {:?}",
+                uri,
+                token
+            );
+        }
+    }
+
+    /// アカウント権限変更APIが、変更後の権限を記録したアカウントを返却することを確認する。
+    #[actix_web::test]
+    async fn test_change_role_response_with_mock() {
+        let account = test_account(test_datetime());
+        let app = test::init_service(mock_app(
+            MockDatabaseService::account_exists(account.clone()).await,
+        ))
+        .await;
+        let req = TestRequest::put()
+            .uri(&format!("/accounts/{}/role", account.id()))
+            .insert_header((
+                header::AUTHORIZATION,
+                bearer_token("01FVH5SD9H9Y4HNZ4J1Q3E3PFD", Role::Admin),
+            ))
+            .set_json(json!({ "role": "admin" }))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["id"], account.id().to_string());
+        assert_eq!(body["role"], "admin");
+        assert_eq!(body["updatedBy"], "01FVH5SD9H9Y4HNZ4J1Q3E3PFD");
+    }
+
+    /// パスワード再設定APIが、管理者のトークンのみ受け付けて、アカウントの有無及びパスワードに応じたレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_reset_password_with_mock() {
+        let account = test_account(test_datetime());
+        let uri = format!("/accounts/{}/reset_password", account.id());
+        let admin = Some(bearer_token("01FVH5SD9H9Y4HNZ4J1Q3E3PFD", Role::Admin));
+        let user = Some(bearer_token(&account.id().to_string(), Role::User));
+        for (db_service, token, password, expected) in [
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                admin.clone(),
+                "345hijKLM=+",
+                StatusCode::OK,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                user,
+                "345hijKLM=+",
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                None,
+                "345hijKLM=+",
+                StatusCode::UNAUTHORIZED,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                admin.clone(),
+                "password",
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                MockDatabaseService::account_missing().await,
+                admin.clone(),
+                "345hijKLM=+",
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                MockDatabaseService::db_error().await,
+                admin.clone(),
+                "345hijKLM=+",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        ] {
+            let mut req = TestRequest::post()
+                .uri(&uri)
+                .set_json(json!({ "newPassword": password }));
+            if let Some(token) = &token {
+                req = req.insert_header((header::AUTHORIZATION, token.as_str()));
+            }
+            assert_eq!(
+                mock_call(db_service, req).await,
+                expected,
+                "{} {:?}",
+                password,
+                token
+            );
+        }
+    }
+
+    /// パスワード再設定APIが、管理者を更新したアカウントとして記録して、アカウントのトークンを削除することを確認する。
+    #[actix_web::test]
+    async fn test_reset_password_revokes_tokens_with_mock() {
+        let account = test_account(test_datetime());
+        let id = account.id();
+        let uri = format!("/accounts/{}/reset_password", id);
+        let admin_id = AccountId::gen();
+        let expected = (id.clone(), admin_id.clone());
+        let db_service = MockDatabaseService::account_exists(account.clone())
+            .await
+            .with_account(move |mock| {
+                let found = account.clone();
+                mock.expect_find_by_id()
+                    .returning(move |_| Ok(Some(found.clone())));
+                let (id, admin_id) = expected.clone();
+                mock.expect_change_password()
+                    .withf(move |target, _, updated_by| {
+                        *target == id && updated_by.as_ref() == Some(&admin_id)
+                    })
+                    .returning(|_, _, _| Ok(true));
+            })
+            .with_jwt_tokens(move |mock| {
+                let id = id.clone();
+                mock.expect_delete_by_account_id()
+                    .withf(move |target| *target == id)
+                    .times(1)
+                    .returning(|_| Ok(1));
+            });
+        let req = TestRequest::post()
+            .uri(&uri)
+            .insert_header((
+                header::AUTHORIZATION,
+                bearer_token(&admin_id.to_string(), Role::Admin),
+            ))
+            .set_json(json!({ "newPassword": "345hijKLM=+" }));
+        assert_eq!(mock_call(db_service, req).await, StatusCode::OK);
+    }
+
+    /// アカウント一括登録APIが、管理者ではないトークンを拒否することを確認する。
+    #[actix_web::test]
+    async fn test_bulk_insert_requires_admin_with_mock() {
+        for (token, expected) in [
+            (
+                Some(bearer_token("01FVH5SD9H9Y4HNZ4J1Q3E3PFD", Role::User)),
+                StatusCode::FORBIDDEN,
+            ),
+            (None, StatusCode::UNAUTHORIZED),
+        ] {
+            let mut req = TestRequest::post()
+                .uri("/accounts/bulk")
+                .set_json(json!([]));
+            if let Some(token) = &token {
+                req = req.insert_header((header::AUTHORIZATION, token.as_str()));
+            }
+            assert_eq!(
+                mock_call(MockDatabaseService::new().await, req).await,
+                expected,
+                "{:?}",
+                token
+            );
+        }
+    }
+
+    /// アカウントインポートAPIが、管理者ではないトークンと、CSVではないリクエストボディを拒否することを確認する。
+    #[actix_web::test]
+    async fn test_import_requires_admin_and_csv_with_mock() {
+        for (role, content_type, expected) in [
+            (Role::User, "text/csv", StatusCode::FORBIDDEN),
+            (
+                Role::Admin,
+                "application/json",
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ),
+            (Role::Admin, "text/csv", StatusCode::BAD_REQUEST),
+        ] {
+            let req = TestRequest::post()
+                .uri("/accounts/import")
+                .insert_header((header::CONTENT_TYPE, content_type))
+                .insert_header((
+                    header::AUTHORIZATION,
+                    bearer_token("01FVH5SD9H9Y4HNZ4J1Q3E3PFD", role),
+                ))
+                .set_payload("email,name\n");
+            assert_eq!(
+                mock_call(MockDatabaseService::new().await, req).await,
+                expected,
+                "{:?} {}",
+                role,
+                content_type
+            );
+        }
+    }
+
+    /// アカウントエクスポートAPIが、ヘッダ行と、カンマを含む値をエスケープしたアカウントの行を返却することを確認する。
+    #[actix_web::test]
+    async fn test_export_with_mock() {
+        let mut account = test_account(test_datetime());
+        account.set_address(Address::new(
+            Prefecture::from_code(13).unwrap(),
+            AddressDetails::new("千代田区永田町1-7-1,国会議事堂").unwrap(),
+        ));
+        let id = account.id();
+        let password = account.password().value();
+        let db_service = MockDatabaseService::new().await.with_account(move |mock| {
+            let account = account.clone();
+            // 最初の呼び出しでアカウントを返却して、2回目の呼び出しで空のリストを返却
+            mock.expect_list_paged()
+                .returning(move |after, _| match after {
+                    None => Ok(vec![account.clone()]),
+                    Some(_) => Ok(vec![]),
+                });
+        });
+        let app = test::init_service(mock_app(db_service)).await;
+        let req = TestRequest::get().uri("/accounts/export").insert_header((
+            header::AUTHORIZATION,
+            bearer_token("01FVH5SD9H9Y4HNZ4J1Q3E3PFD", Role::Admin),
+        ));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+            r#"attachment; filename="accounts.csv""#
+        );
+        let body = String::from_utf8(test::read_body(res).await.to_vec()).unwrap();
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 2, "{}", body);
+        assert_eq!(
+            lines[0],
+            "id,email,name,isActive,fixedNumber,mobileNumber,postalCode,prefectureCode,prefectureName,addressDetails,createdAt,updatedAt"
+        );
+        assert!(
+            lines[1].starts_with(&format!(
+                r#"{},foo@example.com,foo,true,,090-1234-5678,100-0014,13,東京都,"千代田区永田町1-7-1,国会議事堂","#,
+                id
+            )),
+            "{}",
+            lines[1]
+        );
+        assert!(!body.contains(&password), "{}", body);
+    }
+
+    /// アカウントエクスポートAPIが、管理者ではないトークンを拒否することを確認する。
+    #[actix_web::test]
+    async fn test_export_requires_admin_with_mock() {
+        let req = TestRequest::get().uri("/accounts/export").insert_header((
+            header::AUTHORIZATION,
+            bearer_token("01FVH5SD9H9Y4HNZ4J1Q3E3PFD", Role::User),
+        ));
+        assert_eq!(
+            mock_call(MockDatabaseService::new().await, req).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    /// アカウント一覧APIが、管理者ではないトークンを拒否することを確認する。
+    #[actix_web::test]
+    async fn test_search_requires_admin_with_mock() {
+        for (token, expected) in [
+            (
+                Some(bearer_token("01FVH5SD9H9Y4HNZ4J1Q3E3PFD", Role::User)),
+                StatusCode::FORBIDDEN,
+            ),
+            (None, StatusCode::UNAUTHORIZED),
+        ] {
+            let mut req = TestRequest::get().uri("/accounts?q=foo");
+            if let Some(token) = &token {
+                req = req.insert_header((header::AUTHORIZATION, token.as_str()));
+            }
+            assert_eq!(
+                mock_call(MockDatabaseService::new().await, req).await,
+                expected,
+                "{:?}",
+                token
+            );
+        }
+    }
+
+    /// アカウント一覧APIの検索条件またはページ指定が不正な場合に、データベースにアクセスせずにBAD_REQUESTを返却することを確認する。
+    #[actix_web::test]
+    async fn test_search_invalid_query() {
+        for query in ["isActive=maybe", "prefectureCode=tokyo", "q=foo&perPage=0"] {
+            let req = TestRequest::get()
+                .uri(&format!("/accounts?{}", query))
+                .insert_header((
+                    header::AUTHORIZATION,
+                    bearer_token("01FVH5SD9H9Y4HNZ4J1Q3E3PFD", Role::Admin),
+                ));
+            assert_eq!(
+                mock_call(MockDatabaseService::new().await, req).await,
+                StatusCode::BAD_REQUEST,
+                "{}",
+                query
+            );
+        }
+    }
+
+    /// アカウント本人または管理者ではない場合は、他のアカウントを有効化または無効化できず、
+    /// 対象のアカウントのトークンが削除されないことを確認する。
+    #[actix_web::test]
+    async fn test_set_active_requires_owner_or_admin_on_sqlite() {
+        dotenv::from_filename(".env").ok();
+        let db_service = Arc::new(sqlite_db_service().await);
+        let target = insert_sqlite_account(&db_service, "foo@example.com").await;
+        let other = insert_sqlite_account(&db_service, "bar@example.com").await;
+        let tokens =
+            insert_sqlite_tokens(&db_service, target.id.clone(), "access", "refresh").await;
+        let app = test::init_service(accounts_app(db_service.clone())).await;
+        let find_tokens = || async {
+            let txn = db_service.conn.begin().await.unwrap();
+            let found = db_service
+                .jwt_tokens(&txn)
+                .find_by_access_token(&tokens.access().token.value())
+                .await
+                .unwrap();
+            txn.commit().await.unwrap();
+
+            found
+        };
+
+        // 一般ユーザーは、他のアカウントを有効化または無効化できない
+        for action in ["deactivate", "activate"] {
+            let req = TestRequest::post()
+                .uri(&format!("/accounts/{}/{}", target.id, action))
+                .insert_header((
+                    header::AUTHORIZATION,
+                    bearer_token(&other.id.to_string(), Role::User),
+                ))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::FORBIDDEN, "{}", action);
+        }
+        let found = db_service
+            .account_reader()
+            .find_by_id(target.id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(found.is_active());
+        assert!(find_tokens().await.is_some());
+
+        // 管理者は、他のアカウントを無効化して、トークンを削除できる
+        let req = TestRequest::post()
+            .uri(&format!("/accounts/{}/deactivate", target.id))
+            .insert_header((
+                header::AUTHORIZATION,
+                bearer_token(&other.id.to_string(), Role::Admin),
+            ))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(find_tokens().await.is_none());
+    }
+
+    /// アカウント本人または管理者ではない場合は、他のアカウントを更新できず、
+    /// アカウント本人がアカウントを無効化した場合は、アカウントのトークンが削除されることを確認する。
+    #[actix_web::test]
+    async fn test_update_requires_owner_or_admin_on_sqlite() {
+        dotenv::from_filename(".env").ok();
+        let db_service = Arc::new(sqlite_db_service().await);
+        let target = insert_sqlite_account(&db_service, "foo@example.com").await;
+        let other = insert_sqlite_account(&db_service, "bar@example.com").await;
+        let tokens =
+            insert_sqlite_tokens(&db_service, target.id.clone(), "access", "refresh").await;
+        let app = test::init_service(accounts_app(db_service.clone())).await;
+        let find_tokens = || async {
+            let txn = db_service.conn.begin().await.unwrap();
+            let found = db_service
+                .jwt_tokens(&txn)
+                .find_by_access_token(&tokens.access().token.value())
+                .await
+                .unwrap();
+            txn.commit().await.unwrap();
+
+            found
+        };
+        let request = |sub: &str| {
+            TestRequest::put()
+                .uri(&format!("/accounts/{}", target.id))
+                .insert_header((header::AUTHORIZATION, bearer_token(sub, Role::User)))
+                .set_json(json!({
+                    "id": target.id.to_string(),
+                    "name": "bar",
+                    "isActive": false,
+                    "mobileNumber": "090-1234-5678",
+                    "postalCode": "100-0014",
+                    "prefectureCode": 13,
+                    "addressDetails": "千代田区永田町1-7-1",
+                    "updatedAt": target.updated_at.to_rfc3339(),
+                }))
+                .to_request()
+        };
+
+        // 一般ユーザーは、他のアカウントを更新できない
+        let resp = test::call_service(&app, request(&other.id.to_string())).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        let found = db_service
+            .account_reader()
+            .find_by_id(target.id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.name().value(), "foo");
+        assert!(found.is_active());
+        assert!(find_tokens().await.is_some());
+
+        // アカウント本人は、アカウントを無効化して、トークンを削除できる
+        let resp = test::call_service(&app, request(&target.id.to_string())).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(find_tokens().await.is_none());
+    }
+
+    /// テストで使用する、アカウントと有効期限付きアクセス・リフレッシュトークンを構築する。
+    fn account_tokens(account: &Account) -> AccountTokens {
+        let expired_at = test_datetime() + Duration::hours(1);
+        AccountTokens {
+            account: account.clone(),
+            tokens: Some(JwtTokens::new(
+                JwtTokensId::try_from("01FVH5SD9H9Y4HNZ4J1Q3E3PFE").unwrap(),
+                account.id(),
+                JwtTokenWithExpiredAt {
+                    token: JwtToken::new("raw-access-token").unwrap(),
+                    expired_at,
+                },
+                JwtTokenWithExpiredAt {
+                    token: JwtToken::new("raw-refresh-token").unwrap(),
+                    expired_at: expired_at + Duration::hours(1),
+                },
+            )),
+        }
+    }
+
+    /// アカウント本人がセッションを取得した場合に、トークンの文字列を含まずに、トークンIDと有効期限を返却することを確認する。
+    #[actix_web::test]
+    async fn test_sessions_by_owner() {
+        let account = test_account(test_datetime());
+        let id = account.id().to_string();
+        let found = account_tokens(&account);
+        let db_service = MockDatabaseService::new()
+            .await
+            .with_account_service(move |mock| {
+                let found = AccountTokens {
+                    account: found.account.clone(),
+                    tokens: found.tokens.clone(),
+                };
+                mock.expect_find_active_account_by_id()
+                    .return_once(move |_| Ok(Some(found)));
+            });
+        let app = test::init_service(mock_app(db_service)).await;
+        let req = TestRequest::get()
+            .uri(&format!("/accounts/{}/sessions", id))
+            .insert_header((header::AUTHORIZATION, bearer_token(&id, Role::User)))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = test::read_body(res).await;
+        let text = std::str::from_utf8(&body).unwrap();
+        assert!(!text.contains("raw-access-token"), "{}", text);
+        assert!(!text.contains("raw-refresh-token"), "{}", text);
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["account"]["id"], id);
+        assert_eq!(body["session"]["id"], "01FVH5SD9H9Y4HNZ4J1Q3E3PFE");
+        assert_eq!(
+            body["session"]["accessExpiredAt"],
+            "2022-04-01T10:00:00+09:00"
+        );
+        assert_eq!(
+            body["session"]["refreshExpiredAt"],
+            "2022-04-01T11:00:00+09:00"
+        );
+    }
+
+    /// 管理者は他のアカウントのセッションを、非アクティブなアカウントを含めて取得でき、
+    /// 管理者ではないアカウントは他のアカウントのセッションを取得できないことを確認する。
+    #[actix_web::test]
+    async fn test_sessions_by_admin_and_other_user() {
+        let account = test_account(test_datetime());
+        let id = account.id().to_string();
+        let found = account_tokens(&account);
+        let db_service = MockDatabaseService::new()
+            .await
+            .with_account_service(move |mock| {
+                let found = AccountTokens {
+                    account: found.account.clone(),
+                    tokens: None,
+                };
+                mock.expect_find_account_by_id()
+                    .return_once(move |_| Ok(Some(found)));
+            });
+        let app = test::init_service(mock_app(db_service)).await;
+        let req = TestRequest::get()
+            .uri(&format!("/accounts/{}/sessions", id))
+            .insert_header((
+                header::AUTHORIZATION,
+                bearer_token("01FVH5SD9H9Y4HNZ4J1Q3E3PFD", Role::Admin),
+            ))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["account"]["id"], id);
+        assert!(body["session"].is_null());
+
+        let req = TestRequest::get()
+            .uri(&format!("/accounts/{}/sessions", id))
+            .insert_header((
+                header::AUTHORIZATION,
+                bearer_token("01FVH5SD9H9Y4HNZ4J1Q3E3PFD", Role::User),
+            ));
+        assert_eq!(
+            mock_call(MockDatabaseService::new().await, req).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    /// アカウントが見つからない場合に、NOT_FOUNDを返却することを確認する。
+    #[actix_web::test]
+    async fn test_sessions_not_found() {
+        let id = "01FVH5SD9H9Y4HNZ4J1Q3E3PFD";
+        let db_service = MockDatabaseService::new()
+            .await
+            .with_account_service(|mock| {
+                mock.expect_find_active_account_by_id()
+                    .returning(|_| Ok(None));
+            });
+        let req = TestRequest::get()
+            .uri(&format!("/accounts/{}/sessions", id))
+            .insert_header((header::AUTHORIZATION, bearer_token(id, Role::User)));
+        assert_eq!(mock_call(db_service, req).await, StatusCode::NOT_FOUND);
+    }
 }
@@ -1,33 +1,416 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{
+    cookie::{time, Cookie, SameSite},
+    web, HttpResponse, Responder,
+};
 use serde_json::json;
 
+use common::{
+    jwt_token::{Claims, TokenDelivery, ACCESS_TOKEN_COOKIE, REFRESH_TOKEN_COOKIE},
+    ENV_VALUES,
+};
+use domains::services::{clock::Clock, hashers::PasswordHasher};
 use usecases::{
-    auth::{Credential, ErrorKind},
+    auth::{
+        CheckPassword, Credential, IntrospectToken, IntrospectionResult, JwtTokensDto,
+        PasswordCheckResult,
+    },
     database_service::DatabaseService,
 };
 
+use crate::{app_error::AppError, openapi::ErrorMessage};
+
 /// 有効期限付きアクセス・リフレッシュトークンを取得する。
 ///
 /// # Arguments
 ///
 /// * `repos` - リポジトリエクステンション。
+/// * `clock` - 時計。
+/// * `hasher` - パスワードハッシュ化サービス。
 /// * `credential` - Eメールとパスワードを格納したクレデンシャル。
 ///
 /// ```bash
 /// curl --include --request POST --header "Content-Type: application/json" --data '{"email": "foo@example.com", "password": "012abcEFG=+"}' http://127.0.0.1:8000/auth/obtain_tokens
 /// ```
+#[utoipa::path(
+    post,
+    path = "/auth/obtain_tokens",
+    tag = "auth",
+    request_body = Credential,
+    responses(
+        (status = 200, description = "有効期限付きアクセス・リフレッシュトークン(TOKEN_DELIVERYがcookieの場合は、トークンをHttpOnlyクッキーに設定して、ボディのaccessとrefreshを省略)", body = JwtTokensDto),
+        (status = 400, description = "クレデンシャルが不正", body = ErrorMessage),
+        (status = 429, description = "リクエストが多すぎる", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    )
+)]
 pub async fn obtain_tokens(
     db_service: web::Data<dyn DatabaseService>,
+    clock: web::Data<dyn Clock>,
+    hasher: web::Data<dyn PasswordHasher>,
     credential: web::Json<Credential>,
-) -> impl Responder {
-    match usecases::auth::obtain_tokens(db_service.as_ref(), credential.into_inner()).await {
-        Ok(tokens) => HttpResponse::Ok().json(tokens),
-        Err(err) => {
-            let mut response = match err.code {
-                ErrorKind::InternalServerError => HttpResponse::InternalServerError(),
-                _ => HttpResponse::BadRequest(),
-            };
-            response.json(json!({"message": err.message }))
+) -> Result<HttpResponse, AppError> {
+    let tokens = usecases::auth::obtain_tokens(
+        db_service.as_ref(),
+        clock.as_ref(),
+        hasher.as_ref(),
+        credential.into_inner(),
+    )
+    .await?;
+
+    Ok(tokens_response(&tokens, ENV_VALUES.token_delivery))
+}
+
+/// アクセストークンを記録するクッキーのパス。
+const ACCESS_TOKEN_COOKIE_PATH: &str = "/";
+
+/// リフレッシュトークンを記録するクッキーのパス。
+///
+/// リフレッシュトークンは認証API以外に送信する必要がないため、認証スコープに限定する。
+const REFRESH_TOKEN_COOKIE_PATH: &str = "/auth";
+
+/// トークン取得APIのレスポンスを構築する。
+///
+/// トークンをクッキーで返却する場合は、`HttpOnly`、`Secure`及び`SameSite=Strict`のクッキーに
+/// アクセストークンとリフレッシュトークンを設定する。
+/// トークンをレスポンスボディで返却しない場合は、ボディからアクセストークンとリフレッシュトークンを除く。
+///
+/// # Arguments
+///
+/// * `tokens` - 有効期限付きアクセス・リフレッシュトークン。
+/// * `delivery` - トークンを返却する方法。
+///
+/// # Returns
+///
+/// OKレスポンス。
+fn tokens_response(tokens: &JwtTokensDto, delivery: TokenDelivery) -> HttpResponse {
+    let mut response = HttpResponse::Ok();
+    if delivery.uses_cookie() {
+        response
+            .cookie(token_cookie(
+                ACCESS_TOKEN_COOKIE,
+                &tokens.access,
+                ACCESS_TOKEN_COOKIE_PATH,
+                time::Duration::seconds(ENV_VALUES.access_token_seconds),
+            ))
+            .cookie(token_cookie(
+                REFRESH_TOKEN_COOKIE,
+                &tokens.refresh,
+                REFRESH_TOKEN_COOKIE_PATH,
+                time::Duration::seconds(ENV_VALUES.refresh_token_seconds),
+            ));
+    }
+    if delivery.uses_body() {
+        return response.json(tokens);
+    }
+    let mut body = json!(tokens);
+    if let Some(body) = body.as_object_mut() {
+        body.remove("access");
+        body.remove("refresh");
+    }
+
+    response.json(body)
+}
+
+/// トークンを記録するクッキーを構築する。
+///
+/// # Arguments
+///
+/// * `name` - クッキー名。
+/// * `token` - トークン。
+/// * `path` - クッキーを送信するパス。
+/// * `max_age` - クッキーの有効期間。
+///
+/// # Returns
+///
+/// `HttpOnly`、`Secure`及び`SameSite=Strict`のクッキー。
+fn token_cookie<'c>(
+    name: &'c str,
+    token: &str,
+    path: &'c str,
+    max_age: time::Duration,
+) -> Cookie<'c> {
+    Cookie::build(name, token.to_owned())
+        .path(path)
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Strict)
+        .max_age(max_age)
+        .finish()
+}
+
+/// 全セッションログアウトAPI
+///
+/// 認証されたアカウントに発行したすべてのトークンを削除して、すべてのセッションからログアウトする。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `claims` - アクセストークンのクレーム。
+///
+/// ```bash
+/// curl --include --request DELETE --header "Authorization: Bearer <access token>" http://127.0.0.1:8000/auth/tokens
+/// ```
+#[utoipa::path(
+    delete,
+    path = "/auth/tokens",
+    tag = "auth",
+    responses(
+        (status = 204, description = "すべてのトークンを削除した"),
+        (status = 400, description = "アクセストークンが不正", body = ErrorMessage),
+        (status = 401, description = "認証されていない", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn revoke_tokens(
+    db_service: web::Data<dyn DatabaseService>,
+    claims: Claims,
+) -> Result<HttpResponse, AppError> {
+    let count = usecases::auth::revoke_tokens(db_service.as_ref(), &claims.sub).await?;
+    let mut response = HttpResponse::NoContent();
+    // トークンをクッキーで返却している場合は、クッキーも削除
+    if ENV_VALUES.token_delivery.uses_cookie() {
+        for (name, path) in [
+            (ACCESS_TOKEN_COOKIE, ACCESS_TOKEN_COOKIE_PATH),
+            (REFRESH_TOKEN_COOKIE, REFRESH_TOKEN_COOKIE_PATH),
+        ] {
+            let mut cookie = token_cookie(name, "", path, time::Duration::ZERO);
+            cookie.make_removal();
+            response.cookie(cookie);
+        }
+    }
+
+    Ok(response.json(json!({
+        "message": format!("{}件のトークンを削除しました。", count)
+    })))
+}
+
+/// パスワードチェックAPI
+///
+/// パスワードが規則を満たしているか確認して、満たしていない場合は違反しているすべての規則を返却する。
+/// フロントエンドでパスワードの強度を表示するために使用する。
+///
+/// # Arguments
+///
+/// * `data` - 確認するパスワード。
+///
+/// ```bash
+/// curl --include --request POST --header "Content-Type: application/json" --data '{"password": "012abcEFG=+"}' http://127.0.0.1:8000/auth/check_password
+/// ```
+#[utoipa::path(
+    post,
+    path = "/auth/check_password",
+    tag = "auth",
+    request_body = CheckPassword,
+    responses(
+        (status = 200, description = "パスワードチェック結果", body = PasswordCheckResult),
+    )
+)]
+pub async fn check_password(data: web::Json<CheckPassword>) -> impl Responder {
+    HttpResponse::Ok().json(usecases::auth::check_password(data.into_inner()))
+}
+
+/// トークンイントロスペクションAPI
+///
+/// リクエストボディで受け取ったトークンを検証して、RFC 7662に従いトークンが有効かを返却する。
+/// トークンが有効な場合はアカウントIDと有効期限を返却して、無効な場合も`{"active": false}`を200で返却する。
+///
+/// # Arguments
+///
+/// * `data` - 検証するトークン。
+///
+/// ```bash
+/// curl --include --request POST --header "Content-Type: application/json" --data '{"token": "<access token>"}' http://127.0.0.1:8000/auth/introspect
+/// ```
+#[utoipa::path(
+    post,
+    path = "/auth/introspect",
+    tag = "auth",
+    request_body = IntrospectToken,
+    responses(
+        (status = 200, description = "トークンイントロスペクション結果", body = IntrospectionResult),
+    )
+)]
+pub async fn introspect(data: web::Json<IntrospectToken>) -> impl Responder {
+    HttpResponse::Ok().json(usecases::auth::introspect(data.into_inner()))
+}
+
+#[cfg(test)]
+mod auth_handler_tests {
+    use actix_web::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        App,
+    };
+    use std::sync::Arc;
+
+    use chrono::{Duration, Utc};
+    use common::{hashing::HashingConfig, jwt_token::gen_jwt_token};
+    use domains::services::{clock::SystemClock, hashers::PasswordHasherImpl};
+
+    use super::*;
+    use crate::mock_database_service::{
+        test_account, test_datetime, MockDatabaseService, TEST_PASSWORD,
+    };
+
+    /// パスワードが規則を満たしているかを返却することを確認する。
+    #[actix_web::test]
+    async fn test_check_password() {
+        let app = test::init_service(
+            App::new().route("/auth/check_password", web::post().to(check_password)),
+        )
+        .await;
+        let req = TestRequest::post()
+            .uri("/auth/check_password")
+            .set_json(json!({"password": "012abcEFG=+"}))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body, json!({"valid": true}));
+        let req = TestRequest::post()
+            .uri("/auth/check_password")
+            .set_json(json!({"password": "012abcEFG"}))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(
+            body,
+            json!({"valid": false, "reasons": ["パスワードに記号が含まれていません。"]})
+        );
+    }
+    /// トークンを検証して、有効なトークンの場合のみクレームを返却することを確認する。
+    #[actix_web::test]
+    async fn test_introspect() {
+        let app =
+            test::init_service(App::new().route("/auth/introspect", web::post().to(introspect)))
+                .await;
+        let exp = (Utc::now() + Duration::hours(1)).timestamp();
+        let valid = gen_jwt_token(&Claims {
+            sub: "01FVH5SD9H9Y4HNZ4J1Q3E3PFD".to_owned(),
+            exp,
+            ..Default::default()
+        })
+        .unwrap();
+        let expired = gen_jwt_token(&Claims {
+            sub: "01FVH5SD9H9Y4HNZ4J1Q3E3PFD".to_owned(),
+            exp: (Utc::now() - Duration::hours(1)).timestamp(),
+            ..Default::default()
+        })
+        .unwrap();
+        for (token, expected) in [
+            (
+                valid.as_str(),
+                json!({"active": true, "sub": "01FVH5SD9H9Y4HNZ4J1Q3E3PFD", "exp": exp}),
+            ),
+            (expired.as_str(), json!({"active": false})),
+            ("this-is-not-a-token", json!({"active": false})),
+        ] {
+            let req = TestRequest::post()
+                .uri("/auth/introspect")
+                .set_json(json!({ "token": token }))
+                .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            let body: serde_json::Value = test::read_body_json(res).await;
+            assert_eq!(body, expected, "{}", token);
+        }
+    }
+
+    /// トークンをクッキーで返却する場合に、`HttpOnly`、`Secure`及び`SameSite=Strict`のクッキーを設定して、
+    /// トークンをボディで返却しない場合はボディからトークンを除くことを確認する。
+    #[actix_web::test]
+    async fn test_tokens_response() {
+        use actix_web::body::to_bytes;
+        use domains::models::{accounts::AccountId, auth::JwtTokensId};
+
+        let now = Utc::now().into();
+        let tokens = JwtTokensDto {
+            id: JwtTokensId::gen(),
+            account_id: AccountId::gen(),
+            access: "access.token".to_owned(),
+            access_expired_at: now,
+            refresh: "refresh.token".to_owned(),
+            refresh_expired_at: now,
+        };
+        // ボディ
+        let res = tokens_response(&tokens, TokenDelivery::Body);
+        assert!(res.cookies().next().is_none());
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(res.into_body()).await.unwrap()).unwrap();
+        assert_eq!(body["access"], "access.token");
+        // クッキー
+        let res = tokens_response(&tokens, TokenDelivery::Cookie);
+        for (name, value, path) in [
+            (
+                ACCESS_TOKEN_COOKIE,
+                "access.token",
+                ACCESS_TOKEN_COOKIE_PATH,
+            ),
+            (
+                REFRESH_TOKEN_COOKIE,
+                "refresh.token",
+                REFRESH_TOKEN_COOKIE_PATH,
+            ),
+        ] {
+            let cookie = res.cookies().find(|c| c.name() == name).unwrap();
+            assert_eq!(cookie.value(), value);
+            assert_eq!(cookie.path(), Some(path));
+            assert_eq!(cookie.http_only(), Some(true));
+            assert_eq!(cookie.secure(), Some(true));
+            assert_eq!(cookie.same_site(), Some(SameSite::Strict));
+        }
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(res.into_body()).await.unwrap()).unwrap();
+        assert!(body.get("access").is_none());
+        assert!(body.get("refresh").is_none());
+        assert_eq!(body["accountId"], tokens.account_id.to_string());
+    }
+
+    /// トークン取得APIが、クレデンシャルとデータベースの状態に応じたレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_obtain_tokens_with_mock() {
+        let account = test_account(test_datetime());
+        for (db_service, password, expected) in [
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                TEST_PASSWORD,
+                StatusCode::OK,
+            ),
+            (
+                MockDatabaseService::account_exists(account.clone()).await,
+                "987zyxWVU#-",
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                MockDatabaseService::account_missing().await,
+                TEST_PASSWORD,
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                MockDatabaseService::db_error().await,
+                TEST_PASSWORD,
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        ] {
+            let db_service: Arc<dyn DatabaseService> = Arc::new(db_service);
+            let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+            let hasher: Arc<dyn PasswordHasher> =
+                Arc::new(PasswordHasherImpl::new(HashingConfig::from_env()));
+            let app = test::init_service(
+                App::new()
+                    .app_data(web::Data::from(db_service))
+                    .app_data(web::Data::from(clock))
+                    .app_data(web::Data::from(hasher))
+                    .route("/auth/obtain_tokens", web::post().to(obtain_tokens)),
+            )
+            .await;
+            let req = TestRequest::post()
+                .uri("/auth/obtain_tokens")
+                .set_json(json!({"email": "foo@example.com", "password": password}))
+                .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), expected, "{}", password);
         }
     }
 }
@@ -2,9 +2,132 @@ pub mod accounts;
 pub mod auth;
 pub mod prefectures;
 
-use actix_web::{HttpResponse, Responder};
+use actix_web::{web, HttpResponse, Responder};
+use serde_json::json;
+use utoipa::OpenApi;
+
+use crate::{metrics::Metrics, openapi::ApiDoc};
 
 /// `Hello world!`を返却する。
 pub async fn hello() -> impl Responder {
     HttpResponse::Ok().body("Hello world!")
 }
+
+/// 指定されたパスに一致するリソースが存在しない場合に、NOT_FOUNDレスポンスを返却する。
+pub async fn not_found() -> HttpResponse {
+    HttpResponse::NotFound().json(json!({
+        "message": "指定されたリソースが見つかりません。",
+    }))
+}
+
+/// OpenAPI仕様書を公開するパス。
+pub const OPENAPI_PATH: &str = "/api-docs/openapi.json";
+
+/// OpenAPI仕様書API。
+///
+/// Web APIのOpenAPI仕様書をJSONで返却する。
+/// 従来のパス(`/openapi.json`)でも取得できる。
+///
+/// ```bash
+/// curl --include --request GET http://127.0.0.1:8000/api-docs/openapi.json
+/// ```
+pub async fn openapi() -> impl Responder {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
+
+/// Swagger UIを表示するHTML。
+///
+/// Swagger UIはCDNから読み込み、`OPENAPI_PATH`で公開しているOpenAPI仕様書を表示する。
+const SWAGGER_UI_HTML: &str = r##"<!DOCTYPE html>
+<html lang="ja">
+<head>
+  <meta charset="utf-8">
+  <title>actixweb-seaorm-example API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    window.onload = () => {
+      window.ui = SwaggerUIBundle({ url: "{openapi_path}", dom_id: "#swagger-ui" });
+    };
+  </script>
+</body>
+</html>
+"##;
+
+/// Swagger UI。
+///
+/// OpenAPI仕様書をSwagger UIで表示する。環境変数`SWAGGER_UI_ENABLED`が`true`の場合のみ公開する。
+///
+/// ```bash
+/// curl --include --request GET http://127.0.0.1:8000/swagger-ui
+/// ```
+pub async fn swagger_ui() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(SWAGGER_UI_HTML.replace("{openapi_path}", OPENAPI_PATH))
+}
+
+/// メトリクスAPI。
+///
+/// リクエスト数、エラー数、処理時間及びデータベーストランザクションの処理時間を、
+/// Prometheusのテキスト形式で返却する。環境変数`METRICS_ENABLED`が`true`の場合のみ公開する。
+///
+/// ```bash
+/// curl --include --request GET http://127.0.0.1:8000/metrics
+/// ```
+pub async fn metrics(metrics: web::Data<Metrics>) -> HttpResponse {
+    match metrics.encode() {
+        Ok(body) => HttpResponse::Ok()
+            .content_type(prometheus::TEXT_FORMAT)
+            .body(body),
+        Err(err) => {
+            log::error!("{}", err);
+            HttpResponse::InternalServerError().json(json!({
+                "message": "メトリクスを出力できません。",
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod api_doc_handler_tests {
+    use actix_web::{
+        http::{header, StatusCode},
+        test::{self, TestRequest},
+        App,
+    };
+
+    use super::*;
+
+    /// Swagger UIのHTMLが、公開しているOpenAPI仕様書を参照していることを確認する。
+    #[actix_web::test]
+    async fn test_swagger_ui() {
+        let app = test::init_service(
+            App::new()
+                .route(OPENAPI_PATH, web::get().to(openapi))
+                .route("/swagger-ui", web::get().to(swagger_ui)),
+        )
+        .await;
+        let req = TestRequest::get().uri("/swagger-ui").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/html; charset=utf-8"
+        );
+        let body = test::read_body(res).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(
+            body.contains(r#"url: "/api-docs/openapi.json""#),
+            "{}",
+            body
+        );
+
+        let req = TestRequest::get().uri(OPENAPI_PATH).to_request();
+        let doc: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert!(doc["paths"]["/accounts/{id}"].is_object());
+    }
+}
@@ -1,8 +1,15 @@
 use actix_web::{web, HttpResponse, Responder};
 use serde_json::json;
 
+use domains::models::common::Prefecture;
+use usecases::accounts::AccountDto;
 use usecases::database_service::DatabaseService;
-use usecases::prefectures;
+use usecases::prefectures::{self, PrefectureCodes, RegionWithPrefectures};
+use usecases::queries::{CreatedAtRange, Page, Pagination, PrefectureStats};
+
+use crate::{
+    authorization::RequireAdmin, openapi::ErrorMessage, prefecture_cache::CachedPrefectureService,
+};
 
 /// 内部サーバーエラーレスポンスを生成する。
 ///
@@ -20,39 +27,102 @@ fn internal_server_error(err: anyhow::Error) -> HttpResponse {
 /// 都道府県リストAPI。
 ///
 /// 都道府県のリストをJSONで返却する。
+/// 都道府県のリストはキャッシュから返却する。
 ///
 /// # Arguments
 ///
 /// * `db_service` - データベースサービス。
+/// * `cache` - 都道府県キャッシュサービス。
 ///
 /// # Returns
 ///
 /// レスポンス。
-pub async fn list(db_service: web::Data<dyn DatabaseService>) -> impl Responder {
-    match prefectures::list(db_service.as_ref()).await {
+#[utoipa::path(
+    get,
+    path = "/prefectures",
+    tag = "prefectures",
+    responses(
+        (status = 200, description = "都道府県のリスト", body = [Prefecture]),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    )
+)]
+pub async fn list(
+    db_service: web::Data<dyn DatabaseService>,
+    cache: web::Data<CachedPrefectureService>,
+) -> impl Responder {
+    match cache.list(|| prefectures::list(db_service.as_ref())).await {
         Ok(prefectures) => HttpResponse::Ok().json(prefectures),
         Err(err) => internal_server_error(err),
     }
 }
 
+/// 地方別都道府県リストAPI。
+///
+/// 地方別に分類した都道府県のリストをJSONで返却する。
+/// 都道府県のリストはキャッシュから取得する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `cache` - 都道府県キャッシュサービス。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    get,
+    path = "/prefectures/regions",
+    tag = "prefectures",
+    responses(
+        (status = 200, description = "地方別都道府県のリスト", body = [RegionWithPrefectures]),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    )
+)]
+pub async fn list_by_region(
+    db_service: web::Data<dyn DatabaseService>,
+    cache: web::Data<CachedPrefectureService>,
+) -> impl Responder {
+    match cache.list(|| prefectures::list(db_service.as_ref())).await {
+        Ok(list) => HttpResponse::Ok().json(prefectures::group_by_region(list)),
+        Err(err) => internal_server_error(err),
+    }
+}
+
 /// 都道府県検索API。
 ///
 /// 指定された都道府県コードと一致する都道府県をJSONで返却する。
+/// 都道府県はキャッシュした都道府県のリストから検索する。
 ///
 /// # Arguments
 ///
 /// * `db_service` - データベースサービス。
+/// * `cache` - 都道府県キャッシュサービス。
 /// * `path` - 引数で指定されたデータを格納するタプル。
 ///
 /// # Returns
 ///
 /// レスポンス。
+#[utoipa::path(
+    get,
+    path = "/prefectures/{code}",
+    tag = "prefectures",
+    params(("code" = u8, Path, description = "都道府県コード")),
+    responses(
+        (status = 200, description = "都道府県", body = Prefecture),
+        (status = 404, description = "都道府県が見つからない", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    )
+)]
 pub async fn find_by_code(
     db_service: web::Data<dyn DatabaseService>,
+    cache: web::Data<CachedPrefectureService>,
     path: web::Path<(u8,)>,
 ) -> impl Responder {
     let code = path.into_inner().0;
-    match prefectures::find_by_code(db_service.as_ref(), code).await {
+    match cache
+        .find_by_code(code, || prefectures::list(db_service.as_ref()))
+        .await
+    {
         Ok(result) => match result {
             Some(prefecture) => HttpResponse::Ok().json(prefecture),
             _ => HttpResponse::NotFound().json(json!({
@@ -66,3 +136,317 @@ pub async fn find_by_code(
         Err(err) => internal_server_error(err),
     }
 }
+
+/// 都道府県一括取得API。
+///
+/// リクエストボディで指定された都道府県コードと一致する都道府県を、指定された都道府県コードの順にJSONで返却する。
+/// 都道府県が見つからなかった都道府県コードの要素は`null`にする。
+/// 都道府県コードが数値でないなど、リクエストボディをデシリアライズできない場合はUNPROCESSABLE_ENTITYを返却する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `data` - 都道府県コードのリスト。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    post,
+    path = "/prefectures/batch",
+    tag = "prefectures",
+    request_body = PrefectureCodes,
+    responses(
+        (status = 200, description = "都道府県コードの順に並べた都道府県のリスト", body = [Option<Prefecture>]),
+        (status = 400, description = "リクエストボディをJSONとして解釈できない", body = ErrorMessage),
+        (status = 422, description = "都道府県コードのリストが不足しているか、型が不正", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    )
+)]
+pub async fn find_by_codes(
+    db_service: web::Data<dyn DatabaseService>,
+    data: web::Json<PrefectureCodes>,
+) -> impl Responder {
+    match prefectures::find_by_codes(db_service.as_ref(), &data.codes).await {
+        Ok(prefectures) => HttpResponse::Ok().json(prefectures),
+        Err(err) => internal_server_error(err),
+    }
+}
+
+/// 都道府県別アカウントリストAPI。
+///
+/// URLで指定された都道府県コードの都道府県に住所を登録しているアカウントを、登録日時順にページ単位でJSONで返却する。
+/// クエリパラメーター`createdFrom`及び`createdTo`をISO 8601形式で指定した場合は、登録日時がその範囲のアカウントのみを返却する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `path` - 都道府県コードを格納したタプル。
+/// * `created_at` - 登録日時の範囲指定。日時を解釈できなかった場合はエラー。
+/// * `pagination` - ページ指定。
+/// * `_admin` - 管理者のクレイム。管理者のみアカウントのリストを取得できる。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    get,
+    path = "/prefectures/{code}/accounts",
+    tag = "prefectures",
+    params(("code" = u8, Path, description = "都道府県コード"), CreatedAtRange, Pagination),
+    responses(
+        (status = 200, description = "アカウントのページ", body = Page<AccountDto>),
+        (status = 400, description = "登録日時の範囲指定またはページ指定が不正", body = ErrorMessage),
+        (status = 401, description = "認証されていない"),
+        (status = 403, description = "管理者ではない", body = ErrorMessage),
+        (status = 404, description = "都道府県が見つからない", body = ErrorMessage),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_accounts(
+    db_service: web::Data<dyn DatabaseService>,
+    path: web::Path<(u8,)>,
+    created_at: Result<web::Query<CreatedAtRange>, actix_web::Error>,
+    pagination: web::Query<Pagination>,
+    _admin: RequireAdmin,
+) -> impl Responder {
+    let code = path.into_inner().0;
+    let created_at = match created_at {
+        Ok(created_at) => created_at.into_inner(),
+        Err(_) => {
+            return HttpResponse::BadRequest().json(json!({
+                "message": "登録日時の範囲(createdFrom、createdTo)は、ISO 8601形式の日時で指定してください。"
+            }))
+        }
+    };
+    if let Err(err) = created_at.validate() {
+        return HttpResponse::BadRequest().json(json!({ "message": format!("{}", err) }));
+    }
+    let pagination = pagination.into_inner();
+    if let Err(err) = pagination.validate() {
+        return HttpResponse::BadRequest().json(json!({ "message": format!("{}", err) }));
+    }
+    match prefectures::list_accounts(db_service.as_ref(), code, created_at, pagination).await {
+        Ok(Some(page)) => HttpResponse::Ok().json(page),
+        Ok(None) => HttpResponse::NotFound().json(json!({
+            "message":
+                format!(
+                    "都道府県コード({})に一致する都道府県が見つかりませんでした。",
+                    code
+                )
+        })),
+        Err(err) => internal_server_error(err),
+    }
+}
+
+/// 都道府県別アカウント数API。
+///
+/// 都道府県別のアカウント数をJSONで返却する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    get,
+    path = "/prefectures/stats",
+    tag = "prefectures",
+    responses(
+        (status = 200, description = "都道府県別アカウント統計のリスト", body = [PrefectureStats]),
+        (status = 500, description = "内部サーバーエラー", body = ErrorMessage),
+    )
+)]
+pub async fn stats(db_service: web::Data<dyn DatabaseService>) -> impl Responder {
+    match prefectures::stats(db_service.as_ref()).await {
+        Ok(stats) => HttpResponse::Ok().json(stats),
+        Err(err) => internal_server_error(err),
+    }
+}
+
+/// 都道府県キャッシュ破棄API。
+///
+/// 都道府県のリストのキャッシュを破棄する。
+/// 次に都道府県を取得したときに、データベースから都道府県のリストを再取得する。
+///
+/// # Arguments
+///
+/// * `_admin` - 管理者のクレイム。
+/// * `cache` - 都道府県キャッシュサービス。
+///
+/// # Returns
+///
+/// レスポンス。
+#[utoipa::path(
+    delete,
+    path = "/prefectures/cache",
+    tag = "prefectures",
+    responses(
+        (status = 204, description = "キャッシュを破棄した"),
+        (status = 401, description = "認証されていない"),
+        (status = 403, description = "管理者ではない", body = ErrorMessage),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn invalidate_cache(
+    _admin: RequireAdmin,
+    cache: web::Data<CachedPrefectureService>,
+) -> impl Responder {
+    cache.invalidate().await;
+
+    HttpResponse::NoContent().finish()
+}
+
+#[cfg(test)]
+mod list_accounts_tests {
+    use std::sync::Arc;
+
+    use actix_web::{
+        http::{header, StatusCode},
+        test::{self, TestRequest},
+        App,
+    };
+    use domains::models::accounts::Role;
+    use sea_orm::DatabaseConnection;
+
+    use super::*;
+    use crate::{authorization::bearer_token, database_service::DatabaseServiceImpl};
+
+    /// 登録日時の範囲指定を解釈できない場合に、データベースにアクセスせずにBAD_REQUESTを返却することを確認する。
+    #[actix_web::test]
+    async fn test_list_accounts_invalid_created_at() {
+        let db_service: Arc<dyn DatabaseService> =
+            Arc::new(DatabaseServiceImpl::new(DatabaseConnection::Disconnected));
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(db_service))
+                .route("/prefectures/{code}/accounts", web::get().to(list_accounts)),
+        )
+        .await;
+        for query in [
+            "createdFrom=2022-04-01",
+            "createdTo=yesterday",
+            "createdFrom=2022-04-02T00:00:00%2B09:00&createdTo=2022-04-01T00:00:00%2B09:00",
+        ] {
+            let req = TestRequest::get()
+                .uri(&format!("/prefectures/13/accounts?{}", query))
+                .insert_header((
+                    header::AUTHORIZATION,
+                    bearer_token("01FVH5SD9H9Y4HNZ4J1Q3E3PFD", Role::Admin),
+                ))
+                .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{}", query);
+            let body: serde_json::Value = test::read_body_json(res).await;
+            assert!(
+                body["message"].as_str().unwrap().contains("createdTo"),
+                "{}",
+                query
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod find_by_codes_tests {
+    use std::sync::Arc;
+
+    use actix_web::{
+        body::MessageBody,
+        dev::{ServiceFactory, ServiceRequest, ServiceResponse},
+        http::{header, StatusCode},
+        test::{self, TestRequest},
+        App,
+    };
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        json_config::json_config, json_error_handlers, mock_database_service::MockDatabaseService,
+    };
+
+    /// 本番と同じJSONリクエストボディの抽出設定及びエラーハンドラーを登録した、都道府県一括取得APIのアプリケーションを構築する。
+    fn batch_app(
+        db_service: Arc<dyn DatabaseService>,
+    ) -> App<
+        impl ServiceFactory<
+            ServiceRequest,
+            Config = (),
+            Response = ServiceResponse<impl MessageBody>,
+            Error = actix_web::Error,
+            InitError = (),
+        >,
+    > {
+        App::new()
+            .wrap(json_error_handlers())
+            .app_data(web::Data::from(db_service))
+            .app_data(json_config(1024 * 1024))
+            .route("/prefectures/batch", web::post().to(find_by_codes))
+    }
+
+    /// 都道府県一括取得APIが、指定された都道府県コードの順に都道府県を返却して、
+    /// 見つからなかった都道府県コードの要素を`null`にすることを確認する。
+    #[actix_web::test]
+    async fn test_find_by_codes_with_mock() {
+        let db_service: Arc<dyn DatabaseService> =
+            Arc::new(MockDatabaseService::account_missing().await);
+        let app = test::init_service(batch_app(db_service)).await;
+        let req = TestRequest::post()
+            .uri("/prefectures/batch")
+            .set_json(json!({ "codes": [13, 99, 27, 1, 0, 13] }))
+            .to_request();
+        let returned: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let codes: Vec<serde_json::Value> = returned
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["code"].clone())
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                json!(13),
+                serde_json::Value::Null,
+                json!(27),
+                json!(1),
+                serde_json::Value::Null,
+                json!(13),
+            ]
+        );
+        assert_eq!(returned[0]["name"], "東京都");
+        assert!(returned[1].is_null());
+        // 都道府県コードが数値でない場合
+        let req = TestRequest::post()
+            .uri("/prefectures/batch")
+            .set_json(json!({ "codes": ["13"] }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["code"], "VALIDATION_ERROR");
+        // リクエストボディをJSONとして解釈できない場合
+        let req = TestRequest::post()
+            .uri("/prefectures/batch")
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .set_payload(r#"{"codes": [13"#)
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    /// データベースの操作に失敗した場合に、INTERNAL_SERVER_ERRORを返却することを確認する。
+    #[actix_web::test]
+    async fn test_find_by_codes_db_error_with_mock() {
+        let db_service: Arc<dyn DatabaseService> = Arc::new(MockDatabaseService::db_error().await);
+        let app = test::init_service(batch_app(db_service)).await;
+        let req = TestRequest::post()
+            .uri("/prefectures/batch")
+            .set_json(json!({ "codes": [13] }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,377 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Serialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use usecases::accounts::AccountDto;
+
+/// 冪等キーを指定するHTTPヘッダ名。
+pub const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+/// 冪等キーの最大文字数。
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// 冪等キーに記録したリクエストの処理状況
+enum IdempotencyState {
+    /// リクエストを処理中。
+    InProgress,
+    /// リクエストの処理が完了して、アカウントを登録した。
+    Completed(Box<AccountDto>),
+}
+
+/// 冪等キーに記録したリクエスト
+struct IdempotencyEntry {
+    /// リクエストボディのフィンガープリント。
+    fingerprint: Vec<u8>,
+    /// リクエストの処理状況。
+    state: IdempotencyState,
+    /// 記録した日時。
+    stored_at: Instant,
+}
+
+/// 冪等キーでリクエストを開始した結果
+#[derive(Debug)]
+pub enum IdempotencyStatus {
+    /// 初めて受け付けた冪等キーで、リクエストを処理する。
+    New,
+    /// 同じリクエストボディで登録したアカウント。
+    Replay(Box<AccountDto>),
+    /// 同じ冪等キーで、異なるリクエストボディのリクエストを受け付けている。
+    Mismatch,
+    /// 同じ冪等キーのリクエストを処理中。
+    InProgress,
+}
+
+/// 冪等キーストア
+///
+/// `Idempotency-Key`ヘッダを指定したアカウント登録リクエストについて、冪等キーと登録したアカウントを
+/// 有効期間の間メモリに記録して、タイムアウトなどで再送されたリクエストでアカウントを重複して登録しないようにする。
+/// 記録はWeb APIサーバーのプロセスごとに保持するため、複数のプロセスで冪等キーは共有されない。
+/// `IdempotencyGuard`が冪等キーの記録をドロップ時に破棄できるように、ロックを保持したまま待機しない。
+pub struct IdempotencyStore {
+    /// 冪等キーと、冪等キーに記録したリクエスト。
+    entries: Mutex<HashMap<String, IdempotencyEntry>>,
+    /// 冪等キーを記録する有効期間。
+    ttl: Duration,
+}
+
+impl IdempotencyStore {
+    /// コンストラクタ。
+    ///
+    /// # Arguments
+    ///
+    /// * `ttl` - 冪等キーを記録する有効期間。
+    ///
+    /// # Returns
+    ///
+    /// 冪等キーストア。
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// 冪等キーでリクエストの処理を開始する。
+    ///
+    /// 初めて受け付けた冪等キーの場合は、リクエストを処理中として記録する。
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - 冪等キー。
+    /// * `fingerprint` - リクエストボディのフィンガープリント。
+    ///
+    /// # Returns
+    ///
+    /// 冪等キーでリクエストを開始した結果。
+    pub fn begin(&self, key: &str, fingerprint: Vec<u8>) -> IdempotencyStatus {
+        let mut entries = self.entries();
+        // 有効期間を経過した冪等キーを破棄
+        entries.retain(|_, entry| entry.stored_at.elapsed() < self.ttl);
+        if let Some(entry) = entries.get(key) {
+            if entry.fingerprint != fingerprint {
+                return IdempotencyStatus::Mismatch;
+            }
+            return match &entry.state {
+                IdempotencyState::InProgress => IdempotencyStatus::InProgress,
+                IdempotencyState::Completed(account) => IdempotencyStatus::Replay(account.clone()),
+            };
+        }
+        entries.insert(
+            key.to_owned(),
+            IdempotencyEntry {
+                fingerprint,
+                state: IdempotencyState::InProgress,
+                stored_at: Instant::now(),
+            },
+        );
+
+        IdempotencyStatus::New
+    }
+
+    /// 冪等キーで処理したリクエストで、登録したアカウントを記録する。
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - 冪等キー。
+    /// * `account` - 登録したアカウント。
+    pub fn complete(&self, key: &str, account: &AccountDto) {
+        if let Some(entry) = self.entries().get_mut(key) {
+            entry.state = IdempotencyState::Completed(Box::new(account.clone()));
+            entry.stored_at = Instant::now();
+        }
+    }
+
+    /// 冪等キーで処理したリクエストが失敗したため、同じ冪等キーで再試行できるように記録を破棄する。
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - 冪等キー。
+    pub fn abort(&self, key: &str) {
+        self.entries().remove(key);
+    }
+
+    /// 冪等キーの記録のロックを取得する。
+    ///
+    /// 記録を更新中に他のスレッドがパニックした場合でも、記録を使用し続ける。
+    ///
+    /// # Returns
+    ///
+    /// 冪等キーの記録のロック。
+    fn entries(&self) -> MutexGuard<'_, HashMap<String, IdempotencyEntry>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 冪等キーで処理中のリクエスト
+///
+/// `complete`を呼び出さずにドロップされた場合は、同じ冪等キーで再試行できるように、冪等キーの記録を破棄する。
+/// ハンドラーがエラーを返却した場合に加えて、クライアントが切断してハンドラーのフューチャーがドロップされた場合でも、
+/// 冪等キーが処理中のまま残らないようにする。
+pub struct IdempotencyGuard {
+    /// 冪等キーストア。
+    store: web::Data<IdempotencyStore>,
+    /// 冪等キー。
+    key: String,
+    /// 登録したアカウントを記録した場合は`true`。
+    completed: bool,
+}
+
+impl IdempotencyGuard {
+    /// コンストラクタ。
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - 冪等キーストア。
+    /// * `key` - `IdempotencyStore::begin`で処理を開始した冪等キー。
+    ///
+    /// # Returns
+    ///
+    /// 冪等キーで処理中のリクエスト。
+    pub fn new(store: web::Data<IdempotencyStore>, key: String) -> Self {
+        Self {
+            store,
+            key,
+            completed: false,
+        }
+    }
+
+    /// 冪等キーで処理したリクエストで、登録したアカウントを記録する。
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - 登録したアカウント。
+    pub fn complete(mut self, account: &AccountDto) {
+        self.store.complete(&self.key, account);
+        self.completed = true;
+    }
+}
+
+impl Drop for IdempotencyGuard {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.store.abort(&self.key);
+        }
+    }
+}
+
+/// リクエストボディのフィンガープリントを生成する。
+///
+/// # Arguments
+///
+/// * `body` - リクエストボディ。
+///
+/// # Returns
+///
+/// リクエストボディをJSONにシリアライズしたSHA-256ハッシュ。
+pub fn fingerprint<T: Serialize>(body: &T) -> Vec<u8> {
+    Sha256::digest(serde_json::to_vec(body).unwrap_or_default()).to_vec()
+}
+
+/// リクエストの`Idempotency-Key`ヘッダから冪等キーを取得する。
+///
+/// # Arguments
+///
+/// * `req` - リクエスト。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 冪等キー。`Idempotency-Key`ヘッダが指定されていない場合は`None`。
+/// * `Err`: 冪等キーが空、長すぎる、または表示可能なASCII文字以外を含む場合のBAD_REQUESTレスポンス。
+#[allow(clippy::result_large_err)]
+pub fn idempotency_key(req: &HttpRequest) -> Result<Option<String>, HttpResponse> {
+    let value = match req.headers().get(IDEMPOTENCY_KEY) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    match value.to_str().map(str::trim) {
+        Ok(key)
+            if !key.is_empty()
+                && key.len() <= MAX_IDEMPOTENCY_KEY_LEN
+                && key.chars().all(|c| c.is_ascii_graphic()) =>
+        {
+            Ok(Some(key.to_owned()))
+        }
+        _ => Err(HttpResponse::BadRequest().json(json!({
+            "message": format!(
+                "Idempotency-Keyヘッダには、{}文字以内の表示可能なASCII文字を指定してください。",
+                MAX_IDEMPOTENCY_KEY_LEN
+            )
+        }))),
+    }
+}
+
+#[cfg(test)]
+mod idempotency_tests {
+    use actix_web::test::TestRequest;
+    use chrono::{FixedOffset, TimeZone};
+    use domains::models::{
+        accounts::{AccountId, AccountName, Role},
+        common::{AddressDetails, EmailAddress, PostalCode},
+    };
+
+    use super::*;
+
+    /// 指定されたアカウントIDのアカウントを構築する。
+    fn account_dto(id: AccountId) -> AccountDto {
+        let dt = FixedOffset::east_opt(9 * 60 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2022, 4, 1, 9, 0, 0)
+            .unwrap();
+        AccountDto {
+            id,
+            email: EmailAddress::new("foo@example.com").unwrap(),
+            name: AccountName::new("foo").unwrap(),
+            is_active: true,
+            role: Role::User,
+            fixed_number: None,
+            mobile_number: None,
+            postal_code: PostalCode::new("100-0014").unwrap(),
+            prefecture_code: 13,
+            prefecture_name: "東京都".to_owned(),
+            address_details: AddressDetails::new("千代田区永田町1-7-1").unwrap(),
+            logged_in_at: None,
+            created_at: dt,
+            updated_at: dt,
+            updated_by: None,
+        }
+    }
+
+    /// 同じ冪等キーと同じリクエストボディで再試行した場合に、登録したアカウントを返却することを確認する。
+    #[test]
+    fn test_replay() {
+        let store = IdempotencyStore::new(Duration::from_secs(60));
+        let body = json!({"email": "foo@example.com"});
+        assert!(matches!(
+            store.begin("key", fingerprint(&body)),
+            IdempotencyStatus::New
+        ));
+        // 処理中の場合
+        assert!(matches!(
+            store.begin("key", fingerprint(&body)),
+            IdempotencyStatus::InProgress
+        ));
+        let id = AccountId::gen();
+        store.complete("key", &account_dto(id.clone()));
+        match store.begin("key", fingerprint(&body)) {
+            IdempotencyStatus::Replay(account) => assert_eq!(account.id, id),
+            status => panic!("{:?}", status),
+        }
+        // 異なるリクエストボディの場合
+        let other = json!({"email": "bar@example.com"});
+        assert!(matches!(
+            store.begin("key", fingerprint(&other)),
+            IdempotencyStatus::Mismatch
+        ));
+    }
+
+    /// 処理に失敗した冪等キーと、有効期間を経過した冪等キーで、リクエストを再度処理できることを確認する。
+    #[test]
+    fn test_abort_and_expire() {
+        let store = IdempotencyStore::new(Duration::from_secs(60));
+        let body = json!({"email": "foo@example.com"});
+        store.begin("key", fingerprint(&body));
+        store.abort("key");
+        assert!(matches!(
+            store.begin("key", fingerprint(&body)),
+            IdempotencyStatus::New
+        ));
+
+        let store = IdempotencyStore::new(Duration::ZERO);
+        store.begin("key", fingerprint(&body));
+        store.complete("key", &account_dto(AccountId::gen()));
+        assert!(matches!(
+            store.begin("key", fingerprint(&body)),
+            IdempotencyStatus::New
+        ));
+    }
+
+    /// 冪等キーで処理中のリクエストを、登録したアカウントを記録せずにドロップした場合は冪等キーの記録を破棄して、
+    /// 記録した場合は登録したアカウントを返却することを確認する。
+    #[test]
+    fn test_guard() {
+        let store = web::Data::new(IdempotencyStore::new(Duration::from_secs(60)));
+        let body = json!({"email": "foo@example.com"});
+        store.begin("key", fingerprint(&body));
+        drop(IdempotencyGuard::new(store.clone(), "key".to_owned()));
+        assert!(matches!(
+            store.begin("key", fingerprint(&body)),
+            IdempotencyStatus::New
+        ));
+
+        let guard = IdempotencyGuard::new(store.clone(), "key".to_owned());
+        let id = AccountId::gen();
+        guard.complete(&account_dto(id.clone()));
+        match store.begin("key", fingerprint(&body)) {
+            IdempotencyStatus::Replay(account) => assert_eq!(account.id, id),
+            status => panic!("{:?}", status),
+        }
+    }
+
+    /// `Idempotency-Key`ヘッダから冪等キーを取得できることを確認する。
+    #[test]
+    fn test_idempotency_key() {
+        let req = TestRequest::default()
+            .insert_header((IDEMPOTENCY_KEY, " abc-123 "))
+            .to_http_request();
+        assert_eq!(idempotency_key(&req).unwrap(), Some("abc-123".to_owned()));
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(idempotency_key(&req).unwrap(), None);
+        for value in ["", "a b", &"a".repeat(MAX_IDEMPOTENCY_KEY_LEN + 1)] {
+            let req = TestRequest::default()
+                .insert_header((IDEMPOTENCY_KEY, value))
+                .to_http_request();
+            assert!(idempotency_key(&req).is_err(), "{}", value);
+        }
+    }
+}
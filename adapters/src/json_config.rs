@@ -0,0 +1,188 @@
+use actix_web::{
+    error::{InternalError, JsonPayloadError},
+    web, HttpRequest, HttpResponse, ResponseError,
+};
+use serde_json::json;
+
+/// JSONリクエストボディの抽出設定を構築する。
+///
+/// リクエストボディのサイズを制限して、サイズの超過やJSONの不正を、
+/// `code`と`message`を含むJSONのエラーレスポンスとして返却する。
+/// JSONとして解釈できないリクエストボディはBAD_REQUEST、JSONとして解釈できるが、
+/// フィールドの不足や型の誤りなどでデシリアライズできないリクエストボディはUNPROCESSABLE_ENTITYで拒否する。
+///
+/// # Arguments
+///
+/// * `limit` - リクエストボディの最大バイト数。
+///
+/// # Returns
+///
+/// JSONリクエストボディの抽出設定。
+pub fn json_config(limit: usize) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(limit)
+        .error_handler(json_error_handler)
+}
+
+/// JSONリクエストボディの抽出に失敗したときのエラーを、JSONのエラーレスポンスに変換する。
+///
+/// # Arguments
+///
+/// * `err` - JSONリクエストボディの抽出エラー。
+/// * `_req` - リクエスト。
+///
+/// # Returns
+///
+/// エラーレスポンスを格納したエラー。
+fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let response = match &err {
+        JsonPayloadError::OverflowKnownLength { limit, .. }
+        | JsonPayloadError::Overflow { limit } => HttpResponse::PayloadTooLarge().json(json!({
+            "code": "PAYLOAD_TOO_LARGE",
+            "message": format!("リクエストボディが最大サイズ({}バイト)を超えています。", limit),
+        })),
+        JsonPayloadError::Deserialize(err) if err.is_data() => {
+            let message = match field_name(err) {
+                Some(field) => format!("リクエストボディの{}が不正です。({})", field, err),
+                None => format!("リクエストボディの値が不正です。({})", err),
+            };
+            HttpResponse::UnprocessableEntity().json(json!({
+                "code": "VALIDATION_ERROR",
+                "message": message,
+                "field": field_name(err),
+                "line": err.line(),
+                "column": err.column(),
+            }))
+        }
+        JsonPayloadError::Deserialize(err) => HttpResponse::BadRequest().json(json!({
+            "code": "INVALID_JSON",
+            "message": format!("リクエストボディがJSONとして解釈できません。({})", err),
+            "line": err.line(),
+            "column": err.column(),
+        })),
+        JsonPayloadError::ContentType => HttpResponse::UnsupportedMediaType().json(json!({
+            "code": "UNSUPPORTED_MEDIA_TYPE",
+            "message": "リクエストボディのContent-Typeはapplication/jsonを指定してください。",
+        })),
+        _ => HttpResponse::build(err.status_code()).json(json!({
+            "code": "INVALID_REQUEST_BODY",
+            "message": format!("リクエストボディを読み込めません。({})", err),
+        })),
+    };
+
+    InternalError::from_response(err, response).into()
+}
+
+/// JSONのデシリアライズエラーから、エラーの原因となったフィールド名を取得する。
+///
+/// serdeは、フィールドが不足している場合や、未知または重複したフィールドが指定された場合のみ、
+/// エラーメッセージにフィールド名を含める。
+///
+/// # Arguments
+///
+/// * `err` - JSONのデシリアライズエラー。
+///
+/// # Returns
+///
+/// フィールド名。エラーメッセージにフィールド名が含まれていない場合は`None`。
+fn field_name(err: &serde_json::Error) -> Option<String> {
+    let message = err.to_string();
+    ["missing field `", "unknown field `", "duplicate field `"]
+        .iter()
+        .find_map(|prefix| message.split_once(prefix))
+        .and_then(|(_, rest)| rest.split_once('`'))
+        .map(|(field, _)| field.to_owned())
+}
+
+#[cfg(test)]
+mod json_config_tests {
+    use actix_web::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        App,
+    };
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Body {
+        #[allow(dead_code)]
+        email: String,
+        #[allow(dead_code)]
+        prefecture_code: u8,
+    }
+
+    async fn accept(_body: web::Json<Body>) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    /// 最大サイズを超えるリクエストボディを、PAYLOAD_TOO_LARGEで拒否することを確認する。
+    #[actix_web::test]
+    async fn test_oversized_body() {
+        let app = test::init_service(
+            App::new()
+                .app_data(json_config(64))
+                .route("/", web::post().to(accept)),
+        )
+        .await;
+        let body = json!({"email": "a".repeat(100), "prefectureCode": 13});
+        let req = TestRequest::post().uri("/").set_json(body).to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["code"], "PAYLOAD_TOO_LARGE");
+        assert!(body["message"].as_str().unwrap().contains("64"));
+    }
+
+    /// JSONとして解釈できないリクエストボディを、BAD_REQUESTで拒否することを確認する。
+    #[actix_web::test]
+    async fn test_malformed_body() {
+        let app = test::init_service(
+            App::new()
+                .app_data(json_config(1024))
+                .route("/", web::post().to(accept)),
+        )
+        .await;
+        let req = TestRequest::post()
+            .uri("/")
+            .insert_header(("content-type", "application/json"))
+            .set_payload(r#"{"email": "foo@example.com""#)
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let res: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(res["code"], "INVALID_JSON");
+        assert!(res["line"].as_u64().unwrap() >= 1);
+    }
+
+    /// デシリアライズできないリクエストボディを、フィールド名を含めてUNPROCESSABLE_ENTITYで拒否することを確認する。
+    #[actix_web::test]
+    async fn test_invalid_body() {
+        let app = test::init_service(
+            App::new()
+                .app_data(json_config(1024))
+                .route("/", web::post().to(accept)),
+        )
+        .await;
+        for (body, field) in [
+            (r#"{"prefectureCode": 13}"#, Some("email")),
+            (
+                r#"{"email": "foo@example.com", "prefectureCode": "x"}"#,
+                None,
+            ),
+        ] {
+            let req = TestRequest::post()
+                .uri("/")
+                .insert_header(("content-type", "application/json"))
+                .set_payload(body)
+                .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+            let res: serde_json::Value = test::read_body_json(res).await;
+            assert_eq!(res["code"], "VALIDATION_ERROR", "{}", body);
+            assert_eq!(res["field"].as_str(), field, "{}", body);
+        }
+    }
+}
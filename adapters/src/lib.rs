@@ -1,25 +1,72 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{fmt::Display, future::Future, io, net::SocketAddr, sync::Arc, time::Duration};
 
+use actix_cors::Cors;
 use actix_web::{
     self,
+    body::MessageBody,
+    dev::{RequestHead, ServerHandle, ServiceFactory, ServiceRequest, ServiceResponse},
+    http::{
+        header::{self, HeaderName, HeaderValue},
+        Method, StatusCode,
+    },
+    middleware::{Compress, Condition, ErrorHandlerResponse, ErrorHandlers},
     web::{self, Data},
-    App, HttpServer,
+    App, HttpResponse, HttpServer,
 };
 use anyhow::anyhow;
-use sea_orm::Database;
+use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use serde_json::json;
 
-use common::ENV_VALUES;
-use usecases::database_service::DatabaseService;
+use common::{hashing::HashingConfig, ENV_VALUES};
+use domains::services::{
+    clock::{Clock, SystemClock},
+    hashers::{PasswordHasher, PasswordHasherImpl},
+};
+use usecases::{
+    accounts::MAX_BULK_INSERT_ACCOUNTS,
+    database_service::DatabaseService,
+    events::{AccountEventSink, NoopAccountEventSink},
+};
 
+mod access_log;
+mod app_error;
+mod authorization;
 mod database_service;
 mod handlers;
-use crate::database_service::DatabaseServiceImpl;
+mod idempotency;
+mod json_config;
+mod metrics;
+mod migrations;
+#[cfg(test)]
+mod mock_database_service;
+mod openapi;
+mod prefecture_cache;
+mod rate_limit;
+mod request_id;
+mod request_transaction;
+mod tls;
+mod token_validation;
+use crate::{
+    access_log::AccessLogMiddleware,
+    database_service::DatabaseServiceImpl,
+    idempotency::IdempotencyStore,
+    json_config::json_config,
+    metrics::{Metrics, MetricsMiddleware},
+    prefecture_cache::CachedPrefectureService,
+    rate_limit::{RateLimitMiddleware, RateLimiter},
+    request_id::RequestIdMiddleware,
+    token_validation::TokenValidationMiddleware,
+};
+
+pub use crate::migrations::MIGRATOR;
+pub use crate::request_transaction::{RequestTransaction, RequestTransactionMiddleware};
 
 /// Web APIサーバーを起動する。
 ///
 /// # Arguments
 ///
 /// * `address` - Web APIサーバーのソケットアドレス。
+/// * `migrate` - データベースに接続した後で、実行されていないマイグレーションを実行する場合は`true`。
 ///
 /// # Returns
 ///
@@ -27,58 +74,543 @@ use crate::database_service::DatabaseServiceImpl;
 ///
 /// * `Ok`: ()
 /// * `Err`: エラー。
-pub async fn run(address: &SocketAddr) -> anyhow::Result<()> {
+pub async fn run(address: &SocketAddr, migrate: bool) -> anyhow::Result<()> {
+    // サーバー証明書と秘密鍵が設定されている場合は、データベースに接続する前にTLSの設定を構築
+    let tls_config = match (&ENV_VALUES.tls_cert_path, &ENV_VALUES.tls_key_path) {
+        (Some(cert_path), Some(key_path)) => Some(tls::rustls_config(cert_path, key_path)?),
+        _ => None,
+    };
     // データベースに接続
-    log::info!("Connecting to database...");
-    let conn = Database::connect(&ENV_VALUES.database_url)
-        .await
-        .map_err(|_| {
-            anyhow!("環境変数に設定されているDATABASE_URLで、データベースに接続できません。")
-        });
-    let conn = conn.unwrap();
+    let options = connect_options()?;
+    log::info!(
+        "Connecting to database... (max_connections: {}, min_connections: {}, connect_timeout: {}s, idle_timeout: {}s, sqlx_logging: {})",
+        ENV_VALUES.database_max_connections,
+        ENV_VALUES.database_min_connections,
+        ENV_VALUES.database_connect_timeout_seconds,
+        ENV_VALUES.database_idle_timeout_seconds,
+        ENV_VALUES.database_sqlx_logging,
+    );
+    let conn = connect_with_retry(
+        || Database::connect(options.clone()),
+        ENV_VALUES.database_connect_retries,
+        Duration::from_secs(ENV_VALUES.database_connect_retry_interval_seconds),
+    )
+    .await
+    .map_err(|err| {
+        anyhow!(
+            "環境変数に設定されているDATABASE_URLで、データベースに接続できません。{}",
+            err
+        )
+    })?;
     log::info!("Connected to database...");
     // データベースサービスを構築
     let db_service: Arc<dyn DatabaseService> = Arc::new(DatabaseServiceImpl { conn });
+    // Web APIサーバーを起動する前に、実行されていないマイグレーションを実行
+    if migrate {
+        migrations::run_migrations(db_service.as_ref())
+            .await
+            .map_err(|err| anyhow!("マイグレーションを実行できません。{}", err))?;
+        log::info!("Applied all migrations...");
+    }
     let db_service: Data<dyn DatabaseService> = Data::from(db_service);
+    // サーバーが停止した後でデータベースから切断するため、データベースサービスを保持
+    let closing_db_service = db_service.clone();
+    // 時計を構築
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+    let clock: Data<dyn Clock> = Data::from(clock);
+    // パスワードハッシュ化サービスを構築
+    let hasher: Arc<dyn PasswordHasher> =
+        Arc::new(PasswordHasherImpl::new(HashingConfig::from_env()));
+    let hasher: Data<dyn PasswordHasher> = Data::from(hasher);
+    // アカウントイベントシンクを構築
+    let event_sink: Arc<dyn AccountEventSink> = Arc::new(NoopAccountEventSink);
+    let event_sink: Data<dyn AccountEventSink> = Data::from(event_sink);
+    // 都道府県キャッシュサービスを構築
+    let prefecture_cache = Data::new(CachedPrefectureService::new(Duration::from_secs(
+        ENV_VALUES.prefecture_cache_seconds,
+    )));
+    // 冪等キーストアを構築
+    let idempotency = Data::new(IdempotencyStore::new(Duration::from_secs(
+        ENV_VALUES.idempotency_key_ttl_seconds,
+    )));
+    // 認証APIのレートリミッターを構築(すべてのワーカーで共有)
+    let auth_rate_limiter = Arc::new(RateLimiter::new(
+        ENV_VALUES.auth_rate_limit_per_minute,
+        Duration::from_secs(60),
+    ));
+    // メトリクスを構築
+    let metrics = Metrics::new()?;
+    // 環境変数が設定されていない場合は、Actix Webの既定値を使用
+    let workers = ENV_VALUES
+        .web_server_workers
+        .unwrap_or_else(default_workers);
+    let keep_alive = Duration::from_secs(
+        ENV_VALUES
+            .web_server_keep_alive_seconds
+            .unwrap_or(DEFAULT_KEEP_ALIVE_SECONDS),
+    );
+    let client_request_timeout = Duration::from_secs(
+        ENV_VALUES
+            .web_server_client_request_timeout_seconds
+            .unwrap_or(DEFAULT_CLIENT_REQUEST_TIMEOUT_SECONDS),
+    );
+    log::info!(
+        "Web API server settings... (workers: {}, keep_alive: {}s, client_request_timeout: {}s)",
+        workers,
+        keep_alive.as_secs(),
+        client_request_timeout.as_secs(),
+    );
     // Web APIサーバーを起動
-    HttpServer::new(move || {
+    let server = HttpServer::new(move || {
         App::new()
+            .wrap(json_error_handlers())
+            // エラーレスポンスも圧縮するため、エラーハンドラーの外側で圧縮
+            .wrap(Condition::new(
+                ENV_VALUES.http_compression_enabled,
+                Compress::default(),
+            ))
+            .wrap(Condition::new(
+                ENV_VALUES.validate_token_in_db,
+                TokenValidationMiddleware,
+            ))
+            .wrap(cors(
+                &ENV_VALUES.cors_allowed_origins,
+                ENV_VALUES.token_delivery.uses_cookie(),
+            ))
+            .wrap(Condition::new(
+                ENV_VALUES.metrics_enabled,
+                MetricsMiddleware::new(metrics.clone()),
+            ))
+            .wrap(AccessLogMiddleware)
+            .wrap(RequestIdMiddleware)
             .app_data(db_service.clone())
+            .app_data(clock.clone())
+            .app_data(hasher.clone())
+            .app_data(event_sink.clone())
+            .app_data(prefecture_cache.clone())
+            .app_data(idempotency.clone())
+            .app_data(json_config(ENV_VALUES.max_json_body_bytes))
+            .service(web::resource(handlers::OPENAPI_PATH).route(web::get().to(handlers::openapi)))
+            .service(web::resource("/openapi.json").route(web::get().to(handlers::openapi)))
+            .configure(|cfg| {
+                if ENV_VALUES.swagger_ui_enabled {
+                    cfg.service(
+                        web::resource("/swagger-ui").route(web::get().to(handlers::swagger_ui)),
+                    );
+                }
+                if ENV_VALUES.metrics_enabled {
+                    cfg.app_data(Data::new(metrics.clone()))
+                        .service(web::resource("/metrics").route(web::get().to(handlers::metrics)));
+                }
+            })
             .service(
                 web::scope("/").service(web::resource("").route(web::get().to(handlers::hello))),
             )
             .service(prefecture_scope())
             .service(accounts_scope())
-            .service(auth_scope())
+            .service(auth_scope(auth_rate_limiter.clone()))
+            .default_service(web::to(handlers::not_found))
     })
-    .bind(address)?
-    .run()
-    .await?;
+    .workers(workers)
+    .keep_alive(keep_alive)
+    .client_request_timeout(client_request_timeout)
+    .shutdown_timeout(ENV_VALUES.shutdown_timeout_seconds)
+    .disable_signals();
+    let (server, scheme) = match tls_config {
+        Some(config) => (server.bind_rustls_0_23(address, config)?, "https"),
+        None => (server.bind(address)?, "http"),
+    };
+    for address in server.addrs() {
+        log::info!("Web API server listening on {}://{}...", scheme, address);
+    }
+    let server = server.run();
+    spawn_shutdown_handler(server.handle())?;
+    server.await?;
+    log::info!("Web API server stopped...");
+    // 処理中のリクエストが完了した後で、データベースから切断
+    log::info!("Closing database connection...");
+    close_connection(closing_db_service.connection()).await;
+    log::info!("Database connection closed...");
+
+    Ok(())
+}
+
+/// コネクションプールのすべてのコネクションを切断する。
+///
+/// # Arguments
+///
+/// * `conn` - データベースコネクション。
+async fn close_connection(conn: &DatabaseConnection) {
+    match conn {
+        DatabaseConnection::SqlxPostgresPoolConnection(_) => {
+            conn.get_postgres_connection_pool().close().await
+        }
+        #[cfg(feature = "sqlite")]
+        DatabaseConnection::SqlxSqlitePoolConnection(_) => {
+            conn.get_sqlite_connection_pool().close().await
+        }
+        _ => {}
+    }
+}
+
+/// キープアライブ接続を維持する秒数の既定値(Actix Webの既定値)。
+const DEFAULT_KEEP_ALIVE_SECONDS: u64 = 5;
+
+/// クライアントからリクエストヘッダを受信するまで待機する秒数の既定値(Actix Webの既定値)。
+const DEFAULT_CLIENT_REQUEST_TIMEOUT_SECONDS: u64 = 5;
+
+/// アカウントインポートAPIで受け付けるCSVの1行あたりのバイト数。
+const IMPORT_BYTES_PER_ROW: usize = 1024;
+
+/// アカウント一括登録APIで受け付けるJSONの1アカウントあたりのバイト数。
+const BULK_INSERT_BYTES_PER_ACCOUNT: usize = 1024;
+
+/// 都道府県コードが登録されていない都道府県を登録して、47都道府県を揃える。
+///
+/// 登録されている都道府県はスキップするため、繰り返し実行できる。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は下記の通り。
+///
+/// * `Ok`: 登録した都道府県の数。
+/// * `Err`: エラー。
+pub async fn seed_prefectures() -> anyhow::Result<u64> {
+    let conn = Database::connect(connect_options()?).await.map_err(|err| {
+        anyhow!(
+            "環境変数に設定されているDATABASE_URLで、データベースに接続できません。{}",
+            err
+        )
+    })?;
+    let db_service = DatabaseServiceImpl { conn };
+    let result = usecases::prefectures::seed_prefectures(&db_service).await;
+    db_service.conn.close().await?;
+
+    result
+}
+
+/// 正規化されていないEメールアドレスで記録されているアカウントのEメールアドレスを、正規化したEメールアドレスに更新する。
+///
+/// 正規化したEメールアドレスが他のアカウントに記録されている場合は、警告をログに出力してスキップする。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は下記の通り。
+///
+/// * `Ok`: Eメールアドレスを更新したアカウントの数。
+/// * `Err`: エラー。
+pub async fn normalize_email_addresses() -> anyhow::Result<u64> {
+    let conn = Database::connect(connect_options()?).await.map_err(|err| {
+        anyhow!(
+            "環境変数に設定されているDATABASE_URLで、データベースに接続できません。{}",
+            err
+        )
+    })?;
+    let db_service = DatabaseServiceImpl { conn };
+    let result = usecases::accounts::normalize_email_addresses(&db_service).await;
+    db_service.conn.close().await?;
+
+    result
+}
+
+/// ワーカースレッド数の既定値を返却する。
+///
+/// Actix Webと同様に、使用できるCPUの数を既定値とする。
+///
+/// # Returns
+///
+/// ワーカースレッド数。使用できるCPUの数を取得できない場合は2。
+fn default_workers() -> usize {
+    std::thread::available_parallelism().map_or(2, |n| n.get())
+}
+
+/// シャットダウンシグナルを受信したときに、Web APIサーバーを停止するタスクを起動する。
+///
+/// SIGTERMまたはSIGINTを受信すると、新しい接続の受け付けを停止して、処理中のリクエストが完了するか、
+/// シャットダウンタイムアウトを経過するまで待機した後で、Web APIサーバーを停止する。
+///
+/// # Arguments
+///
+/// * `handle` - Web APIサーバーのハンドル。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は下記の通り。
+///
+/// * `Ok`: ()
+/// * `Err`: シグナルハンドラの登録に失敗した場合のエラー。
+fn spawn_shutdown_handler(handle: ServerHandle) -> io::Result<()> {
+    #[cfg(unix)]
+    let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+    actix_web::rt::spawn(async move {
+        #[cfg(unix)]
+        tokio::select! {
+            _ = terminate.recv() => log::info!("SIGTERM received, shutting down..."),
+            _ = tokio::signal::ctrl_c() => log::info!("SIGINT received, shutting down..."),
+        }
+        #[cfg(not(unix))]
+        if tokio::signal::ctrl_c().await.is_ok() {
+            log::info!("SIGINT received, shutting down...");
+        }
+        log::info!(
+            "Waiting up to {}s for in-flight requests to complete...",
+            ENV_VALUES.shutdown_timeout_seconds
+        );
+        handle.stop(true).await;
+    });
 
     Ok(())
 }
 
+/// 環境変数からデータベースの接続オプションを構築する。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は下記の通り。
+///
+/// * `Ok`: データベースの接続オプション。
+/// * `Err`: 環境変数に設定されたコネクションプールの設定が不正な場合のエラー。
+fn connect_options() -> anyhow::Result<ConnectOptions> {
+    if ENV_VALUES.database_max_connections == 0 {
+        return Err(anyhow!(
+            "環境変数に設定されているDATABASE_MAX_CONNECTIONSは1以上を指定してください。"
+        ));
+    }
+    if ENV_VALUES.database_max_connections < ENV_VALUES.database_min_connections {
+        return Err(anyhow!(
+            "環境変数に設定されているDATABASE_MAX_CONNECTIONS({})が、DATABASE_MIN_CONNECTIONS({})より小さいです。",
+            ENV_VALUES.database_max_connections,
+            ENV_VALUES.database_min_connections,
+        ));
+    }
+    let mut options = ConnectOptions::new(ENV_VALUES.database_url.clone());
+    options
+        .max_connections(ENV_VALUES.database_max_connections)
+        .min_connections(ENV_VALUES.database_min_connections)
+        .connect_timeout(Duration::from_secs(
+            ENV_VALUES.database_connect_timeout_seconds,
+        ))
+        .idle_timeout(Duration::from_secs(
+            ENV_VALUES.database_idle_timeout_seconds,
+        ))
+        .sqlx_logging(ENV_VALUES.database_sqlx_logging);
+    // SQLiteのインメモリデータベースは、コネクションごとに別のデータベースになるため、コネクションを1つに制限
+    if infra::sqlite::is_sqlite_memory_url(&ENV_VALUES.database_url) {
+        options.max_connections(1).min_connections(1);
+    }
+
+    Ok(options)
+}
+
+/// フレームワークが返却するエラーレスポンスを、JSONのエラーレスポンスに置き換えるミドルウェアを構築する。
+///
+/// リソースが許可していないメソッドで呼び出された場合に返却される、ボディが空のMETHOD_NOT_ALLOWEDレスポンスを、
+/// `Allow`ヘッダを維持したままJSONのレスポンスに置き換える。
+/// ハンドラーはMETHOD_NOT_ALLOWEDレスポンスを返却しないため、すべてのMETHOD_NOT_ALLOWEDレスポンスを置き換える。
+///
+/// # Returns
+///
+/// エラーハンドラーミドルウェア。
+fn json_error_handlers<B: 'static>() -> ErrorHandlers<B> {
+    ErrorHandlers::new().handler(StatusCode::METHOD_NOT_ALLOWED, method_not_allowed)
+}
+
+/// METHOD_NOT_ALLOWEDレスポンスを、`Allow`ヘッダを維持したままJSONのレスポンスに置き換える。
+///
+/// # Arguments
+///
+/// * `res` - METHOD_NOT_ALLOWEDレスポンス。
+///
+/// # Returns
+///
+/// JSONのMETHOD_NOT_ALLOWEDレスポンス。
+fn method_not_allowed<B>(res: ServiceResponse<B>) -> actix_web::Result<ErrorHandlerResponse<B>> {
+    let message = format!(
+        "指定されたメソッド({})は許可されていません。",
+        res.request().method()
+    );
+    let allow = res.headers().get(header::ALLOW).cloned();
+    let (req, _) = res.into_parts();
+    let mut response = HttpResponse::MethodNotAllowed();
+    if let Some(allow) = allow {
+        response.insert_header((header::ALLOW, allow));
+    }
+    let res = ServiceResponse::new(req, response.json(json!({ "message": message })));
+
+    Ok(ErrorHandlerResponse::Response(res.map_into_right_body()))
+}
+
+/// CORSミドルウェアを構築する。
+///
+/// Web APIが使用するメソッドと、`Authorization`、`Content-Type`、`Idempotency-Key`、`If-None-Match`及び
+/// `X-Request-Id`ヘッダを許可して、`Location`、`ETag`及び`X-Request-Id`ヘッダをスクリプトに公開する。
+/// 許可するオリジンが指定されていない場合は、Web APIサーバーと同じオリジンからのリクエストのみを許可する。
+/// トークンをクッキーで返却する場合は、許可したオリジンからのクッキーを含むリクエストを許可する。
+///
+/// # Arguments
+///
+/// * `allowed_origins` - リクエストを許可するオリジンのリスト。
+/// * `supports_credentials` - クッキーを含むリクエストを許可する場合は`true`。
+///
+/// # Returns
+///
+/// CORSミドルウェア。
+fn cors(allowed_origins: &[String], supports_credentials: bool) -> Cors {
+    let mut cors = Cors::default()
+        .allowed_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allowed_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            HeaderName::from_static(idempotency::IDEMPOTENCY_KEY),
+            header::IF_NONE_MATCH,
+            HeaderName::from_static(request_id::X_REQUEST_ID),
+        ])
+        .expose_headers([
+            header::LOCATION,
+            header::ETAG,
+            HeaderName::from_static(request_id::X_REQUEST_ID),
+        ])
+        .max_age(3600);
+    if allowed_origins.is_empty() {
+        cors = cors.allowed_origin_fn(is_same_origin);
+    }
+    for origin in allowed_origins {
+        cors = cors.allowed_origin(origin);
+    }
+    // 許可したオリジンのみを応答するため、ワイルドカードのオリジンでクッキーを許可することはない
+    if supports_credentials {
+        cors = cors.supports_credentials();
+    }
+
+    cors
+}
+
+/// リクエストのオリジンが、Web APIサーバーと同じオリジンであるか確認する。
+///
+/// # Arguments
+///
+/// * `origin` - リクエストの`Origin`ヘッダの値。
+/// * `req` - リクエストヘッド。
+///
+/// # Returns
+///
+/// 同じオリジンの場合は`true`。
+fn is_same_origin(origin: &HeaderValue, req: &RequestHead) -> bool {
+    let host = match req.headers().get(header::HOST) {
+        Some(host) => host.as_bytes(),
+        None => return false,
+    };
+    let origin = origin.as_bytes();
+    [&b"http://"[..], &b"https://"[..]]
+        .iter()
+        .any(|scheme| origin.strip_prefix(*scheme) == Some(host))
+}
+
+/// 接続に失敗した場合に、指定された回数だけ再試行してデータベースに接続する。
+///
+/// # Arguments
+///
+/// * `connect` - データベースに接続する関数。
+/// * `retries` - 接続に失敗したときに再試行する回数。
+/// * `interval` - 接続を再試行するまでの待機時間。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は下記の通り。
+///
+/// * `Ok`: データベースコネクション。
+/// * `Err`: 最後に試行した接続で発生したエラー。
+async fn connect_with_retry<T, E, F, Fut>(
+    mut connect: F,
+    retries: u32,
+    interval: Duration,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    let mut attempts = 0;
+    loop {
+        match connect().await {
+            Ok(conn) => return Ok(conn),
+            Err(err) if attempts < retries => {
+                attempts += 1;
+                log::warn!(
+                    "データベースへの接続に失敗しました。{:?}後に再試行します({}/{})。{}",
+                    interval,
+                    attempts,
+                    retries,
+                    err
+                );
+                actix_web::rt::time::sleep(interval).await;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
 /// 都道府県スコープ
 ///
 /// ```bash
+/// # TLS_CERT_PATHとTLS_KEY_PATHを設定してHTTPSで起動した場合は、http://をhttps://に置き換える
+/// # (自己署名証明書の場合は、--cacert <証明書のパス>または--insecureを付与する)
 /// # 都道府県リストAPI
 /// curl --include --request GET http://127.0.0.1:8000/prefectures
 ///
+/// # 地方別都道府県リストAPI
+/// curl --include --request GET http://127.0.0.1:8000/prefectures/regions
+///
+/// # 都道府県別アカウント数API
+/// curl --include --request GET http://127.0.0.1:8000/prefectures/stats
+///
+/// # 都道府県キャッシュ破棄API
+/// curl --include --request DELETE --header "Authorization: Bearer <token>" \
+///     http://127.0.0.1:8000/prefectures/cache
+///
 /// # 都道府県取得API
 /// curl --include --request GET http://127.0.0.1:8000/prefectures/<prefecture_code>
+///
+/// # 都道府県一括取得API
+/// curl --include --request POST --header "Content-Type: application/json" \
+///     --data '{"codes": [13, 27, 1]}' \
+///     http://127.0.0.1:8000/prefectures/batch
+///
+/// # 都道府県別アカウントリストAPI
+/// curl --include --request GET "http://127.0.0.1:8000/prefectures/<prefecture_code>/accounts?page=1&perPage=20"
+///
+/// # 都道府県別アカウントリストAPI(登録日時の範囲指定)
+/// curl --include --get --data-urlencode "createdFrom=2022-04-01T00:00:00+09:00" \
+///     --data-urlencode "createdTo=2022-04-30T23:59:59+09:00" \
+///     http://127.0.0.1:8000/prefectures/<prefecture_code>/accounts
 /// ```
 fn prefecture_scope() -> actix_web::Scope {
     web::scope("/prefectures")
-        .route("", web::get().to(handlers::prefectures::list))
-        .route(
-            "/{code}",
-            web::get().to(handlers::prefectures::find_by_code),
+        .service(web::resource("").route(web::get().to(handlers::prefectures::list)))
+        .service(
+            web::resource("/regions").route(web::get().to(handlers::prefectures::list_by_region)),
+        )
+        .service(web::resource("/stats").route(web::get().to(handlers::prefectures::stats)))
+        .service(
+            web::resource("/cache")
+                .route(web::delete().to(handlers::prefectures::invalidate_cache)),
+        )
+        // `/{code}`より前に登録して、`batch`を都道府県コードとして扱わないようにする
+        .service(
+            web::resource("/batch").route(web::post().to(handlers::prefectures::find_by_codes)),
+        )
+        .service(web::resource("/{code}").route(web::get().to(handlers::prefectures::find_by_code)))
+        .service(
+            web::resource("/{code}/accounts")
+                .route(web::get().to(handlers::prefectures::list_accounts)),
         )
 }
 
 /// アカウントスコープ
 ///
 /// ```bash
+/// # TLS_CERT_PATHとTLS_KEY_PATHを設定してHTTPSで起動した場合は、http://をhttps://に置き換える
+/// # (自己署名証明書の場合は、--cacert <証明書のパス>または--insecureを付与する)
 /// # アカウント取得API
 /// curl --include --request GET http://127.0.0.1:8000/accounts/<account_id>
 ///
@@ -89,41 +621,743 @@ fn prefecture_scope() -> actix_web::Scope {
 ///         "postalCode": "012-3456", "prefectureCode": 13, "addressDetails": "千代田区永田町1-7-1"}' \
 ///     http://127.0.0.1:8000/accounts
 ///
+/// # アカウント登録API(再送してもアカウントを重複して登録しない冪等キーを指定)
+/// curl --include --request POST --header "Content-Type: application/json" \
+///     --header "Idempotency-Key: <uuidなどの一意な値>" \
+///     --data '{"email": "foo@example.com", "name": "foo", "password": "012abcEFG=+", \
+///         "isActive": true, "mobileNumber": "090-1234-5678", \
+///         "postalCode": "012-3456", "prefectureCode": 13, "addressDetails": "千代田区永田町1-7-1"}' \
+///     http://127.0.0.1:8000/accounts
+///
+/// # アカウント一括登録API(管理者のみ)
+/// curl --include --request POST --header "Content-Type: application/json" --header "Authorization: Bearer <token>" \
+///     --data '[{"email": "foo@example.com", "name": "foo", "password": "012abcEFG=+", \
+///         "isActive": true, "fixedNumber": "012-345-6789", "mobileNumber": "090-1234-5678", \
+///         "postalCode": "012-3456", "prefectureCode": 13, "addressDetails": "千代田区永田町1-7-1"}]' \
+///     http://127.0.0.1:8000/accounts/bulk
+///
+/// # アカウントインポートAPI(管理者のみ)
+/// curl --include --request POST --header "Content-Type: text/csv" --header "Authorization: Bearer <token>" \
+///     --data-binary @accounts.csv \
+///     http://127.0.0.1:8000/accounts/import
+///
+/// # アカウントエクスポートAPI(管理者のみ)
+/// curl --request GET --header "Authorization: Bearer <token>" --output accounts.csv \
+///     http://127.0.0.1:8000/accounts/export
+///
 /// # アカウント更新API
-/// curl --include --request PUT --header "Content-Type: application/json" \
+/// curl --include --request PUT --header "Content-Type: application/json" --header "Authorization: Bearer <token>" \
 ///     --data '{"id": "<account_id>", "name": "foo", "isActive": false, "fixedNumber": "06-6208-8181", \
-///         "postalCode": "530-8201", "prefectureCode": 27, "addressDetails": "大阪市北区中之島1-3-20"}' \
+///         "postalCode": "530-8201", "prefectureCode": 27, "addressDetails": "大阪市北区中之島1-3-20", \
+///         "updatedAt": "<アカウント取得APIで取得したupdatedAt>"}' \
 ///     http://127.0.0.1:8000/accounts/<account_id>
 ///
-/// # アカウント削除API
-/// curl --include --request DELETE http://127.0.0.1:8000/accounts/<account_id>
+/// # アカウント削除API(アカウント本人はパスワードを指定、管理者は指定しなくてもよい)
+/// curl --include --request DELETE --header "Content-Type: application/json" --header "Authorization: Bearer <token>" \
+///     --data '{"password": "<password>"}' \
+///     http://127.0.0.1:8000/accounts/<account_id>
 ///
 /// # パスワード更新API
 /// curl --include --request POST --header "Content-Type: application/json" --header "Authorization: Bearer <token>" \
 ///     --data '{"id": "<account_id>", "oldPassword": "<old_password>", "newPassword": "<new_password>"}'
 ///     http://127.0.0.1:8000/accounts/<account_id>/change_password
+///
+/// # Eメールアドレス変更リクエストAPI
+/// curl --include --request POST --header "Content-Type: application/json" --header "Authorization: Bearer <token>" \
+///     --data '{"newEmail": "<new_email>"}' \
+///     http://127.0.0.1:8000/accounts/<account_id>/request_email_change
+///
+/// # Eメールアドレス変更確認API
+/// curl --include --request POST --header "Content-Type: application/json" \
+///     --data '{"token": "<email_change_token>"}' \
+///     http://127.0.0.1:8000/accounts/confirm_email
+///
+/// # Eメールアドレスによるアカウント検索API(管理者以外は自分のEメールアドレスのみ)
+/// curl --include --request GET --header "Authorization: Bearer <token>" \
+///     "http://127.0.0.1:8000/accounts/search?email=foo@example.com"
+///
+/// # アカウントセッション取得API(アカウント本人または管理者のみ)
+/// curl --include --request GET --header "Authorization: Bearer <token>" \
+///     http://127.0.0.1:8000/accounts/<account_id>/sessions
+///
+/// # アカウント有効化API
+/// curl --include --request POST --header "Authorization: Bearer <token>" \
+///     http://127.0.0.1:8000/accounts/<account_id>/activate
+///
+/// # アカウント無効化API
+/// curl --include --request POST --header "Authorization: Bearer <token>" \
+///     http://127.0.0.1:8000/accounts/<account_id>/deactivate
+///
+/// # アカウント権限変更API(管理者のみ)
+/// curl --include --request PUT --header "Content-Type: application/json" --header "Authorization: Bearer <token>" \
+///     --data '{"role": "admin"}' \
+///     http://127.0.0.1:8000/accounts/<account_id>/role
+///
+/// # パスワード再設定API(管理者のみ)
+/// curl --include --request POST --header "Content-Type: application/json" --header "Authorization: Bearer <token>" \
+///     --data '{"newPassword": "<new_password>"}' \
+///     http://127.0.0.1:8000/accounts/<account_id>/reset_password
 /// ```
 fn accounts_scope() -> actix_web::Scope {
     web::scope("/accounts")
-        .route("", web::post().to(handlers::accounts::insert))
-        .route("/{id}", web::get().to(handlers::accounts::find_by_id))
-        .route("/{id}", web::put().to(handlers::accounts::update))
-        .route("/{id}", web::delete().to(handlers::accounts::delete))
-        .route(
-            "/{id}/change_password",
-            web::post().to(handlers::accounts::change_password),
+        .service(
+            web::resource("")
+                .route(web::get().to(handlers::accounts::search))
+                .route(web::post().to(handlers::accounts::insert)),
+        )
+        .service(
+            web::resource("/bulk")
+                // 一括登録できるアカウントの数に応じて、JSONリクエストボディのサイズの上限を設定
+                .app_data(json_config(
+                    MAX_BULK_INSERT_ACCOUNTS * BULK_INSERT_BYTES_PER_ACCOUNT,
+                ))
+                .route(web::post().to(handlers::accounts::bulk_insert)),
+        )
+        .service(
+            web::resource("/import")
+                // インポートできる行数に応じて、CSVのサイズの上限を設定
+                .app_data(web::PayloadConfig::new(
+                    ENV_VALUES.account_import_max_rows * IMPORT_BYTES_PER_ROW,
+                ))
+                .route(web::post().to(handlers::accounts::import)),
+        )
+        .service(web::resource("/export").route(web::get().to(handlers::accounts::export)))
+        // `/{id}`より前に登録して、`search`をアカウントIDとして扱わないようにする
+        .service(web::resource("/search").route(web::get().to(handlers::accounts::find_by_email)))
+        .service(
+            web::resource("/confirm_email")
+                .route(web::post().to(handlers::accounts::confirm_email)),
+        )
+        .service(
+            web::resource("/{id}")
+                .route(web::get().to(handlers::accounts::find_by_id))
+                .route(web::put().to(handlers::accounts::update))
+                .route(web::delete().to(handlers::accounts::delete)),
+        )
+        .service(web::resource("/{id}/sessions").route(web::get().to(handlers::accounts::sessions)))
+        .service(
+            web::resource("/{id}/change_password")
+                .route(web::post().to(handlers::accounts::change_password)),
+        )
+        .service(
+            web::resource("/{id}/request_email_change")
+                .route(web::post().to(handlers::accounts::request_email_change)),
+        )
+        .service(
+            web::resource("/{id}/activate").route(web::post().to(handlers::accounts::activate)),
+        )
+        .service(
+            web::resource("/{id}/deactivate").route(web::post().to(handlers::accounts::deactivate)),
+        )
+        .service(web::resource("/{id}/role").route(web::put().to(handlers::accounts::change_role)))
+        .service(
+            web::resource("/{id}/reset_password")
+                .route(web::post().to(handlers::accounts::reset_password)),
         )
 }
 
 /// 認証スコープ
 ///
 /// ```bash
+/// # TLS_CERT_PATHとTLS_KEY_PATHを設定してHTTPSで起動した場合は、http://をhttps://に置き換える
+/// # (自己署名証明書の場合は、--cacert <証明書のパス>または--insecureを付与する)
 /// # トークン取得API
 /// curl --include --request POST --header "Content-Type: application/json" --data '{"email": "<email>"", "password": "<password>"}' http://127.0.0.1:8000/auth/obtain_tokens
+///
+/// # パスワードチェックAPI
+/// curl --include --request POST --header "Content-Type: application/json" --data '{"password": "<password>"}' http://127.0.0.1:8000/auth/check_password
+///
+/// # 全セッションログアウトAPI
+/// curl --include --request DELETE --header "Authorization: Bearer <access token>" http://127.0.0.1:8000/auth/tokens
+///
+/// # トークンイントロスペクションAPI
+/// curl --include --request POST --header "Content-Type: application/json" --data '{"token": "<access token>"}' http://127.0.0.1:8000/auth/introspect
 /// ```
-fn auth_scope() -> actix_web::Scope {
-    web::scope("/auth").route(
-        "/obtain_tokens",
-        web::post().to(handlers::auth::obtain_tokens),
-    )
+///
+/// クレデンシャルの総当たりを防ぐため、AUTH_RATE_LIMIT_PER_MINUTEが0でない場合は、
+/// クライアントのIPアドレスごとにリクエストの数を制限する。
+///
+/// # Arguments
+///
+/// * `rate_limiter` - すべてのワーカーで共有するレートリミッター。
+fn auth_scope(
+    rate_limiter: Arc<RateLimiter>,
+) -> actix_web::Scope<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    web::scope("/auth")
+        .wrap(Condition::new(
+            0 < ENV_VALUES.auth_rate_limit_per_minute,
+            RateLimitMiddleware::new(rate_limiter),
+        ))
+        .service(
+            web::resource("/obtain_tokens").route(web::post().to(handlers::auth::obtain_tokens)),
+        )
+        .service(
+            web::resource("/check_password").route(web::post().to(handlers::auth::check_password)),
+        )
+        .service(web::resource("/tokens").route(web::delete().to(handlers::auth::revoke_tokens)))
+        .service(web::resource("/introspect").route(web::post().to(handlers::auth::introspect)))
+}
+
+#[cfg(test)]
+mod connect_with_retry_tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    /// 再試行回数以内に接続に成功した場合に、コネクションを返却することを確認する。
+    #[actix_web::test]
+    async fn test_connect_with_retry() {
+        let calls = Cell::new(0);
+        let result = connect_with_retry(
+            || {
+                calls.set(calls.get() + 1);
+                let attempt = calls.get();
+                async move {
+                    if attempt < 3 {
+                        Err("connection refused")
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            },
+            3,
+            Duration::ZERO,
+        )
+        .await;
+        assert_eq!(result, Ok(3));
+        assert_eq!(calls.get(), 3);
+    }
+
+    /// 再試行回数を超えて接続に失敗した場合に、エラーを返却することを確認する。
+    #[actix_web::test]
+    async fn test_connect_with_retry_exhausted() {
+        let calls = Cell::new(0);
+        let result: Result<(), &str> = connect_with_retry(
+            || {
+                calls.set(calls.get() + 1);
+                async { Err("connection refused") }
+            },
+            2,
+            Duration::ZERO,
+        )
+        .await;
+        assert_eq!(result, Err("connection refused"));
+        assert_eq!(calls.get(), 3);
+    }
+}
+
+#[cfg(test)]
+mod cors_tests {
+    use actix_web::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        HttpResponse,
+    };
+
+    use super::*;
+
+    /// 許可したオリジンからのリクエストを許可することを確認する。
+    #[actix_web::test]
+    async fn test_cors_allowed_origin() {
+        let origins = vec!["http://localhost:3000".to_owned()];
+        let app = test::init_service(
+            App::new()
+                .wrap(cors(&origins, false))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let req = TestRequest::get()
+            .uri("/")
+            .insert_header((header::ORIGIN, "http://localhost:3000"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers()
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .unwrap(),
+            "http://localhost:3000"
+        );
+    }
+
+    /// プリフライトリクエストで、Web APIが使用するリクエストヘッダを許可することを確認する。
+    #[actix_web::test]
+    async fn test_cors_preflight_allowed_headers() {
+        let origins = vec!["http://localhost:3000".to_owned()];
+        let app = test::init_service(
+            App::new()
+                .wrap(cors(&origins, false))
+                .route("/", web::post().to(HttpResponse::Ok)),
+        )
+        .await;
+        let req = TestRequest::default()
+            .method(Method::OPTIONS)
+            .uri("/")
+            .insert_header((header::ORIGIN, "http://localhost:3000"))
+            .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "POST"))
+            .insert_header((
+                header::ACCESS_CONTROL_REQUEST_HEADERS,
+                "authorization, content-type, idempotency-key, if-none-match, x-request-id",
+            ))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let allowed = res
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_HEADERS)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_lowercase();
+        for name in ["idempotency-key", "if-none-match", "x-request-id"] {
+            assert!(allowed.contains(name), "{}", allowed);
+        }
+    }
+
+    /// スクリプトに、`ETag`及び`X-Request-Id`ヘッダを公開することを確認する。
+    #[actix_web::test]
+    async fn test_cors_expose_headers() {
+        let origins = vec!["http://localhost:3000".to_owned()];
+        let app = test::init_service(
+            App::new()
+                .wrap(cors(&origins, false))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let req = TestRequest::get()
+            .uri("/")
+            .insert_header((header::ORIGIN, "http://localhost:3000"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        let exposed = res
+            .headers()
+            .get(header::ACCESS_CONTROL_EXPOSE_HEADERS)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_lowercase();
+        for name in ["location", "etag", "x-request-id"] {
+            assert!(exposed.contains(name), "{}", exposed);
+        }
+    }
+
+    /// トークンをクッキーで返却する場合は、許可したオリジンからのクッキーを含むリクエストを許可して、
+    /// それ以外の場合は許可しないことを確認する。
+    #[actix_web::test]
+    async fn test_cors_supports_credentials() {
+        let origins = vec!["http://localhost:3000".to_owned()];
+        for supports_credentials in [true, false] {
+            let app = test::init_service(
+                App::new()
+                    .wrap(cors(&origins, supports_credentials))
+                    .route("/", web::get().to(HttpResponse::Ok)),
+            )
+            .await;
+            let req = TestRequest::get()
+                .uri("/")
+                .insert_header((header::ORIGIN, "http://localhost:3000"))
+                .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(
+                res.headers()
+                    .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                    .unwrap(),
+                "http://localhost:3000"
+            );
+            assert_eq!(
+                res.headers()
+                    .get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+                    .is_some(),
+                supports_credentials
+            );
+        }
+    }
+
+    /// 許可していないオリジンからのリクエストを拒否することを確認する。
+    #[actix_web::test]
+    async fn test_cors_disallowed_origin() {
+        let origins = vec!["http://localhost:3000".to_owned()];
+        let app = test::init_service(
+            App::new()
+                .wrap(cors(&origins, false))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let req = TestRequest::get()
+            .uri("/")
+            .insert_header((header::ORIGIN, "http://example.com"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert!(res
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+    }
+
+    /// 許可するオリジンを指定しない場合は、同じオリジンからのリクエストのみを許可することを確認する。
+    #[actix_web::test]
+    async fn test_cors_same_origin_only() {
+        let app = test::init_service(
+            App::new()
+                .wrap(cors(&[], false))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let req = TestRequest::get()
+            .uri("/")
+            .insert_header((header::HOST, "127.0.0.1:8000"))
+            .insert_header((header::ORIGIN, "http://127.0.0.1:8000"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let req = TestRequest::get()
+            .uri("/")
+            .insert_header((header::HOST, "127.0.0.1:8000"))
+            .insert_header((header::ORIGIN, "http://localhost:3000"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert!(res
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+    }
+}
+
+#[cfg(all(test, unix))]
+mod shutdown_tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+        process::Command,
+        thread,
+    };
+
+    use actix_web::HttpResponse;
+
+    use super::*;
+
+    /// 時間のかかる処理をするハンドラ。
+    async fn slow() -> HttpResponse {
+        actix_web::rt::time::sleep(Duration::from_millis(500)).await;
+        HttpResponse::Ok().body("done")
+    }
+
+    /// SIGTERMを受信したときに、処理中のリクエストが完了してからWeb APIサーバーが停止することを確認する。
+    #[actix_web::test]
+    async fn test_graceful_shutdown() {
+        let server = HttpServer::new(|| App::new().route("/slow", web::get().to(slow)))
+            .workers(1)
+            .shutdown_timeout(5)
+            .disable_signals()
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let address = server.addrs()[0];
+        let server = server.run();
+        spawn_shutdown_handler(server.handle()).unwrap();
+        let server = actix_web::rt::spawn(server);
+        // 時間のかかるリクエストを送信
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            stream
+                .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        // リクエストの処理中にSIGTERMを送信
+        actix_web::rt::time::sleep(Duration::from_millis(100)).await;
+        let status = Command::new("kill")
+            .args(["-TERM", &std::process::id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        server.await.unwrap().unwrap();
+        let response = client.join().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        assert!(response.ends_with("done"), "{}", response);
+    }
+}
+
+#[cfg(test)]
+mod default_response_tests {
+    use actix_web::test::{self, TestRequest};
+
+    use super::*;
+
+    /// 存在しないパスを指定した場合に、JSONのNOT_FOUNDレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_not_found() {
+        let app = test::init_service(
+            App::new()
+                .wrap(json_error_handlers())
+                .service(
+                    web::scope("/")
+                        .service(web::resource("").route(web::get().to(handlers::hello))),
+                )
+                .service(prefecture_scope())
+                .default_service(web::to(handlers::not_found)),
+        )
+        .await;
+        for uri in ["/unknown", "/prefectures/13/unknown"] {
+            let req = TestRequest::get().uri(uri).to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::NOT_FOUND, "{}", uri);
+            let body: serde_json::Value = test::read_body_json(res).await;
+            assert_eq!(body["message"], "指定されたリソースが見つかりません。");
+        }
+    }
+
+    /// リソースが許可していないメソッドを指定した場合に、許可するメソッドを`Allow`ヘッダに設定した、
+    /// JSONのMETHOD_NOT_ALLOWEDレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_method_not_allowed() {
+        let app = test::init_service(
+            App::new()
+                .wrap(json_error_handlers())
+                .service(prefecture_scope())
+                .service(accounts_scope())
+                .default_service(web::to(handlers::not_found)),
+        )
+        .await;
+        let cases = [
+            (Method::PATCH, "/prefectures", "GET"),
+            // `batch`は、都道府県コードとして扱わない
+            (Method::GET, "/prefectures/batch", "POST"),
+            // `search`は、アカウントIDとして扱わない
+            (Method::PATCH, "/accounts/search", "GET"),
+            (
+                Method::PATCH,
+                "/accounts/01G6XJ6A8B9P8YQ7N3V2S0E5KM",
+                "GET, PUT, DELETE",
+            ),
+        ];
+        for (method, uri, allow) in cases {
+            let req = TestRequest::default()
+                .method(method.clone())
+                .uri(uri)
+                .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED, "{}", uri);
+            assert_eq!(res.headers().get(header::ALLOW).unwrap(), allow);
+            let body: serde_json::Value = test::read_body_json(res).await;
+            assert_eq!(
+                body["message"],
+                format!("指定されたメソッド({})は許可されていません。", method)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod json_body_limit_tests {
+    use actix_web::test::{self, TestRequest};
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{authorization::bearer_token, database_service::DatabaseServiceImpl};
+
+    /// アカウント一括登録APIは、`MAX_JSON_BODY_BYTES`の既定値を超えるリクエストボディでも、
+    /// 一括登録できるアカウントの数に応じたサイズまで受け付けることを確認する。
+    #[actix_web::test]
+    async fn test_bulk_insert_body_limit() {
+        let db_service: Arc<dyn DatabaseService> =
+            Arc::new(DatabaseServiceImpl::new(DatabaseConnection::Disconnected));
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let hasher: Arc<dyn PasswordHasher> =
+            Arc::new(PasswordHasherImpl::new(HashingConfig::from_env()));
+        let event_sink: Arc<dyn AccountEventSink> = Arc::new(NoopAccountEventSink);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(db_service))
+                .app_data(Data::from(clock))
+                .app_data(Data::from(hasher))
+                .app_data(Data::from(event_sink))
+                .app_data(json_config(64 * 1024))
+                .service(accounts_scope()),
+        )
+        .await;
+        let account = |address_details: &str| {
+            json!({
+                "email": "invalid-email",
+                "name": "foo",
+                "password": "012abcEFG=+",
+                "isActive": true,
+                "fixedNumber": null,
+                "mobileNumber": "090-1234-5678",
+                "postalCode": "100-0014",
+                "prefectureCode": 13,
+                "addressDetails": address_details,
+            })
+        };
+        let call = |body: Value| {
+            let req = TestRequest::post()
+                .uri("/accounts/bulk")
+                .insert_header((
+                    header::AUTHORIZATION,
+                    bearer_token(
+                        "01G6XJ6A8B9P8YQ7N3V2S0E5KM",
+                        domains::models::accounts::Role::Admin,
+                    ),
+                ))
+                .set_json(body)
+                .to_request();
+            test::call_service(&app, req)
+        };
+
+        // 既定の上限を超えるが、一括登録できるアカウントの数に応じた上限以内の場合は、アカウントを検証する
+        let body = Value::Array(vec![account("千代田区永田町1-7-1"); 500]);
+        assert!(64 * 1024 < serde_json::to_vec(&body).unwrap().len());
+        let res = call(body).await;
+        assert_ne!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(res.status().is_client_error(), "{}", res.status());
+
+        // 一括登録できるアカウントの数に応じた上限を超える場合は拒否する
+        let body = Value::Array(vec![account(
+            &"a".repeat(MAX_BULK_INSERT_ACCOUNTS * BULK_INSERT_BYTES_PER_ACCOUNT),
+        )]);
+        let res = call(body).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
+
+#[cfg(test)]
+mod compression_tests {
+    use std::io::Read;
+
+    use actix_web::test::{self, TestRequest};
+    use domains::models::accounts::Role;
+    use flate2::read::GzDecoder;
+    use usecases::queries::Page;
+
+    use super::*;
+    use crate::{
+        authorization::bearer_token,
+        mock_database_service::{test_account, test_datetime, MockDatabaseService},
+    };
+
+    /// レスポンスボディを圧縮するアプリケーションで、リクエストを処理したレスポンスを返却する。
+    async fn call(uri: &str, accept_encoding: Option<&str>) -> ServiceResponse {
+        let app = test::init_service(
+            App::new()
+                .wrap(json_error_handlers())
+                .wrap(Compress::default())
+                .service(
+                    web::resource(handlers::OPENAPI_PATH).route(web::get().to(handlers::openapi)),
+                )
+                .default_service(web::to(handlers::not_found)),
+        )
+        .await;
+        let mut req = TestRequest::get().uri(uri);
+        if let Some(encoding) = accept_encoding {
+            req = req.insert_header((header::ACCEPT_ENCODING, encoding));
+        }
+
+        test::call_service(&app, req.to_request())
+            .await
+            .map_into_boxed_body()
+    }
+
+    /// `Accept-Encoding: gzip`を指定した場合に、JSONのレスポンスボディをgzipで圧縮して返却して、
+    /// 展開したボディが圧縮しない場合のボディと一致することを確認する。
+    #[actix_web::test]
+    async fn test_gzip() {
+        for uri in [handlers::OPENAPI_PATH, "/unknown"] {
+            let identity = test::read_body(call(uri, None).await).await;
+            let res = call(uri, Some("gzip")).await;
+            assert_eq!(
+                res.headers().get(header::CONTENT_ENCODING).unwrap(),
+                "gzip",
+                "{}",
+                uri
+            );
+            assert_eq!(
+                res.headers().get(header::CONTENT_TYPE).unwrap(),
+                "application/json",
+                "{}",
+                uri
+            );
+            let compressed = test::read_body(res).await;
+            let mut decompressed = vec![];
+            GzDecoder::new(&compressed[..])
+                .read_to_end(&mut decompressed)
+                .unwrap();
+            assert_eq!(decompressed, identity, "{}", uri);
+            let body: serde_json::Value = serde_json::from_slice(&decompressed).unwrap();
+            assert!(body.is_object(), "{}", uri);
+        }
+    }
+
+    /// `Accept-Encoding`ヘッダを指定しない場合に、レスポンスボディを圧縮しないことを確認する。
+    #[actix_web::test]
+    async fn test_identity() {
+        let res = call(handlers::OPENAPI_PATH, None).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().get(header::CONTENT_ENCODING).is_none());
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert!(body["paths"].is_object());
+    }
+
+    /// 件数の多いアカウント一覧を、`Accept-Encoding: gzip`を指定した場合にのみgzipで圧縮して返却することを確認する。
+    #[actix_web::test]
+    async fn test_gzip_account_list() {
+        let account = test_account(test_datetime());
+        let db_service = MockDatabaseService::new()
+            .await
+            .with_account_service(move |mock| {
+                let account = account.clone();
+                mock.expect_search().returning(move |_, pagination| {
+                    Ok(Page {
+                        items: vec![account.clone(); 100],
+                        page: pagination.page,
+                        per_page: pagination.per_page,
+                        total: 100,
+                    })
+                });
+            });
+        let db_service: Arc<dyn DatabaseService> = Arc::new(db_service);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(db_service))
+                .wrap(Compress::default())
+                .route("/accounts", web::get().to(handlers::accounts::search)),
+        )
+        .await;
+        let request = |accept_encoding: Option<&str>| {
+            let mut req = TestRequest::get()
+                .uri("/accounts?perPage=100")
+                .insert_header((
+                    header::AUTHORIZATION,
+                    bearer_token("01FVH5SD9H9Y4HNZ4J1Q3E3PFD", Role::Admin),
+                ));
+            if let Some(encoding) = accept_encoding {
+                req = req.insert_header((header::ACCEPT_ENCODING, encoding));
+            }
+            req.to_request()
+        };
+
+        let res = test::call_service(&app, request(None)).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().get(header::CONTENT_ENCODING).is_none());
+        let identity = test::read_body(res).await;
+
+        let res = test::call_service(&app, request(Some("gzip"))).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+        let compressed = test::read_body(res).await;
+        assert!(compressed.len() < identity.len());
+        let mut decompressed = vec![];
+        GzDecoder::new(&compressed[..])
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, identity);
+    }
 }
@@ -0,0 +1,265 @@
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+    time::Instant,
+};
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
+};
+
+use usecases::metrics::DB_TRANSACTION_DURATION_SECONDS;
+
+/// ルートに一致しなかったリクエストのパスラベル。
+///
+/// 存在しないパスごとに時系列が増えないように、ルートに一致しなかったリクエストはこのラベルにまとめる。
+const UNMATCHED_PATH: &str = "unmatched";
+
+/// メトリクス
+///
+/// Web APIのリクエスト数、エラー数、処理時間及びデータベーストランザクションの処理時間を、
+/// Prometheus形式で公開するためのレジストリと各メトリクスを保持する。
+#[derive(Clone)]
+pub struct Metrics {
+    /// メトリクスを登録したレジストリ。
+    registry: Registry,
+    /// メソッド、ルート及びステータスコード別のリクエスト数。
+    requests: IntCounterVec,
+    /// メソッド及びルート別の、ステータスコードが400以上のリクエスト数。
+    errors: IntCounterVec,
+    /// メソッド及びルート別のリクエストの処理時間(秒)。
+    latency: HistogramVec,
+}
+
+impl Metrics {
+    /// メトリクスを構築する。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: メトリクス。
+    /// * `Err`: メトリクスの構築またはレジストリへの登録に失敗した場合のエラー。
+    pub fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new();
+        let requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "リクエスト数。"),
+            &["method", "path", "status"],
+        )?;
+        let errors = IntCounterVec::new(
+            Opts::new(
+                "http_request_errors_total",
+                "ステータスコードが400以上のリクエスト数。",
+            ),
+            &["method", "path"],
+        )?;
+        let latency = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "リクエストの処理時間(秒)。",
+            ),
+            &["method", "path"],
+        )?;
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(errors.clone()))?;
+        registry.register(Box::new(latency.clone()))?;
+        registry.register(Box::new(DB_TRANSACTION_DURATION_SECONDS.clone()))?;
+
+        Ok(Self {
+            registry,
+            requests,
+            errors,
+            latency,
+        })
+    }
+
+    /// メトリクスをPrometheusのテキスト形式で出力する。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: Prometheusのテキスト形式のメトリクス。
+    /// * `Err`: エラー。
+    pub fn encode(&self) -> prometheus::Result<String> {
+        let mut buf = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buf)?;
+
+        String::from_utf8(buf).map_err(|err| prometheus::Error::Msg(err.to_string()))
+    }
+
+    /// リクエストの処理結果を記録する。
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - リクエストのメソッド。
+    /// * `path` - リクエストが一致したルートのパターン。
+    /// * `status` - レスポンスのステータスコード。
+    /// * `seconds` - リクエストの処理時間(秒)。
+    fn record(&self, method: &str, path: &str, status: u16, seconds: f64) {
+        self.requests
+            .with_label_values(&[method, path, &status.to_string()])
+            .inc();
+        if 400 <= status {
+            self.errors.with_label_values(&[method, path]).inc();
+        }
+        self.latency
+            .with_label_values(&[method, path])
+            .observe(seconds);
+    }
+}
+
+/// メトリクスミドルウェア
+///
+/// リクエストごとに、メソッド、ルートのパターン(例: `/accounts/{id}`)及びステータスコード別に、
+/// リクエスト数、エラー数及び処理時間を記録する。
+pub struct MetricsMiddleware {
+    /// リクエストを記録するメトリクス。
+    metrics: Metrics,
+}
+
+impl MetricsMiddleware {
+    /// メトリクスミドルウェアを構築する。
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics` - リクエストを記録するメトリクス。
+    ///
+    /// # Returns
+    ///
+    /// メトリクスミドルウェア。
+    pub fn new(metrics: Metrics) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for MetricsMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = MetricsService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MetricsService {
+            service: Rc::new(service),
+            metrics: self.metrics.clone(),
+        }))
+    }
+}
+
+/// メトリクスサービス
+pub struct MetricsService<S> {
+    /// 後続のサービス。
+    service: Rc<S>,
+    /// リクエストを記録するメトリクス。
+    metrics: Metrics,
+}
+
+impl<S, B> Service<ServiceRequest> for MetricsService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let started_at = Instant::now();
+        let method = req.method().to_string();
+        let path = req
+            .match_pattern()
+            .unwrap_or_else(|| UNMATCHED_PATH.to_owned());
+        let service = Rc::clone(&self.service);
+        let metrics = self.metrics.clone();
+
+        Box::pin(async move {
+            let result = service.call(req).await;
+            let status = match &result {
+                Ok(res) => res.status(),
+                Err(err) => err.as_response_error().status_code(),
+            };
+            metrics.record(
+                &method,
+                &path,
+                status.as_u16(),
+                started_at.elapsed().as_secs_f64(),
+            );
+
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod metrics_tests {
+    use actix_web::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    use super::*;
+    use crate::handlers;
+
+    /// メトリクスAPIのレスポンスから、Prometheusのテキスト形式のメトリクスを取り出す。
+    async fn read_metrics(res: ServiceResponse) -> String {
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res
+            .headers()
+            .get("content-type")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain"));
+        String::from_utf8(test::read_body(res).await.to_vec()).unwrap()
+    }
+
+    /// リクエストを処理した後で、スクレイプしたメトリクスのリクエスト数とエラー数が増加していることを確認する。
+    #[actix_web::test]
+    async fn test_metrics() {
+        let metrics = Metrics::new().unwrap();
+        let app = test::init_service(
+            App::new()
+                .wrap(MetricsMiddleware::new(metrics.clone()))
+                .app_data(web::Data::new(metrics))
+                .route("/metrics", web::get().to(handlers::metrics))
+                .route("/accounts/{id}", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let counter = r#"http_requests_total{method="GET",path="/accounts/{id}",status="200"}"#;
+        let req = TestRequest::get().uri("/metrics").to_request();
+        let body = read_metrics(test::call_service(&app, req).await).await;
+        assert!(!body.contains(counter), "{}", body);
+
+        let req = TestRequest::get().uri("/accounts/foo").to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+        let req = TestRequest::get().uri("/metrics").to_request();
+        let body = read_metrics(test::call_service(&app, req).await).await;
+        assert!(body.contains(&format!("{} 1\n", counter)), "{}", body);
+
+        let req = TestRequest::get().uri("/accounts/bar").to_request();
+        test::call_service(&app, req).await;
+        let req = TestRequest::get().uri("/unknown").to_request();
+        test::call_service(&app, req).await;
+        let req = TestRequest::get().uri("/metrics").to_request();
+        let body = read_metrics(test::call_service(&app, req).await).await;
+        assert!(body.contains(&format!("{} 2\n", counter)), "{}", body);
+        assert!(
+            body.contains(r#"http_request_errors_total{method="GET",path="unmatched"} 1"#),
+            "{}",
+            body
+        );
+        assert!(body.contains("db_transaction_duration_seconds"), "{}", body);
+    }
+}
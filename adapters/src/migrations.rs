@@ -0,0 +1,46 @@
+use anyhow::anyhow;
+use sea_orm::{ConnectionTrait, DbBackend};
+use sqlx::migrate::Migrator;
+
+use usecases::database_service::DatabaseService;
+
+/// `migrations`ディレクトリのマイグレーション。
+///
+/// `sqlx-cli`と同じ`_sqlx_migrations`テーブルにマイグレーション履歴を記録するため、
+/// `sqlx migrate run`で実行したマイグレーションと混在して実行できる。
+pub static MIGRATOR: Migrator = sqlx::migrate!("../migrations");
+
+/// 実行されていないすべてのマイグレーションを実行する。
+///
+/// PostgreSQLの場合は`migrations`ディレクトリのマイグレーションを実行する。
+/// SQLiteの場合は、マイグレーションを実行できないため、エンティティの定義から作成されていないテーブルを作成して、
+/// 47都道府県を登録する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は下記の通り。
+///
+/// * `Ok`: ()
+/// * `Err`: エラー。
+pub(crate) async fn run_migrations(db_service: &dyn DatabaseService) -> anyhow::Result<()> {
+    let conn = db_service.connection();
+    match conn.get_database_backend() {
+        DbBackend::Postgres => MIGRATOR.run(conn.get_postgres_connection_pool()).await?,
+        DbBackend::Sqlite => {
+            infra::sqlite::create_tables(conn).await?;
+            usecases::prefectures::seed_prefectures(db_service).await?;
+        }
+        backend => {
+            return Err(anyhow!(
+                "データベース({:?})のマイグレーションには対応していません。",
+                backend
+            ))
+        }
+    }
+
+    Ok(())
+}
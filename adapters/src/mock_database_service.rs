@@ -0,0 +1,406 @@
+use anyhow::anyhow;
+use chrono::{DateTime, FixedOffset, TimeZone};
+use sea_orm::{Database, DatabaseConnection, DatabaseTransaction};
+
+use common::hashing::HashingConfig;
+use domains::{
+    models::{
+        accounts::{Account, AccountName, FixedMobileNumbers, HashedPassword, RawPassword},
+        common::{Address, AddressDetails, EmailAddress, PhoneNumber, PostalCode, Prefecture},
+    },
+    repositories::{
+        accounts::{
+            AccountRepository, MockAccountRepository, MockPasswordHistoryRepository,
+            PasswordHistoryRepository,
+        },
+        auth::{JwtTokensRepository, MockJwtTokensRepository},
+        common::{MockPrefectureRepository, PrefectureRepository},
+    },
+    services::hashers::PasswordHasherImpl,
+};
+use usecases::{
+    database_service::DatabaseService,
+    queries::{
+        AccountQueryService, MockAccountQueryService, MockPrefectureStatsQueryService,
+        PrefectureStatsQueryService,
+    },
+};
+
+/// モックを生成する関数。
+type MockFactory<T> = Box<dyn Fn() -> T + Send + Sync>;
+
+/// テスト用のデータベースサービス
+///
+/// リポジトリとクエリサービスを要求されるたびに、登録された関数でモックを生成して返却する。
+/// ユースケースは複数回リポジトリを要求するため、期待する呼び出しは関数でモックに設定する。
+/// ユースケースが開始するトランザクションには、SQLiteのインメモリデータベースのコネクションを使用するが、
+/// モックはトランザクションを使用しないため、SQLは実行されない。
+/// モックを登録していないリポジトリやクエリサービスのメソッドを呼び出した場合はパニックする。
+pub struct MockDatabaseService {
+    /// トランザクションを開始するデータベースコネクション。
+    conn: DatabaseConnection,
+    /// 都道府県リポジトリのモックを生成する関数。
+    prefecture: MockFactory<MockPrefectureRepository>,
+    /// アカウントリポジトリのモックを生成する関数。
+    account: MockFactory<MockAccountRepository>,
+    /// パスワード履歴リポジトリのモックを生成する関数。
+    password_history: MockFactory<MockPasswordHistoryRepository>,
+    /// JWTトークンリポジトリのモックを生成する関数。
+    jwt_tokens: MockFactory<MockJwtTokensRepository>,
+    /// アカウントクエリサービスのモックを生成する関数。
+    account_service: MockFactory<MockAccountQueryService>,
+    /// 都道府県統計クエリサービスのモックを生成する関数。
+    prefecture_stats_service: MockFactory<MockPrefectureStatsQueryService>,
+}
+
+/// 期待する呼び出しを設定したモックを生成する関数を構築する。
+///
+/// # Arguments
+///
+/// * `new` - モックのコンストラクタ。
+/// * `expect` - モックに期待する呼び出しを設定する関数。
+///
+/// # Returns
+///
+/// モックを生成する関数。
+fn factory<T: 'static>(
+    new: fn() -> T,
+    expect: impl Fn(&mut T) + Send + Sync + 'static,
+) -> MockFactory<T> {
+    Box::new(move || {
+        let mut mock = new();
+        expect(&mut mock);
+        mock
+    })
+}
+
+impl MockDatabaseService {
+    /// コンストラクタ。
+    ///
+    /// すべてのリポジトリとクエリサービスは、期待する呼び出しを設定していないモックを返却する。
+    ///
+    /// # Returns
+    ///
+    /// テスト用のデータベースサービス。
+    pub async fn new() -> Self {
+        Self {
+            conn: Database::connect("sqlite::memory:")
+                .await
+                .expect("SQLiteのインメモリデータベースに接続できません。"),
+            prefecture: factory(MockPrefectureRepository::new, |_| {}),
+            account: factory(MockAccountRepository::new, |_| {}),
+            password_history: factory(MockPasswordHistoryRepository::new, |_| {}),
+            jwt_tokens: factory(MockJwtTokensRepository::new, |_| {}),
+            account_service: factory(MockAccountQueryService::new, |_| {}),
+            prefecture_stats_service: factory(MockPrefectureStatsQueryService::new, |_| {}),
+        }
+    }
+
+    /// 都道府県リポジトリのモックに期待する呼び出しを設定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `expect` - モックに期待する呼び出しを設定する関数。
+    ///
+    /// # Returns
+    ///
+    /// テスト用のデータベースサービス。
+    pub fn with_prefecture(
+        mut self,
+        expect: impl Fn(&mut MockPrefectureRepository) + Send + Sync + 'static,
+    ) -> Self {
+        self.prefecture = factory(MockPrefectureRepository::new, expect);
+        self
+    }
+
+    /// アカウントリポジトリのモックに期待する呼び出しを設定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `expect` - モックに期待する呼び出しを設定する関数。
+    ///
+    /// # Returns
+    ///
+    /// テスト用のデータベースサービス。
+    pub fn with_account(
+        mut self,
+        expect: impl Fn(&mut MockAccountRepository) + Send + Sync + 'static,
+    ) -> Self {
+        self.account = factory(MockAccountRepository::new, expect);
+        self
+    }
+
+    /// パスワード履歴リポジトリのモックに期待する呼び出しを設定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `expect` - モックに期待する呼び出しを設定する関数。
+    ///
+    /// # Returns
+    ///
+    /// テスト用のデータベースサービス。
+    pub fn with_password_history(
+        mut self,
+        expect: impl Fn(&mut MockPasswordHistoryRepository) + Send + Sync + 'static,
+    ) -> Self {
+        self.password_history = factory(MockPasswordHistoryRepository::new, expect);
+        self
+    }
+
+    /// JWTトークンリポジトリのモックに期待する呼び出しを設定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `expect` - モックに期待する呼び出しを設定する関数。
+    ///
+    /// # Returns
+    ///
+    /// テスト用のデータベースサービス。
+    pub fn with_jwt_tokens(
+        mut self,
+        expect: impl Fn(&mut MockJwtTokensRepository) + Send + Sync + 'static,
+    ) -> Self {
+        self.jwt_tokens = factory(MockJwtTokensRepository::new, expect);
+        self
+    }
+
+    /// アカウントクエリサービスのモックに期待する呼び出しを設定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `expect` - モックに期待する呼び出しを設定する関数。
+    ///
+    /// # Returns
+    ///
+    /// テスト用のデータベースサービス。
+    pub fn with_account_service(
+        mut self,
+        expect: impl Fn(&mut MockAccountQueryService) + Send + Sync + 'static,
+    ) -> Self {
+        self.account_service = factory(MockAccountQueryService::new, expect);
+        self
+    }
+
+    /// アカウントが存在する場合のデータベースサービスを構築する。
+    ///
+    /// アカウントリポジトリは、指定されたアカウントを検索結果として返却して、登録、更新及び削除に成功する。
+    /// 都道府県リポジトリは都道府県コードに対応する都道府県を返却して、JWTトークンの登録と削除に成功する。
+    /// パスワード履歴は空で、パスワード履歴への追加に成功する。
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - 存在するアカウント。
+    ///
+    /// # Returns
+    ///
+    /// テスト用のデータベースサービス。
+    pub async fn account_exists(account: Account) -> Self {
+        Self::new()
+            .await
+            .with_prefecture(found_prefecture)
+            .with_account(move |mock| {
+                let found = account.clone();
+                mock.expect_find_by_id()
+                    .returning(move |_| Ok(Some(found.clone())));
+                let found = account.clone();
+                mock.expect_find_by_email()
+                    .returning(move |_| Ok(Some(found.clone())));
+                accept_account_writes(mock);
+            })
+            .with_password_history(accept_password_history_writes)
+            .with_jwt_tokens(accept_jwt_tokens_writes)
+    }
+
+    /// アカウントが存在しない場合のデータベースサービスを構築する。
+    ///
+    /// アカウントリポジトリはアカウントを見つけられず、アカウントの登録に成功する。
+    /// 都道府県リポジトリは都道府県コードに対応する都道府県を返却して、JWTトークンの登録と削除に成功する。
+    ///
+    /// # Returns
+    ///
+    /// テスト用のデータベースサービス。
+    pub async fn account_missing() -> Self {
+        Self::new()
+            .await
+            .with_prefecture(found_prefecture)
+            .with_account(|mock| {
+                mock.expect_find_by_id().returning(|_| Ok(None));
+                mock.expect_find_by_email().returning(|_| Ok(None));
+                accept_account_writes(mock);
+            })
+            .with_jwt_tokens(accept_jwt_tokens_writes)
+    }
+
+    /// データベースの操作に失敗する場合のデータベースサービスを構築する。
+    ///
+    /// すべてのリポジトリのメソッドがエラーを返却する。
+    ///
+    /// # Returns
+    ///
+    /// テスト用のデータベースサービス。
+    pub async fn db_error() -> Self {
+        Self::new()
+            .await
+            .with_prefecture(|mock| {
+                mock.expect_find_by_code().returning(|_| Err(db_error()));
+                mock.expect_find_by_codes().returning(|_| Err(db_error()));
+                mock.expect_list().returning(|| Err(db_error()));
+                mock.expect_insert_missing().returning(|_| Err(db_error()));
+            })
+            .with_account(|mock| {
+                mock.expect_find_by_id().returning(|_| Err(db_error()));
+                mock.expect_find_by_email().returning(|_| Err(db_error()));
+                mock.expect_list().returning(|| Err(db_error()));
+                mock.expect_list_paged().returning(|_, _| Err(db_error()));
+                mock.expect_insert().returning(|_| Err(db_error()));
+                mock.expect_update().returning(|_| Err(db_error()));
+                mock.expect_update_if_unmodified()
+                    .returning(|_, _| Err(db_error()));
+                mock.expect_delete().returning(|_| Err(db_error()));
+                mock.expect_change_password()
+                    .returning(|_, _, _| Err(db_error()));
+            })
+            .with_password_history(|mock| {
+                mock.expect_list().returning(|_, _| Err(db_error()));
+                mock.expect_push().returning(|_, _, _, _| Err(db_error()));
+            })
+            .with_jwt_tokens(|mock| {
+                mock.expect_find_by_id().returning(|_| Err(db_error()));
+                mock.expect_find_by_access_token()
+                    .returning(|_| Err(db_error()));
+                mock.expect_find_by_refresh_token()
+                    .returning(|_| Err(db_error()));
+                mock.expect_insert().returning(|_| Err(db_error()));
+                mock.expect_delete().returning(|_| Err(db_error()));
+                mock.expect_delete_by_account_id()
+                    .returning(|_| Err(db_error()));
+            })
+    }
+}
+
+/// データベースの操作に失敗したときのエラーを返却する。
+fn db_error() -> anyhow::Error {
+    anyhow!("データベースに接続できません。")
+}
+
+/// 都道府県リポジトリのモックが、都道府県コードに対応する都道府県を返却するように設定する。
+fn found_prefecture(mock: &mut MockPrefectureRepository) {
+    mock.expect_find_by_code()
+        .returning(|code| Ok(Prefecture::from_code(code).ok()));
+    mock.expect_find_by_codes().returning(|codes| {
+        Ok(codes
+            .iter()
+            .filter_map(|&code| Prefecture::from_code(code).ok())
+            .collect())
+    });
+}
+
+/// アカウントリポジトリのモックが、アカウントの登録、更新及び削除に成功するように設定する。
+fn accept_account_writes(mock: &mut MockAccountRepository) {
+    mock.expect_insert()
+        .returning(|account| Ok(account.clone()));
+    mock.expect_update()
+        .returning(|account| Ok(account.clone()));
+    mock.expect_update_if_unmodified()
+        .returning(|account, _| Ok(Some(account.clone())));
+    mock.expect_delete().returning(|_| Ok(()));
+    mock.expect_change_password().returning(|_, _, _| Ok(true));
+}
+
+/// パスワード履歴リポジトリのモックが、空のパスワード履歴を返却して、パスワード履歴への追加に成功するように設定する。
+fn accept_password_history_writes(mock: &mut MockPasswordHistoryRepository) {
+    mock.expect_list().returning(|_, _| Ok(vec![]));
+    mock.expect_push().returning(|_, _, _, _| Ok(()));
+}
+
+/// JWTトークンリポジトリのモックが、JWTトークンの登録と削除に成功するように設定する。
+fn accept_jwt_tokens_writes(mock: &mut MockJwtTokensRepository) {
+    mock.expect_insert().returning(|tokens| Ok(tokens.clone()));
+    mock.expect_delete_by_account_id().returning(|_| Ok(1));
+}
+
+/// テストで使用するアカウントのパスワード。
+pub const TEST_PASSWORD: &str = "012abcEFG=+";
+
+/// テストで使用するアカウントを構築する。
+///
+/// Eメールアドレスは`foo@example.com`、パスワードは`TEST_PASSWORD`で、有効なアカウントを構築する。
+///
+/// # Arguments
+///
+/// * `now` - 登録日時及び更新日時。
+///
+/// # Returns
+///
+/// アカウント。
+pub fn test_account(now: DateTime<FixedOffset>) -> Account {
+    let hasher = PasswordHasherImpl::new(HashingConfig::from_env());
+    Account::new(
+        EmailAddress::new("foo@example.com").unwrap(),
+        AccountName::new("foo").unwrap(),
+        HashedPassword::new(RawPassword::new(TEST_PASSWORD).unwrap(), &hasher).unwrap(),
+        true,
+        FixedMobileNumbers::new(None, Some(PhoneNumber::new("090-1234-5678").unwrap())).unwrap(),
+        PostalCode::new("100-0014").unwrap(),
+        Address::new(
+            Prefecture::from_code(13).unwrap(),
+            AddressDetails::new("千代田区永田町1-7-1").unwrap(),
+        ),
+        now,
+    )
+}
+
+/// テストで使用する日時を返却する。
+pub fn test_datetime() -> DateTime<FixedOffset> {
+    FixedOffset::east_opt(9 * 60 * 60)
+        .unwrap()
+        .with_ymd_and_hms(2022, 4, 1, 9, 0, 0)
+        .unwrap()
+}
+
+impl DatabaseService for MockDatabaseService {
+    fn connection(&self) -> &DatabaseConnection {
+        &self.conn
+    }
+
+    fn prefecture<'a>(&self, _txn: &'a DatabaseTransaction) -> Box<dyn PrefectureRepository + 'a> {
+        Box::new((self.prefecture)())
+    }
+
+    fn account<'a>(&self, _txn: &'a DatabaseTransaction) -> Box<dyn AccountRepository + 'a> {
+        Box::new((self.account)())
+    }
+
+    fn prefecture_reader(&self) -> Box<dyn PrefectureRepository + '_> {
+        Box::new((self.prefecture)())
+    }
+
+    fn account_reader(&self) -> Box<dyn AccountRepository + '_> {
+        Box::new((self.account)())
+    }
+
+    fn password_history<'a>(
+        &self,
+        _txn: &'a DatabaseTransaction,
+    ) -> Box<dyn PasswordHistoryRepository + 'a> {
+        Box::new((self.password_history)())
+    }
+
+    fn jwt_tokens<'a>(&self, _txn: &'a DatabaseTransaction) -> Box<dyn JwtTokensRepository + 'a> {
+        Box::new((self.jwt_tokens)())
+    }
+
+    fn account_service<'a>(
+        &self,
+        _txn: &'a DatabaseTransaction,
+    ) -> Box<dyn AccountQueryService + 'a> {
+        Box::new((self.account_service)())
+    }
+
+    fn prefecture_stats_service<'a>(
+        &self,
+        _txn: &'a DatabaseTransaction,
+    ) -> Box<dyn PrefectureStatsQueryService + 'a> {
+        Box::new((self.prefecture_stats_service)())
+    }
+}
@@ -0,0 +1,201 @@
+use serde::Serialize;
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi, ToSchema,
+};
+
+use domains::models::{
+    accounts::Role,
+    common::{Prefecture, Region},
+};
+use usecases::{
+    accounts::{
+        AccountDto, AccountSessionsDto, BulkInsertedAccounts, ChangePassword, ChangeRole,
+        ConfirmEmailChange, DeleteAccount, EmailChangeToken, FieldError, ImportRowError,
+        NewAccount, RequestEmailChange, ResetPassword, SessionDto, UpdateAccount,
+    },
+    auth::{
+        CheckPassword, Credential, IntrospectToken, IntrospectionResult, JwtTokensDto,
+        PasswordCheckResult,
+    },
+    prefectures::{PrefectureCodes, RegionWithPrefectures},
+    queries::{Page, PrefectureStats},
+};
+
+use crate::handlers;
+
+/// メッセージレスポンス
+///
+/// エラーが発生した場合などに返却するJSONの構造を示す。
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorMessage {
+    /// メッセージ。
+    pub message: String,
+    /// リクエストID。エラーレスポンスの場合に含まれる。
+    pub request_id: Option<String>,
+    /// フィールドの検証エラーのリスト。複数のフィールドを検証して、検証に失敗した場合に含まれる。
+    pub errors: Option<Vec<FieldError>>,
+}
+
+/// Bearerトークンによる認証をOpenAPI仕様書に追加する構造体
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Web APIのOpenAPI仕様書
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        handlers::prefectures::list,
+        handlers::prefectures::list_by_region,
+        handlers::prefectures::find_by_code,
+        handlers::prefectures::find_by_codes,
+        handlers::prefectures::list_accounts,
+        handlers::prefectures::stats,
+        handlers::prefectures::invalidate_cache,
+        handlers::accounts::search,
+        handlers::accounts::find_by_id,
+        handlers::accounts::find_by_email,
+        handlers::accounts::sessions,
+        handlers::accounts::insert,
+        handlers::accounts::bulk_insert,
+        handlers::accounts::import,
+        handlers::accounts::export,
+        handlers::accounts::update,
+        handlers::accounts::delete,
+        handlers::accounts::change_password,
+        handlers::accounts::request_email_change,
+        handlers::accounts::confirm_email,
+        handlers::accounts::activate,
+        handlers::accounts::deactivate,
+        handlers::accounts::change_role,
+        handlers::accounts::reset_password,
+        handlers::auth::obtain_tokens,
+        handlers::auth::check_password,
+        handlers::auth::revoke_tokens,
+        handlers::auth::introspect,
+    ),
+    components(schemas(
+        Prefecture,
+        Region,
+        RegionWithPrefectures,
+        PrefectureCodes,
+        PrefectureStats,
+        Page<AccountDto>,
+        AccountDto,
+        AccountSessionsDto,
+        SessionDto,
+        Role,
+        NewAccount,
+        BulkInsertedAccounts,
+        ImportRowError,
+        UpdateAccount,
+        DeleteAccount,
+        ChangePassword,
+        ChangeRole,
+        ResetPassword,
+        RequestEmailChange,
+        EmailChangeToken,
+        ConfirmEmailChange,
+        Credential,
+        JwtTokensDto,
+        CheckPassword,
+        PasswordCheckResult,
+        IntrospectToken,
+        IntrospectionResult,
+        FieldError,
+        ErrorMessage,
+    )),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "prefectures", description = "都道府県API"),
+        (name = "accounts", description = "アカウントAPI"),
+        (name = "auth", description = "認証API"),
+    )
+)]
+pub struct ApiDoc;
+
+#[cfg(test)]
+mod api_doc_tests {
+    use super::*;
+
+    /// OpenAPI仕様書にすべてのパスが含まれていることを確認する。
+    #[test]
+    fn test_api_doc_paths() {
+        let doc = ApiDoc::openapi();
+        for path in [
+            "/prefectures",
+            "/prefectures/regions",
+            "/prefectures/{code}",
+            "/prefectures/batch",
+            "/prefectures/{code}/accounts",
+            "/prefectures/stats",
+            "/prefectures/cache",
+            "/accounts",
+            "/accounts/bulk",
+            "/accounts/search",
+            "/accounts/{id}",
+            "/accounts/{id}/role",
+            "/accounts/{id}/reset_password",
+            "/accounts/{id}/change_password",
+            "/accounts/{id}/request_email_change",
+            "/accounts/confirm_email",
+            "/accounts/{id}/activate",
+            "/accounts/{id}/deactivate",
+            "/auth/obtain_tokens",
+            "/auth/check_password",
+            "/auth/tokens",
+            "/auth/introspect",
+        ] {
+            assert!(doc.paths.paths.contains_key(path), "{}", path);
+        }
+    }
+
+    /// OpenAPI仕様書に、ULIDのアカウントIDを指定するパスパラメーターが含まれていることを確認する。
+    #[test]
+    fn test_api_doc_path_parameters() {
+        let json = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        for path in [
+            "/accounts/{id}",
+            "/accounts/{id}/change_password",
+            "/accounts/{id}/activate",
+        ] {
+            let parameters = json["paths"][path]
+                .as_object()
+                .unwrap()
+                .values()
+                .flat_map(|operation| operation["parameters"].as_array().unwrap().clone())
+                .filter(|parameter| parameter["in"] == "path")
+                .collect::<Vec<_>>();
+            assert!(!parameters.is_empty(), "{}", path);
+            for parameter in parameters {
+                assert_eq!(parameter["name"], "id", "{}", path);
+                assert_eq!(parameter["required"], true, "{}", path);
+            }
+        }
+    }
+
+    /// OpenAPI仕様書のスキーマのプロパティ名がキャメルケースであることを確認する。
+    #[test]
+    fn test_api_doc_camel_case() {
+        let json = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let properties = &json["components"]["schemas"]["AccountDto"]["properties"];
+        assert!(properties.get("isActive").is_some());
+        assert!(properties.get("prefectureCode").is_some());
+        assert!(properties.get("is_active").is_none());
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::RwLock;
+
+use domains::models::common::Prefecture;
+
+/// 都道府県キャッシュサービス
+///
+/// 都道府県はほとんど変更されない参照データであるため、都道府県のリストをメモリにキャッシュして、
+/// リクエストごとにデータベースに問い合わせることを避ける。
+/// キャッシュは有効期間を経過するか、`invalidate`を呼び出すと破棄される。
+pub struct CachedPrefectureService {
+    /// キャッシュした日時と都道府県のリスト。
+    cache: RwLock<Option<(Instant, Vec<Prefecture>)>>,
+    /// キャッシュの有効期間。
+    ttl: Duration,
+}
+
+impl CachedPrefectureService {
+    /// コンストラクタ。
+    ///
+    /// # Arguments
+    ///
+    /// * `ttl` - キャッシュの有効期間。
+    ///
+    /// # Returns
+    ///
+    /// 都道府県キャッシュサービス。
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            cache: RwLock::new(None),
+            ttl,
+        }
+    }
+
+    /// 都道府県のリストを返却する。
+    ///
+    /// キャッシュが存在しないか有効期間を経過している場合は、`load`で都道府県のリストを取得してキャッシュする。
+    ///
+    /// # Arguments
+    ///
+    /// * `load` - 都道府県のリストを取得する関数。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 都道府県のリスト。
+    /// * `Err`: エラー。
+    pub async fn list<F, Fut>(&self, load: F) -> anyhow::Result<Vec<Prefecture>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<Vec<Prefecture>>>,
+    {
+        if let Some(prefectures) = self.cached().await {
+            return Ok(prefectures);
+        }
+        let mut cache = self.cache.write().await;
+        // 書き込みロックを待機している間に、他のリクエストがキャッシュした場合
+        if let Some((cached_at, prefectures)) = cache.as_ref() {
+            if cached_at.elapsed() < self.ttl {
+                return Ok(prefectures.clone());
+            }
+        }
+        let prefectures = load().await?;
+        *cache = Some((Instant::now(), prefectures.clone()));
+
+        Ok(prefectures)
+    }
+
+    /// 指定された都道府県コードと一致する都道府県をキャッシュした都道府県のリストから検索して返却する。
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - 都道府県コード。
+    /// * `load` - 都道府県のリストを取得する関数。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 都道府県。検索できなかった場合は`None`。
+    /// * `Err`: エラー。
+    pub async fn find_by_code<F, Fut>(
+        &self,
+        code: u8,
+        load: F,
+    ) -> anyhow::Result<Option<Prefecture>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<Vec<Prefecture>>>,
+    {
+        let prefectures = self.list(load).await?;
+
+        Ok(prefectures.into_iter().find(|p| p.code() == code))
+    }
+
+    /// キャッシュを破棄する。
+    pub async fn invalidate(&self) {
+        *self.cache.write().await = None;
+    }
+
+    /// 有効期間内のキャッシュした都道府県のリストを返却する。
+    ///
+    /// # Returns
+    ///
+    /// 都道府県のリスト。キャッシュが存在しないか有効期間を経過している場合は`None`。
+    async fn cached(&self) -> Option<Vec<Prefecture>> {
+        let cache = self.cache.read().await;
+        match cache.as_ref() {
+            Some((cached_at, prefectures)) if cached_at.elapsed() < self.ttl => {
+                Some(prefectures.clone())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod cached_prefecture_service_tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    fn prefectures() -> Vec<Prefecture> {
+        vec![
+            Prefecture::from_code(13).unwrap(),
+            Prefecture::from_code(27).unwrap(),
+        ]
+    }
+
+    /// 有効期間内は、都道府県のリストを1回だけ取得することを確認する。
+    #[actix_web::test]
+    async fn test_list_loads_once_within_ttl() {
+        let service = CachedPrefectureService::new(Duration::from_secs(60));
+        let calls = Cell::new(0);
+        let load = || async {
+            calls.set(calls.get() + 1);
+            Ok(prefectures())
+        };
+        for _ in 0..3 {
+            let result = service.list(load).await.unwrap();
+            assert_eq!(result.len(), 2);
+        }
+        let result = service.find_by_code(27, load).await.unwrap();
+        assert_eq!(result.unwrap().name(), "大阪府");
+        let result = service.find_by_code(1, load).await.unwrap();
+        assert!(result.is_none());
+        assert_eq!(calls.get(), 1);
+    }
+
+    /// 有効期間を経過した場合に、都道府県のリストを再取得することを確認する。
+    #[actix_web::test]
+    async fn test_list_reloads_after_ttl() {
+        let service = CachedPrefectureService::new(Duration::ZERO);
+        let calls = Cell::new(0);
+        let load = || async {
+            calls.set(calls.get() + 1);
+            Ok(prefectures())
+        };
+        service.list(load).await.unwrap();
+        service.list(load).await.unwrap();
+        assert_eq!(calls.get(), 2);
+    }
+
+    /// キャッシュを破棄した場合に、都道府県のリストを再取得することを確認する。
+    #[actix_web::test]
+    async fn test_invalidate() {
+        let service = CachedPrefectureService::new(Duration::from_secs(60));
+        let calls = Cell::new(0);
+        let load = || async {
+            calls.set(calls.get() + 1);
+            Ok(prefectures())
+        };
+        service.list(load).await.unwrap();
+        service.invalidate().await;
+        service.list(load).await.unwrap();
+        assert_eq!(calls.get(), 2);
+    }
+
+    /// 取得に失敗した場合は、キャッシュしないことを確認する。
+    #[actix_web::test]
+    async fn test_list_does_not_cache_errors() {
+        let service = CachedPrefectureService::new(Duration::from_secs(60));
+        let result = service
+            .list(|| async { Err(anyhow::anyhow!("connection refused")) })
+            .await;
+        assert!(result.is_err());
+        let result = service.list(|| async { Ok(prefectures()) }).await.unwrap();
+        assert_eq!(result.len(), 2);
+    }
+}
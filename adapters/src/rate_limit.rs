@@ -0,0 +1,245 @@
+use std::{
+    collections::HashMap,
+    future::{ready, Future, Ready},
+    net::IpAddr,
+    pin::Pin,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header,
+    HttpResponse,
+};
+use serde_json::json;
+
+/// 固定ウィンドウ方式のレートリミッター
+///
+/// クライアントのIPアドレスごとに、ウィンドウ内で受け付けたリクエストの数を数えて、
+/// 上限を超えたリクエストを拒否する。
+/// Web APIサーバーのすべてのワーカーで共有するため、`HttpServer::new`に渡すクロージャーの外で構築する。
+pub struct RateLimiter {
+    /// ウィンドウ内で受け付けるリクエストの最大数。
+    limit: u32,
+    /// ウィンドウの長さ。
+    window: Duration,
+    /// クライアントのIPアドレスと、ウィンドウを開始した日時及びウィンドウ内で受け付けたリクエストの数。
+    windows: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    /// コンストラクタ。
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - ウィンドウ内で受け付けるリクエストの最大数。
+    /// * `window` - ウィンドウの長さ。
+    ///
+    /// # Returns
+    ///
+    /// レートリミッター。
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// クライアントのリクエストを受け付けるか確認する。
+    ///
+    /// # Arguments
+    ///
+    /// * `ip` - クライアントのIPアドレス。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: ()
+    /// * `Err`: リクエストの上限を超えた場合に、ウィンドウが終了するまでの時間。
+    pub fn check(&self, ip: IpAddr) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap_or_else(|err| err.into_inner());
+        // ウィンドウが終了したクライアントを破棄
+        windows.retain(|_, (started_at, _)| now.duration_since(*started_at) < self.window);
+        let (started_at, count) = windows.entry(ip).or_insert((now, 0));
+        if self.limit <= *count {
+            return Err(self.window.saturating_sub(now.duration_since(*started_at)));
+        }
+        *count += 1;
+
+        Ok(())
+    }
+}
+
+/// レート制限ミドルウェア
+///
+/// クライアントのIPアドレスごとにリクエストの数を制限して、上限を超えたリクエストを
+/// `Retry-After`ヘッダを付与したTOO_MANY_REQUESTSレスポンスで拒否する。
+/// クライアントのIPアドレスは、偽装できる`X-Forwarded-For`ヘッダではなく、接続元のアドレスから取得する。
+/// 接続元のアドレスを取得できないリクエストは、そのまま後続のサービスに渡す。
+pub struct RateLimitMiddleware {
+    /// レートリミッター。
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitMiddleware {
+    /// コンストラクタ。
+    ///
+    /// # Arguments
+    ///
+    /// * `limiter` - ワーカー間で共有するレートリミッター。
+    ///
+    /// # Returns
+    ///
+    /// レート制限ミドルウェア。
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        Self { limiter }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimitMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = RateLimitService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitService {
+            service: Rc::new(service),
+            limiter: Arc::clone(&self.limiter),
+        }))
+    }
+}
+
+/// レート制限サービス
+pub struct RateLimitService<S> {
+    /// 後続のサービス。
+    service: Rc<S>,
+    /// レートリミッター。
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let result = match req.peer_addr() {
+            Some(addr) => self.limiter.check(addr.ip()),
+            None => Ok(()),
+        };
+
+        Box::pin(async move {
+            match result {
+                Ok(()) => Ok(service.call(req).await?.map_into_left_body()),
+                Err(retry_after) => {
+                    // 端数を切り上げた秒数を、再試行までの待機秒数とする
+                    let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+                    log::warn!(
+                        "Too many requests from {} to {}",
+                        req.peer_addr()
+                            .map(|addr| addr.ip().to_string())
+                            .unwrap_or_default(),
+                        req.path()
+                    );
+                    let response = HttpResponse::TooManyRequests()
+                        .insert_header((header::RETRY_AFTER, seconds.to_string()))
+                        .json(json!({
+                            "message": format!(
+                                "リクエストが多すぎます。{}秒後に再試行してください。",
+                                seconds
+                            )
+                        }));
+
+                    Ok(req.into_response(response).map_into_right_body())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod rate_limit_tests {
+    use std::net::SocketAddr;
+
+    use actix_web::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        web, App,
+    };
+
+    use super::*;
+
+    /// 上限を超えたリクエストをTOO_MANY_REQUESTSレスポンスで拒否して、
+    /// 異なるIPアドレスのリクエストは拒否しないことを確認する。
+    #[actix_web::test]
+    async fn test_rate_limit() {
+        let limit = 3;
+        let limiter = Arc::new(RateLimiter::new(limit, Duration::from_secs(60)));
+        let app = test::init_service(
+            App::new().service(
+                web::scope("/auth")
+                    .wrap(RateLimitMiddleware::new(limiter))
+                    .route("/obtain_tokens", web::post().to(HttpResponse::Ok)),
+            ),
+        )
+        .await;
+        let request = |addr: &str| {
+            TestRequest::post()
+                .uri("/auth/obtain_tokens")
+                .peer_addr(addr.parse::<SocketAddr>().unwrap())
+                .to_request()
+        };
+        for _ in 0..limit {
+            let res = test::call_service(&app, request("192.0.2.1:50000")).await;
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+        let res = test::call_service(&app, request("192.0.2.1:50001")).await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = res
+            .headers()
+            .get(header::RETRY_AFTER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(0 < retry_after && retry_after <= 60);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert!(body["message"].is_string());
+        // 異なるIPアドレス
+        let res = test::call_service(&app, request("192.0.2.2:50000")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    /// ウィンドウが終了した後で、リクエストを再度受け付けることを確認する。
+    #[test]
+    fn test_window_reset() {
+        let ip: IpAddr = "192.0.2.1".parse().unwrap();
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        assert!(limiter.check(ip).is_ok());
+        assert!(limiter.check(ip).is_err());
+        let limiter = RateLimiter::new(1, Duration::ZERO);
+        assert!(limiter.check(ip).is_ok());
+        assert!(limiter.check(ip).is_ok());
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+};
+
+use actix_web::{
+    body::{self, BoxBody, MessageBody},
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    error::ErrorInternalServerError,
+    http::header::{self, HeaderName, HeaderValue},
+    FromRequest, HttpMessage, HttpRequest,
+};
+use ulid::Ulid;
+
+/// リクエストIDを格納するHTTPヘッダ名。
+pub const X_REQUEST_ID: &str = "x-request-id";
+
+/// クライアントから受け取るリクエストIDの最大文字数。
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// リクエストID
+///
+/// リクエストを識別するIDで、リクエストのエクステンションに格納される。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// リクエストヘッダからリクエストIDを取得する。
+    ///
+    /// リクエストヘッダにリクエストIDが含まれていないか、リクエストIDが不正な場合は、
+    /// ULIDでリクエストIDを生成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - `X-Request-Id`ヘッダの値。
+    ///
+    /// # Returns
+    ///
+    /// リクエストID。
+    fn from_header(value: Option<&HeaderValue>) -> Self {
+        let id = value
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|v| {
+                !v.is_empty()
+                    && v.len() <= MAX_REQUEST_ID_LEN
+                    && v.chars().all(|c| c.is_ascii_graphic())
+            });
+        match id {
+            Some(id) => Self(id.to_owned()),
+            None => Self(Ulid::new().to_string()),
+        }
+    }
+}
+
+impl FromRequest for RequestId {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let id = req
+            .extensions()
+            .get::<RequestId>()
+            .cloned()
+            .ok_or_else(|| ErrorInternalServerError("リクエストIDが設定されていません。"));
+
+        ready(id)
+    }
+}
+
+/// リクエストIDミドルウェア
+///
+/// リクエストにリクエストIDを割り当てて、レスポンスの`X-Request-Id`ヘッダに設定する。
+/// また、エラーレスポンスのJSONに`requestId`としてリクエストIDを追加する。
+pub struct RequestIdMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestIdMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = actix_web::Error;
+    type Transform = RequestIdService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestIdService {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+/// リクエストIDサービス
+pub struct RequestIdService<S> {
+    /// 後続のサービス。
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = actix_web::Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let request_id = RequestId::from_header(req.headers().get(X_REQUEST_ID));
+        req.extensions_mut().insert(request_id.clone());
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            let res = service.call(req).await?;
+            let status = res.status();
+            let mut res = if status.is_client_error() || status.is_server_error() {
+                add_request_id_to_body(res, &request_id).await?
+            } else {
+                res.map_into_boxed_body()
+            };
+            res.headers_mut().insert(
+                HeaderName::from_static(X_REQUEST_ID),
+                HeaderValue::from_str(&request_id.0).map_err(ErrorInternalServerError)?,
+            );
+
+            Ok(res)
+        })
+    }
+}
+
+/// エラーレスポンスのJSONに、リクエストIDを追加する。
+///
+/// レスポンスボディがJSONオブジェクトでない場合は、レスポンスボディを変更しない。
+///
+/// # Arguments
+///
+/// * `res` - レスポンス。
+/// * `request_id` - リクエストID。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: レスポンス。
+/// * `Err`: レスポンスボディの読み込みに失敗した場合のエラー。
+async fn add_request_id_to_body<B>(
+    res: ServiceResponse<B>,
+    request_id: &RequestId,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error>
+where
+    B: MessageBody + 'static,
+{
+    let is_json = res
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !is_json {
+        return Ok(res.map_into_boxed_body());
+    }
+    let (req, res) = res.into_parts();
+    let (res, body) = res.into_parts();
+    let bytes = body::to_bytes(body)
+        .await
+        .map_err(|err| ErrorInternalServerError(err.into().to_string()))?;
+    let bytes = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+        Ok(serde_json::Value::Object(mut object)) => {
+            object.insert(
+                "requestId".to_owned(),
+                serde_json::Value::String(request_id.0.clone()),
+            );
+            serde_json::to_vec(&object)?.into()
+        }
+        _ => bytes,
+    };
+
+    Ok(ServiceResponse::new(req, res.set_body(BoxBody::new(bytes))))
+}
+
+#[cfg(test)]
+mod request_id_tests {
+    use actix_web::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+    use serde_json::json;
+
+    use super::*;
+
+    async fn not_found(request_id: RequestId) -> HttpResponse {
+        HttpResponse::NotFound().json(json!({"message": format!("not found: {}", request_id.0)}))
+    }
+
+    /// リクエストごとにリクエストIDを生成して、レスポンスヘッダとエラーレスポンスのJSONに同じリクエストIDが含まれることを確認する。
+    #[actix_web::test]
+    async fn test_request_id_generated() {
+        let app = test::init_service(
+            App::new()
+                .wrap(RequestIdMiddleware)
+                .route("/", web::get().to(not_found)),
+        )
+        .await;
+        let req = TestRequest::get().uri("/").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        let request_id = res
+            .headers()
+            .get(X_REQUEST_ID)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned();
+        assert!(Ulid::from_string(&request_id).is_ok());
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["requestId"], request_id.as_str());
+        assert_eq!(body["message"], format!("not found: {}", request_id));
+        // リクエストごとに異なるリクエストIDを生成
+        let req = TestRequest::get().uri("/").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_ne!(
+            res.headers().get(X_REQUEST_ID).unwrap(),
+            request_id.as_str()
+        );
+    }
+
+    /// リクエストヘッダに指定されたリクエストIDを、レスポンスヘッダに設定することを確認する。
+    #[actix_web::test]
+    async fn test_request_id_from_header() {
+        let app = test::init_service(
+            App::new()
+                .wrap(RequestIdMiddleware)
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let req = TestRequest::get()
+            .uri("/")
+            .insert_header((X_REQUEST_ID, "client-request-id"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(X_REQUEST_ID).unwrap(),
+            "client-request-id"
+        );
+    }
+
+    /// 不正なリクエストIDがリクエストヘッダに指定された場合は、リクエストIDを生成することを確認する。
+    #[test]
+    fn test_request_id_from_invalid_header() {
+        let too_long = "a".repeat(MAX_REQUEST_ID_LEN + 1);
+        for value in ["", " ", "has space", too_long.as_str()] {
+            let header = HeaderValue::from_str(value).unwrap();
+            let request_id = RequestId::from_header(Some(&header));
+            assert!(Ulid::from_string(&request_id.0).is_ok(), "{}", value);
+        }
+        assert!(Ulid::from_string(&RequestId::from_header(None).0).is_ok());
+    }
+}
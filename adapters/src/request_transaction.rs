@@ -0,0 +1,341 @@
+use std::{
+    future::{ready, Future, Ready},
+    ops::Deref,
+    pin::Pin,
+    rc::Rc,
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    error::ErrorInternalServerError,
+    web, FromRequest, HttpMessage, HttpRequest, HttpResponse,
+};
+use sea_orm::{DatabaseTransaction, IsolationLevel};
+use serde_json::json;
+
+use usecases::{
+    database_service::DatabaseService, metrics::TimedTransaction, transaction::begin_transaction,
+};
+
+/// リクエストトランザクションの分離レベル。
+///
+/// `None`の場合はデータベースの既定の分離レベル(PostgreSQLはREAD COMMITTED)で実行する。
+/// 環境変数`TRANSACTION_ISOLATION_LEVEL`が設定されている場合は、環境変数の分離レベルで実行する。
+const TRANSACTION_ISOLATION_LEVEL: Option<IsolationLevel> = None;
+
+/// リクエストトランザクション
+///
+/// `RequestTransactionMiddleware`がリクエストの開始時に開始して、リクエストのエクステンションに格納する
+/// データベーストランザクション。ハンドラーの引数に指定して、`*_in_txn`ユースケースに渡す。
+#[derive(Clone)]
+pub struct RequestTransaction(Rc<TimedTransaction>);
+
+impl Deref for RequestTransaction {
+    type Target = DatabaseTransaction;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromRequest for RequestTransaction {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let txn = req
+            .extensions()
+            .get::<RequestTransaction>()
+            .cloned()
+            .ok_or_else(|| {
+                ErrorInternalServerError("リクエストトランザクションが開始されていません。")
+            });
+
+        ready(txn)
+    }
+}
+
+/// リクエストトランザクションミドルウェア
+///
+/// リクエストの開始時にトランザクションを開始して、ハンドラーが成功(2xx)のレスポンスを返却した場合はコミットし、
+/// それ以外のレスポンスまたはエラーを返却した場合はロールバックする。
+/// 複数のユースケースの書き込みを、単一のトランザクションでまとめてコミットまたはロールバックしたいリソースにのみ登録する。
+///
+/// ユースケースが個別にトランザクションを開始する通常の方式と比べて、以下のトレードオフがある。
+///
+/// * レスポンスを生成するまでトランザクションを保持するため、コネクションプールのコネクションと行ロックを保持する時間が長くなる。
+/// * トランザクションを使用しないハンドラーでも、トランザクションを開始する。
+/// * コミットはレスポンスを返却する直前に行うため、コミットに失敗した場合はハンドラーのレスポンスを破棄して、
+///   INTERNAL_SERVER_ERRORレスポンスを返却する。
+/// * `*_in_txn`ユースケースはコミット前にイベントを通知しないため、必要な場合はハンドラーが通知する。
+///
+/// ```ignore
+/// web::resource("/accounts/pair")
+///     .wrap(RequestTransactionMiddleware)
+///     .route(web::post().to(insert_pair))
+/// ```
+pub struct RequestTransactionMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTransactionMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = RequestTransactionService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestTransactionService {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+/// リクエストトランザクションサービス
+pub struct RequestTransactionService<S> {
+    /// 後続のサービス。
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTransactionService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            // トランザクションを開始
+            let db_service = match req.app_data::<web::Data<dyn DatabaseService>>().cloned() {
+                Some(db_service) => db_service,
+                None => {
+                    let response = HttpResponse::InternalServerError()
+                        .json(json!({"message": "データベースサービスが登録されていません。"}));
+                    return Ok(req.into_response(response).map_into_right_body());
+                }
+            };
+            let txn = match begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL)
+                .await
+            {
+                Ok(txn) => RequestTransaction(Rc::new(txn)),
+                Err(err) => {
+                    log::error!("リクエストトランザクションを開始できません。{}", err);
+                    let response = HttpResponse::InternalServerError()
+                        .json(json!({"message": "トランザクションを開始できません。"}));
+                    return Ok(req.into_response(response).map_into_right_body());
+                }
+            };
+            req.extensions_mut().insert(txn.clone());
+            // 後続のサービスを呼び出して、リクエストのエクステンションからトランザクションを取り除く
+            let result = service.call(req).await;
+            let succeeded = match &result {
+                Ok(res) => {
+                    res.request()
+                        .extensions_mut()
+                        .remove::<RequestTransaction>();
+                    res.status().is_success()
+                }
+                Err(_) => false,
+            };
+            let txn = match Rc::try_unwrap(txn.0) {
+                Ok(txn) => txn,
+                Err(_) => {
+                    // ハンドラーがトランザクションの参照を保持している場合は、コミットせずに破棄(ロールバック)
+                    log::warn!(
+                        "リクエストトランザクションの参照が残っているため、コミットしません。"
+                    );
+                    return result.map(ServiceResponse::map_into_left_body);
+                }
+            };
+            // 成功した場合はコミット、それ以外の場合はロールバック
+            if !succeeded {
+                if let Err(err) = txn.rollback().await {
+                    log::error!(
+                        "リクエストトランザクションをロールバックできません。{}",
+                        err
+                    );
+                }
+                return result.map(ServiceResponse::map_into_left_body);
+            }
+            let res = result?;
+            match txn.commit().await {
+                Ok(_) => Ok(res.map_into_left_body()),
+                Err(err) => {
+                    log::error!("リクエストトランザクションをコミットできません。{}", err);
+                    let response = HttpResponse::InternalServerError()
+                        .json(json!({"message": "トランザクションをコミットできません。"}));
+                    let (req, _) = res.into_parts();
+                    Ok(ServiceResponse::new(req, response).map_into_right_body())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod request_transaction_tests {
+    use std::sync::Arc;
+
+    use actix_web::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        App,
+    };
+    use common::hashing::HashingConfig;
+    use domains::services::{
+        clock::{Clock, SystemClock},
+        hashers::{PasswordHasher, PasswordHasherImpl},
+    };
+    use sea_orm::DatabaseConnection;
+    use ulid::Ulid;
+    use usecases::accounts::{ErrorKind, NewAccount};
+
+    use super::*;
+    use crate::database_service::{sqlite_db_service, DatabaseServiceImpl};
+
+    /// 指定されたEメールアドレスの新規アカウントを構築する。
+    fn new_account(email: &str) -> NewAccount {
+        NewAccount {
+            email: email.to_owned(),
+            name: "foo".to_owned(),
+            password: "012abcEFG=+".to_owned(),
+            is_active: true,
+            fixed_number: None,
+            mobile_number: Some("090-1234-5678".to_owned()),
+            postal_code: "100-0014".to_owned(),
+            prefecture_code: 13,
+            address_details: "千代田区永田町1-7-1".to_owned(),
+        }
+    }
+
+    /// 2つのアカウントを、リクエストトランザクションで登録する。
+    async fn insert_pair(
+        db_service: web::Data<dyn DatabaseService>,
+        clock: web::Data<dyn Clock>,
+        hasher: web::Data<dyn PasswordHasher>,
+        txn: RequestTransaction,
+        emails: web::Json<(String, String)>,
+    ) -> HttpResponse {
+        let (first, second) = emails.into_inner();
+        for email in [first, second] {
+            if let Err(err) = usecases::accounts::insert_in_txn(
+                db_service.as_ref(),
+                &txn,
+                clock.as_ref(),
+                hasher.as_ref(),
+                new_account(&email),
+            )
+            .await
+            {
+                let mut response = match err.code {
+                    ErrorKind::InternalServerError => HttpResponse::InternalServerError(),
+                    _ => HttpResponse::BadRequest(),
+                };
+                return response.json(json!({"message": err.message}));
+            }
+        }
+
+        HttpResponse::Created().finish()
+    }
+
+    /// ミドルウェアを登録していないリソースでは、リクエストトランザクションを抽出できないことを確認する。
+    #[actix_web::test]
+    async fn test_extract_without_middleware() {
+        let app = test::init_service(App::new().route(
+            "/",
+            web::post().to(|_: RequestTransaction| async { HttpResponse::Ok().finish() }),
+        ))
+        .await;
+        let req = TestRequest::post().uri("/").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    /// トランザクションを開始できない場合は、ハンドラーを呼び出さずにサーバー内部エラーのレスポンスを返却することを確認する。
+    #[actix_web::test]
+    async fn test_begin_failure() {
+        let db_service: Arc<dyn DatabaseService> =
+            Arc::new(DatabaseServiceImpl::new(DatabaseConnection::Disconnected));
+        let app = test::init_service(
+            App::new().app_data(web::Data::from(db_service)).service(
+                web::resource("/")
+                    .wrap(RequestTransactionMiddleware)
+                    .route(web::post().to(HttpResponse::Ok)),
+            ),
+        )
+        .await;
+        let req = TestRequest::post().uri("/").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["message"], "トランザクションを開始できません。");
+    }
+
+    /// 2つ目のアカウントの登録に失敗した場合に、1つ目のアカウントの登録もロールバックされることを確認する。
+    #[actix_web::test]
+    async fn test_rollback_composed_writes() {
+        dotenv::from_filename(".env").ok();
+        let db_service: Arc<dyn DatabaseService> = Arc::new(sqlite_db_service().await);
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let hasher: Arc<dyn PasswordHasher> =
+            Arc::new(PasswordHasherImpl::new(HashingConfig::from_env()));
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(db_service.clone()))
+                .app_data(web::Data::from(clock))
+                .app_data(web::Data::from(hasher))
+                .service(
+                    web::resource("/accounts/pair")
+                        .wrap(RequestTransactionMiddleware)
+                        .route(web::post().to(insert_pair)),
+                ),
+        )
+        .await;
+        let email = |prefix: &str| format!("{}-{}@example.com", prefix, Ulid::new()).to_lowercase();
+        let exists = |email: String| {
+            let db_service = db_service.clone();
+            async move {
+                let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL)
+                    .await
+                    .unwrap();
+                let account = db_service
+                    .account(&txn)
+                    .find_by_email(domains::models::common::EmailAddress::new(&email).unwrap())
+                    .await
+                    .unwrap();
+                account.is_some()
+            }
+        };
+        // 2つ目のアカウントのEメールアドレスが不正な場合は、1つ目のアカウントも登録しない
+        let first = email("first");
+        let req = TestRequest::post()
+            .uri("/accounts/pair")
+            .set_json((first.clone(), "invalid-email".to_owned()))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert!(!exists(first).await);
+        // 2つのアカウントの登録に成功した場合は、両方のアカウントを登録する
+        let (first, second) = (email("first"), email("second"));
+        let req = TestRequest::post()
+            .uri("/accounts/pair")
+            .set_json((first.clone(), second.clone()))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert!(exists(first).await);
+        assert!(exists(second).await);
+    }
+}
@@ -0,0 +1,97 @@
+use std::{fs::File, io::BufReader, sync::Arc};
+
+use anyhow::{anyhow, Context};
+use rustls::{crypto::ring::default_provider, ServerConfig};
+
+/// PEMファイルからサーバー証明書と秘密鍵を読み込んで、TLSのサーバー設定を構築する。
+///
+/// # Arguments
+///
+/// * `cert_path` - サーバー証明書(証明書チェーン)を格納したPEMファイルのパス。
+/// * `key_path` - 秘密鍵を格納したPEMファイルのパス。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: TLSのサーバー設定。
+/// * `Err`: ファイルを開けない場合、ファイルを解釈できない場合、または証明書と秘密鍵が対応していない場合のエラー。
+pub(crate) fn rustls_config(cert_path: &str, key_path: &str) -> anyhow::Result<ServerConfig> {
+    let mut reader = BufReader::new(
+        File::open(cert_path)
+            .with_context(|| format!("サーバー証明書({})を開けません。", cert_path))?,
+    );
+    let certs = rustls_pemfile::certs(&mut reader)
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("サーバー証明書({})を解釈できません。", cert_path))?;
+    if certs.is_empty() {
+        return Err(anyhow!(
+            "サーバー証明書({})に証明書が含まれていません。",
+            cert_path
+        ));
+    }
+    let mut reader = BufReader::new(
+        File::open(key_path).with_context(|| format!("秘密鍵({})を開けません。", key_path))?,
+    );
+    let key = rustls_pemfile::private_key(&mut reader)
+        .with_context(|| format!("秘密鍵({})を解釈できません。", key_path))?
+        .ok_or_else(|| anyhow!("秘密鍵({})に秘密鍵が含まれていません。", key_path))?;
+
+    ServerConfig::builder_with_provider(Arc::new(default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .with_context(|| {
+            format!(
+                "サーバー証明書({})と秘密鍵({})からTLSの設定を構築できません。",
+                cert_path, key_path
+            )
+        })
+}
+
+#[cfg(test)]
+mod tls_tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+
+    /// テスト用のファイルを一時ディレクトリに作成して、そのパスを返却する。
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    /// サーバー証明書または秘密鍵を読み込めない場合に、エラーを返却することを確認する。
+    #[test]
+    fn test_rustls_config_invalid_files() {
+        let not_pem = temp_file("not-pem.pem", "not a pem file\n");
+        let not_pem = not_pem.to_str().unwrap();
+        let missing = "/nonexistent/server.crt";
+
+        let err = rustls_config(missing, not_pem).unwrap_err();
+        assert!(err.to_string().contains("開けません"), "{}", err);
+        let err = rustls_config(not_pem, not_pem).unwrap_err();
+        assert!(
+            err.to_string().contains("証明書が含まれていません"),
+            "{}",
+            err
+        );
+
+        let cert = temp_file(
+            "cert.pem",
+            "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n",
+        );
+        let cert = cert.to_str().unwrap();
+        let err = rustls_config(cert, missing).unwrap_err();
+        assert!(err.to_string().contains("秘密鍵"), "{}", err);
+        let err = rustls_config(cert, not_pem).unwrap_err();
+        assert!(
+            err.to_string().contains("秘密鍵が含まれていません"),
+            "{}",
+            err
+        );
+        let _ = fs::remove_file(cert);
+        let _ = fs::remove_file(not_pem);
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    web, HttpResponse,
+};
+use serde_json::json;
+
+use common::{jwt_token::access_token, ENV_VALUES};
+use usecases::database_service::DatabaseService;
+
+/// トークン検証ミドルウェア
+///
+/// `Authorization`ヘッダにBearerトークンが指定されたリクエスト(トークンをクッキーで返却している場合は、
+/// アクセストークンのクッキーが指定されたリクエストを含む)について、
+/// アクセストークンがデータベースに記録されているか確認して、記録されていない場合はリクエストを拒否する。
+/// JWTの署名と有効期限は`Claims`の抽出時に検証するため、このミドルウェアでは検証しない。
+/// `Authorization`ヘッダが指定されていないリクエストは、そのまま後続のサービスに渡す。
+pub struct TokenValidationMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for TokenValidationMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = TokenValidationService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TokenValidationService {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+/// トークン検証サービス
+pub struct TokenValidationService<S> {
+    /// 後続のサービス。
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for TokenValidationService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            let token = match bearer_token(&req) {
+                Some(token) => token,
+                None => return Ok(service.call(req).await?.map_into_left_body()),
+            };
+            let db_service = req.app_data::<web::Data<dyn DatabaseService>>().cloned();
+            let response = match db_service {
+                Some(db_service) => {
+                    match usecases::auth::is_access_token_stored(db_service.as_ref(), &token).await
+                    {
+                        Ok(true) => return Ok(service.call(req).await?.map_into_left_body()),
+                        Ok(false) => HttpResponse::Unauthorized()
+                            .json(json!({"message": "アクセストークンが無効です。"})),
+                        Err(err) => HttpResponse::InternalServerError()
+                            .json(json!({"message": err.message})),
+                    }
+                }
+                None => HttpResponse::InternalServerError()
+                    .json(json!({"message": "データベースサービスが登録されていません。"})),
+            };
+
+            Ok(req.into_response(response).map_into_right_body())
+        })
+    }
+}
+
+/// `Authorization`ヘッダからBearerトークンを取得する。
+///
+/// トークンをクッキーで返却している場合は、`Authorization`ヘッダが指定されていないリクエストの
+/// アクセストークンをクッキーから取得する。
+///
+/// # Arguments
+///
+/// * `req` - リクエスト。
+///
+/// # Returns
+///
+/// Bearerトークン。`Authorization`ヘッダが存在しないか、Bearerトークンが指定されていない場合は`None`。
+fn bearer_token(req: &ServiceRequest) -> Option<String> {
+    access_token(req.request(), ENV_VALUES.token_delivery.uses_cookie())
+}
+
+#[cfg(test)]
+mod token_validation_tests {
+    use std::sync::Arc;
+
+    use actix_web::{
+        http::{header, StatusCode},
+        test::{self, TestRequest},
+        App,
+    };
+    use domains::{models::accounts::Role, services::clock::SystemClock};
+
+    use super::*;
+    use crate::{
+        authorization,
+        database_service::{insert_sqlite_account, insert_sqlite_tokens, sqlite_db_service},
+    };
+
+    /// `Authorization`ヘッダからBearerトークンを取得できることを確認する。
+    #[test]
+    fn test_bearer_token() {
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Bearer  abc.def.ghi "))
+            .to_srv_request();
+        assert_eq!(bearer_token(&req), Some("abc.def.ghi".to_owned()));
+        for value in ["Basic abc", "Bearer", "Bearer   "] {
+            let req = TestRequest::default()
+                .insert_header((header::AUTHORIZATION, value))
+                .to_srv_request();
+            assert_eq!(bearer_token(&req), None, "{}", value);
+        }
+        let req = TestRequest::default().to_srv_request();
+        assert_eq!(bearer_token(&req), None);
+    }
+
+    /// `Authorization`ヘッダが指定されていないリクエストは、データベースにアクセスせずに後続のサービスに渡すことを確認する。
+    #[actix_web::test]
+    async fn test_request_without_token() {
+        let app = test::init_service(
+            App::new()
+                .wrap(TokenValidationMiddleware)
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let req = TestRequest::get().uri("/").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    /// データベースサービスが登録されていない場合は、トークンを指定したリクエストを後続のサービスに渡さないことを確認する。
+    #[actix_web::test]
+    async fn test_request_without_database_service() {
+        let app = test::init_service(
+            App::new()
+                .wrap(TokenValidationMiddleware)
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let req = TestRequest::get()
+            .uri("/")
+            .insert_header((header::AUTHORIZATION, "Bearer abc.def.ghi"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    /// 有効期限内のアクセストークンでも、トークンを取り消した場合や、アカウントを無効化してトークンが削除された場合は、
+    /// リクエストを拒否することを確認する。
+    #[actix_web::test]
+    async fn test_request_with_deleted_token_on_sqlite() {
+        dotenv::from_filename(".env").ok();
+        let db_service = Arc::new(sqlite_db_service().await);
+        let account = insert_sqlite_account(&db_service, "foo@example.com").await;
+        let account_id = account.id.to_string();
+        let app_db_service: Arc<dyn DatabaseService> = db_service.clone();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(app_db_service))
+                .wrap(TokenValidationMiddleware)
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let call = |authorization: String| {
+            let req = TestRequest::get()
+                .uri("/")
+                .insert_header((header::AUTHORIZATION, authorization))
+                .to_request();
+            test::call_service(&app, req)
+        };
+
+        // トークンを取り消した場合
+        let authorization = authorization::bearer_token(&account_id, Role::User);
+        let token = authorization.strip_prefix("Bearer ").unwrap();
+        insert_sqlite_tokens(&db_service, account.id.clone(), token, "revoked-refresh").await;
+        assert_eq!(call(authorization.clone()).await.status(), StatusCode::OK);
+        usecases::auth::revoke_tokens(db_service.as_ref(), &account_id)
+            .await
+            .unwrap();
+        assert_eq!(call(authorization).await.status(), StatusCode::UNAUTHORIZED);
+
+        // アカウントを無効化した場合
+        // 取り消したトークンと同じトークンにならないように、クレームの権限を変えて生成
+        let authorization = authorization::bearer_token(&account_id, Role::Admin);
+        let token = authorization.strip_prefix("Bearer ").unwrap();
+        insert_sqlite_tokens(
+            &db_service,
+            account.id.clone(),
+            token,
+            "deactivated-refresh",
+        )
+        .await;
+        assert_eq!(call(authorization.clone()).await.status(), StatusCode::OK);
+        usecases::accounts::set_active(
+            db_service.as_ref(),
+            &SystemClock,
+            account.id.clone(),
+            false,
+            account.id.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(call(authorization).await.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+
+use crate::ENV_VALUES;
+
+/// パスワードハッシュ化設定
+///
+/// パスワードをハッシュ化するときと、ハッシュ化したパスワードを検証するときに使用する。
+#[derive(Debug, Clone)]
+pub struct HashingConfig {
+    /// ハッシュ関数名。
+    pub func: String,
+    /// ハッシュ化ラウンド数。
+    pub round: u32,
+    /// ソルト文字数。
+    pub sault_len: usize,
+    /// パスワードに追加するペッパー。
+    ///
+    /// ペッパーのバージョンを記録していない、ハッシュ化したパスワードに使用する。
+    pub pepper: String,
+    /// バージョンをキーとした、パスワードに追加するペッパーのマップ。
+    pub peppers: BTreeMap<u32, String>,
+}
+
+impl HashingConfig {
+    /// 環境変数`PASSWORD_HASH_FUNC`、`PASSWORD_HASH_ROUND`、`PASSWORD_SAULT_LEN`、`PASSWORD_PEPPER`及び
+    /// `PASSWORD_PEPPER_V<n>`に設定された値で、パスワードハッシュ化設定を構築する。
+    ///
+    /// # Returns
+    ///
+    /// パスワードハッシュ化設定。
+    pub fn from_env() -> Self {
+        Self {
+            func: ENV_VALUES.password_hash_func.clone(),
+            round: ENV_VALUES.password_hash_round,
+            sault_len: ENV_VALUES.password_sault_len,
+            pepper: ENV_VALUES.password_pepper.clone(),
+            peppers: ENV_VALUES.password_peppers.clone(),
+        }
+    }
+
+    /// パスワードをハッシュ化するときに使用するペッパーを返却する。
+    ///
+    /// # Returns
+    ///
+    /// 最も大きいバージョンとペッパー。バージョン付きのペッパーが設定されていない場合は、`None`とバージョンのないペッパー。
+    pub fn current_pepper(&self) -> (Option<u32>, &str) {
+        match self.peppers.iter().next_back() {
+            Some((version, pepper)) => (Some(*version), pepper),
+            None => (None, &self.pepper),
+        }
+    }
+
+    /// 指定されたバージョンのペッパーを返却する。
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - ペッパーのバージョン。`None`の場合はバージョンのないペッパー。
+    ///
+    /// # Returns
+    ///
+    /// ペッパー。指定されたバージョンのペッパーが設定されていない場合は`None`。
+    pub fn pepper(&self, version: Option<u32>) -> Option<&str> {
+        match version {
+            Some(version) => self.peppers.get(&version).map(String::as_str),
+            None => Some(&self.pepper),
+        }
+    }
+}
@@ -1,22 +1,350 @@
-use std::{future::Future, pin::Pin};
+use std::{fs, future::Future, pin::Pin, str::FromStr};
 
-use actix_web::{error::ErrorUnauthorized, Error, FromRequest};
-use anyhow::anyhow;
-use chrono::{TimeZone, Utc};
+use actix_web::{error::ErrorUnauthorized, http::header, Error, FromRequest};
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use hmac::{Hmac, Mac};
-use jwt::{Header, SignWithKey, Token, VerifyWithKey};
-use serde::{Deserialize, Serialize};
-use sha2::Sha256;
+use jwt::{
+    Header, PKeyWithDigest, SignWithKey, SigningAlgorithm, Token, VerifyWithKey, VerifyingAlgorithm,
+};
+use once_cell::sync::Lazy;
+use openssl::{
+    hash::MessageDigest,
+    pkey::{PKey, Private, Public},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Sha256, Sha384, Sha512};
 
 use crate::ENV_VALUES;
 
+/// JWTの署名アルゴリズム
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JwtAlgorithm {
+    /// HMAC SHA-256
+    Hs256,
+    /// HMAC SHA-384
+    Hs384,
+    /// HMAC SHA-512
+    Hs512,
+    /// RSASSA-PKCS1-v1_5 SHA-256
+    Rs256,
+}
+
+impl FromStr for JwtAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "HS256" => Ok(Self::Hs256),
+            "HS384" => Ok(Self::Hs384),
+            "HS512" => Ok(Self::Hs512),
+            "RS256" => Ok(Self::Rs256),
+            _ => Err(anyhow!("JWTの署名アルゴリズム({})に対応していません。", s)),
+        }
+    }
+}
+
+/// アクセストークンを記録するクッキー名。
+pub const ACCESS_TOKEN_COOKIE: &str = "access_token";
+
+/// リフレッシュトークンを記録するクッキー名。
+pub const REFRESH_TOKEN_COOKIE: &str = "refresh_token";
+
+/// トークン取得APIが、アクセストークンとリフレッシュトークンを返却する方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenDelivery {
+    /// レスポンスボディのJSONで返却する。
+    Body,
+    /// `HttpOnly`なクッキーに設定して、レスポンスボディには含めない。
+    Cookie,
+    /// レスポンスボディのJSONで返却して、クッキーにも設定する。
+    Both,
+}
+
+impl TokenDelivery {
+    /// トークンをクッキーに設定するか確認する。
+    ///
+    /// # Returns
+    ///
+    /// トークンをクッキーに設定する場合は`true`。
+    pub fn uses_cookie(&self) -> bool {
+        matches!(self, Self::Cookie | Self::Both)
+    }
+
+    /// トークンをレスポンスボディで返却するか確認する。
+    ///
+    /// # Returns
+    ///
+    /// トークンをレスポンスボディで返却する場合は`true`。
+    pub fn uses_body(&self) -> bool {
+        matches!(self, Self::Body | Self::Both)
+    }
+}
+
+impl FromStr for TokenDelivery {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "body" => Ok(Self::Body),
+            "cookie" => Ok(Self::Cookie),
+            "both" => Ok(Self::Both),
+            _ => Err(anyhow!("トークンの返却方法({})に対応していません。", s)),
+        }
+    }
+}
+
+/// JWTの署名と検証に使用する鍵
+pub enum JwtKeys {
+    /// HMAC SHA-256の秘密鍵
+    Hs256(Hmac<Sha256>),
+    /// HMAC SHA-384の秘密鍵
+    Hs384(Hmac<Sha384>),
+    /// HMAC SHA-512の秘密鍵
+    Hs512(Hmac<Sha512>),
+    /// RS256のRSA秘密鍵と公開鍵
+    Rs256 {
+        /// 署名に使用するRSA秘密鍵。
+        private: PKeyWithDigest<Private>,
+        /// 検証に使用するRSA公開鍵。
+        public: PKeyWithDigest<Public>,
+    },
+}
+
+impl JwtKeys {
+    /// HMACの秘密鍵を構築する。
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - HMACの署名アルゴリズム。
+    /// * `secret_key` - 秘密鍵。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: JWTの署名と検証に使用する鍵。
+    /// * `Err`: 署名アルゴリズムがHMACでない場合、または鍵を生成できない場合のエラー。
+    pub fn hmac(algorithm: JwtAlgorithm, secret_key: &[u8]) -> anyhow::Result<Self> {
+        let context = "トークンを生成する鍵の生成に失敗しました。";
+        match algorithm {
+            JwtAlgorithm::Hs256 => Ok(Self::Hs256(
+                Hmac::new_from_slice(secret_key).context(context)?,
+            )),
+            JwtAlgorithm::Hs384 => Ok(Self::Hs384(
+                Hmac::new_from_slice(secret_key).context(context)?,
+            )),
+            JwtAlgorithm::Hs512 => Ok(Self::Hs512(
+                Hmac::new_from_slice(secret_key).context(context)?,
+            )),
+            JwtAlgorithm::Rs256 => Err(anyhow!("RS256はHMACの署名アルゴリズムではありません。")),
+        }
+    }
+
+    /// PEM形式のRSA秘密鍵と公開鍵から、RS256の鍵を構築する。
+    ///
+    /// # Arguments
+    ///
+    /// * `private_pem` - PEM形式のRSA秘密鍵。
+    /// * `public_pem` - PEM形式のRSA公開鍵。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: JWTの署名と検証に使用する鍵。
+    /// * `Err`: 鍵を解釈できない場合のエラー。
+    pub fn rs256(private_pem: &[u8], public_pem: &[u8]) -> anyhow::Result<Self> {
+        Ok(Self::Rs256 {
+            private: PKeyWithDigest {
+                digest: MessageDigest::sha256(),
+                key: PKey::private_key_from_pem(private_pem)
+                    .context("JWTに署名するRSA秘密鍵を解釈できません。")?,
+            },
+            public: PKeyWithDigest {
+                digest: MessageDigest::sha256(),
+                key: PKey::public_key_from_pem(public_pem)
+                    .context("JWTの署名を検証するRSA公開鍵を解釈できません。")?,
+            },
+        })
+    }
+
+    /// 環境変数に設定された署名アルゴリズムと鍵から、JWTの署名と検証に使用する鍵を構築する。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: JWTの署名と検証に使用する鍵。
+    /// * `Err`: 鍵のファイルを読み込めない場合、または鍵を構築できない場合のエラー。
+    fn from_env() -> anyhow::Result<Self> {
+        match ENV_VALUES.jwt_algorithm {
+            JwtAlgorithm::Rs256 => {
+                let read = |path: &Option<String>| {
+                    let path = path.as_deref().unwrap_or_default();
+                    fs::read(path)
+                        .with_context(|| format!("鍵ファイル({})を読み込めません。", path))
+                };
+                Self::rs256(
+                    &read(&ENV_VALUES.jwt_private_key_path)?,
+                    &read(&ENV_VALUES.jwt_public_key_path)?,
+                )
+            }
+            algorithm => Self::hmac(algorithm, ENV_VALUES.jwt_token_secret_key.as_bytes()),
+        }
+    }
+
+    /// クレイムに署名してJWTを生成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `claims` - クレイム。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: JWT。
+    /// * `Err`: エラー。
+    pub fn sign<T: Serialize>(&self, claims: &T) -> anyhow::Result<String> {
+        match self {
+            Self::Hs256(key) => sign_with_key(claims, key),
+            Self::Hs384(key) => sign_with_key(claims, key),
+            Self::Hs512(key) => sign_with_key(claims, key),
+            Self::Rs256 { private, .. } => sign_with_key(claims, private),
+        }
+    }
+
+    /// JWTの署名を検証して、クレイムをデコードする。
+    ///
+    /// JWTのヘッダに記録された署名アルゴリズムが鍵の署名アルゴリズムと異なる場合は、検証に失敗する。
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - JWT。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: クレイム。
+    /// * `Err`: エラー。
+    pub fn verify<T: DeserializeOwned>(&self, token: &str) -> anyhow::Result<T> {
+        match self {
+            Self::Hs256(key) => verify_with_key(token, key),
+            Self::Hs384(key) => verify_with_key(token, key),
+            Self::Hs512(key) => verify_with_key(token, key),
+            Self::Rs256 { public, .. } => verify_with_key(token, public),
+        }
+    }
+}
+
+/// 環境変数から構築した、JWTの署名と検証に使用する鍵。
+static JWT_KEYS: Lazy<Result<JwtKeys, String>> =
+    Lazy::new(|| JwtKeys::from_env().map_err(|err| format!("{:#}", err)));
+
+/// 環境変数から、JWTの署名と検証に使用する鍵を構築できるか確認する。
+///
+/// Webサーバーの起動時に呼び出して、鍵の設定に誤りがある場合に起動を中止するために使用する。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 鍵を構築できた場合は`()`。
+/// * `Err`: エラー。
+pub fn validate_jwt_keys() -> anyhow::Result<()> {
+    jwt_keys().map(|_| ())
+}
+
+/// 環境変数から構築した、JWTの署名と検証に使用する鍵を返却する。
+fn jwt_keys() -> anyhow::Result<&'static JwtKeys> {
+    JWT_KEYS.as_ref().map_err(|err| anyhow!("{}", err))
+}
+
+/// 鍵の署名アルゴリズムをヘッダに設定して、クレイムに署名する。
+fn sign_with_key<T: Serialize>(claims: &T, key: &impl SigningAlgorithm) -> anyhow::Result<String> {
+    let header = Header {
+        algorithm: key.algorithm_type(),
+        ..Default::default()
+    };
+    let signed_token = Token::new(header, claims)
+        .sign_with_key(key)
+        .map_err(|err| anyhow!("トークンの生成に失敗しました。{}", err))?;
+
+    Ok(signed_token.into())
+}
+
+/// 鍵でJWTの署名を検証して、クレイムをデコードする。
+fn verify_with_key<T: DeserializeOwned>(
+    token: &str,
+    key: &impl VerifyingAlgorithm,
+) -> anyhow::Result<T> {
+    let token: Token<Header, T, _> = VerifyWithKey::verify_with_key(token, key)
+        .map_err(|err| anyhow!("トークンのデコードに失敗しました。{}", err))?;
+    let (_, claims) = token.into();
+
+    Ok(claims)
+}
+
 /// クレイム
+///
+/// Eメールアドレス変更トークンなど、別の用途のトークンをアクセストークンとして使用できないように、
+/// 未知のクレイムを含むトークンはデコードしない。
 #[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Claims {
     /// アカウントID.
     pub sub: String,
     /// 有効期限を示すUnixエポック(1970-01-01(UTC)からの経過秒数)。
     pub exp: i64,
+    /// 発行日時を示すUnixエポック。`gen_jwt_token`がトークンを生成した日時を設定する。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    /// 有効開始日時を示すUnixエポック。`gen_jwt_token`がトークンを生成した日時を設定する。
+    ///
+    /// 記録されていないトークンは、発行された直後から有効なトークンとして扱う。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
+    /// アカウントの権限(`user`または`admin`)。権限が記録されていないトークンは一般ユーザーとして扱う。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// 発行者。`gen_jwt_token`が環境変数`JWT_ISSUER`の値を設定する。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// 受信者。`gen_jwt_token`が環境変数`JWT_AUDIENCE`の値を設定する。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+}
+
+/// Eメールアドレス変更クレイム
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmailChangeClaims {
+    /// アカウントID。
+    pub sub: String,
+    /// トークンを発行したときのアカウントのEメールアドレス。
+    ///
+    /// Eメールアドレスを変更した後は一致しなくなるため、同じトークンで再度変更できない。
+    pub current_email: String,
+    /// 変更後のEメールアドレス。
+    pub new_email: String,
+    /// 有効期限を示すUnixエポック(1970-01-01(UTC)からの経過秒数)。
+    pub exp: i64,
+    /// 発行日時を示すUnixエポック。`gen_jwt_token`がトークンを生成した日時を設定する。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    /// 有効開始日時を示すUnixエポック。`gen_jwt_token`がトークンを生成した日時を設定する。
+    ///
+    /// 記録されていないトークンは、発行された直後から有効なトークンとして扱う。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
+    /// 発行者。`gen_jwt_token`が環境変数`JWT_ISSUER`の値を設定する。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// 受信者。`gen_jwt_token`が環境変数`JWT_AUDIENCE`の値を設定する。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
 }
 
 impl FromRequest for Claims {
@@ -27,25 +355,50 @@ impl FromRequest for Claims {
         req: &actix_web::HttpRequest,
         _payload: &mut actix_http::Payload,
     ) -> Self::Future {
-        // Authorizationヘッダを取得
-        let auth = req.headers().get("Authorization");
-        if auth.is_none() {
-            return Box::pin(async move {
-                Err(ErrorUnauthorized("Authorizationヘッダが存在しません。"))
-            });
-        }
-        let auth = auth.unwrap().to_owned();
-        // Bearerトークンを取得
-        let split: Vec<&str> = auth.to_str().unwrap().split("Bearer").collect();
-        let token = split[1].trim().to_owned();
-        // トークンをデコード
+        // Authorizationヘッダ、またはクッキーからアクセストークンを取得
+        let token = access_token(req, ENV_VALUES.token_delivery.uses_cookie());
         Box::pin(async move {
+            let token =
+                token.ok_or_else(|| ErrorUnauthorized("アクセストークンが指定されていません。"))?;
+            // トークンをデコード
             decode_jwt_token(&token).map_err(|err| ErrorUnauthorized(format!("{}", err)))
         })
     }
 }
 
-/// JWTトークンを生成する。
+/// リクエストからアクセストークンを取得する。
+///
+/// `Authorization`ヘッダが指定されている場合は、ヘッダのBearerトークンを取得する。
+/// `Authorization`ヘッダが指定されておらず、`read_cookie`が`true`の場合は、クッキーからアクセストークンを取得する。
+///
+/// # Arguments
+///
+/// * `req` - リクエスト。
+/// * `read_cookie` - `Authorization`ヘッダが指定されていない場合に、クッキーからアクセストークンを取得するか。
+///
+/// # Returns
+///
+/// アクセストークン。アクセストークンが指定されていない場合は`None`。
+pub fn access_token(req: &actix_web::HttpRequest, read_cookie: bool) -> Option<String> {
+    let token = match req.headers().get(header::AUTHORIZATION) {
+        Some(value) => value
+            .to_str()
+            .ok()
+            .and_then(|value| value.trim().strip_prefix("Bearer"))
+            .map(|token| token.trim().to_owned()),
+        None if read_cookie => req
+            .cookie(ACCESS_TOKEN_COOKIE)
+            .map(|cookie| cookie.value().trim().to_owned()),
+        None => None,
+    };
+
+    token.filter(|token| !token.is_empty())
+}
+
+/// 環境変数に設定された署名アルゴリズムで、JWTトークンを生成する。
+///
+/// 環境変数`JWT_ISSUER`または`JWT_AUDIENCE`が設定されている場合は、クレイムの`iss`または`aud`に設定する。
+/// クレイムに`iat`または`nbf`が設定されていない場合は、トークンを生成した日時を設定する。
 ///
 /// # Arguments
 ///
@@ -57,19 +410,146 @@ impl FromRequest for Claims {
 ///
 /// * `Ok`: JWT。
 /// * `Err`: エラー。
-pub fn gen_jwt_token(claims: &Claims) -> anyhow::Result<String> {
-    // 環境変数から秘密鍵を取得して鍵を生成
-    let secret_key = &ENV_VALUES.jwt_token_secret_key;
-    let key: Hmac<Sha256> = Hmac::new_from_slice(secret_key.as_bytes())
-        .map_err(|err| anyhow!("トークを生成する鍵の生成に失敗しました。{}", err))?;
-    // JWTを生成
-    let header: Header = Default::default();
-    let unsigned_token = Token::new(header, claims);
-    let signed_token = unsigned_token
-        .sign_with_key(&key)
-        .map_err(|err| anyhow!("トークンの生成に失敗しました。{}", err))?;
+pub fn gen_jwt_token<T: Serialize>(claims: &T) -> anyhow::Result<String> {
+    let claims = with_registered_claims(
+        claims,
+        ENV_VALUES.jwt_issuer.as_deref(),
+        ENV_VALUES.jwt_audience.as_deref(),
+        Utc::now().timestamp(),
+    )?;
 
-    Ok(signed_token.into())
+    jwt_keys()?.sign(&claims)
+}
+
+/// クレイムに発行者、受信者、発行日時及び有効開始日時を設定する。
+///
+/// # Arguments
+///
+/// * `claims` - クレイム。
+/// * `iss` - 発行者。`None`の場合は設定しない。
+/// * `aud` - 受信者。`None`の場合は設定しない。
+/// * `now` - 現在日時を示すUnixエポック。クレイムに`iat`または`nbf`が設定されていない場合に設定する。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 発行者、受信者、発行日時及び有効開始日時を設定したクレイム。
+/// * `Err`: クレイムをJSONオブジェクトに変換できない場合のエラー。
+fn with_registered_claims<T: Serialize>(
+    claims: &T,
+    iss: Option<&str>,
+    aud: Option<&str>,
+    now: i64,
+) -> anyhow::Result<serde_json::Value> {
+    let mut claims = serde_json::to_value(claims)?;
+    let object = claims
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("クレイムをJSONオブジェクトに変換できません。"))?;
+    if let Some(iss) = iss {
+        object.insert("iss".to_owned(), iss.into());
+    }
+    if let Some(aud) = aud {
+        object.insert("aud".to_owned(), aud.into());
+    }
+    object.entry("iat").or_insert_with(|| now.into());
+    object.entry("nbf").or_insert_with(|| now.into());
+
+    Ok(claims)
+}
+
+/// トークンの発行者と受信者を確認する。
+///
+/// 期待する発行者または受信者が`None`の場合は、その項目を確認しない。
+///
+/// # Arguments
+///
+/// * `iss` - トークンの発行者。
+/// * `aud` - トークンの受信者。
+/// * `expected_iss` - 期待する発行者。
+/// * `expected_aud` - 期待する受信者。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 発行者と受信者が一致する場合は`()`。
+/// * `Err`: 発行者または受信者が一致しない場合のエラー。
+fn check_registered_claims(
+    iss: Option<&str>,
+    aud: Option<&str>,
+    expected_iss: Option<&str>,
+    expected_aud: Option<&str>,
+) -> anyhow::Result<()> {
+    if expected_iss.is_some() && iss != expected_iss {
+        return Err(anyhow!("トークンの発行者が一致しません。"));
+    }
+    if expected_aud.is_some() && aud != expected_aud {
+        return Err(anyhow!("トークンの受信者が一致しません。"));
+    }
+
+    Ok(())
+}
+
+/// JWTトークンの署名を検証して、クレイムをデコードする。
+///
+/// # Arguments
+///
+/// * `token` - JWTトークン。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: クレイム。
+/// * `Err`: エラー。
+fn verify_jwt_token<T: DeserializeOwned>(token: &str) -> anyhow::Result<T> {
+    jwt_keys()?.verify(token)
+}
+
+/// トークンの有効期限と有効開始日時を確認する。
+///
+/// サーバー間の時計のずれを許容するため、有効期限を`skew_seconds`秒過ぎるまで、
+/// また有効開始日時の`skew_seconds`秒前から、トークンを有効とする。
+///
+/// # Arguments
+///
+/// * `exp` - 有効期限を示すUnixエポック。
+/// * `nbf` - 有効開始日時を示すUnixエポック。`None`の場合は確認しない。
+/// * `now` - 現在日時。
+/// * `skew_seconds` - 許容する時計のずれの秒数。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 有効期間内の場合は`()`。
+/// * `Err`: 有効期限が切れている場合、または有効開始日時前の場合のエラー。
+fn check_time_claims(
+    exp: i64,
+    nbf: Option<i64>,
+    now: DateTime<Utc>,
+    skew_seconds: u64,
+) -> anyhow::Result<()> {
+    let skew = Duration::seconds(skew_seconds as i64);
+    let expired = Utc
+        .timestamp_opt(exp, 0)
+        .single()
+        .ok_or_else(|| anyhow!("トークンの有効期限({})が不正です。", exp))?;
+    if expired <= now - skew {
+        return Err(anyhow!("トークンの有効期限が切れています。"));
+    }
+    if let Some(nbf) = nbf {
+        let not_before = Utc
+            .timestamp_opt(nbf, 0)
+            .single()
+            .ok_or_else(|| anyhow!("トークンの有効開始日時({})が不正です。", nbf))?;
+        if now + skew < not_before {
+            return Err(anyhow!("トークンの有効開始日時になっていません。"));
+        }
+    }
+
+    Ok(())
 }
 
 /// JWTトークンをデコードする。
@@ -85,19 +565,51 @@ pub fn gen_jwt_token(claims: &Claims) -> anyhow::Result<String> {
 /// * `Ok`: アカウントIDを示す文字列と、トークンの有効期限を示すUnixエポック(1970-01-01からの経過秒数)。
 /// * `Err`: エラー。
 pub fn decode_jwt_token(token: &str) -> anyhow::Result<Claims> {
-    // 環境変数から秘密鍵を取得して鍵を生成
-    let secret_key = &ENV_VALUES.jwt_token_secret_key;
-    let key: Hmac<Sha256> = Hmac::new_from_slice(secret_key.as_bytes())
-        .map_err(|err| anyhow!("トークンを生成する鍵の生成に失敗しました。{}", err))?;
-    // トークンをデコード
-    let token: Token<Header, Claims, _> = VerifyWithKey::verify_with_key(token, &key)
-        .map_err(|err| anyhow!("トークンのデコードに失敗しました。{}", err))?;
-    let (_, claims) = token.into();
-    // トークンの有効期限を確認
-    let expired = Utc.timestamp(claims.exp, 0);
-    if expired <= Utc::now() {
-        return Err(anyhow!("トークンの有効期限が切れています。"));
-    }
+    let claims: Claims = verify_jwt_token(token)?;
+    // トークンの有効期限、有効開始日時、発行者及び受信者を確認
+    check_time_claims(
+        claims.exp,
+        claims.nbf,
+        Utc::now(),
+        ENV_VALUES.jwt_clock_skew_seconds,
+    )?;
+    check_registered_claims(
+        claims.iss.as_deref(),
+        claims.aud.as_deref(),
+        ENV_VALUES.jwt_issuer.as_deref(),
+        ENV_VALUES.jwt_audience.as_deref(),
+    )?;
+
+    Ok(claims)
+}
+
+/// Eメールアドレス変更トークンをデコードする。
+///
+/// # Arguments
+///
+/// * `token` - Eメールアドレス変更トークン。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: Eメールアドレス変更クレイム。
+/// * `Err`: エラー。
+pub fn decode_email_change_token(token: &str) -> anyhow::Result<EmailChangeClaims> {
+    let claims: EmailChangeClaims = verify_jwt_token(token)?;
+    // トークンの有効期限、有効開始日時、発行者及び受信者を確認
+    check_time_claims(
+        claims.exp,
+        claims.nbf,
+        Utc::now(),
+        ENV_VALUES.jwt_clock_skew_seconds,
+    )?;
+    check_registered_claims(
+        claims.iss.as_deref(),
+        claims.aud.as_deref(),
+        ENV_VALUES.jwt_issuer.as_deref(),
+        ENV_VALUES.jwt_audience.as_deref(),
+    )?;
 
     Ok(claims)
 }
@@ -119,22 +631,224 @@ mod auth_tests {
         let claims = Claims {
             sub: id.clone(),
             exp: expired.timestamp(),
+            role: Some("admin".to_owned()),
+            ..Default::default()
         };
         let token = gen_jwt_token(&claims);
         if let Err(ref err) = token {
-            assert!(
-                false,
-                "JWTトークンをエンコードできませんでした。{:?}。",
-                err
-            );
+            panic!("JWTトークンをエンコードできませんでした。{:?}。", err);
         }
         // 生成したトークンを検証
         let decoded = decode_jwt_token(&token.unwrap());
         if let Err(ref err) = decoded {
-            assert!(false, "JWTトークンをデコードできませんでした。{:?}。", err);
+            panic!("JWTトークンをデコードできませんでした。{:?}。", err);
         }
         let decoded = decoded.unwrap();
         assert_eq!(claims.sub, decoded.sub);
         assert_eq!(claims.exp, decoded.exp);
+        assert_eq!(claims.role, decoded.role);
+    }
+
+    /// Eメールアドレス変更トークンとアクセストークンを、互いに取り違えてデコードしないことを確認する。
+    #[test]
+    fn test_email_change_token() {
+        dotenv::from_filename(".env.dev").ok();
+        let expired = Utc::now() + Duration::minutes(30);
+        let claims = EmailChangeClaims {
+            sub: Ulid::new().to_string(),
+            current_email: "foo@example.com".to_owned(),
+            new_email: "bar@example.com".to_owned(),
+            exp: expired.timestamp(),
+            ..Default::default()
+        };
+        let token = gen_jwt_token(&claims).unwrap();
+        let decoded = decode_email_change_token(&token).unwrap();
+        assert_eq!(decoded.sub, claims.sub);
+        assert_eq!(decoded.current_email, claims.current_email);
+        assert_eq!(decoded.new_email, claims.new_email);
+        assert!(decode_jwt_token(&token).is_err());
+
+        let access = gen_jwt_token(&Claims {
+            sub: claims.sub.clone(),
+            exp: claims.exp,
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(decode_email_change_token(&access).is_err());
+    }
+    /// 各署名アルゴリズムでJWTを生成して検証でき、異なる署名アルゴリズムの鍵では検証できないことを確認する。
+    #[test]
+    fn test_jwt_algorithms() {
+        let claims = Claims {
+            sub: Ulid::new().to_string(),
+            exp: (Utc::now() + Duration::days(1)).timestamp(),
+            ..Default::default()
+        };
+        let secret = b"this-is-very-very-long-secret_key";
+        let rsa = openssl::rsa::Rsa::generate(2048).unwrap();
+        let keys = [
+            ("HS256", JwtKeys::hmac(JwtAlgorithm::Hs256, secret).unwrap()),
+            ("HS384", JwtKeys::hmac(JwtAlgorithm::Hs384, secret).unwrap()),
+            ("HS512", JwtKeys::hmac(JwtAlgorithm::Hs512, secret).unwrap()),
+            (
+                "RS256",
+                JwtKeys::rs256(
+                    &rsa.private_key_to_pem().unwrap(),
+                    &rsa.public_key_to_pem().unwrap(),
+                )
+                .unwrap(),
+            ),
+        ];
+        for (algorithm, key) in keys.iter() {
+            let token = key.sign(&claims).unwrap();
+            let header: serde_json::Value =
+                serde_json::from_slice(&base64_decode(token.split('.').next().unwrap())).unwrap();
+            assert_eq!(header["alg"], *algorithm);
+            let decoded: Claims = key.verify(&token).unwrap();
+            assert_eq!(decoded.sub, claims.sub);
+            for (other, other_key) in keys.iter().filter(|(other, _)| other != algorithm) {
+                assert!(
+                    other_key.verify::<Claims>(&token).is_err(),
+                    "{} -> {}",
+                    algorithm,
+                    other
+                );
+            }
+        }
+        assert!(JwtKeys::hmac(JwtAlgorithm::Rs256, secret).is_err());
+        assert!(JwtKeys::rs256(b"invalid", b"invalid").is_err());
+    }
+
+    /// 発行者と受信者を設定したトークンを、設定された発行者と受信者が一致する場合のみ受け付けることを確認する。
+    #[test]
+    fn test_registered_claims() {
+        let key = JwtKeys::hmac(JwtAlgorithm::Hs256, b"secret").unwrap();
+        let claims = Claims {
+            sub: Ulid::new().to_string(),
+            exp: (Utc::now() + Duration::days(1)).timestamp(),
+            ..Default::default()
+        };
+        let claims =
+            with_registered_claims(&claims, Some("issuer"), Some("audience"), 1_000).unwrap();
+        let token = key.sign(&claims).unwrap();
+        let decoded: Claims = key.verify(&token).unwrap();
+        assert_eq!(decoded.iss.as_deref(), Some("issuer"));
+        assert_eq!(decoded.aud.as_deref(), Some("audience"));
+        assert_eq!(decoded.iat, Some(1_000));
+        assert_eq!(decoded.nbf, Some(1_000));
+        let (iss, aud) = (decoded.iss.as_deref(), decoded.aud.as_deref());
+        // 一致する場合、または確認しない場合
+        assert!(check_registered_claims(iss, aud, Some("issuer"), Some("audience")).is_ok());
+        assert!(check_registered_claims(iss, aud, None, None).is_ok());
+        assert!(check_registered_claims(None, None, None, None).is_ok());
+        // 一致しない場合
+        assert!(check_registered_claims(iss, aud, None, Some("other")).is_err());
+        assert!(check_registered_claims(iss, aud, Some("other"), None).is_err());
+        assert!(check_registered_claims(None, None, None, Some("audience")).is_err());
+    }
+
+    /// 有効期限と有効開始日時を、許容する時計のずれの範囲内で受け付けることを確認する。
+    #[test]
+    fn test_time_claims() {
+        let now = Utc::now();
+        let at = |seconds: i64| (now + Duration::seconds(seconds)).timestamp();
+        // 有効期間内
+        assert!(check_time_claims(at(60), Some(at(-60)), now, 30).is_ok());
+        assert!(check_time_claims(at(60), None, now, 30).is_ok());
+        // 時計のずれの範囲内でのみ有効
+        assert!(check_time_claims(at(-10), None, now, 30).is_ok());
+        assert!(check_time_claims(at(-10), None, now, 0).is_err());
+        assert!(check_time_claims(at(60), Some(at(10)), now, 30).is_ok());
+        assert!(check_time_claims(at(60), Some(at(10)), now, 0).is_err());
+        // 時計のずれの範囲外
+        assert!(check_time_claims(at(-31), None, now, 30).is_err());
+        assert!(check_time_claims(at(60), Some(at(31)), now, 30).is_err());
+    }
+
+    /// 有効期限を過ぎた直後のトークンと、有効開始日時の直前のトークンを、時計のずれの範囲内でデコードできることを確認する。
+    #[test]
+    fn test_decode_within_clock_skew() {
+        dotenv::from_filename(".env.dev").ok();
+        let skew = ENV_VALUES.jwt_clock_skew_seconds as i64;
+        if skew < 2 {
+            return;
+        }
+        let now = Utc::now();
+        let claims = Claims {
+            sub: Ulid::new().to_string(),
+            exp: (now - Duration::seconds(1)).timestamp(),
+            ..Default::default()
+        };
+        let token = gen_jwt_token(&claims).unwrap();
+        assert!(decode_jwt_token(&token).is_ok());
+        // 明示した有効開始日時は上書きしない
+        let claims = Claims {
+            sub: Ulid::new().to_string(),
+            exp: (now + Duration::days(1)).timestamp(),
+            nbf: Some((now + Duration::seconds(1)).timestamp()),
+            ..Default::default()
+        };
+        let token = gen_jwt_token(&claims).unwrap();
+        assert_eq!(decode_jwt_token(&token).unwrap().nbf, claims.nbf);
+        // 時計のずれの範囲外
+        let claims = Claims {
+            sub: Ulid::new().to_string(),
+            exp: (now - Duration::seconds(skew + 60)).timestamp(),
+            ..Default::default()
+        };
+        let token = gen_jwt_token(&claims).unwrap();
+        assert!(decode_jwt_token(&token).is_err());
+    }
+
+    /// クッキーのアクセストークンと、クッキーより優先する`Authorization`ヘッダのアクセストークンを取得できることを確認する。
+    #[test]
+    fn test_access_token() {
+        use actix_web::{cookie::Cookie, test::TestRequest};
+
+        // クッキー
+        let req = TestRequest::default()
+            .cookie(Cookie::new(ACCESS_TOKEN_COOKIE, "cookie.token"))
+            .to_http_request();
+        assert_eq!(access_token(&req, true).as_deref(), Some("cookie.token"));
+        assert_eq!(access_token(&req, false), None);
+        // Authorizationヘッダ
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Bearer header.token"))
+            .cookie(Cookie::new(ACCESS_TOKEN_COOKIE, "cookie.token"))
+            .to_http_request();
+        assert_eq!(access_token(&req, true).as_deref(), Some("header.token"));
+        assert_eq!(access_token(&req, false).as_deref(), Some("header.token"));
+        // Authorizationヘッダが不正な場合は、クッキーを参照しない
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Basic abc"))
+            .cookie(Cookie::new(ACCESS_TOKEN_COOKIE, "cookie.token"))
+            .to_http_request();
+        assert_eq!(access_token(&req, true), None);
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(access_token(&req, true), None);
+    }
+
+    /// トークンの返却方法を解釈できることを確認する。
+    #[test]
+    fn test_token_delivery() {
+        let expected = [
+            ("body", TokenDelivery::Body, false, true),
+            ("cookie", TokenDelivery::Cookie, true, false),
+            ("both", TokenDelivery::Both, true, true),
+        ];
+        for (value, delivery, uses_cookie, uses_body) in expected {
+            let parsed: TokenDelivery = value.parse().unwrap();
+            assert_eq!(parsed, delivery);
+            assert_eq!(parsed.uses_cookie(), uses_cookie);
+            assert_eq!(parsed.uses_body(), uses_body);
+        }
+        assert!("header".parse::<TokenDelivery>().is_err());
+    }
+
+    /// パディングなしのURLセーフBase64をデコードする。
+    fn base64_decode(value: &str) -> Vec<u8> {
+        let mut value = value.replace('-', "+").replace('_', "/");
+        value.push_str(&"=".repeat((4 - value.len() % 4) % 4));
+        openssl::base64::decode_block(&value).unwrap()
     }
 }
@@ -1,27 +1,74 @@
+use std::fmt;
+
 use anyhow::anyhow;
 use chrono::{DateTime, FixedOffset};
-use validator::Validate;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use unicode_segmentation::UnicodeSegmentation;
+use utoipa::ToSchema;
+
+use common::ENV_VALUES;
 
-use super::common::{local_now, Address, EmailAddress, EntityId, PhoneNumber, PostalCode};
+use super::super::services::hashers::PasswordHasher;
+use super::common::{
+    impl_string_value_object, Address, EmailAddress, EntityId, PhoneNumber, PostalCode,
+};
 
 /// アカウントID型
 pub type AccountId = EntityId<Account>;
 
-/// アカウント名の文字列の長さ
-const ACCOUNT_NAME_MIN_LENGTH: usize = 2;
-const ACCOUNT_NAME_MAX_LENGTH: usize = 20;
-
 /// パスワードの最小文字数
 const RAW_PASSWORD_MIN_LENGTH: usize = 8;
+/// パスワードの最大文字数
+///
+/// パスワードはハッシュ化を繰り返すため、長すぎるパスワードはハッシュ化する前に拒否する。
+const RAW_PASSWORD_MAX_LENGTH: usize = 128;
 // パスワードに使用できる文字
 const RAW_PASSWORD_SIGNS: &str = r##" !"#$%&'()*+,-./:;<=>?@[\]^_`{|}~"##;
+/// パスワードに含まれている必要がある文字を判定する関数と、含まれていない場合のエラーメッセージ。
+type CharRule = (fn(char) -> bool, &'static str);
+/// パスワードに含まれている必要がある文字の規則。
+const RAW_PASSWORD_CHAR_RULES: [CharRule; 5] = [
+    (
+        |ch| ch.is_ascii_alphabetic(),
+        "パスワードにアルファベットが含まれていません。",
+    ),
+    (
+        |ch| ch.is_ascii_lowercase(),
+        "パスワードに小文字のアルファベットが含まれていません。",
+    ),
+    (
+        |ch| ch.is_ascii_uppercase(),
+        "パスワードに大文字のアルファベットが含まれていません。",
+    ),
+    (
+        |ch| ch.is_ascii_digit(),
+        "パスワードに数字が含まれていません。",
+    ),
+    (
+        |ch| RAW_PASSWORD_SIGNS.contains(ch),
+        "パスワードに記号が含まれていません。",
+    ),
+];
+/// パスワードに連続して含めることができる同じ文字の最大数。
+const RAW_PASSWORD_MAX_REPEATED_CHARS: usize = 3;
+/// パスワードに含まれているか確認するEメールアドレスのローカル部やアカウント名の最小文字数。
+///
+/// これより短いEメールアドレスのローカル部やアカウント名は、パスワードに含まれているか確認しない。
+const RAW_PASSWORD_CONTEXT_MIN_LENGTH: usize = 3;
+/// よく使用されるパスワードのリスト(1行に1つのパスワードを小文字で記録)。
+const COMMON_PASSWORDS: &str = include_str!("common_passwords.txt");
 
 /// アカウント名構造体
 ///
-/// アカウント名は2文字以上かつ20文字以下までの文字列を受け付ける。
-#[derive(Debug, Clone, Validate)]
+/// アカウント名は前後の空白を取り除いた後、環境変数`ACCOUNT_NAME_MIN_LEN`で指定された文字数以上、
+/// かつ`ACCOUNT_NAME_MAX_LEN`で指定された文字数以下の文字列を受け付ける(省略時は2文字以上20文字以下)。
+/// 文字数は、結合文字などを含めて利用者が1文字と認識する書記素クラスタ単位で数える。
+/// なお、データベースの列の長さはコードポイント単位で数えるため、結合文字を含むアカウント名は
+/// 最大文字数以内でも登録できない場合がある。
+/// 制御文字を含むアカウント名は受け付けない。
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AccountName {
-    #[validate(length(min = "ACCOUNT_NAME_MIN_LENGTH", max = "ACCOUNT_NAME_MAX_LENGTH"))]
     value: String,
 }
 
@@ -39,17 +86,46 @@ impl AccountName {
     /// * `Ok`: アカウント名。
     /// * `Err`: エラーメッセージ。
     pub fn new(value: &str) -> anyhow::Result<Self> {
-        let result = Self {
-            value: value.to_owned(),
-        };
-        if result.validate().is_err() {
+        Self::with_length(
+            value,
+            ENV_VALUES.account_name_min_len,
+            ENV_VALUES.account_name_max_len,
+        )
+    }
+
+    /// 文字数の範囲を指定して、アカウント名を構築する。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - アカウント名。
+    /// * `min` - アカウント名の最小文字数。
+    /// * `max` - アカウント名の最大文字数。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: アカウント名。
+    /// * `Err`: エラーメッセージ。
+    fn with_length(value: &str, min: usize, max: usize) -> anyhow::Result<Self> {
+        let trimmed = value.trim();
+        if trimmed.chars().any(char::is_control) {
+            return Err(anyhow!(format!(
+                "アカウント名({:?})に制御文字が含まれています。",
+                value
+            )));
+        }
+        let len = trimmed.graphemes(true).count();
+        if !(min..=max).contains(&len) {
             return Err(anyhow!(format!(
                 "アカウント名({})は{}以上{}以下の文字列を指定してください。",
-                value, ACCOUNT_NAME_MIN_LENGTH, ACCOUNT_NAME_MAX_LENGTH
+                value, min, max
             )));
         }
 
-        Ok(result)
+        Ok(Self {
+            value: trimmed.to_owned(),
+        })
     }
 
     /// アカウント名を文字列で返却する。
@@ -62,6 +138,8 @@ impl AccountName {
     }
 }
 
+impl_string_value_object!(AccountName);
+
 #[cfg(test)]
 mod account_name_tests {
     use super::*;
@@ -69,10 +147,9 @@ mod account_name_tests {
     /// アカウント名を構築できることを確認する。
     #[test]
     fn test_account_name_new() {
-        let valid_names = vec![
-            "0".repeat(ACCOUNT_NAME_MIN_LENGTH),
-            "0".repeat(ACCOUNT_NAME_MAX_LENGTH),
-        ];
+        let min = ENV_VALUES.account_name_min_len;
+        let max = ENV_VALUES.account_name_max_len;
+        let valid_names = vec!["0".repeat(min), "0".repeat(max), "あ".repeat(max)];
         for name in valid_names {
             let result = AccountName::new(&name);
             assert!(result.is_ok());
@@ -83,25 +160,104 @@ mod account_name_tests {
     /// アカウント名を構築できないことを確認する。
     #[test]
     fn test_account_name_new_invalid() {
-        #[allow(clippy::all)]
-        let invalid_names = vec![
-            "0".repeat(ACCOUNT_NAME_MIN_LENGTH - 1),
-            "0".repeat(ACCOUNT_NAME_MAX_LENGTH + 1),
-        ];
+        let min = ENV_VALUES.account_name_min_len;
+        let max = ENV_VALUES.account_name_max_len;
+        let invalid_names = vec!["0".repeat(min - 1), "0".repeat(max + 1)];
         for name in invalid_names {
             let result = AccountName::new(&name);
             assert!(result.is_err());
         }
     }
+
+    /// 指定された文字数の範囲でアカウント名を検証することを確認する。
+    #[test]
+    fn test_account_name_with_length() {
+        assert!(AccountName::with_length("abc", 3, 5).is_ok());
+        assert!(AccountName::with_length("abcde", 3, 5).is_ok());
+        assert!(AccountName::with_length("ab", 3, 5).is_err());
+        assert!(AccountName::with_length("abcdef", 3, 5).is_err());
+    }
+
+    /// アカウント名の前後の空白を取り除いて、文字数を検証することを確認する。
+    #[test]
+    fn test_account_name_trim() {
+        let result = AccountName::new(" \tfoo bar\u{3000}\n").unwrap();
+        assert_eq!(result.value(), "foo bar");
+        assert!(AccountName::with_length("  a  ", 2, 20).is_err());
+        assert!(AccountName::new(&" ".repeat(20)).is_err());
+    }
+
+    /// 全角空白を含む空白のみのアカウント名を構築できないことを確認する。
+    #[test]
+    fn test_account_name_new_blank() {
+        for name in ["  ", "\u{3000}\u{3000}", " \u{3000} "] {
+            assert!(AccountName::new(name).is_err(), "{:?}", name);
+        }
+        let result = AccountName::new("\u{3000}山田太郎\u{3000}").unwrap();
+        assert_eq!(result.value(), "山田太郎");
+    }
+
+    /// アカウント名の文字数を、バイト数ではなく文字数で検証することを確認する。
+    #[test]
+    fn test_account_name_multibyte_length() {
+        assert!(AccountName::with_length(&"あ".repeat(2), 2, 20).is_ok());
+        assert!(AccountName::with_length(&"あ".repeat(20), 2, 20).is_ok());
+        assert!(AccountName::with_length("あ", 2, 20).is_err());
+        assert!(AccountName::with_length(&"あ".repeat(21), 2, 20).is_err());
+    }
+
+    /// アカウント名の文字数を、書記素クラスタ単位で数えることを確認する。
+    #[test]
+    fn test_account_name_grapheme_length() {
+        // 濁点を結合文字で表現した「が」は、2つのコードポイントで構成された1文字
+        let ga = "か\u{3099}";
+        assert!(AccountName::with_length(&ga.repeat(2), 2, 20).is_ok());
+        assert!(AccountName::with_length(&ga.repeat(20), 2, 20).is_ok());
+        assert!(AccountName::with_length(ga, 2, 20).is_err());
+        assert!(AccountName::with_length(&ga.repeat(21), 2, 20).is_err());
+        // 20文字の日本語のアカウント名
+        let name = "東京都新宿区西新宿二丁目8番1号東京都庁";
+        assert!(AccountName::with_length(name, 2, 20).is_ok());
+        assert!(AccountName::with_length(&format!("{}舎", name), 2, 20).is_err());
+    }
+
+    /// 制御文字を含むアカウント名を構築できないことを確認する。
+    #[test]
+    fn test_account_name_new_control_chars() {
+        for name in ["foo\u{0}bar", "foo\tbar", "foo\nbar", "foo\u{7f}bar"] {
+            assert!(AccountName::new(name).is_err(), "{:?}", name);
+        }
+    }
+
+    /// アカウント名を文字列としてシリアライズ及びデシリアライズできることを確認する。
+    #[test]
+    fn test_account_name_serde() {
+        let value = AccountName::new("foo").unwrap();
+        assert_eq!(value.to_string(), "foo");
+        assert_eq!(value.as_ref(), "foo");
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#""foo""#);
+        let deserialized: AccountName = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, value);
+        let deserialized: AccountName = serde_json::from_str(r#""foo""#).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    /// 不正なアカウント名をデシリアライズできないことを確認する。
+    #[test]
+    fn test_account_name_deserialize_invalid() {
+        assert!(serde_json::from_str::<AccountName>(r#""a""#).is_err());
+    }
 }
 
 /// パスワード構造体
 ///
-/// パスワードは、アルファベットの大文字と小文字、数字及び記号で構成された、8文字以上の文字列
+/// パスワードは、アルファベットの大文字と小文字、数字及び記号で構成された、8文字以上128文字以下の文字列
 /// でなければならない。
-#[derive(Debug, Clone, Validate)]
+/// アルファベット、数字及び`RAW_PASSWORD_SIGNS`に含まれる記号以外の文字(制御文字や全角文字など)は使用できない。
+/// 新しく設定するパスワードは`RawPassword::new_with_context`で構築して、パスワードの強度も検証すること。
+#[derive(Debug, Clone)]
 pub struct RawPassword {
-    #[validate(length(min = "RAW_PASSWORD_MIN_LENGTH"))]
     value: String,
 }
 
@@ -117,35 +273,107 @@ impl RawPassword {
     /// `Result`。返却される`Result`の内容は以下の通り。
     ///
     /// * `Ok`: パスワード。
-    /// * `Err`: エラーメッセージ。
+    /// * `Err`: 最初に検出した違反を示すエラーメッセージ。
     pub fn new(value: &str) -> anyhow::Result<Self> {
-        let result = Self {
-            value: value.to_owned(),
-        };
-        if result.validate().is_err() {
-            return Err(anyhow!(format!(
+        match Self::validate_all(value).into_iter().next() {
+            Some(message) => Err(anyhow!(message)),
+            None => Ok(Self {
+                value: value.to_owned(),
+            }),
+        }
+    }
+
+    /// パスワードを検証して、違反しているすべての規則を返却する。
+    ///
+    /// `RawPassword::new`と同じ規則で検証する。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - パスワード。
+    ///
+    /// # Returns
+    ///
+    /// 違反している規則を示すエラーメッセージのリスト。パスワードが規則を満たしている場合は空。
+    pub fn validate_all(value: &str) -> Vec<String> {
+        let mut violations = vec![];
+        let len = value.chars().count();
+        if len < RAW_PASSWORD_MIN_LENGTH {
+            violations.push(format!(
                 "パスワードは{}文字以上の文字列で指定してください。",
                 RAW_PASSWORD_MIN_LENGTH
-            )));
-        }
-        if !value.chars().any(|ch| ch.is_ascii_alphabetic()) {
-            return Err(anyhow!("パスワードにアルファベットが含まれていません。"));
-        }
-        if !value.chars().any(|ch| ch.is_ascii_lowercase()) {
-            return Err(anyhow!(
-                "パスワードに小文字のアルファベットが含まれていません。"
             ));
         }
-        if !value.chars().any(|ch| ch.is_ascii_uppercase()) {
-            return Err(anyhow!(
-                "パスワードに大文字のアルファベットが含まれていません。"
+        if RAW_PASSWORD_MAX_LENGTH < len {
+            violations.push(format!(
+                "パスワードは{}文字以下の文字列で指定してください。",
+                RAW_PASSWORD_MAX_LENGTH
             ));
         }
-        if !value.chars().any(|ch| ch.is_ascii_digit()) {
-            return Err(anyhow!("パスワードに数字が含まれていません。"));
+        if value.chars().any(|ch| ch.is_control()) {
+            violations.push("パスワードに制御文字が含まれています。".to_owned());
         }
-        if !value.chars().any(|ch| RAW_PASSWORD_SIGNS.contains(ch)) {
-            return Err(anyhow!("パスワードに記号が含まれていません。"));
+        if value.chars().any(|ch| {
+            !ch.is_control() && !ch.is_ascii_alphanumeric() && !RAW_PASSWORD_SIGNS.contains(ch)
+        }) {
+            violations.push(
+                "パスワードにアルファベット、数字及び記号以外の文字が含まれています。".to_owned(),
+            );
+        }
+        for (rule, message) in RAW_PASSWORD_CHAR_RULES {
+            if !value.chars().any(rule) {
+                violations.push(message.to_owned());
+            }
+        }
+
+        violations
+    }
+
+    /// パスワードの強度を検証して、パスワードを構築する。
+    ///
+    /// `RawPassword::new`の検証に加えて、よく使用されるパスワード、Eメールアドレスのローカル部またはアカウント名を
+    /// 含むパスワード、及び同じ文字が4文字以上連続するパスワードを拒否する。
+    /// 既に登録されているパスワードを検証すると、強度の基準を満たさないパスワードで認証できなくなるため、
+    /// このコンストラクタは、アカウントの登録やパスワードの変更など、新しいパスワードを設定するときのみ使用すること。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - パスワード。
+    /// * `email` - パスワードを設定するアカウントのEメールアドレス。
+    /// * `name` - パスワードを設定するアカウントのアカウント名。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: パスワード。
+    /// * `Err`: エラーメッセージ。パスワードの強度が不足している場合は、`PasswordStrengthError`を格納したエラー。
+    pub fn new_with_context(
+        value: &str,
+        email: Option<&EmailAddress>,
+        name: Option<&AccountName>,
+    ) -> anyhow::Result<Self> {
+        let result = Self::new(value)?;
+        let lowercase = value.to_lowercase();
+        if COMMON_PASSWORDS
+            .lines()
+            .any(|common| common.trim() == lowercase)
+        {
+            return Err(PasswordStrengthError::Common.into());
+        }
+        if let Some(email) = email {
+            let local_part = email
+                .as_ref()
+                .rsplit_once('@')
+                .map_or(email.as_ref(), |(local_part, _)| local_part);
+            if contains_context(&lowercase, local_part) {
+                return Err(PasswordStrengthError::ContainsEmail.into());
+            }
+        }
+        if name.is_some_and(|name| contains_context(&lowercase, name.as_ref())) {
+            return Err(PasswordStrengthError::ContainsName.into());
+        }
+        if has_repeated_chars(value) {
+            return Err(PasswordStrengthError::RepeatedCharacters.into());
         }
 
         Ok(result)
@@ -161,6 +389,81 @@ impl RawPassword {
     }
 }
 
+/// パスワード強度エラー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordStrengthError {
+    /// よく使用されるパスワード。
+    Common,
+    /// Eメールアドレスのローカル部を含んでいる。
+    ContainsEmail,
+    /// アカウント名を含んでいる。
+    ContainsName,
+    /// 同じ文字が連続している。
+    RepeatedCharacters,
+}
+
+impl fmt::Display for PasswordStrengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Common => write!(f, "よく使用されるパスワードは指定できません。"),
+            Self::ContainsEmail => {
+                write!(f, "パスワードにEメールアドレスを含めることはできません。")
+            }
+            Self::ContainsName => write!(f, "パスワードにアカウント名を含めることはできません。"),
+            Self::RepeatedCharacters => write!(
+                f,
+                "パスワードに同じ文字を{}文字より多く連続して含めることはできません。",
+                RAW_PASSWORD_MAX_REPEATED_CHARS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PasswordStrengthError {}
+
+/// パスワードに、Eメールアドレスのローカル部やアカウント名が含まれているか確認する。
+///
+/// # Arguments
+///
+/// * `lowercase` - 小文字に変換したパスワード。
+/// * `context` - Eメールアドレスのローカル部またはアカウント名。
+///
+/// # Returns
+///
+/// 含まれている場合は`true`。`context`が`RAW_PASSWORD_CONTEXT_MIN_LENGTH`より短い場合は`false`。
+fn contains_context(lowercase: &str, context: &str) -> bool {
+    let context = context.trim().to_lowercase();
+
+    RAW_PASSWORD_CONTEXT_MIN_LENGTH <= context.chars().count() && lowercase.contains(&context)
+}
+
+/// パスワードに、同じ文字が`RAW_PASSWORD_MAX_REPEATED_CHARS`より多く連続しているか確認する。
+///
+/// # Arguments
+///
+/// * `value` - パスワード。
+///
+/// # Returns
+///
+/// 連続している場合は`true`。
+fn has_repeated_chars(value: &str) -> bool {
+    let mut previous = None;
+    let mut count = 0;
+    for ch in value.chars() {
+        if previous == Some(ch) {
+            count += 1;
+        } else {
+            previous = Some(ch);
+            count = 1;
+        }
+        if RAW_PASSWORD_MAX_REPEATED_CHARS < count {
+            return true;
+        }
+    }
+
+    false
+}
+
 #[cfg(test)]
 mod raw_password_tests {
     use super::*;
@@ -190,6 +493,137 @@ mod raw_password_tests {
         // 記号を含んでいない
         assert!(RawPassword::new("01abCDef").is_err());
     }
+
+    /// 最大文字数までのパスワードを構築できて、最大文字数を超えるパスワードを構築できないことを確認する。
+    #[test]
+    fn test_raw_password_max_length() {
+        let max = format!("01abCD#${}", "x".repeat(RAW_PASSWORD_MAX_LENGTH - 8));
+        assert!(RawPassword::new(&max).is_ok());
+        let too_long = format!("{}x", max);
+        assert_eq!(
+            RawPassword::new(&too_long).unwrap_err().to_string(),
+            "パスワードは128文字以下の文字列で指定してください。"
+        );
+    }
+
+    /// 制御文字や、アルファベット、数字及び記号以外の文字を含むパスワードを構築できないことを確認する。
+    #[test]
+    fn test_raw_password_disallowed_characters() {
+        for value in ["01abCD#$\0", "01abCD#$\n", "01abCD#$\t", "01abCD#$\u{7f}"] {
+            assert_eq!(
+                RawPassword::validate_all(value),
+                vec!["パスワードに制御文字が含まれています。"],
+                "{:?}",
+                value
+            );
+        }
+        for value in ["01abCD#$あ", "01abCD#$Ａ", "01abCD#$\u{3000}"] {
+            assert_eq!(
+                RawPassword::validate_all(value),
+                vec!["パスワードにアルファベット、数字及び記号以外の文字が含まれています。"],
+                "{:?}",
+                value
+            );
+        }
+    }
+
+    /// パスワードが違反しているすべての規則を返却することを確認する。
+    #[test]
+    fn test_raw_password_validate_all() {
+        assert!(RawPassword::validate_all("01abCD#$").is_empty());
+        assert_eq!(
+            RawPassword::validate_all("abc"),
+            vec![
+                "パスワードは8文字以上の文字列で指定してください。",
+                "パスワードに大文字のアルファベットが含まれていません。",
+                "パスワードに数字が含まれていません。",
+                "パスワードに記号が含まれていません。",
+            ]
+        );
+        // 最初に違反した規則のエラーメッセージを返却
+        assert_eq!(
+            RawPassword::new("abc").unwrap_err().to_string(),
+            "パスワードは8文字以上の文字列で指定してください。"
+        );
+    }
+
+    /// 強度を検証したパスワードを構築できることを確認する。
+    #[test]
+    fn test_raw_password_new_with_context() {
+        let email = EmailAddress::new("foo@example.com").unwrap();
+        let name = AccountName::new("bar").unwrap();
+        let result = RawPassword::new_with_context("01abCD#$", Some(&email), Some(&name));
+        assert_eq!(result.unwrap().value(), "01abCD#$");
+        // 同じ文字が3文字まで連続している
+        assert!(RawPassword::new_with_context("01abCD###", Some(&email), Some(&name)).is_ok());
+        // 3文字より短いアカウント名は確認しない
+        let name = AccountName::new("ab").unwrap();
+        assert!(RawPassword::new_with_context("01abCD#$", None, Some(&name)).is_ok());
+        // 強度を検証しない場合は、よく使用されるパスワードも構築できる
+        assert!(RawPassword::new("Password1!").is_ok());
+    }
+
+    /// 強度を検証したパスワードの構築に失敗したときのエラーを返却する。
+    fn strength_error(
+        value: &str,
+        email: Option<&EmailAddress>,
+        name: Option<&AccountName>,
+    ) -> Option<PasswordStrengthError> {
+        RawPassword::new_with_context(value, email, name)
+            .unwrap_err()
+            .downcast_ref::<PasswordStrengthError>()
+            .copied()
+    }
+
+    /// よく使用されるパスワードを拒否することを確認する。
+    #[test]
+    fn test_raw_password_common() {
+        for value in ["Password1!", "P@ssw0rd", "Qwerty123!"] {
+            assert_eq!(
+                strength_error(value, None, None),
+                Some(PasswordStrengthError::Common),
+                "{}",
+                value
+            );
+        }
+    }
+
+    /// Eメールアドレスのローカル部を含むパスワードを拒否することを確認する。
+    #[test]
+    fn test_raw_password_contains_email() {
+        let email = EmailAddress::new("Taro.Yamada@example.com").unwrap();
+        assert_eq!(
+            strength_error("01#taro.yamadA", Some(&email), None),
+            Some(PasswordStrengthError::ContainsEmail)
+        );
+        // ドメインは確認しない
+        assert!(RawPassword::new_with_context("01#Example.com", Some(&email), None).is_ok());
+    }
+
+    /// アカウント名を含むパスワードを拒否することを確認する。
+    #[test]
+    fn test_raw_password_contains_name() {
+        let name = AccountName::new("Hanako").unwrap();
+        assert_eq!(
+            strength_error("01#HANAKO#x", None, Some(&name)),
+            Some(PasswordStrengthError::ContainsName)
+        );
+    }
+
+    /// 同じ文字が3文字より多く連続するパスワードを拒否することを確認する。
+    #[test]
+    fn test_raw_password_repeated_characters() {
+        assert_eq!(
+            strength_error("01abCD####", None, None),
+            Some(PasswordStrengthError::RepeatedCharacters)
+        );
+    }
+
+    /// `RawPassword::new`で拒否されるパスワードは、パスワード強度エラーを返却しないことを確認する。
+    #[test]
+    fn test_raw_password_new_with_context_invalid() {
+        assert_eq!(strength_error("01abCD#", None, None), None);
+    }
 }
 
 /// ハッシュ化パスワード構造体
@@ -205,18 +639,18 @@ impl HashedPassword {
     /// # Arguments
     ///
     /// * `raw` - パスワード。
+    /// * `hasher` - パスワードをハッシュ化する構造体。
     ///
     /// # Returns
     ///
-    /// * ハッシュ化したパスワード。
-    pub fn new(raw: RawPassword) -> Self {
-        use crate::services::hashers::{hash_password, SaultProviderImpl};
-
-        let sault_provider = SaultProviderImpl {};
-
-        Self {
-            value: hash_password(&sault_provider, &raw.value).unwrap(),
-        }
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: ハッシュ化したパスワード。
+    /// * `Err`: パスワードのハッシュ化に失敗した場合のエラー。
+    pub fn new(raw: RawPassword, hasher: &dyn PasswordHasher) -> anyhow::Result<Self> {
+        Ok(Self {
+            value: hasher.hash(&raw.value)?,
+        })
     }
 
     /// コンストラクタ。
@@ -249,8 +683,25 @@ impl HashedPassword {
 
 #[cfg(test)]
 mod hashed_password_tests {
+    use super::super::super::services::hashers::{test_config, test_hasher, PasswordHasherImpl};
     use super::*;
 
+    /// パスワードをハッシュ化できることを確認する。
+    #[test]
+    fn test_hashed_password_new() {
+        let raw = RawPassword::new("01abCD#$").unwrap();
+        let hashed = HashedPassword::new(raw.clone(), &test_hasher()).unwrap();
+        assert_ne!(hashed.value(), raw.value());
+    }
+
+    /// パスワードのハッシュ化に失敗した場合に、パニックせずにエラーを返却することを確認する。
+    #[test]
+    fn test_hashed_password_new_invalid_hash_func() {
+        let hasher = PasswordHasherImpl::new(test_config("MD5", 10, 16));
+        let raw = RawPassword::new("01abCD#$").unwrap();
+        assert!(HashedPassword::new(raw, &hasher).is_err());
+    }
+
     /// ハッシュ化したパスワードをチェックなしで構築できるか確認する。
     #[test]
     fn test_hashed_password_new_unchecked() {
@@ -327,9 +778,37 @@ mod optional_phone_number_tests {
     }
 }
 
+/// 固定携帯電話番号エラー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixedMobileNumbersError {
+    /// 固定電話番号と携帯電話番号のどちらも設定されていない。
+    Empty,
+    /// 固定電話番号に携帯電話番号が設定されている。
+    MobileInFixed,
+    /// 携帯電話番号に固定電話番号が設定されている。
+    LandlineInMobile,
+}
+
+impl fmt::Display for FixedMobileNumbersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::Empty => {
+                "少なくとも固定電話番号か携帯電話番号に、電話番号を設定する必要があります。"
+            }
+            Self::MobileInFixed => "固定電話番号に携帯電話番号が設定されています。",
+            Self::LandlineInMobile => "携帯電話番号に固定電話番号が設定されています。",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for FixedMobileNumbersError {}
+
 /// 固定携帯電話番号構造体
 ///
 /// 固定電話番号または携帯電話番号のうち、とちらかの電話番号を記録する必要がある。
+/// 固定電話番号には携帯電話番号を、携帯電話番号には固定電話番号を設定できない。
+/// IP電話など、地域に割り当てられていない電話番号はどちらにも設定できる。
 #[derive(Debug, Clone)]
 pub struct FixedMobileNumbers {
     /// 固定電話番号。
@@ -351,15 +830,42 @@ impl FixedMobileNumbers {
     /// `Result`。`Result`の内容は以下の通り。
     ///
     /// * `Ok`: 固定携帯電話番号。
-    /// * `Err`: エラーメッセージ。
+    /// * `Err`: `FixedMobileNumbersError`を格納したエラー。
     pub fn new(
         fixed: Option<PhoneNumber>,
         mobile: Option<PhoneNumber>,
+    ) -> anyhow::Result<FixedMobileNumbers> {
+        if fixed.as_ref().is_some_and(|n| n.is_mobile()) {
+            return Err(FixedMobileNumbersError::MobileInFixed.into());
+        }
+        if mobile.as_ref().is_some_and(|n| n.is_landline()) {
+            return Err(FixedMobileNumbersError::LandlineInMobile.into());
+        }
+
+        Self::new_lenient(fixed, mobile)
+    }
+
+    /// 固定電話番号と携帯電話番号の種類を検証せずに、固定携帯電話番号を構築する。
+    ///
+    /// 種類を検証する前にデータベースに記録された電話番号を読み込むために使用する。
+    ///
+    /// # Arguments
+    ///
+    /// * `fixed` - 固定電話番号。
+    /// * `mobile` - 携帯電話番号。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 固定携帯電話番号。
+    /// * `Err`: `FixedMobileNumbersError`を格納したエラー。
+    pub fn new_lenient(
+        fixed: Option<PhoneNumber>,
+        mobile: Option<PhoneNumber>,
     ) -> anyhow::Result<FixedMobileNumbers> {
         if fixed.is_none() && mobile.is_none() {
-            return Err(anyhow!(
-                "少なくとも固定電話番号か携帯電話番号に、電話番号を設定する必要があります。"
-            ));
+            return Err(FixedMobileNumbersError::Empty.into());
         }
 
         Ok(Self { fixed, mobile })
@@ -410,7 +916,98 @@ mod fixed_mobile_phone_numbers_tests {
     /// 固定携帯電話番号を構築できないことを確認する。
     #[test]
     fn test_fixed_mobile_phone_numbers_new_invalid() {
-        assert!(FixedMobileNumbers::new(None, None).is_err());
+        let err = FixedMobileNumbers::new(None, None).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<FixedMobileNumbersError>(),
+            Some(&FixedMobileNumbersError::Empty)
+        );
+    }
+
+    /// 固定電話番号に携帯電話番号を設定できないことを確認する。
+    #[test]
+    fn test_fixed_mobile_phone_numbers_new_mobile_in_fixed() {
+        let mobile = PhoneNumber::new("090-1234-5678").unwrap();
+        let err = FixedMobileNumbers::new(Some(mobile.clone()), Some(mobile)).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<FixedMobileNumbersError>(),
+            Some(&FixedMobileNumbersError::MobileInFixed)
+        );
+    }
+
+    /// 携帯電話番号に固定電話番号を設定できないことを確認する。
+    #[test]
+    fn test_fixed_mobile_phone_numbers_new_landline_in_mobile() {
+        let fixed = PhoneNumber::new("03-1234-5678").unwrap();
+        let err = FixedMobileNumbers::new(None, Some(fixed)).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<FixedMobileNumbersError>(),
+            Some(&FixedMobileNumbersError::LandlineInMobile)
+        );
+    }
+
+    /// IP電話番号は、固定電話番号と携帯電話番号のどちらにも設定できることを確認する。
+    #[test]
+    fn test_fixed_mobile_phone_numbers_new_ip_phone() {
+        let ip_phone = PhoneNumber::new("050-1234-5678").unwrap();
+        assert!(FixedMobileNumbers::new(Some(ip_phone.clone()), None).is_ok());
+        assert!(FixedMobileNumbers::new(None, Some(ip_phone)).is_ok());
+    }
+
+    /// 種類を検証せずに固定携帯電話番号を構築できることを確認する。
+    #[test]
+    fn test_fixed_mobile_phone_numbers_new_lenient() {
+        let fixed = PhoneNumber::new("03-1234-5678").unwrap();
+        let mobile = PhoneNumber::new("090-1234-5678").unwrap();
+        let result = FixedMobileNumbers::new_lenient(Some(mobile), Some(fixed)).unwrap();
+        assert_eq!(result.fixed().unwrap().value(), "090-1234-5678");
+        assert_eq!(result.mobile().unwrap().value(), "03-1234-5678");
+        assert!(FixedMobileNumbers::new_lenient(None, None).is_err());
+    }
+}
+
+/// アカウントの権限列挙型
+///
+/// データベースとJWTトークンには、小文字の文字列で記録する。
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Display,
+    EnumString,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Role {
+    /// 一般ユーザー。
+    #[default]
+    User,
+    /// 管理者。
+    Admin,
+}
+
+#[cfg(test)]
+mod role_tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    /// 権限を小文字の文字列に変換して、文字列から権限を復元できることを確認する。
+    #[test]
+    fn test_role_string() {
+        for (role, value) in [(Role::User, "user"), (Role::Admin, "admin")] {
+            assert_eq!(role.to_string(), value);
+            assert_eq!(Role::from_str(value).unwrap(), role);
+            assert_eq!(serde_json::to_value(role).unwrap(), value);
+        }
+        assert!(Role::from_str("Admin").is_err());
+        assert!(Role::from_str("root").is_err());
+        assert_eq!(Role::default(), Role::User);
     }
 }
 
@@ -429,6 +1026,8 @@ pub struct Account {
     password: HashedPassword,
     /// アクティブフラグ。
     is_active: bool,
+    /// 権限。
+    role: Role,
     /// 固定携帯電話番号。
     phone_numbers: FixedMobileNumbers,
     /// 郵便番号。
@@ -441,6 +1040,8 @@ pub struct Account {
     created_at: DateTime<FixedOffset>,
     /// 更新日時。
     updated_at: DateTime<FixedOffset>,
+    /// 最後にアカウントを更新したアカウントのアカウントID。
+    updated_by: Option<AccountId>,
 }
 
 impl Account {
@@ -450,38 +1051,41 @@ impl Account {
     ///
     /// * `email` - Eメールアドレス。
     /// * `name` - アカウント名。
-    /// * `password` - パスワード。
+    /// * `password` - ハッシュ化したパスワード。
     /// * `is_active` - アクティブフラグ。
     /// * `phone_numbers` - 固定携帯電話番号。
     /// * `postal_code` - 郵便番号。
     /// * `address` - 住所。
+    /// * `now` - 登録日時及び更新日時に記録する現在日時。
     ///
     /// # Returns
     ///
-    /// * アカウント。
+    /// * 一般ユーザーの権限を持つアカウント。
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         email: EmailAddress,
         name: AccountName,
-        password: RawPassword,
+        password: HashedPassword,
         is_active: bool,
         phone_numbers: FixedMobileNumbers,
         postal_code: PostalCode,
         address: Address,
+        now: DateTime<FixedOffset>,
     ) -> Self {
-        let dt = local_now(None);
-
         Self {
             id: AccountId::gen(),
             email,
             name,
-            password: HashedPassword::new(password),
+            password,
             is_active,
+            role: Role::User,
             phone_numbers,
             postal_code,
             address,
             logged_in_at: None,
-            created_at: dt,
-            updated_at: dt,
+            created_at: now,
+            updated_at: now,
+            updated_by: None,
         }
     }
 
@@ -489,6 +1093,7 @@ impl Account {
     ///
     /// この関連関数はリポジトリから呼び出すこと。
     /// リポジトリ以外からは呼び出してはならない。
+    /// 引数の順番を間違えやすいため、代わりに`AccountBuilder`を使用すること。
     ///
     /// # Arguments
     ///
@@ -508,6 +1113,7 @@ impl Account {
     ///
     /// * アカウント。
     #[allow(clippy::too_many_arguments)]
+    #[deprecated(note = "AccountBuilderを使用してください。")]
     pub fn new_unchecked(
         id: AccountId,
         email: EmailAddress,
@@ -527,12 +1133,14 @@ impl Account {
             name,
             password,
             is_active,
+            role: Role::User,
             phone_numbers,
             postal_code,
             address,
             logged_in_at,
             created_at,
             updated_at,
+            updated_by: None,
         }
     }
 
@@ -554,6 +1162,15 @@ impl Account {
         self.email.clone()
     }
 
+    /// Eメールアドレスを設定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Eメールアドレス。
+    pub fn set_email(&mut self, value: EmailAddress) {
+        self.email = value;
+    }
+
     /// アカウント名を返却する。
     ///
     /// # Returns
@@ -581,6 +1198,18 @@ impl Account {
         self.password.clone()
     }
 
+    /// ハッシュ化済パスワードを設定する。
+    ///
+    /// パスワードの変更はリポジトリの`change_password`で記録するため、
+    /// リポジトリに記録したパスワードとアカウントのパスワードを一致させる場合のみ使用する。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - ハッシュ化済パスワード。
+    pub(crate) fn set_password(&mut self, value: HashedPassword) {
+        self.password = value;
+    }
+
     /// アカウントが有効かどうかを返却する。
     ///
     /// # Returns
@@ -599,6 +1228,24 @@ impl Account {
         self.is_active = value;
     }
 
+    /// 権限を返却する。
+    ///
+    /// # Returns
+    ///
+    /// * 権限。
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// 権限を設定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - 権限。
+    pub fn set_role(&mut self, value: Role) {
+        self.role = value;
+    }
+
     /// 固定携帯電話番号を返却する。
     ///
     /// # Returns
@@ -698,6 +1345,25 @@ impl Account {
     pub fn set_updated_at(&mut self, value: DateTime<FixedOffset>) {
         self.updated_at = value;
     }
+
+    /// 最後にアカウントを更新したアカウントのアカウントIDを返却する。
+    ///
+    /// # Returns
+    ///
+    /// * 最後にアカウントを更新したアカウントのアカウントID。
+    /// * 登録後に更新されていない場合は`None`。
+    pub fn updated_by(&self) -> Option<AccountId> {
+        self.updated_by.clone()
+    }
+
+    /// 最後にアカウントを更新したアカウントのアカウントIDを設定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - 最後にアカウントを更新したアカウントのアカウントID。
+    pub fn set_updated_by(&mut self, value: Option<AccountId>) {
+        self.updated_by = value;
+    }
 }
 
 impl PartialEq for Account {
@@ -712,9 +1378,170 @@ impl PartialOrd for Account {
     }
 }
 
+/// アカウントビルダー
+///
+/// リポジトリに記録されたアカウントを復元するときに使用する。
+/// 最終ログイン日時、権限及び更新したアカウントのアカウントID以外のすべての値を設定する必要があり、設定されていない値がある場合は`build`がエラーを返却する。
+/// 権限を設定しない場合は、一般ユーザーの権限を持つアカウントを構築する。
+#[derive(Debug, Clone, Default)]
+pub struct AccountBuilder {
+    id: Option<AccountId>,
+    email: Option<EmailAddress>,
+    name: Option<AccountName>,
+    password: Option<HashedPassword>,
+    is_active: Option<bool>,
+    role: Option<Role>,
+    phone_numbers: Option<FixedMobileNumbers>,
+    postal_code: Option<PostalCode>,
+    address: Option<Address>,
+    logged_in_at: Option<DateTime<FixedOffset>>,
+    created_at: Option<DateTime<FixedOffset>>,
+    updated_at: Option<DateTime<FixedOffset>>,
+    updated_by: Option<AccountId>,
+}
+
+impl AccountBuilder {
+    /// コンストラクタ。
+    ///
+    /// # Returns
+    ///
+    /// * 値が設定されていないアカウントビルダー。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// アカウントIDを設定する。
+    pub fn id(mut self, id: AccountId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Eメールアドレスを設定する。
+    pub fn email(mut self, email: EmailAddress) -> Self {
+        self.email = Some(email);
+        self
+    }
+
+    /// アカウント名を設定する。
+    pub fn name(mut self, name: AccountName) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// ハッシュ化されたパスワードを設定する。
+    pub fn password(mut self, password: HashedPassword) -> Self {
+        self.password = Some(password);
+        self
+    }
+
+    /// アクティブフラグを設定する。
+    pub fn is_active(mut self, is_active: bool) -> Self {
+        self.is_active = Some(is_active);
+        self
+    }
+
+    /// 権限を設定する。
+    pub fn role(mut self, role: Role) -> Self {
+        self.role = Some(role);
+        self
+    }
+
+    /// 固定携帯電話番号を設定する。
+    pub fn phone_numbers(mut self, phone_numbers: FixedMobileNumbers) -> Self {
+        self.phone_numbers = Some(phone_numbers);
+        self
+    }
+
+    /// 郵便番号を設定する。
+    pub fn postal_code(mut self, postal_code: PostalCode) -> Self {
+        self.postal_code = Some(postal_code);
+        self
+    }
+
+    /// 住所を設定する。
+    pub fn address(mut self, address: Address) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    /// 最終ログイン日時を設定する。
+    pub fn logged_in_at(mut self, logged_in_at: Option<DateTime<FixedOffset>>) -> Self {
+        self.logged_in_at = logged_in_at;
+        self
+    }
+
+    /// 登録日時を設定する。
+    pub fn created_at(mut self, created_at: DateTime<FixedOffset>) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    /// 更新日時を設定する。
+    pub fn updated_at(mut self, updated_at: DateTime<FixedOffset>) -> Self {
+        self.updated_at = Some(updated_at);
+        self
+    }
+
+    /// 最後にアカウントを更新したアカウントのアカウントIDを設定する。
+    pub fn updated_by(mut self, updated_by: Option<AccountId>) -> Self {
+        self.updated_by = updated_by;
+        self
+    }
+
+    /// アカウントを構築する。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: アカウント。
+    /// * `Err`: 設定されていない値を示すエラー。
+    pub fn build(self) -> anyhow::Result<Account> {
+        let missing: Vec<&str> = [
+            ("id", self.id.is_none()),
+            ("email", self.email.is_none()),
+            ("name", self.name.is_none()),
+            ("password", self.password.is_none()),
+            ("is_active", self.is_active.is_none()),
+            ("phone_numbers", self.phone_numbers.is_none()),
+            ("postal_code", self.postal_code.is_none()),
+            ("address", self.address.is_none()),
+            ("created_at", self.created_at.is_none()),
+            ("updated_at", self.updated_at.is_none()),
+        ]
+        .into_iter()
+        .filter(|(_, is_none)| *is_none)
+        .map(|(field, _)| field)
+        .collect();
+        if !missing.is_empty() {
+            return Err(anyhow!(
+                "アカウントを構築するために必要な値({})が設定されていません。",
+                missing.join(", ")
+            ));
+        }
+
+        Ok(Account {
+            id: self.id.unwrap(),
+            email: self.email.unwrap(),
+            name: self.name.unwrap(),
+            password: self.password.unwrap(),
+            is_active: self.is_active.unwrap(),
+            role: self.role.unwrap_or_default(),
+            phone_numbers: self.phone_numbers.unwrap(),
+            postal_code: self.postal_code.unwrap(),
+            address: self.address.unwrap(),
+            logged_in_at: self.logged_in_at,
+            created_at: self.created_at.unwrap(),
+            updated_at: self.updated_at.unwrap(),
+            updated_by: self.updated_by,
+        })
+    }
+}
+
 #[cfg(test)]
 mod account_tests {
-    use super::super::common::{AddressDetails, Prefecture};
+    use super::super::super::services::hashers::test_hasher;
+    use super::super::common::{local_now, AddressDetails, Prefecture};
     use super::*;
     use ulid::Ulid;
 
@@ -723,7 +1550,8 @@ mod account_tests {
     fn test_account_new() {
         let email = EmailAddress::new("foo@example.com").unwrap();
         let name = AccountName::new("foo").unwrap();
-        let password = RawPassword::new("01abCD#$").unwrap();
+        let password =
+            HashedPassword::new(RawPassword::new("01abCD#$").unwrap(), &test_hasher()).unwrap();
         let is_active = true;
         let fixed_number = PhoneNumber::new("012-345-6890").unwrap();
         let mobile_number = PhoneNumber::new("090-1234-5678").unwrap();
@@ -733,9 +1561,10 @@ mod account_tests {
         let postal_code = PostalCode::new("012-3456").unwrap();
         let pref_code = 13;
         let pref_name = "東京都";
-        let prefecture = Prefecture::new(pref_code, pref_name);
+        let prefecture = Prefecture::new(pref_code, pref_name).unwrap();
         let address_details = AddressDetails::new("新宿区西新宿2-8-1").unwrap();
         let address = Address::new(prefecture.clone(), address_details.clone());
+        let now = local_now(None);
         // アカウントを構築
         let account = Account::new(
             email.clone(),
@@ -745,6 +1574,7 @@ mod account_tests {
             phone_numbers.clone(),
             postal_code.clone(),
             address.clone(),
+            now,
         );
         assert_eq!(account.email().value(), email.value());
         assert_eq!(account.name().value(), name.value());
@@ -757,11 +1587,16 @@ mod account_tests {
         assert_eq!(account.address().prefecture().code(), pref_code);
         assert_eq!(account.address().prefecture().name(), pref_name);
         assert_eq!(account.address().details().value(), address_details.value());
+        assert_eq!(account.role(), Role::User);
+        assert!(account.logged_in_at().is_none());
+        assert_eq!(account.created_at(), now);
+        assert_eq!(account.updated_at(), now);
+        assert!(account.updated_by().is_none());
     }
 
-    /// アカウントを構築できることを確認する。
+    /// アカウントビルダーでアカウントを構築できることを確認する。
     #[test]
-    fn test_account_new_unchecked() {
+    fn test_account_builder() {
         let id = Ulid::new();
         let email = EmailAddress::new("foo@example.com").unwrap();
         let name = AccountName::new("foo").unwrap();
@@ -775,26 +1610,30 @@ mod account_tests {
         let postal_code = PostalCode::new("012-3456").unwrap();
         let pref_code = 13;
         let pref_name = "東京都";
-        let prefecture = Prefecture::new(pref_code, pref_name);
+        let prefecture = Prefecture::new(pref_code, pref_name).unwrap();
         let address_details = AddressDetails::new("新宿区西新宿2-8-1").unwrap();
         let address = Address::new(prefecture.clone(), address_details.clone());
         let logged_in_at = Some(local_now(None));
         let created_at = local_now(None);
         let updated_at = local_now(None);
+        let updated_by = AccountId::gen();
         // アカウントを構築
-        let account = Account::new_unchecked(
-            AccountId::new(id),
-            email.clone(),
-            name.clone(),
-            password.clone(),
-            is_active,
-            phone_numbers.clone(),
-            postal_code.clone(),
-            address.clone(),
-            logged_in_at,
-            created_at,
-            updated_at,
-        );
+        let account = AccountBuilder::new()
+            .id(AccountId::new(id))
+            .email(email.clone())
+            .name(name.clone())
+            .password(password.clone())
+            .is_active(is_active)
+            .role(Role::Admin)
+            .phone_numbers(phone_numbers.clone())
+            .postal_code(postal_code.clone())
+            .address(address.clone())
+            .logged_in_at(logged_in_at)
+            .created_at(created_at)
+            .updated_at(updated_at)
+            .updated_by(Some(updated_by.clone()))
+            .build()
+            .unwrap();
         assert_eq!(account.id.value, id);
         assert_eq!(account.email().value(), email.value());
         assert_eq!(account.name().value(), name.value());
@@ -807,8 +1646,29 @@ mod account_tests {
         assert_eq!(account.address().prefecture().code(), pref_code);
         assert_eq!(account.address().prefecture().name(), pref_name);
         assert_eq!(account.address().details().value(), address_details.value());
+        assert_eq!(account.role(), Role::Admin);
         assert_eq!(account.logged_in_at(), logged_in_at);
         assert_eq!(account.created_at, created_at);
         assert_eq!(account.updated_at, updated_at);
+        assert_eq!(account.updated_by(), Some(updated_by));
+    }
+
+    /// アカウントビルダーに設定されていない値がある場合は、設定されていない値を示すエラーを返却することを確認する。
+    #[test]
+    fn test_account_builder_missing_fields() {
+        let err = AccountBuilder::new()
+            .id(AccountId::gen())
+            .email(EmailAddress::new("foo@example.com").unwrap())
+            .name(AccountName::new("foo").unwrap())
+            .is_active(true)
+            .created_at(local_now(None))
+            .build()
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("(password, phone_numbers, postal_code, address, updated_at)"),
+            "{}",
+            err
+        );
     }
 }
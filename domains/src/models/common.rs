@@ -1,14 +1,18 @@
-use std::marker::PhantomData;
+use std::{fmt, marker::PhantomData, str::FromStr};
 
 use anyhow::anyhow;
 use chrono::{DateTime, FixedOffset, Utc};
 use derive_new::new;
 use lazy_static::lazy_static;
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use ulid::Ulid;
+use unicode_segmentation::UnicodeSegmentation;
+use utoipa::ToSchema;
 use validator::Validate;
 
+use common::ENV_VALUES;
+
 lazy_static! {
     /// 電話番号の正規表現。
     static ref PHONE_NUMBER_REGEX: Regex = Regex::new(r"^0\d{1,4}-\d{1,4}-\d{4}$").unwrap();
@@ -16,6 +20,41 @@ lazy_static! {
     static ref POSTAL_CODE_REGEX: Regex = Regex::new(r"^\d{3}-\d{4}$").unwrap();
 }
 
+/// 文字列を値に持つ値オブジェクトに、共通のトレイトを実装する。
+///
+/// 値オブジェクトは`value`フィールドに文字列を記録し、`new`コンストラクタで値を検証する必要がある。
+/// シリアライズでは文字列として出力して、デシリアライズでは`new`コンストラクタで値を検証する。
+macro_rules! impl_string_value_object {
+    ($t:ty) => {
+        impl std::fmt::Display for $t {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&self.value)
+            }
+        }
+
+        impl AsRef<str> for $t {
+            fn as_ref(&self) -> &str {
+                &self.value
+            }
+        }
+
+        impl serde::Serialize for $t {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.value)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $t {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+                Self::new(&value).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_string_value_object;
+
 /// エンティティID構造体
 ///
 /// # Description
@@ -45,6 +84,28 @@ impl<T> EntityId<T> {
     }
 }
 
+impl<T> fmt::Display for EntityId<T> {
+    /// エンティティIDをULID文字列で出力する。
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl<T> FromStr for EntityId<T> {
+    type Err = anyhow::Error;
+
+    /// 文字列からエンティティIDを構築して返却する。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - エンティティIDを構築する文字列。
+    fn from_str(value: &str) -> anyhow::Result<Self, Self::Err> {
+        Ulid::from_string(value)
+            .map(|id| Self::new(id))
+            .map_err(|err| anyhow!("エンティティID({})が不正です。{:?}", value, err))
+    }
+}
+
 impl<T> TryFrom<&str> for EntityId<T> {
     type Error = anyhow::Error;
 
@@ -54,9 +115,33 @@ impl<T> TryFrom<&str> for EntityId<T> {
     ///
     /// * `value` - エンティティIDを構築する文字列。
     fn try_from(value: &str) -> anyhow::Result<Self, Self::Error> {
-        Ulid::from_string(value)
-            .map(|id| Self::new(id))
-            .map_err(|err| anyhow!("{:?}", err))
+        value.parse()
+    }
+}
+
+impl<T> TryFrom<String> for EntityId<T> {
+    type Error = anyhow::Error;
+
+    /// 文字列からエンティティIDを構築して返却する。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - エンティティIDを構築する文字列。
+    fn try_from(value: String) -> anyhow::Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl<T> Serialize for EntityId<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for EntityId<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
     }
 }
 
@@ -86,19 +171,81 @@ mod entity_id_tests {
         let id = EntityId::<i32>::try_from("invalid-ulid-string");
         assert!(id.is_err());
     }
+
+    /// エンティティIDをULID文字列で表示できることを確認する。
+    #[test]
+    fn entity_id_display() {
+        let value = Ulid::new();
+        let id = EntityId::<i32>::new(value);
+        assert_eq!(id.to_string(), value.to_string());
+    }
+
+    /// `FromStr`及び`TryFrom<String>`でエンティティIDを構築できることを確認する。
+    #[test]
+    fn entity_id_from_str() {
+        let value = "01D39ZY06FGSCTVN4T2V9PKHFZ";
+        let id = value.parse::<EntityId<i32>>().unwrap();
+        assert_eq!(id.to_string(), value);
+        assert_eq!(EntityId::<i32>::try_from(value.to_owned()).unwrap(), id);
+        assert!("invalid-ulid-string".parse::<EntityId<i32>>().is_err());
+    }
+
+    /// エンティティIDをULID文字列としてシリアライズ及びデシリアライズできることを確認する。
+    #[test]
+    fn entity_id_serde() {
+        let id = EntityId::<i32>::gen();
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, format!(r#""{}""#, id.value));
+        let deserialized: EntityId<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, id);
+        assert!(serde_json::from_str::<EntityId<i32>>(r#""invalid-ulid-string""#).is_err());
+    }
 }
 
 /// Eメールアドレス構造体
-#[derive(Debug, Clone, Validate)]
+#[derive(Debug, Clone, PartialEq, Eq, Validate)]
 pub struct EmailAddress {
     /// Eメールアドレス。
     #[validate(email)]
     value: String,
 }
 
+/// Eメールアドレス正規化方針構造体
+///
+/// Eメールアドレスのドメイン部は常に小文字に変換して、ローカル部は方針に従って小文字に変換する。
+/// 既定値はローカル部を小文字に変換しない。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmailNormalization {
+    /// ローカル部を小文字に変換する場合は`true`。
+    pub lowercase_local_part: bool,
+}
+
+/// Eメールアドレスを正規化する。
+///
+/// ドメイン部(最後の`@`より後)を小文字に変換して、`lowercase_local_part`が`true`の場合はローカル部も小文字に変換する。
+///
+/// # Arguments
+///
+/// * `value` - Eメールアドレス。
+/// * `lowercase_local_part` - ローカル部を小文字に変換する場合は`true`。
+///
+/// # Returns
+///
+/// 正規化したEメールアドレス。
+pub fn normalize_email_address(value: &str, lowercase_local_part: bool) -> String {
+    match value.rsplit_once('@') {
+        Some(_) if lowercase_local_part => value.to_lowercase(),
+        Some((local, domain)) => format!("{}@{}", local, domain.to_lowercase()),
+        None => value.to_owned(),
+    }
+}
+
 impl EmailAddress {
     /// コンストラクタ。
     ///
+    /// ドメイン部を小文字に変換して、ローカル部は変換しない。
+    /// 入力されたEメールアドレスからローカル部も正規化して構築する場合は、`normalized`を使用する。
+    ///
     /// # Arguments
     ///
     /// * `value` - Eメールアドレス。
@@ -110,8 +257,28 @@ impl EmailAddress {
     /// * `Ok`: Eメールアドレス構造体。
     /// * `Err`: エラーメッセージ。
     pub fn new(value: &str) -> anyhow::Result<Self> {
+        Self::normalized(value, EmailNormalization::default())
+    }
+
+    /// 正規化方針に従って正規化したEメールアドレスを構築する。
+    ///
+    /// Eメールアドレスの大文字と小文字の違いで別のアカウントとして扱わないように、ドメイン部を小文字に変換して、
+    /// 正規化方針がローカル部を小文字に変換する場合は、ローカル部も小文字に変換する。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Eメールアドレス。
+    /// * `normalization` - Eメールアドレス正規化方針。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: Eメールアドレス構造体。
+    /// * `Err`: エラーメッセージ。
+    pub fn normalized(value: &str, normalization: EmailNormalization) -> anyhow::Result<Self> {
         let result = Self {
-            value: value.to_owned(),
+            value: normalize_email_address(value, normalization.lowercase_local_part),
         };
         if result.validate().is_err() {
             return Err(anyhow!(format!("Eメールアドレス({})が不正です。", value)));
@@ -130,6 +297,8 @@ impl EmailAddress {
     }
 }
 
+impl_string_value_object!(EmailAddress);
+
 #[cfg(test)]
 mod email_address_tests {
     use super::*;
@@ -143,15 +312,117 @@ mod email_address_tests {
         assert_eq!(result.unwrap().value(), value);
     }
 
+    /// 大文字を含むEメールアドレスを、正規化方針に従って小文字に変換して構築することを確認する。
+    #[test]
+    fn test_email_address_normalized() {
+        let lowercase = EmailNormalization {
+            lowercase_local_part: true,
+        };
+        let value = EmailAddress::normalized("Foo@Example.COM", lowercase).unwrap();
+        assert_eq!(value.value(), "foo@example.com");
+        assert_eq!(
+            value,
+            EmailAddress::normalized("foo@example.com", lowercase).unwrap()
+        );
+        let value =
+            EmailAddress::normalized("Foo@Example.COM", EmailNormalization::default()).unwrap();
+        assert_eq!(value.value(), "Foo@example.com");
+        assert_eq!(value, EmailAddress::new("Foo@Example.COM").unwrap());
+    }
+
+    /// ドメイン部を常に小文字に変換して、ローカル部は指定された場合のみ小文字に変換することを確認する。
+    #[test]
+    fn test_normalize_email_address() {
+        assert_eq!(
+            normalize_email_address("Foo.Bar@Example.COM", true),
+            "foo.bar@example.com"
+        );
+        assert_eq!(
+            normalize_email_address("Foo.Bar@Example.COM", false),
+            "Foo.Bar@example.com"
+        );
+        // ローカル部に`@`を含む場合は、最後の`@`より後をドメイン部とする
+        assert_eq!(
+            normalize_email_address(r#""A@B"@Example.com"#, false),
+            r#""A@B"@example.com"#
+        );
+        assert_eq!(normalize_email_address("Invalid", true), "Invalid");
+    }
+
     /// Eメールアドレスを構築できないことを確認する。
     #[test]
     fn test_email_address_new_invalid() {
         assert!(EmailAddress::new("@example.com").is_err());
     }
+
+    /// Eメールアドレスを文字列としてシリアライズ及びデシリアライズできることを確認する。
+    #[test]
+    fn test_email_address_serde() {
+        let value = EmailAddress::new("email@example.com").unwrap();
+        assert_eq!(value.to_string(), "email@example.com");
+        assert_eq!(value.as_ref(), "email@example.com");
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#""email@example.com""#);
+        let deserialized: EmailAddress = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, value);
+        let deserialized: EmailAddress = serde_json::from_str(r#""email@example.com""#).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    /// 不正なEメールアドレスをデシリアライズできないことを確認する。
+    #[test]
+    fn test_email_address_deserialize_invalid() {
+        assert!(serde_json::from_str::<EmailAddress>(r#""@example.com""#).is_err());
+    }
+}
+
+/// 全角の数字、ハイフン及びプラス記号を半角に変換する。
+///
+/// # Arguments
+///
+/// * `value` - 変換する文字列。
+///
+/// # Returns
+///
+/// 変換後の文字列。
+fn to_half_width(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '０'..='９' => char::from_u32(c as u32 - '０' as u32 + '0' as u32).unwrap(),
+            '－' | 'ー' | '‐' | '−' | '―' => '-',
+            '＋' => '+',
+            _ => c,
+        })
+        .collect()
 }
 
+/// 国際電話番号の日本の国番号。
+const JAPAN_COUNTRY_CODE: &str = "+81";
+
+/// 市外局番が2桁の地域の市外局番。
+const TWO_DIGIT_AREA_CODES: [&str; 2] = ["03", "06"];
+
+/// 携帯電話番号の先頭3桁。
+const MOBILE_PREFIXES: [&str; 3] = ["070", "080", "090"];
+
+/// 地域に割り当てられていない電話番号の先頭の番号。
+///
+/// M2M等専用番号、IP電話、フリーダイヤル及びナビダイヤルなどが該当する。
+const NON_GEOGRAPHIC_PREFIXES: [&str; 7] = ["020", "050", "060", "0120", "0570", "0800", "0990"];
+
+/// 11桁の電話番号の先頭3桁。
+///
+/// 携帯電話、PHS、IP電話及びM2M等専用番号が該当する。
+const ELEVEN_DIGIT_PREFIXES: [&str; 5] = ["020", "050", "070", "080", "090"];
+
 /// 電話番号構造体
-#[derive(Debug, Clone, Validate)]
+///
+/// 電話番号は`0X-XXXX-XXXX`形式の国内表記で記録する。
+/// `+81-X-XXXX-XXXX`形式の国際表記を受け付けた場合は、国内表記に変換して記録する。
+/// 全角の数字及びハイフンは半角に変換し、ハイフンを含まない電話番号はハイフンで区切って記録する。
+#[derive(Debug, Clone, PartialEq, Eq, Validate)]
 pub struct PhoneNumber {
     /// 電話番号。
     #[validate(regex = "PHONE_NUMBER_REGEX")]
@@ -163,35 +434,144 @@ impl PhoneNumber {
     ///
     /// # Arguments
     ///
-    /// * `value` - 電話番号。
+    /// * `value` - 電話番号。国内表記または国際表記。ハイフンは省略可能。
     ///
     /// # Returns
     ///
     /// `Result`。`Result`の内容は以下の通り。
     ///
-    /// * `Ok`: Eメールアドレス構造体。
+    /// * `Ok`: 電話番号構造体。
     /// * `Err`: エラーメッセージ。
     pub fn new(value: &str) -> anyhow::Result<Self> {
-        let result = Self {
-            value: value.to_owned(),
-        };
-        if result.validate().is_err() {
+        Ok(Self {
+            value: Self::normalize(value)?,
+        })
+    }
+
+    /// 電話番号を、電話番号構造体が記録する形式に正規化する。
+    ///
+    /// 前後の空白を取り除き、全角の数字及びハイフンを半角に変換して、国際表記の電話番号は国内表記に変換する。
+    /// ハイフンを含まない電話番号は、ハイフンで区切る。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - 電話番号。国内表記または国際表記。ハイフンは省略可能。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: `0X-XXXX-XXXX`形式の国内表記の電話番号。
+    /// * `Err`: エラーメッセージ。
+    pub fn normalize(value: &str) -> anyhow::Result<String> {
+        let number = Self::hyphenate(&Self::to_domestic(&to_half_width(value)));
+        if !PHONE_NUMBER_REGEX.is_match(&number) {
             return Err(anyhow!(format!("電話番号({})が不正です。", value)));
         }
 
-        Ok(result)
+        Ok(number)
+    }
+
+    /// 国際表記の電話番号を国内表記に変換する。
+    ///
+    /// 国際表記でない場合は、そのまま返却する。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - 電話番号。
+    ///
+    /// # Returns
+    ///
+    /// 国内表記の電話番号。
+    fn to_domestic(value: &str) -> String {
+        match value.strip_prefix(JAPAN_COUNTRY_CODE) {
+            Some(number) => {
+                let number = number.strip_prefix('-').unwrap_or(number);
+                // 国番号の直後に市外局番の先頭の0を含めた表記は受け付けない
+                if number.starts_with('0') {
+                    return value.to_owned();
+                }
+                format!("0{}", number)
+            }
+            None => value.to_owned(),
+        }
+    }
+
+    /// ハイフンを含まない電話番号を、ハイフンで区切る。
+    ///
+    /// 11桁の電話番号は`0X0-XXXX-XXXX`形式で区切る。
+    /// 10桁の電話番号は、市外局番が2桁の地域(03、06)は`0X-XXXX-XXXX`形式で区切り、
+    /// それ以外は市外局番が3桁であるとみなして`0XX-XXX-XXXX`形式で区切る。
+    /// 市外局番が4桁以上の電話番号は、ハイフンで区切って指定する必要がある。
+    /// ハイフンを含む電話番号や、上記に該当しない電話番号はそのまま返却する。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - 電話番号。
+    ///
+    /// # Returns
+    ///
+    /// ハイフンで区切った電話番号。
+    fn hyphenate(value: &str) -> String {
+        if !value.starts_with('0') || !value.chars().all(|c| c.is_ascii_digit()) {
+            return value.to_owned();
+        }
+        let area_code_len = match value.len() {
+            11 if ELEVEN_DIGIT_PREFIXES.contains(&&value[..3]) => 3,
+            10 if TWO_DIGIT_AREA_CODES.contains(&&value[..2]) => 2,
+            10 => 3,
+            _ => return value.to_owned(),
+        };
+        let (area_code, rest) = value.split_at(area_code_len);
+        let (local_code, subscriber) = rest.split_at(rest.len() - 4);
+
+        format!("{}-{}-{}", area_code, local_code, subscriber)
     }
 
     /// 電話番号を返却する。
     ///
     /// # Returns
     ///
-    /// * 電話番号。
+    /// * 国内表記の電話番号。
     pub fn value(&self) -> String {
         self.value.clone()
     }
+
+    /// 国際表記の電話番号を返却する。
+    ///
+    /// # Returns
+    ///
+    /// * `+81-X-XXXX-XXXX`形式の国際表記の電話番号。
+    pub fn value_international(&self) -> String {
+        format!("{}-{}", JAPAN_COUNTRY_CODE, &self.value[1..])
+    }
+
+    /// 携帯電話番号であるか確認する。
+    ///
+    /// # Returns
+    ///
+    /// * 070、080または090で始まる電話番号の場合は`true`。
+    pub fn is_mobile(&self) -> bool {
+        MOBILE_PREFIXES.iter().any(|p| self.value.starts_with(p))
+    }
+
+    /// 地域に割り当てられた固定電話番号であるか確認する。
+    ///
+    /// 050で始まるIP電話やフリーダイヤルなど、地域に割り当てられていない電話番号は固定電話番号とみなさない。
+    ///
+    /// # Returns
+    ///
+    /// * 固定電話番号の場合は`true`。
+    pub fn is_landline(&self) -> bool {
+        !self.is_mobile()
+            && !NON_GEOGRAPHIC_PREFIXES
+                .iter()
+                .any(|p| self.value.starts_with(p))
+    }
 }
 
+impl_string_value_object!(PhoneNumber);
+
 #[cfg(test)]
 mod phone_number_tests {
     use super::*;
@@ -205,15 +585,156 @@ mod phone_number_tests {
         assert_eq!(result.unwrap().value(), valid_number);
     }
 
+    /// 国際表記の電話番号から、国内表記の電話番号を構築できることを確認する。
+    #[test]
+    fn test_phone_number_new_international() {
+        let domestic = PhoneNumber::new("090-1234-5678").unwrap();
+        for number in ["+81-90-1234-5678", "+8190-1234-5678"] {
+            let international = PhoneNumber::new(number).unwrap();
+            assert_eq!(international.value(), domestic.value());
+        }
+    }
+
+    /// 国際表記の電話番号を返却できることを確認する。
+    #[test]
+    fn test_phone_number_value_international() {
+        let number = PhoneNumber::new("03-1234-5678").unwrap();
+        assert_eq!(number.value_international(), "+81-3-1234-5678");
+        let number = PhoneNumber::new("+81-3-1234-5678").unwrap();
+        assert_eq!(number.value_international(), "+81-3-1234-5678");
+    }
+
+    /// ハイフンを含まない電話番号を、ハイフンで区切って構築できることを確認する。
+    #[test]
+    fn test_phone_number_new_without_hyphens() {
+        let expected = [
+            ("0312345678", "03-1234-5678"),
+            ("0612345678", "06-1234-5678"),
+            ("0451234567", "045-123-4567"),
+            ("09012345678", "090-1234-5678"),
+            ("08012345678", "080-1234-5678"),
+            ("07012345678", "070-1234-5678"),
+            ("05012345678", "050-1234-5678"),
+            ("+81312345678", "03-1234-5678"),
+            ("+819012345678", "090-1234-5678"),
+        ];
+        for (number, canonical) in expected {
+            assert_eq!(PhoneNumber::new(number).unwrap().value(), canonical);
+        }
+    }
+
+    /// ハイフンを含む電話番号は、区切り位置を変更せずに構築することを確認する。
+    #[test]
+    fn test_phone_number_new_preserves_hyphens() {
+        for number in [
+            "0123-45-6789",
+            "01234-5-6789",
+            "0565-12-3456",
+            "03-1234-5678",
+        ] {
+            assert_eq!(PhoneNumber::new(number).unwrap().value(), number);
+        }
+    }
+
+    /// 全角の数字及びハイフンを含む電話番号を、半角に変換して構築できることを確認する。
+    #[test]
+    fn test_phone_number_new_full_width() {
+        let expected = [
+            ("０３－１２３４－５６７８", "03-1234-5678"),
+            ("０９０ー１２３４ー５６７８", "090-1234-5678"),
+            ("０３１２３４５６７８", "03-1234-5678"),
+            ("03-１２３４-5678", "03-1234-5678"),
+            ("０９０1234５６７８", "090-1234-5678"),
+            ("＋８１－９０－１２３４－５６７８", "090-1234-5678"),
+            (" 090-1234-5678 ", "090-1234-5678"),
+        ];
+        for (number, canonical) in expected {
+            assert_eq!(PhoneNumber::new(number).unwrap().value(), canonical);
+        }
+    }
+
+    /// 携帯電話番号と固定電話番号を判別できることを確認する。
+    #[test]
+    fn test_phone_number_is_mobile_is_landline() {
+        let expected = [
+            ("090-1234-5678", true, false),
+            ("080-1234-5678", true, false),
+            ("070-1234-5678", true, false),
+            ("03-1234-5678", false, true),
+            ("045-123-4567", false, true),
+            ("0565-12-3456", false, true),
+            ("050-1234-5678", false, false),
+            ("020-1234-5678", false, false),
+        ];
+        for (number, is_mobile, is_landline) in expected {
+            let number = PhoneNumber::new(number).unwrap();
+            assert_eq!(number.is_mobile(), is_mobile, "{}", number.value());
+            assert_eq!(number.is_landline(), is_landline, "{}", number.value());
+        }
+    }
+
     /// 電話番号を構築できないことを確認する。
     #[test]
     fn test_phone_number_new_invalid() {
+        assert!(PhoneNumber::new("").is_err());
+        assert!(PhoneNumber::new("abcdefghij").is_err());
+        assert!(PhoneNumber::new("031234567").is_err());
+        assert!(PhoneNumber::new("031234567890").is_err());
+        assert!(PhoneNumber::new("1234567890").is_err());
+        assert!(PhoneNumber::new("01012345678").is_err());
+        assert!(PhoneNumber::new("03-1234-567８９").is_err());
+        assert!(PhoneNumber::new("03 1234 5678").is_err());
+        assert!(PhoneNumber::new("０３１２３４５６７").is_err());
         assert!(PhoneNumber::new("999-9999-9999").is_err());
+        assert!(PhoneNumber::new("+81-090-1234-5678").is_err());
+        assert!(PhoneNumber::new("+1-90-1234-5678").is_err());
+        assert!(PhoneNumber::new("+81-").is_err());
+        assert!(PhoneNumber::new("+81-90-1234-567").is_err());
+    }
+
+    /// 電話番号を文字列としてシリアライズ及びデシリアライズできることを確認する。
+    #[test]
+    fn test_phone_number_serde() {
+        let value = PhoneNumber::new("09012345678").unwrap();
+        assert_eq!(value.to_string(), "090-1234-5678");
+        assert_eq!(value.as_ref(), "090-1234-5678");
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#""090-1234-5678""#);
+        let deserialized: PhoneNumber = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, value);
+        let deserialized: PhoneNumber = serde_json::from_str(r#""09012345678""#).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    /// 不正な電話番号をデシリアライズできないことを確認する。
+    #[test]
+    fn test_phone_number_deserialize_invalid() {
+        assert!(serde_json::from_str::<PhoneNumber>(r#""abc""#).is_err());
+    }
+
+    /// 電話番号を構築せずに、構築した電話番号と同じ形式に正規化できることを確認する。
+    #[test]
+    fn test_phone_number_normalize() {
+        for number in [
+            "０９０ー１２３４ー５６７８",
+            "+81-90-1234-5678",
+            "09012345678",
+        ] {
+            assert_eq!(PhoneNumber::normalize(number).unwrap(), "090-1234-5678");
+            assert_eq!(
+                PhoneNumber::normalize(number).unwrap(),
+                PhoneNumber::new(number).unwrap().value()
+            );
+        }
+        assert!(PhoneNumber::normalize("０３１２３４５６７").is_err());
     }
 }
 
 /// 郵便番号構造体
-#[derive(Debug, Clone, Validate)]
+///
+/// 郵便番号は`XXX-XXXX`形式で記録する。
+/// 全角の数字及びハイフンは半角に変換し、ハイフンを含まない郵便番号はハイフンで区切って記録する。
+#[derive(Debug, Clone, PartialEq, Eq, Validate)]
 pub struct PostalCode {
     /// 郵便番号。
     #[validate(regex = "POSTAL_CODE_REGEX")]
@@ -225,23 +746,44 @@ impl PostalCode {
     ///
     /// # Arguments
     ///
-    /// * `value` - 郵便番号。
+    /// * `value` - 郵便番号。ハイフンは省略可能。
     ///
     /// # Returns
     ///
     /// `Result`。`Result`の内容は以下の通り。
     ///
-    /// * `Ok`: Eメールアドレス構造体。
+    /// * `Ok`: 郵便番号構造体。
     /// * `Err`: エラーメッセージ。
     pub fn new(value: &str) -> anyhow::Result<Self> {
-        let result = PostalCode {
-            value: value.to_owned(),
-        };
-        if result.validate().is_err() {
+        Ok(PostalCode {
+            value: Self::normalize(value)?,
+        })
+    }
+
+    /// 郵便番号を、郵便番号構造体が記録する形式に正規化する。
+    ///
+    /// 前後の空白を取り除き、全角の数字及びハイフンを半角に変換して、ハイフンを含まない郵便番号はハイフンで区切る。
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - 郵便番号。ハイフンは省略可能。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: `XXX-XXXX`形式の郵便番号。
+    /// * `Err`: エラーメッセージ。
+    pub fn normalize(value: &str) -> anyhow::Result<String> {
+        let mut code = to_half_width(value);
+        if code.len() == 7 && code.chars().all(|c| c.is_ascii_digit()) {
+            code.insert(3, '-');
+        }
+        if !POSTAL_CODE_REGEX.is_match(&code) {
             return Err(anyhow::anyhow!(format!("郵便番号({})が不正です。", value)));
         }
 
-        Ok(result)
+        Ok(code)
     }
 
     /// 郵便番号を返却する。
@@ -254,6 +796,8 @@ impl PostalCode {
     }
 }
 
+impl_string_value_object!(PostalCode);
+
 #[cfg(test)]
 mod postal_code_tests {
     use super::*;
@@ -267,15 +811,74 @@ mod postal_code_tests {
         assert_eq!(result.unwrap().value(), valid_code);
     }
 
+    /// ハイフンを含まない郵便番号や全角の郵便番号を、半角のハイフンで区切って構築できることを確認する。
+    #[test]
+    fn test_postal_code_new_normalize() {
+        let expected = [
+            ("1000014", "100-0014"),
+            ("１００－００１４", "100-0014"),
+            ("１００００１４", "100-0014"),
+            ("１００ー００１４", "100-0014"),
+            ("100-００14", "100-0014"),
+            ("１０００014", "100-0014"),
+            (" 100-0014 ", "100-0014"),
+        ];
+        for (code, canonical) in expected {
+            assert_eq!(PostalCode::new(code).unwrap().value(), canonical);
+        }
+    }
+
     /// 郵便番号を構築できないことを確認する。
     #[test]
     fn test_postal_code_new_invalid() {
         assert!(PostalCode::new("00-0000").is_err());
+        assert!(PostalCode::new("").is_err());
+        assert!(PostalCode::new("100001").is_err());
+        assert!(PostalCode::new("10000145").is_err());
+        assert!(PostalCode::new("1000-014").is_err());
+        assert!(PostalCode::new("abc-defg").is_err());
+        assert!(PostalCode::new("100 0014").is_err());
+        assert!(PostalCode::new("１００００１").is_err());
+    }
+
+    /// 郵便番号を文字列としてシリアライズ及びデシリアライズできることを確認する。
+    #[test]
+    fn test_postal_code_serde() {
+        let value = PostalCode::new("1000014").unwrap();
+        assert_eq!(value.to_string(), "100-0014");
+        assert_eq!(value.as_ref(), "100-0014");
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#""100-0014""#);
+        let deserialized: PostalCode = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, value);
+        let deserialized: PostalCode = serde_json::from_str(r#""1000014""#).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    /// 不正な郵便番号をデシリアライズできないことを確認する。
+    #[test]
+    fn test_postal_code_deserialize_invalid() {
+        assert!(serde_json::from_str::<PostalCode>(r#""12-345""#).is_err());
+    }
+
+    /// 郵便番号を構築せずに、構築した郵便番号と同じ形式に正規化できることを確認する。
+    #[test]
+    fn test_postal_code_normalize() {
+        assert_eq!(
+            PostalCode::normalize("０１２ー３４５６").unwrap(),
+            "012-3456"
+        );
+        assert_eq!(PostalCode::normalize("０１２３４５６").unwrap(), "012-3456");
+        assert_eq!(
+            PostalCode::new("０１２ー３４５６").unwrap().value(),
+            "012-3456"
+        );
+        assert!(PostalCode::normalize("１００００１").is_err());
     }
 }
 
 /// 都道府県構造体
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Prefecture {
     /// 都道府県コード。
@@ -284,19 +887,119 @@ pub struct Prefecture {
     name: String,
 }
 
+/// 都道府県コードの順に並べた都道府県名。
+///
+/// 都道府県コードは、都道府県名のインデックスに1を加えた値。
+pub const PREFECTURE_NAMES: [&str; 47] = [
+    "北海道",
+    "青森県",
+    "岩手県",
+    "宮城県",
+    "秋田県",
+    "山形県",
+    "福島県",
+    "茨城県",
+    "栃木県",
+    "群馬県",
+    "埼玉県",
+    "千葉県",
+    "東京都",
+    "神奈川県",
+    "新潟県",
+    "富山県",
+    "石川県",
+    "福井県",
+    "山梨県",
+    "長野県",
+    "岐阜県",
+    "静岡県",
+    "愛知県",
+    "三重県",
+    "滋賀県",
+    "京都府",
+    "大阪府",
+    "兵庫県",
+    "奈良県",
+    "和歌山県",
+    "鳥取県",
+    "島根県",
+    "岡山県",
+    "広島県",
+    "山口県",
+    "徳島県",
+    "香川県",
+    "愛媛県",
+    "高知県",
+    "福岡県",
+    "佐賀県",
+    "長崎県",
+    "熊本県",
+    "大分県",
+    "宮崎県",
+    "鹿児島県",
+    "沖縄県",
+];
+
 impl Prefecture {
     /// コンストラクタ。
     ///
+    /// # Arguments
+    ///
+    /// * `code` - 都道府県コード。
+    /// * `name` - 都道府県名。
+    ///
     /// # Returns
     ///
-    /// * 都道府県。
-    pub fn new(code: u8, name: &str) -> Self {
-        Self {
+    /// `Result`。`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 都道府県。
+    /// * `Err`: 都道府県コードが1から47の範囲外の場合のエラーメッセージ。
+    pub fn new(code: u8, name: &str) -> anyhow::Result<Self> {
+        if !(1..=PREFECTURE_NAMES.len() as u8).contains(&code) {
+            return Err(anyhow!(format!("都道府県コード({})が不正です。", code)));
+        }
+
+        Ok(Self {
             code,
             name: name.to_owned(),
+        })
+    }
+
+    /// 都道府県コードから、都道府県名を補完して都道府県を構築する。
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - 都道府県コード。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 都道府県。
+    /// * `Err`: 都道府県コードが1から47の範囲外の場合のエラーメッセージ。
+    pub fn from_code(code: u8) -> anyhow::Result<Self> {
+        match PREFECTURE_NAMES.get((code as usize).wrapping_sub(1)) {
+            Some(name) => Self::new(code, name),
+            None => Err(anyhow!(format!("都道府県コード({})が不正です。", code))),
         }
     }
 
+    /// すべての都道府県を、都道府県コードの順に返却する。
+    ///
+    /// # Returns
+    ///
+    /// * 都道府県のリスト。
+    pub fn all() -> Vec<Self> {
+        PREFECTURE_NAMES
+            .iter()
+            .zip(1..)
+            .map(|(name, code)| Self {
+                code,
+                name: (*name).to_owned(),
+            })
+            .collect()
+    }
+
     /// 都道府県コードを返却する。
     ///
     /// # Returns
@@ -314,6 +1017,15 @@ impl Prefecture {
     pub fn name(&self) -> String {
         self.name.clone()
     }
+
+    /// 都道府県が属する地方を返却する。
+    ///
+    /// # Returns
+    ///
+    /// * 地方。都道府県コードが1から47の範囲外の場合は`None`。
+    pub fn region(&self) -> Option<Region> {
+        Region::from_prefecture_code(self.code)
+    }
 }
 
 #[cfg(test)]
@@ -323,20 +1035,184 @@ mod prefecture_tests {
     /// 都道府県を構築できることを確認する。
     #[test]
     fn test_prefecture_new() {
-        let code = 12;
+        let code = 13;
         let name = "東京都";
-        let prefecture = Prefecture::new(code, name);
+        let prefecture = Prefecture::new(code, name).unwrap();
         assert_eq!(prefecture.code(), code);
         assert_eq!(prefecture.name(), name);
     }
+
+    /// 範囲外の都道府県コードで都道府県を構築できないことを確認する。
+    #[test]
+    fn test_prefecture_new_invalid() {
+        assert!(Prefecture::new(0, "不明").is_err());
+        assert!(Prefecture::new(48, "不明").is_err());
+    }
+
+    /// 都道府県コードから都道府県を構築できることを確認する。
+    #[test]
+    fn test_prefecture_from_code() {
+        let expected = [
+            (1, "北海道"),
+            (12, "千葉県"),
+            (13, "東京都"),
+            (47, "沖縄県"),
+        ];
+        for (code, name) in expected {
+            let prefecture = Prefecture::from_code(code).unwrap();
+            assert_eq!(prefecture.code(), code);
+            assert_eq!(prefecture.name(), name);
+        }
+        assert!(Prefecture::from_code(0).is_err());
+        assert!(Prefecture::from_code(48).is_err());
+    }
+
+    /// すべての都道府県を都道府県コードの順に返却することを確認する。
+    #[test]
+    fn test_prefecture_all() {
+        let prefectures = Prefecture::all();
+        assert_eq!(prefectures.len(), 47);
+        for (index, prefecture) in prefectures.iter().enumerate() {
+            assert_eq!(prefecture.code() as usize, index + 1);
+            assert_eq!(
+                prefecture.name(),
+                Prefecture::from_code(prefecture.code()).unwrap().name()
+            );
+        }
+        assert_eq!(prefectures[12].name(), "東京都");
+    }
+
+    /// 都道府県が属する地方を返却できることを確認する。
+    #[test]
+    fn test_prefecture_region() {
+        let prefecture = Prefecture::from_code(13).unwrap();
+        assert_eq!(prefecture.region(), Some(Region::Kanto));
+    }
+}
+
+/// 地方列挙型
+///
+/// 列挙子の順序は、地方を並べる順序を示す。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, ToSchema)]
+pub enum Region {
+    /// 北海道・東北地方。
+    #[serde(rename = "北海道・東北")]
+    HokkaidoTohoku,
+    /// 関東地方。
+    #[serde(rename = "関東")]
+    Kanto,
+    /// 中部地方。
+    #[serde(rename = "中部")]
+    Chubu,
+    /// 近畿地方。
+    #[serde(rename = "近畿")]
+    Kinki,
+    /// 中国地方。
+    #[serde(rename = "中国")]
+    Chugoku,
+    /// 四国地方。
+    #[serde(rename = "四国")]
+    Shikoku,
+    /// 九州・沖縄地方。
+    #[serde(rename = "九州・沖縄")]
+    KyushuOkinawa,
+}
+
+impl Region {
+    /// 都道府県コードから、都道府県が属する地方を返却する。
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - 都道府県コード。
+    ///
+    /// # Returns
+    ///
+    /// * 地方。都道府県コードが1から47の範囲外の場合は`None`。
+    pub fn from_prefecture_code(code: u8) -> Option<Self> {
+        match code {
+            1..=7 => Some(Self::HokkaidoTohoku),
+            8..=14 => Some(Self::Kanto),
+            15..=23 => Some(Self::Chubu),
+            24..=30 => Some(Self::Kinki),
+            31..=35 => Some(Self::Chugoku),
+            36..=39 => Some(Self::Shikoku),
+            40..=47 => Some(Self::KyushuOkinawa),
+            _ => None,
+        }
+    }
+
+    /// 地方名を返却する。
+    ///
+    /// # Returns
+    ///
+    /// * 地方名。
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::HokkaidoTohoku => "北海道・東北",
+            Self::Kanto => "関東",
+            Self::Chubu => "中部",
+            Self::Kinki => "近畿",
+            Self::Chugoku => "中国",
+            Self::Shikoku => "四国",
+            Self::KyushuOkinawa => "九州・沖縄",
+        }
+    }
+}
+
+#[cfg(test)]
+mod region_tests {
+    use super::*;
+
+    /// 都道府県コードから地方を返却できることを確認する。
+    #[test]
+    fn test_region_from_prefecture_code() {
+        let expected = [
+            (1, Region::HokkaidoTohoku),
+            (7, Region::HokkaidoTohoku),
+            (8, Region::Kanto),
+            (14, Region::Kanto),
+            (15, Region::Chubu),
+            (23, Region::Chubu),
+            (24, Region::Kinki),
+            (30, Region::Kinki),
+            (31, Region::Chugoku),
+            (35, Region::Chugoku),
+            (36, Region::Shikoku),
+            (39, Region::Shikoku),
+            (40, Region::KyushuOkinawa),
+            (47, Region::KyushuOkinawa),
+        ];
+        for (code, region) in expected {
+            assert_eq!(Region::from_prefecture_code(code), Some(region));
+        }
+    }
+
+    /// 範囲外の都道府県コードに地方を割り当てないことを確認する。
+    #[test]
+    fn test_region_from_prefecture_code_invalid() {
+        assert!(Region::from_prefecture_code(0).is_none());
+        assert!(Region::from_prefecture_code(48).is_none());
+    }
+
+    /// 地方名がシリアライズした値と一致することを確認する。
+    #[test]
+    fn test_region_name() {
+        let json = serde_json::to_string(&Region::Kanto).unwrap();
+        assert_eq!(json, format!("\"{}\"", Region::Kanto.name()));
+    }
 }
 
+/// 市区町村以下住所の最小文字数。
+const ADDRESS_DETAILS_MIN_LENGTH: usize = 2;
+/// 市区町村以下住所の最大文字数。
+const ADDRESS_DETAILS_MAX_LENGTH: usize = 100;
+
 /// 市区町村以下住所構造体。
 ///
-/// 市町村以下の住所は2文字以上100文字以下の文字列を記録する。
-#[derive(Debug, Clone, Validate)]
+/// 市町村以下の住所は前後の空白(全角空白を含む)を取り除いた後、2文字以上100文字以下の文字列を記録する。
+/// 文字数は、結合文字などを含めて利用者が1文字と認識する書記素クラスタ単位で数える。
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AddressDetails {
-    #[validate(length(min = 2, max = 100))]
     value: String,
 }
 
@@ -354,17 +1230,18 @@ impl AddressDetails {
     /// * `Ok`: 市区町村以下住所。
     /// * `Err`: エラーメッセージ。
     pub fn new(value: &str) -> anyhow::Result<Self> {
-        let result = Self {
-            value: value.to_owned(),
-        };
-        if result.validate().is_err() {
+        let trimmed = value.trim();
+        let len = trimmed.graphemes(true).count();
+        if !(ADDRESS_DETAILS_MIN_LENGTH..=ADDRESS_DETAILS_MAX_LENGTH).contains(&len) {
             return Err(anyhow!(format!(
                 "市区町村以下住所({})は{}文字以上{}文字以下です。",
-                value, 2, 100
+                value, ADDRESS_DETAILS_MIN_LENGTH, ADDRESS_DETAILS_MAX_LENGTH
             )));
         }
 
-        Ok(result)
+        Ok(Self {
+            value: trimmed.to_owned(),
+        })
     }
 
     /// 市区町村以下住所を返却する。
@@ -376,8 +1253,39 @@ impl AddressDetails {
     pub fn value(&self) -> String {
         self.value.clone()
     }
+
+    /// 市区町村以下住所が、都道府県コードと矛盾する都道府県名で始まっていないか確認する。
+    ///
+    /// 市区町村以下住所が都道府県名で始まっていない場合や、都道府県コードが示す都道府県名で
+    /// 始まっている場合は矛盾していないと判断する。住所そのものが実在するかは確認しない。
+    ///
+    /// # Arguments
+    ///
+    /// * `prefecture_code` - 都道府県コード。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: `()`。
+    /// * `Err`: 都道府県コードと矛盾する都道府県名で始まっている場合のエラーメッセージ。
+    pub fn check_prefecture(&self, prefecture_code: u8) -> anyhow::Result<()> {
+        let expected = PREFECTURE_NAMES.get((prefecture_code as usize).wrapping_sub(1));
+        let found = PREFECTURE_NAMES
+            .iter()
+            .find(|name| self.value.starts_with(*name));
+        match (expected, found) {
+            (Some(expected), Some(found)) if expected != found => Err(anyhow!(format!(
+                "市区町村以下住所({})が{}で始まっていますが、都道府県は{}が指定されています。市区町村以下住所には都道府県名を含めないでください。",
+                self.value, found, expected
+            ))),
+            _ => Ok(()),
+        }
+    }
 }
 
+impl_string_value_object!(AddressDetails);
+
 #[cfg(test)]
 mod address_details_tests {
     use super::*;
@@ -396,8 +1304,77 @@ mod address_details_tests {
     /// 市区町村以下住所を構築できないことを確認する。
     #[test]
     fn test_address_details_new_invalid() {
-        assert!(PostalCode::new("0").is_err());
-        assert!(PostalCode::new(&"0".repeat(101)).is_err());
+        assert!(AddressDetails::new("0").is_err());
+        assert!(AddressDetails::new(&"0".repeat(101)).is_err());
+    }
+
+    /// 前後の空白を取り除いた市区町村以下住所を記録し、空白のみの住所を構築できないことを確認する。
+    #[test]
+    fn test_address_details_trim() {
+        let result = AddressDetails::new("  千代田区  ").unwrap();
+        assert_eq!(result.value(), "千代田区");
+        let result = AddressDetails::new("\u{3000}千代田区永田町\u{3000}").unwrap();
+        assert_eq!(result.value(), "千代田区永田町");
+        for details in ["", "  ", "\u{3000}\u{3000}", " 新 "] {
+            assert!(AddressDetails::new(details).is_err(), "{:?}", details);
+        }
+    }
+
+    /// 市区町村以下住所の文字数を、書記素クラスタ単位で数えることを確認する。
+    #[test]
+    fn test_address_details_grapheme_length() {
+        // 濁点を結合文字で表現した「ば」は、2つのコードポイントで構成された1文字
+        let ba = "は\u{3099}";
+        assert!(AddressDetails::new(&ba.repeat(ADDRESS_DETAILS_MIN_LENGTH)).is_ok());
+        assert!(AddressDetails::new(&ba.repeat(ADDRESS_DETAILS_MAX_LENGTH)).is_ok());
+        assert!(AddressDetails::new(ba).is_err());
+        assert!(AddressDetails::new(&ba.repeat(ADDRESS_DETAILS_MAX_LENGTH + 1)).is_err());
+    }
+
+    /// 市区町村以下住所の文字数を、バイト数ではなく文字数で検証することを確認する。
+    #[test]
+    fn test_address_details_multibyte_length() {
+        assert!(AddressDetails::new(&"あ".repeat(ADDRESS_DETAILS_MIN_LENGTH)).is_ok());
+        assert!(AddressDetails::new(&"あ".repeat(ADDRESS_DETAILS_MAX_LENGTH)).is_ok());
+        assert!(AddressDetails::new(&"あ".repeat(ADDRESS_DETAILS_MIN_LENGTH - 1)).is_err());
+        assert!(AddressDetails::new(&"あ".repeat(ADDRESS_DETAILS_MAX_LENGTH + 1)).is_err());
+    }
+
+    /// 市区町村以下住所を文字列としてシリアライズ及びデシリアライズできることを確認する。
+    #[test]
+    fn test_address_details_serde() {
+        let value = AddressDetails::new("千代田区永田町1-7-1").unwrap();
+        assert_eq!(value.to_string(), "千代田区永田町1-7-1");
+        assert_eq!(value.as_ref(), "千代田区永田町1-7-1");
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#""千代田区永田町1-7-1""#);
+        let deserialized: AddressDetails = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, value);
+        let deserialized: AddressDetails =
+            serde_json::from_str(r#""千代田区永田町1-7-1""#).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    /// 不正な市区町村以下住所をデシリアライズできないことを確認する。
+    #[test]
+    fn test_address_details_deserialize_invalid() {
+        assert!(serde_json::from_str::<AddressDetails>(r#""a""#).is_err());
+    }
+
+    /// 都道府県コードと矛盾する都道府県名で始まる市区町村以下住所を検出することを確認する。
+    #[test]
+    fn test_address_details_check_prefecture() {
+        let details = AddressDetails::new("大阪府大阪市北区梅田1-1-1").unwrap();
+        let err = details.check_prefecture(13).unwrap_err().to_string();
+        assert!(err.contains("大阪府"), "{}", err);
+        assert!(err.contains("東京都"), "{}", err);
+        assert!(details.check_prefecture(27).is_ok());
+        let details = AddressDetails::new("千代田区永田町1-7-1").unwrap();
+        assert!(details.check_prefecture(13).is_ok());
+        assert!(details.check_prefecture(27).is_ok());
+        // 都道府県コードが不正な場合は、都道府県コードの検証に任せる
+        let details = AddressDetails::new("東京都千代田区").unwrap();
+        assert!(details.check_prefecture(0).is_ok());
     }
 }
 
@@ -456,7 +1433,7 @@ mod address_tests {
     fn test_address_new() {
         let pref_code = 13;
         let pref_name = "東京都";
-        let prefecture = Prefecture::new(pref_code, pref_name);
+        let prefecture = Prefecture::new(pref_code, pref_name).unwrap();
         let address_details = AddressDetails::new("新宿区西新宿2-8-1").unwrap();
         let address = Address::new(prefecture, address_details.clone());
         assert_eq!(address.prefecture().code(), pref_code);
@@ -465,11 +1442,17 @@ mod address_tests {
     }
 }
 
-/// 日本標準時の現在日時を返却する。
+/// 環境変数`TIME_ZONE_OFFSET_SECONDS`で指定されたタイムゾーンの現在日時を返却する。
+///
+/// 環境変数が設定されていない場合は、日本標準時の現在日時を返却する。
+///
+/// # Arguments
+///
+/// * `utc` - 変換する協定世界時。`None`の場合は現在日時。
 ///
 /// # Returns
 ///
-/// * 日本標準時の現在日時。
+/// * タイムゾーンの現在日時。
 ///
 /// # Example
 ///
@@ -482,7 +1465,7 @@ mod address_tests {
 /// assert_eq!(utc, local);
 /// ```
 pub fn local_now(utc: Option<DateTime<Utc>>) -> DateTime<FixedOffset> {
-    let offset = FixedOffset::east(9 * 60 * 60);
+    let offset = FixedOffset::east_opt(ENV_VALUES.time_zone_offset_seconds).unwrap();
     let utc = utc.unwrap_or_else(Utc::now);
 
     utc.with_timezone(&offset)
@@ -1,9 +1,14 @@
 use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset};
 
 use crate::models::accounts::{Account, AccountId, HashedPassword};
-use crate::models::common::EmailAddress;
+use crate::models::common::{EmailAddress, EmailNormalization};
+
+#[cfg(any(test, feature = "test-util"))]
+use mockall;
 
 /// アカウントリポジトリ
+#[cfg_attr(any(test, feature = "test-util"), mockall::automock)]
 #[async_trait]
 pub trait AccountRepository {
     /// アカウントIDを指定して、アカウントを検索する。
@@ -24,7 +29,7 @@ pub trait AccountRepository {
     ///
     /// # Arguments
     ///
-    /// * `email` - Eメールアドレス。
+    /// * `email` - Eメールアドレス。大文字と小文字を区別せずに検索する。
     ///
     /// # Returns
     ///
@@ -44,6 +49,27 @@ pub trait AccountRepository {
     /// * `Err`: エラーメッセージ。
     async fn list(&self) -> anyhow::Result<Vec<Account>>;
 
+    /// アカウントIDの昇順で、指定されたアカウントIDより後のアカウントを、指定された件数まで返却する。
+    ///
+    /// すべてのアカウントを一度に取得せずに、最後に取得したアカウントのアカウントIDを指定して繰り返し呼び出す。
+    ///
+    /// # Arguments
+    ///
+    /// * `after` - 前回取得した最後のアカウントのアカウントID。最初のアカウントから取得する場合は`None`。
+    /// * `limit` - 取得するアカウントの最大数。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: アカウントを格納したベクタ。これ以上アカウントが存在しない場合は空のベクタ。
+    /// * `Err`: エラーメッセージ。
+    async fn list_paged(
+        &self,
+        after: Option<AccountId>,
+        limit: u64,
+    ) -> anyhow::Result<Vec<Account>>;
+
     /// アカウントを登録する。
     ///
     /// # Arguments
@@ -72,6 +98,25 @@ pub trait AccountRepository {
     /// * `Err`: エラー。
     async fn update(&self, account: &Account) -> anyhow::Result<Account>;
 
+    /// 記録されているアカウントの更新日時が、指定された更新日時と一致する場合のみアカウントを更新する。
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - アカウント。
+    /// * `expected_updated_at` - 記録されていることを期待するアカウントの更新日時。
+    ///
+    /// # Result
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 更新後のアカウント。更新日時が一致しないか、アカウントが見つからない場合は`None`。
+    /// * `Err`: エラー。
+    async fn update_if_unmodified(
+        &self,
+        account: &Account,
+        expected_updated_at: DateTime<FixedOffset>,
+    ) -> anyhow::Result<Option<Account>>;
+
     /// アカウントを削除する。
     ///
     /// アカウントIDが一致するアカウントが登録されていない場合は`OK(())`を返却する。
@@ -94,6 +139,8 @@ pub trait AccountRepository {
     ///
     /// * `id` - パスワードを変更するアカウントのアカウントID。
     /// * `password` - 新たに設定するハッシュ化したパスワード。
+    /// * `updated_by` - パスワードを変更したアカウントのアカウントID。
+    ///   `None`の場合は、最後にアカウントを更新したアカウントを変更しない。
     ///
     /// # Returns
     ///
@@ -105,5 +152,75 @@ pub trait AccountRepository {
         &self,
         id: AccountId,
         new_password: HashedPassword,
+        updated_by: Option<AccountId>,
     ) -> anyhow::Result<bool>;
+
+    /// 正規化されていないEメールアドレスで記録されているアカウントのEメールアドレスを、正規化したEメールアドレスに更新する。
+    ///
+    /// 正規化したEメールアドレスが他のアカウントに記録されている場合や、不正なEメールアドレスが記録されている場合は、
+    /// 更新せずにスキップする。
+    ///
+    /// # Arguments
+    ///
+    /// * `normalization` - Eメールアドレス正規化方針。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: Eメールアドレスを更新したアカウントの数。
+    /// * `Err`: エラー。
+    async fn normalize_email_addresses(
+        &self,
+        normalization: EmailNormalization,
+    ) -> anyhow::Result<u64>;
+}
+
+/// パスワード履歴リポジトリ
+///
+/// アカウントに過去に設定されていた、ハッシュ化したパスワードを記録する。
+#[cfg_attr(any(test, feature = "test-util"), mockall::automock)]
+#[async_trait]
+pub trait PasswordHistoryRepository {
+    /// アカウントに過去に設定されていたパスワードを、新しい順に返却する。
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - アカウントID。
+    /// * `limit` - 返却するパスワードの最大数。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: ハッシュ化したパスワードを新しい順に格納したベクタ。
+    /// * `Err`: エラー。
+    async fn list(
+        &self,
+        account_id: AccountId,
+        limit: usize,
+    ) -> anyhow::Result<Vec<HashedPassword>>;
+
+    /// パスワードを履歴に追加して、新しい順に`size`個を超える古いパスワードを履歴から削除する。
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - アカウントID。
+    /// * `password` - 履歴に追加する、変更前のハッシュ化したパスワード。
+    /// * `created_at` - 履歴に追加した日時。
+    /// * `size` - 履歴に残すパスワードの数。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: `()`。
+    /// * `Err`: エラー。
+    async fn push(
+        &self,
+        account_id: AccountId,
+        password: HashedPassword,
+        created_at: DateTime<FixedOffset>,
+        size: usize,
+    ) -> anyhow::Result<()>;
 }
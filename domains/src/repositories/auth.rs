@@ -4,6 +4,7 @@ use crate::models::accounts::AccountId;
 use crate::models::auth::{JwtTokens, JwtTokensId};
 
 /// 有効期限付きアクセス・リフレッシュトークンリポジトリ
+#[cfg_attr(any(test, feature = "test-util"), mockall::automock)]
 #[async_trait]
 pub trait JwtTokensRepository {
     /// トークンIDを指定して、有効期限付きアクセス・リフレッシュトークンを検索する。
@@ -64,11 +65,11 @@ pub trait JwtTokensRepository {
 
     /// 有効期限付きアクセス・リフレッシュトークンを削除する。
     ///
-    /// アカウントIDが一致するアクセス・リフレッシュトークンが登録されていない場合は`OK(())`を返却する。
+    /// トークンIDが一致するアクセス・リフレッシュトークンが登録されていない場合は`OK(())`を返却する。
     ///
     /// # Arguments
     ///
-    /// * `id` - 削除するアカウントのアカウントID。
+    /// * `id` - 削除するトークンのトークンID。
     ///
     /// # Returns
     ///
@@ -76,5 +77,21 @@ pub trait JwtTokensRepository {
     ///
     /// * `Ok`: `()`。
     /// * `Err`: エラー。
-    async fn delete(&self, id: AccountId) -> anyhow::Result<()>;
+    async fn delete(&self, id: JwtTokensId) -> anyhow::Result<()>;
+
+    /// アカウントに発行したすべての有効期限付きアクセス・リフレッシュトークンを削除する。
+    ///
+    /// アカウントはログインするたびにトークンを発行するため、複数のトークンを持つことがある。
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - トークンを削除するアカウントのアカウントID。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 削除したトークンの数。
+    /// * `Err`: エラー。
+    async fn delete_by_account_id(&self, account_id: AccountId) -> anyhow::Result<u64>;
 }
@@ -3,6 +3,7 @@ use async_trait::async_trait;
 use crate::models::common::Prefecture;
 
 /// 都道府県リポジトリ
+#[cfg_attr(any(test, feature = "test-util"), mockall::automock)]
 #[async_trait]
 pub trait PrefectureRepository {
     /// 都道府県コードを指定して、都道府県を検索する。
@@ -19,6 +20,20 @@ pub trait PrefectureRepository {
     /// * `Err`: エラーメッセージ。
     async fn find_by_code(&self, code: u8) -> anyhow::Result<Option<Prefecture>>;
 
+    /// 都道府県コードを複数指定して、都道府県を検索する。
+    ///
+    /// # Arguments
+    ///
+    /// * `codes` - 都道府県コードのスライス。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 見つかった都道府県を都道府県コードの順に格納したベクタ。見つからなかった都道府県コードは含まない。
+    /// * `Err`: エラーメッセージ。
+    async fn find_by_codes(&self, codes: &[u8]) -> anyhow::Result<Vec<Prefecture>>;
+
     /// 都道府県のリストを返却する。
     ///
     /// # Returns
@@ -28,4 +43,20 @@ pub trait PrefectureRepository {
     /// * `Ok`: 都道府県を格納したベクタ。
     /// * `Err`: エラーメッセージ。
     async fn list(&self) -> anyhow::Result<Vec<Prefecture>>;
+
+    /// 都道府県コードが登録されていない都道府県を登録する。
+    ///
+    /// 都道府県コードが登録されている都道府県は、更新せずにスキップする。
+    ///
+    /// # Arguments
+    ///
+    /// * `prefectures` - 登録する都道府県のリスト。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 登録した都道府県の数。
+    /// * `Err`: エラーメッセージ。
+    async fn insert_missing(&self, prefectures: &[Prefecture]) -> anyhow::Result<u64>;
 }
@@ -1,18 +1,22 @@
 use std::str::FromStr;
 
-use common::ENV_VALUES;
+use anyhow::anyhow;
+use chrono::{DateTime, FixedOffset};
 
-use super::super::models::accounts::{Account, RawPassword};
+use common::hashing::HashingConfig;
+
+use super::super::models::accounts::{Account, HashedPassword, RawPassword};
 use super::super::models::common::EmailAddress;
 use super::super::repositories::accounts::AccountRepository;
-use super::hashers::{decode_password, gen_hashed_password, PasswordHashFunc};
+use super::hashers::{decode_password, gen_hashed_password, PasswordHashFunc, PasswordHasher};
 
 /// パスワードを検証する。
 ///
 /// # Arguments
 ///
 /// * `raw_password` - ハッシュ化していないパスワード。
-/// * `hashed_password` - データベースに記録しているパスワード。ハッシュ化アルゴリズム、ハッシュ化ラウンド数、ソルト文字数、ソルト、ハッシュ化したパスワード。
+/// * `hashed_password` - データベースに記録しているパスワード。ハッシュ化アルゴリズム、ハッシュ化ラウンド数、ソルト文字数、ペッパーのバージョン、ソルト、ハッシュ化したパスワード。
+/// * `config` - パスワードハッシュ化設定。ハッシュ化したパスワードに記録されているバージョンのペッパーを使用する。
 ///
 /// # Returns
 ///
@@ -20,18 +24,23 @@ use super::hashers::{decode_password, gen_hashed_password, PasswordHashFunc};
 ///
 /// * `Ok`: パスワードの検証に成功した場合はtrue。パスワードの検証に失敗した場合はfalse。
 /// * `Err`: エラー。
-pub fn verify_password(raw_password: &str, hashed_password: &str) -> anyhow::Result<bool> {
+pub fn verify_password(
+    raw_password: &str,
+    hashed_password: &str,
+    config: &HashingConfig,
+) -> anyhow::Result<bool> {
     // ハッシュ化されたパスワードをデコード
-    let (algo, round, _, sault, hashed) = decode_password(hashed_password)?;
+    let (algo, round, _, version, sault, hashed) = decode_password(hashed_password)?;
     let func = PasswordHashFunc::from_str(&algo)?;
+    // ハッシュ化したときのバージョンのペッパーを取得
+    let pepper = config.pepper(version).ok_or_else(|| {
+        anyhow!(
+            "ハッシュ化したパスワードに記録されているペッパーのバージョン({})が設定されていません。",
+            version.unwrap_or_default()
+        )
+    })?;
     // 検証するパスワードをハッシュ化
-    let target = gen_hashed_password(
-        raw_password,
-        &sault,
-        &ENV_VALUES.password_pepper,
-        func,
-        round,
-    );
+    let target = gen_hashed_password(raw_password, &sault, pepper, func, round);
 
     // ハッシュ化されたパスワードを確認
     Ok(target == hashed)
@@ -39,11 +48,17 @@ pub fn verify_password(raw_password: &str, hashed_password: &str) -> anyhow::Res
 
 /// ユーザーを認証する。
 ///
+/// 認証に成功した場合は、アカウントの最終ログイン日時を現在日時に更新して、リポジトリに保存する。
+/// また、記録されているパスワードのハッシュ関数、ラウンド数またはソルト文字数が現在の設定と異なる場合は、
+/// 現在の設定でパスワードをハッシュ化し直して、リポジトリに保存する。
+///
 /// # Arguments
 ///
 /// * `repo` - アカウントリポジトリ。
+/// * `hasher` - パスワードをハッシュ化する構造体。
 /// * `email` - ユーザーのアカウントに登録したEメールアドレス。
 /// * `password` - ユーザーのアカウントに登録したパスワード。
+/// * `now` - 最終ログイン日時に記録する現在日時。
 ///
 /// # Returns
 ///
@@ -53,8 +68,10 @@ pub fn verify_password(raw_password: &str, hashed_password: &str) -> anyhow::Res
 /// * `Err`: エラー。
 pub async fn authenticate(
     repo: &dyn AccountRepository,
+    hasher: &dyn PasswordHasher,
     email: EmailAddress,
     password: RawPassword,
+    now: DateTime<FixedOffset>,
 ) -> anyhow::Result<Option<Account>> {
     // Eメールアドレスでアカウントを検索
     let result = repo.find_by_email(email).await?;
@@ -68,9 +85,148 @@ pub async fn authenticate(
         return Ok(None);
     }
     // パスワードを検証
-    if !verify_password(&password.value(), &account.password().value())? {
+    if !hasher.verify(&password.value(), &account.password().value())? {
         return Ok(None);
     }
+    let mut account = account;
+    // パスワードの検証に成功した場合のみ、現在の設定でパスワードをハッシュ化し直す
+    if hasher.needs_rehash(&account.password().value()) {
+        let rehashed = HashedPassword::new(password, hasher)?;
+        repo.change_password(account.id(), rehashed.clone(), None)
+            .await?;
+        account.set_password(rehashed);
+    }
+    // 最終ログイン日時を更新
+    account.set_logged_in_at(Some(now));
+    let account = repo.update(&account).await?;
 
     Ok(Some(account))
 }
+
+#[cfg(test)]
+mod authenticate_tests {
+    use chrono::Duration;
+
+    use super::super::super::models::accounts::{AccountName, FixedMobileNumbers};
+    use super::super::super::models::common::{
+        local_now, Address, AddressDetails, PhoneNumber, PostalCode, Prefecture,
+    };
+    use super::super::super::repositories::accounts::MockAccountRepository;
+    use super::super::hashers::{test_config, test_hasher, PasswordHasherImpl};
+    use super::*;
+
+    /// 指定された構造体でパスワードをハッシュ化した、認証に使用するアカウントを構築する。
+    fn account(hasher: &dyn PasswordHasher) -> Account {
+        let phone_numbers =
+            FixedMobileNumbers::new(None, Some(PhoneNumber::new("090-1234-5678").unwrap()))
+                .unwrap();
+        let address = Address::new(
+            Prefecture::new(13, "東京都").unwrap(),
+            AddressDetails::new("新宿区西新宿2-8-1").unwrap(),
+        );
+        Account::new(
+            EmailAddress::new("foo@example.com").unwrap(),
+            AccountName::new("foo").unwrap(),
+            HashedPassword::new(RawPassword::new("01abCD#$").unwrap(), hasher).unwrap(),
+            true,
+            phone_numbers,
+            PostalCode::new("163-8001").unwrap(),
+            address,
+            local_now(None),
+        )
+    }
+
+    /// 指定されたラウンド数でハッシュ化する構造体を構築する。
+    fn hasher(round: u32) -> PasswordHasherImpl {
+        PasswordHasherImpl::new(test_config("SHA-256", round, 16))
+    }
+
+    /// 認証に成功した場合に、最終ログイン日時が更新されることを確認する。
+    #[tokio::test]
+    async fn test_authenticate_updates_logged_in_at() {
+        let hasher = test_hasher();
+        let account = account(&hasher);
+        assert!(account.logged_in_at().is_none());
+        // 最終ログイン日時に記録する日時を固定
+        let now = local_now(None) + Duration::hours(1);
+        let mut repo = MockAccountRepository::new();
+        repo.expect_find_by_email()
+            .times(1)
+            .returning(move |_| Ok(Some(account.clone())));
+        // パスワードをハッシュ化した設定が変わっていないため、パスワードは変更しない
+        repo.expect_change_password().times(0);
+        repo.expect_update()
+            .times(1)
+            .withf(move |account| account.logged_in_at() == Some(now))
+            .returning(|account| Ok(account.clone()));
+        let result = authenticate(
+            &repo,
+            &hasher,
+            EmailAddress::new("foo@example.com").unwrap(),
+            RawPassword::new("01abCD#$").unwrap(),
+            now,
+        )
+        .await;
+        assert_eq!(result.unwrap().unwrap().logged_in_at(), Some(now));
+    }
+
+    /// 認証に失敗した場合に、アカウントを更新しないことを確認する。
+    #[tokio::test]
+    async fn test_authenticate_wrong_password() {
+        let account = account(&hasher(1));
+        let mut repo = MockAccountRepository::new();
+        repo.expect_find_by_email()
+            .times(1)
+            .returning(move |_| Ok(Some(account.clone())));
+        repo.expect_change_password().times(0);
+        repo.expect_update().times(0);
+        let result = authenticate(
+            &repo,
+            &hasher(10),
+            EmailAddress::new("foo@example.com").unwrap(),
+            RawPassword::new("01abCD#$X").unwrap(),
+            local_now(None),
+        )
+        .await;
+        assert!(result.unwrap().is_none());
+    }
+
+    /// パスワードをハッシュ化したラウンド数が現在の設定と異なる場合に、認証に成功したときに
+    /// 現在の設定でパスワードをハッシュ化し直して保存することを確認する。
+    #[tokio::test]
+    async fn test_authenticate_rehashes_password() {
+        let account = account(&hasher(1));
+        let old_password = account.password().value();
+        assert!(old_password.starts_with("SHA-256$1$"), "{}", old_password);
+        let id = account.id();
+        let mut repo = MockAccountRepository::new();
+        repo.expect_find_by_email()
+            .times(1)
+            .returning(move |_| Ok(Some(account.clone())));
+        repo.expect_change_password()
+            .times(1)
+            .withf(move |account_id, password, updated_by| {
+                *account_id == id
+                    && password.value().starts_with("SHA-256$10$")
+                    && updated_by.is_none()
+            })
+            .returning(|_, _, _| Ok(true));
+        repo.expect_update()
+            .times(1)
+            .withf(|account| account.password().value().starts_with("SHA-256$10$"))
+            .returning(|account| Ok(account.clone()));
+        let account = authenticate(
+            &repo,
+            &hasher(10),
+            EmailAddress::new("foo@example.com").unwrap(),
+            RawPassword::new("01abCD#$").unwrap(),
+            local_now(None),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let new_password = account.password().value();
+        assert_ne!(new_password, old_password);
+        assert!(hasher(10).verify("01abCD#$", &new_password).unwrap());
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, FixedOffset};
+
+use super::super::models::common::local_now;
+
+/// 時計トレイト
+///
+/// 現在日時を返却する。
+/// ユースケースに時計を注入することで、テストで現在日時を固定できるようにする。
+pub trait Clock: Send + Sync {
+    /// 現在日時を返却する。
+    ///
+    /// # Returns
+    ///
+    /// * 現在日時。
+    fn now(&self) -> DateTime<FixedOffset>;
+}
+
+/// システム時計構造体
+///
+/// システムの現在日時を、環境変数で指定されたタイムゾーンで返却する。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        local_now(None)
+    }
+}
+
+/// 固定時計構造体
+///
+/// 常に同じ日時を返却する。
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<FixedOffset>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod clock_tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    /// 固定時計が、常に同じ日時を返却することを確認する。
+    #[test]
+    fn test_fixed_clock() {
+        let dt = FixedOffset::east_opt(9 * 60 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2022, 4, 1, 9, 0, 0)
+            .unwrap();
+        let clock: &dyn Clock = &FixedClock(dt);
+        assert_eq!(clock.now(), dt);
+        assert_eq!(clock.now(), dt);
+    }
+
+    /// システム時計が、環境変数で指定されたタイムゾーンの現在日時を返却することを確認する。
+    #[test]
+    fn test_system_clock() {
+        let before = chrono::Utc::now();
+        let now = SystemClock.now();
+        let after = chrono::Utc::now();
+        assert!(before <= now && now <= after);
+        assert_eq!(
+            now.offset().local_minus_utc(),
+            common::ENV_VALUES.time_zone_offset_seconds
+        );
+    }
+}
@@ -4,7 +4,9 @@ use anyhow::anyhow;
 use sha2::{Digest, Sha224, Sha256, Sha384, Sha512, Sha512_224, Sha512_256};
 use strum_macros::{Display, EnumIter, EnumString};
 
-use common::ENV_VALUES;
+use common::hashing::HashingConfig;
+
+use super::auth::verify_password;
 
 #[cfg(test)]
 use mockall;
@@ -32,14 +34,18 @@ pub enum PasswordHashFunc {
     SHA512_256,
 }
 
-/// 環境変数からパスワードをハッシュ化するハッシュ関数の種類を取得する。
+/// パスワードをハッシュ化するハッシュ関数の種類を取得する。
+///
+/// # Arguments
+///
+/// * `name` - 環境変数`PASSWORD_HASH_FUNC`に設定されたハッシュ関数名。
 ///
 /// # Returns
 ///
 /// * ハッシュ関数の種類を示す`PasswordHashFunc`列挙型の値。
-/// * 環境変数からハッシュ関数の種類を得られなかった場合は`Error`列挙体の値。
-fn password_hash_func() -> anyhow::Result<PasswordHashFunc> {
-    match PasswordHashFunc::from_str(&ENV_VALUES.password_hash_func) {
+/// * ハッシュ関数名からハッシュ関数の種類を得られなかった場合は`Error`列挙体の値。
+fn password_hash_func(name: &str) -> anyhow::Result<PasswordHashFunc> {
+    match PasswordHashFunc::from_str(name) {
         Ok(hash_func) => Ok(hash_func),
         _ => Err(anyhow!(
             "パスワードをハッシュ化する関数を指定する環境変数PASSWORD_HASH_FUNCの値が不正です。"
@@ -97,32 +103,32 @@ fn hash_func_doit(func: PasswordHashFunc, target: &str) -> String {
         PasswordHashFunc::SHA224 => {
             let mut hasher = Sha224::new();
             hasher.update(target);
-            hex::encode(hasher.finalize().to_vec())
+            hex::encode(hasher.finalize())
         }
         PasswordHashFunc::SHA256 => {
             let mut hasher = Sha256::new();
             hasher.update(target);
-            hex::encode(hasher.finalize().to_vec())
+            hex::encode(hasher.finalize())
         }
         PasswordHashFunc::SHA384 => {
             let mut hasher = Sha384::new();
             hasher.update(target);
-            hex::encode(hasher.finalize().to_vec())
+            hex::encode(hasher.finalize())
         }
         PasswordHashFunc::SHA512 => {
             let mut hasher = Sha512::new();
             hasher.update(target);
-            hex::encode(hasher.finalize().to_vec())
+            hex::encode(hasher.finalize())
         }
         PasswordHashFunc::SHA512_224 => {
             let mut hasher = Sha512_224::new();
             hasher.update(target);
-            hex::encode(hasher.finalize().to_vec())
+            hex::encode(hasher.finalize())
         }
         PasswordHashFunc::SHA512_256 => {
             let mut hasher = Sha512_256::new();
             hasher.update(target);
-            hex::encode(hasher.finalize().to_vec())
+            hex::encode(hasher.finalize())
         }
     }
 }
@@ -159,8 +165,8 @@ pub fn gen_hashed_password(
 ///
 /// パスワードにソルトとペッパーを加えた文字列をハッシュ化した文字列を返却する。
 /// 返却する文字列は下記の通り生成される。また、対応するハッシュ関数を以下に示す。
-/// ハッシュ関数は環境変数`PASSWORD_HASH_FUNC`から判別して、環境変数`PASSWORD_HASH_FUNC`は、
-/// 下に示した文字列を設定する。
+/// ハッシュ関数はパスワードハッシュ化設定のハッシュ関数名から判別して、ハッシュ関数名には、
+/// 下に示した文字列を指定する。
 ///
 /// * SHA-224
 /// * SHA-256
@@ -169,39 +175,172 @@ pub fn gen_hashed_password(
 /// * SHA-512/224
 /// * SHA-512/256
 ///
-/// 1. 環境変数からハッシュ関数(PASSWORD_HASH_FUNC)、ソルトの長さ(PASSWORD_SAULT)、
-///    ペッパー(PASSWORD_PEPPER)及びラウンド回数(PASSWORD_HASH_ROUND)を取得する。
+/// 1. パスワードハッシュ化設定からハッシュ関数、ソルトの長さ、ペッパー及びラウンド回数を取得する。
+///    ペッパーは、最も大きいバージョンのペッパーを使用する。
 /// 2. ソルトとなる文字列を生成する。
 /// 3. パスワードの末尾にソルト、ペッパーの順に文字列を追加した文字列を生成する。
 /// 5. 上記文字列をラウンド回数だけハッシュ関数でハッシュ化した文字列を生成する。
-/// 6. ハッシュ関数名$ラウンド回数$ソルト$ハッシュ化文字列の書式で文字列を返却する。
+/// 6. ハッシュ関数名$ラウンド回数$ソルト文字数$ペッパーのバージョン$ソルト$ハッシュ化文字列の書式で文字列を返却する。
+///    バージョン付きのペッパーが設定されていない場合は、ペッパーのバージョンを含めない。
 ///
 /// # Arguments
 ///
+/// * `sault_provider` - ソルトを生成する構造体。
 /// * `raw` - ハッシュ化する前のパスワード（生パスワード）。
+/// * `config` - パスワードハッシュ化設定。
 ///
 /// # Returns
 ///
-/// * ハッシュアルゴリズム、ラウンド回数、ソルト及びパスワードにソルトとペッパーを加えた文字列を指定された回数だけハッシュ化した文字列を
-///   `$`で連結した文字列。返却される文字列の書式は、`<algo>$<round>$<sault_len>$<sault>$<hashed>`。
-pub fn hash_password(sault_provider: &dyn SaultProvider, raw: &str) -> anyhow::Result<String> {
-    let func = password_hash_func()?;
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: ハッシュアルゴリズム、ラウンド回数、ソルト及びパスワードにソルトとペッパーを加えた文字列を指定された回数だけハッシュ化した文字列を
+///   `$`で連結した文字列。返却される文字列の書式は、`<algo>$<round>$<sault_len>$<pepper_version>$<sault>$<hashed>`。
+/// * `Err`: ハッシュ関数名が不正な場合のエラー。
+pub fn hash_password(
+    sault_provider: &dyn SaultProvider,
+    raw: &str,
+    config: &HashingConfig,
+) -> anyhow::Result<String> {
+    let func = password_hash_func(&config.func)?;
     // パスワードの末尾にソルトとペッパーを追加して、ハッシュ化対象文字列を生成
-    let sault = sault_provider.generate(ENV_VALUES.password_sault_len);
-    let hashed = gen_hashed_password(
-        raw,
-        &sault,
-        &ENV_VALUES.password_pepper,
-        func,
-        ENV_VALUES.password_hash_round,
-    );
+    let sault = sault_provider.generate(config.sault_len);
+    let (version, pepper) = config.current_pepper();
+    let hashed = gen_hashed_password(raw, &sault, pepper, func, config.round);
+    let version = version
+        .map(|version| format!("{}$", version))
+        .unwrap_or_default();
 
     Ok(format!(
-        "{}${}${}${}${}",
-        func, ENV_VALUES.password_hash_round, ENV_VALUES.password_sault_len, sault, hashed
+        "{}${}${}${}{}${}",
+        func, config.round, config.sault_len, version, sault, hashed
     ))
 }
 
+/// パスワードをハッシュ化する機能を提供する構造体が実装するトレイト。
+///
+/// ユースケースに注入して、パスワードをハッシュ化するときに使用する。
+pub trait PasswordHasher: Send + Sync {
+    /// パスワードをハッシュ化する。
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - ハッシュ化する前のパスワード（生パスワード）。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: ハッシュ化したパスワード。
+    /// * `Err`: パスワードのハッシュ化に失敗した場合のエラー。
+    fn hash(&self, raw: &str) -> anyhow::Result<String>;
+
+    /// ハッシュ化したパスワードを、現在の設定でハッシュ化し直す必要があるか確認する。
+    ///
+    /// # Arguments
+    ///
+    /// * `hashed` - ハッシュ化したパスワード。
+    ///
+    /// # Returns
+    ///
+    /// ハッシュ関数、ラウンド数、ソルト文字数またはペッパーのバージョンが現在の設定と異なる場合は`true`。
+    fn needs_rehash(&self, hashed: &str) -> bool;
+
+    /// パスワードを検証する。
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - ハッシュ化していないパスワード。
+    /// * `hashed` - ハッシュ化したパスワード。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: パスワードが一致する場合は`true`。
+    /// * `Err`: ハッシュ化したパスワードの書式が不正な場合のエラー。
+    fn verify(&self, raw: &str, hashed: &str) -> anyhow::Result<bool>;
+}
+
+/// パスワードをハッシュ化する構造体。
+pub struct PasswordHasherImpl {
+    /// パスワードハッシュ化設定。
+    config: HashingConfig,
+}
+
+impl PasswordHasherImpl {
+    /// パスワードをハッシュ化する構造体を構築する。
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - パスワードハッシュ化設定。
+    ///
+    /// # Returns
+    ///
+    /// パスワードをハッシュ化する構造体。
+    pub fn new(config: HashingConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl PasswordHasher for PasswordHasherImpl {
+    fn hash(&self, raw: &str) -> anyhow::Result<String> {
+        hash_password(&SaultProviderImpl, raw, &self.config)
+    }
+
+    fn needs_rehash(&self, hashed: &str) -> bool {
+        // 現在のハッシュ関数名が不正な場合は、ハッシュ化し直せないため確認しない
+        let func = match password_hash_func(&self.config.func) {
+            Ok(func) => func,
+            Err(_) => return false,
+        };
+        match decode_password(hashed) {
+            Ok((algo, round, sault_len, version, _, _)) => {
+                algo != func.to_string()
+                    || round != self.config.round
+                    || sault_len != self.config.sault_len
+                    || version != self.config.current_pepper().0
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn verify(&self, raw: &str, hashed: &str) -> anyhow::Result<bool> {
+        verify_password(raw, hashed, &self.config)
+    }
+}
+
+/// テストで使用するパスワードハッシュ化設定を構築する。
+///
+/// # Arguments
+///
+/// * `func` - ハッシュ関数名。
+/// * `round` - ハッシュ化ラウンド数。
+/// * `sault_len` - ソルト文字数。
+///
+/// # Returns
+///
+/// パスワードハッシュ化設定。
+#[cfg(test)]
+pub(crate) fn test_config(func: &str, round: u32, sault_len: usize) -> HashingConfig {
+    HashingConfig {
+        func: func.to_owned(),
+        round,
+        sault_len,
+        pepper: "test-pepper".to_owned(),
+        peppers: Default::default(),
+    }
+}
+
+/// テストで使用する、SHA-256でハッシュ化する構造体を構築する。
+///
+/// # Returns
+///
+/// パスワードをハッシュ化する構造体。
+#[cfg(test)]
+pub(crate) fn test_hasher() -> PasswordHasherImpl {
+    PasswordHasherImpl::new(test_config("SHA-256", 10, 16))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -216,18 +355,104 @@ mod tests {
             for ch in sault.chars() {
                 let index = SAULT_CHARS.find(ch);
                 if index.is_none() {
-                    assert!(
-                        false,
-                        "生成したソルトにソルトに使用できない文字が含まれています。"
-                    );
+                    panic!("生成したソルトにソルトに使用できない文字が含まれています。");
                 }
             }
         }
     }
+
+    /// ハッシュ関数名が不正な場合に、パニックせずにエラーを返却することを確認する。
+    #[test]
+    fn test_password_hasher_invalid_hash_func() {
+        let hasher = PasswordHasherImpl::new(test_config("MD5", 10, 16));
+        assert!(hasher.hash("01abCD#$").is_err());
+        let hasher = test_hasher();
+        assert!(hasher.hash("01abCD#$").unwrap().starts_with("SHA-256$"));
+    }
+
+    /// ハッシュ関数、ラウンド数またはソルト文字数が現在の設定と異なる場合に、ハッシュ化し直す必要があると判断することを確認する。
+    #[test]
+    fn test_password_hasher_needs_rehash() {
+        let hasher = test_hasher();
+        let hashed = hasher.hash("01abCD#$").unwrap();
+        assert!(hashed.starts_with("SHA-256$10$16$"), "{}", hashed);
+        assert!(!hasher.needs_rehash(&hashed));
+        for config in [
+            test_config("SHA-512", 10, 16),
+            test_config("SHA-256", 1, 16),
+            test_config("SHA-256", 10, 8),
+        ] {
+            let other = PasswordHasherImpl::new(config.clone());
+            assert!(other.needs_rehash(&hashed), "{:?}", config);
+        }
+        // ハッシュ関数名が不正な場合はハッシュ化し直さない
+        let invalid = PasswordHasherImpl::new(test_config("MD5", 10, 16));
+        assert!(!invalid.needs_rehash(&hashed));
+    }
+
+    /// ハッシュ化したときと同じペッパーを設定した場合のみ、パスワードの検証に成功することを確認する。
+    #[test]
+    fn test_password_hasher_verify() {
+        let hasher = test_hasher();
+        let hashed = hasher.hash("01abCD#$").unwrap();
+        assert!(hasher.verify("01abCD#$", &hashed).unwrap());
+        assert!(!hasher.verify("01abCD#$X", &hashed).unwrap());
+        let other = PasswordHasherImpl::new(HashingConfig {
+            pepper: "other-pepper".to_owned(),
+            ..test_config("SHA-256", 10, 16)
+        });
+        assert!(!other.verify("01abCD#$", &hashed).unwrap());
+        assert!(hasher.verify("01abCD#$", "SHA-256$10").is_err());
+    }
+
+    /// 新しいバージョンのペッパーを追加した後も、古いバージョンのペッパーでハッシュ化したパスワードを検証できることを確認する。
+    #[test]
+    fn test_password_hasher_pepper_rotation() {
+        let with_peppers = |peppers: &[(u32, &str)]| {
+            PasswordHasherImpl::new(HashingConfig {
+                peppers: peppers
+                    .iter()
+                    .map(|(version, pepper)| (*version, pepper.to_string()))
+                    .collect(),
+                ..test_config("SHA-256", 10, 16)
+            })
+        };
+        // バージョンのないペッパーでハッシュ化
+        let legacy = test_hasher().hash("01abCD#$").unwrap();
+        // バージョン1のペッパーを追加
+        let v1 = with_peppers(&[(1, "pepper-1")]);
+        let hashed_v1 = v1.hash("01abCD#$").unwrap();
+        assert!(hashed_v1.starts_with("SHA-256$10$16$1$"), "{}", hashed_v1);
+        assert!(v1.verify("01abCD#$", &legacy).unwrap());
+        assert!(v1.verify("01abCD#$", &hashed_v1).unwrap());
+        assert!(v1.needs_rehash(&legacy));
+        assert!(!v1.needs_rehash(&hashed_v1));
+        // バージョン2のペッパーを追加
+        let v2 = with_peppers(&[(1, "pepper-1"), (2, "pepper-2")]);
+        let hashed_v2 = v2.hash("01abCD#$").unwrap();
+        assert!(hashed_v2.starts_with("SHA-256$10$16$2$"), "{}", hashed_v2);
+        for hashed in [&legacy, &hashed_v1, &hashed_v2] {
+            assert!(v2.verify("01abCD#$", hashed).unwrap(), "{}", hashed);
+            assert!(!v2.verify("01abCD#$X", hashed).unwrap(), "{}", hashed);
+        }
+        assert!(v2.needs_rehash(&hashed_v1));
+        assert!(!v2.needs_rehash(&hashed_v2));
+        // 同じバージョンでもペッパーが異なる場合は検証に失敗
+        let other = with_peppers(&[(1, "other-pepper")]);
+        assert!(!other.verify("01abCD#$", &hashed_v1).unwrap());
+        // 記録されているバージョンのペッパーが設定されていない場合はエラー
+        assert!(v1.verify("01abCD#$", &hashed_v2).is_err());
+    }
 }
 
 /// ハッシュ化されたパスワードをデコードする。
 ///
+/// ハッシュ化されたパスワードの書式は`<algo>$<round>$<sault_len>$<pepper_version>$<sault>$<hashed>`である。
+/// ソルトには`$`が含まれることがあるため、ソルトはペッパーのバージョンの後の`$`から、最後の`$`までとする。
+/// ペッパーのバージョンを記録していない`<algo>$<round>$<sault_len>$<sault>$<hashed>`の書式の場合は、
+/// ソルトはソルト文字数の後の`$`から、最後の`$`までとする。
+/// ソルト文字数が一致するため、2つの書式を区別できる。
+///
 /// # Arguments
 ///
 /// * `password` - ハッシュ化されたパスワード。
@@ -236,62 +461,74 @@ mod tests {
 ///
 /// `Result`。返却される`Result`の内容は以下の通り。
 ///
-/// * `Ok`: アルゴリズム、ハッシュ化ラウンド数、ソルト文字数、ソルト、パスワードをハッシュ化した結果を格納したタプル。
-/// * `Err`: エラー。
-pub fn decode_password(password: &str) -> anyhow::Result<(String, u32, usize, String, String)> {
-    // アルゴリズムの記録の終了を示す`$`の位置を検索
-    let algo_pos = password.find('$');
-    if algo_pos.is_none() {
+/// * `Ok`: アルゴリズム、ハッシュ化ラウンド数、ソルト文字数、ペッパーのバージョン、ソルト、パスワードをハッシュ化した結果を格納したタプル。
+///   ペッパーのバージョンを記録していない場合、ペッパーのバージョンは`None`。
+/// * `Err`: ハッシュ化されたパスワードの書式が不正な場合のエラー。
+pub fn decode_password(
+    password: &str,
+) -> anyhow::Result<(String, u32, usize, Option<u32>, String, String)> {
+    let mut segments = password.splitn(4, '$');
+    // アルゴリズムを取得
+    let algo = segments.next().unwrap_or_default();
+    if algo.is_empty() {
         return Err(anyhow!(
-            "ハッシュ化したパスワードから、アルゴリズムを取得できません。 "
-        ));
-    };
-    let algo_pos = algo_pos.unwrap();
-    let algo = &password[..algo_pos];
-    let mut start = algo_pos + 1;
-    // ラウンド数の記録の終了を示す'$'の位置を検索
-    let round_pos = password[start..].find('$');
-    if round_pos.is_none() {
-        return Err(anyhow!(
-            "ハッシュ化したパスワードから、ハッシュ化ラウンド数を取得できません。 "
-        ));
-    };
-    let round_pos = round_pos.unwrap();
-    let round = &password[start..start + round_pos];
-    let round = round.parse::<u32>();
-    if round.is_err() {
-        return Err(anyhow!(
-            "ハッシュ化したパスワードから取得したハッシュ化ラウンド数を数値に変換できません。"
+            "ハッシュ化したパスワードから、アルゴリズムを取得できません。"
         ));
     }
-    start += round_pos + 1;
-    // ソルトの文字数の記録の終了を示す'$'の位置を検索
-    let len_pos = password[start..].find('$');
-    if len_pos.is_none() {
+    // ハッシュ化ラウンド数を取得
+    let round = segments.next().ok_or_else(|| {
+        anyhow!("ハッシュ化したパスワードから、ハッシュ化ラウンド数を取得できません。")
+    })?;
+    let round = round.parse::<u32>().map_err(|_| {
+        anyhow!(
+            "ハッシュ化したパスワードから取得したハッシュ化ラウンド数({})を数値に変換できません。",
+            round
+        )
+    })?;
+    // ソルトの文字数を取得
+    let len = segments
+        .next()
+        .ok_or_else(|| anyhow!("ハッシュ化したパスワードから、ソルトの文字数を取得できません。"))?;
+    let len = len.parse::<usize>().map_err(|_| {
+        anyhow!(
+            "ハッシュ化したパスワードから取得したソルトの文字数({})を数値に変換できません。",
+            len
+        )
+    })?;
+    // ソルトとパスワードをハッシュ化した結果を、最後の`$`で分割して取得
+    let (rest, hashed) = segments
+        .next()
+        .and_then(|rest| rest.rsplit_once('$'))
+        .ok_or_else(|| {
+            anyhow!("ハッシュ化したパスワードから、ソルトとハッシュ値を取得できません。")
+        })?;
+    // ソルト文字数と一致しない場合は、ペッパーのバージョンとソルトに分割
+    let (pepper_version, sault) = match rest.split_once('$') {
+        Some((version, sault)) if rest.chars().count() != len => match version.parse::<u32>() {
+            Ok(version) => (Some(version), sault),
+            Err(_) => (None, rest),
+        },
+        _ => (None, rest),
+    };
+    let sault_len = sault.chars().count();
+    if sault_len != len {
         return Err(anyhow!(
-            "ハッシュ化したパスワードから、ソルトの文字数を取得できません。 "
+            "ハッシュ化したパスワードのソルトの文字数({})が、記録されているソルトの文字数({})と一致しません。",
+            sault_len,
+            len
         ));
     }
-    let len_pos = len_pos.unwrap();
-    let len = &password[start..start + len_pos];
-    let len = len.parse::<usize>();
-    if len.is_err() {
+    if hashed.is_empty() {
         return Err(anyhow!(
-            "ハッシュ化したパスワードから取得したソルトの文字数を数値に変換できません。"
+            "ハッシュ化したパスワードから、ハッシュ値を取得できません。"
         ));
     }
-    let len = len.unwrap();
-    start += len_pos + 1;
-    // ソルトを取得
-    let sault = &password[start..start + len];
-    start += len + 1;
-    // パスワードをハッシュ化した結果を取得
-    let hashed = &password[start..];
 
     Ok((
         algo.to_owned(),
-        round.unwrap(),
+        round,
         len,
+        pepper_version,
         sault.to_owned(),
         hashed.to_owned(),
     ))
@@ -316,7 +553,95 @@ mod decode_password_test {
         assert_eq!(result.as_ref().unwrap().0, algo);
         assert_eq!(result.as_ref().unwrap().1, round);
         assert_eq!(result.as_ref().unwrap().2, len);
-        assert_eq!(result.as_ref().unwrap().3, sault);
-        assert_eq!(result.as_ref().unwrap().4, hashed);
+        assert_eq!(result.as_ref().unwrap().3, None);
+        assert_eq!(result.as_ref().unwrap().4, sault);
+        assert_eq!(result.as_ref().unwrap().5, hashed);
+    }
+
+    /// ペッパーのバージョンを記録したハッシュ化したパスワードをデコードできることを確認する。
+    #[test]
+    fn test_decode_password_with_pepper_version() {
+        let (algo, round, len, version, sault, hashed) =
+            decode_password("SHA-256$10$5$2$a$b$c$0123abcd").unwrap();
+        assert_eq!(algo, "SHA-256");
+        assert_eq!(round, 10);
+        assert_eq!(len, 5);
+        assert_eq!(version, Some(2));
+        assert_eq!(sault, "a$b$c");
+        assert_eq!(hashed, "0123abcd");
+        // ソルト文字数と一致する場合は、数字で始まるソルトをペッパーのバージョンと解釈しない
+        let (_, _, _, version, sault, _) = decode_password("SHA-256$10$5$2$a$b$0123abcd").unwrap();
+        assert_eq!(version, None);
+        assert_eq!(sault, "2$a$b");
+        for password in [
+            "SHA-256$10$5$x$a$b$c$0123abcd",
+            "SHA-256$10$5$2$a$b$c$d$0123abcd",
+        ] {
+            assert!(decode_password(password).is_err(), "{}", password);
+        }
+    }
+
+    /// ソルトに`$`が含まれている場合でも、ハッシュ化したパスワードをデコードできることを確認する。
+    #[test]
+    fn test_decode_password_sault_with_dollar() {
+        let password = "SHA-256$10$5$a$b$c$0123abcd";
+        let (algo, round, len, version, sault, hashed) = decode_password(password).unwrap();
+        assert_eq!(algo, "SHA-256");
+        assert_eq!(round, 10);
+        assert_eq!(len, 5);
+        assert_eq!(version, None);
+        assert_eq!(sault, "a$b$c");
+        assert_eq!(hashed, "0123abcd");
+    }
+
+    /// 不正な書式のハッシュ化したパスワードをデコードしたときに、パニックせずにエラーを返却することを確認する。
+    #[test]
+    fn test_decode_password_malformed() {
+        for password in [
+            "",
+            "$",
+            "SHA-256",
+            "SHA-256$",
+            "$10$4$abcd$0123",
+            "SHA-256$10",
+            "SHA-256$10$",
+            "SHA-256$10$4",
+            "SHA-256$10$4$",
+            "SHA-256$10$4$abcd",
+            "SHA-256$10$4$abcd$",
+            "SHA-256$ten$4$abcd$0123",
+            "SHA-256$-1$4$abcd$0123",
+            "SHA-256$99999999999$4$abcd$0123",
+            "SHA-256$10$four$abcd$0123",
+            "SHA-256$10$-4$abcd$0123",
+            "SHA-256$10$99999999999999999999999$abcd$0123",
+            // ソルトの文字数が実際のソルトより長い、または短い
+            "SHA-256$10$100$abcd$0123",
+            "SHA-256$10$3$abcd$0123",
+            // マルチバイト文字を含むソルト
+            "SHA-256$10$2$あいう$0123",
+            "SHA-256$10$9$あいう$0123",
+        ] {
+            assert!(decode_password(password).is_err(), "{}", password);
+        }
+        assert!(decode_password("SHA-256$10$3$あいう$0123").is_ok());
+    }
+
+    /// 正しい書式のハッシュ化したパスワードを途中で切り詰めたり、文字を追加したりしても、パニックしないことを確認する。
+    #[test]
+    fn test_decode_password_truncated_and_overlong() {
+        let hasher = PasswordHasherImpl::new(test_config("SHA-256", 1, 32));
+        let password = format!("{}あ", hasher.hash("01abCD#$").unwrap());
+        for (end, _) in password.char_indices() {
+            let _ = decode_password(&password[..end]);
+        }
+        for suffix in ["$", "$$", "あ", "$あ", "\0"] {
+            let _ = decode_password(&format!("{}{}", password, suffix));
+        }
+        // ソルトとハッシュ値の区切りより前で切り詰めた場合はエラー
+        let sault_end = password.rfind('$').unwrap();
+        for (end, _) in password[..sault_end].char_indices() {
+            assert!(decode_password(&password[..end]).is_err(), "{}", end);
+        }
     }
 }
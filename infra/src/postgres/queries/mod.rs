@@ -1,48 +1,185 @@
+use anyhow::Context;
 use async_trait::async_trait;
 
 use derive_new::new;
 use sea_orm::{
-    prelude::DateTimeWithTimeZone, ColumnTrait, DatabaseTransaction, EntityTrait, FromQueryResult,
-    JoinType, QueryFilter, QuerySelect, RelationTrait,
+    prelude::DateTimeWithTimeZone,
+    sea_query::{extension::postgres::PgExpr, Expr, Func, LikeExpr, SimpleExpr},
+    ColumnTrait, Condition, DatabaseTransaction, EntityTrait, FromQueryResult, JoinType,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Select, SelectTwo,
 };
 
-use super::schema::prelude::Accounts;
+use super::repositories::{
+    accounts::{joined_model_to_account, model_to_account},
+    auth::db_to_model,
+};
+use super::schema::prelude::{Accounts, JwtTokens, Prefectures};
 use super::schema::{accounts, jwt_tokens, prefectures};
 use domains::models::{
-    accounts::{
-        optional_phone_number, Account, AccountId, AccountName, FixedMobileNumbers, HashedPassword,
-    },
-    auth::{JwtToken, JwtTokenWithExpiredAt, JwtTokens, JwtTokensId},
-    common::{Address, AddressDetails, EmailAddress, PostalCode, Prefecture},
+    accounts::{Account, AccountId},
+    common::local_now,
+};
+use usecases::queries::{
+    AccountQueryService, AccountSearch, AccountTokens, CreatedAtRange, Page, Pagination,
+    PrefectureStats, PrefectureStatsQueryService,
 };
-use usecases::queries::{AccountQueryService, AccountTokens};
 
 #[derive(new)]
 pub struct PgAccountQueryService<'a> {
     txn: &'a DatabaseTransaction,
 }
 
+#[derive(new)]
+pub struct PgPrefectureStatsQueryService<'a> {
+    txn: &'a DatabaseTransaction,
+}
+
 #[derive(Debug, FromQueryResult)]
-struct SelectResult {
-    id: String,
-    email: String,
+struct PrefectureStatsResult {
+    code: i16,
     name: String,
-    password: String,
-    is_active: bool,
-    fixed_number: Option<String>,
-    mobile_number: Option<String>,
-    postal_code: String,
-    prefecture_code: i16,
-    address_details: String,
-    logged_in_at: Option<DateTimeWithTimeZone>,
-    created_at: DateTimeWithTimeZone,
-    updated_at: DateTimeWithTimeZone,
-    prefecture_name: String,
-    tokens_id: String,
-    access: Option<String>,
-    access_expired_at: Option<DateTimeWithTimeZone>,
-    refresh: Option<String>,
-    refresh_expired_at: Option<DateTimeWithTimeZone>,
+    account_count: i64,
+    active_count: i64,
+}
+
+/// 都道府県に住所を登録しているアカウントを、登録日時順に取得するクエリ。
+///
+/// 登録日時の範囲が指定された場合は、`accounts_prefecture_code_created_at_index`インデックスを使用できるように、
+/// 都道府県コードと登録日時で絞り込む。
+fn accounts_by_prefecture_select(
+    prefecture_code: u8,
+    created_at: &CreatedAtRange,
+) -> Select<Accounts> {
+    let select =
+        Accounts::find().filter(accounts::Column::PrefectureCode.eq(prefecture_code as i16));
+    let select = match (created_at.created_from, created_at.created_to) {
+        (Some(from), Some(to)) => select.filter(accounts::Column::CreatedAt.between(from, to)),
+        (Some(from), None) => select.filter(accounts::Column::CreatedAt.gte(from)),
+        (None, Some(to)) => select.filter(accounts::Column::CreatedAt.lte(to)),
+        (None, None) => select,
+    };
+    select
+        .order_by_asc(accounts::Column::CreatedAt)
+        .order_by_asc(accounts::Column::Id)
+}
+
+/// LIKE演算子のパターンで、ワイルドカードをエスケープする文字。
+const LIKE_ESCAPE: char = '\\';
+
+/// 指定された文字列を含む値に一致する、LIKE演算子のパターンを返却する。
+///
+/// 文字列に含まれるワイルドカード(`%`と`_`)は、ワイルドカードとして扱わずに、その文字に一致させる。
+///
+/// # Arguments
+///
+/// * `text` - 値に含まれる文字列。
+///
+/// # Returns
+///
+/// LIKE演算子のパターン。
+fn contains_pattern(text: &str) -> LikeExpr {
+    let mut pattern = String::from("%");
+    for ch in text.chars() {
+        if matches!(ch, '%' | '_' | LIKE_ESCAPE) {
+            pattern.push(LIKE_ESCAPE);
+        }
+        pattern.push(ch);
+    }
+    pattern.push('%');
+
+    LikeExpr::new(pattern).escape(LIKE_ESCAPE)
+}
+
+/// 検索条件に一致するアカウントを、登録日時順に取得するクエリ。
+///
+/// アカウント名とEメールアドレスは、前方一致ではない`ILIKE`で検索するため、B-treeインデックスを使用できない。
+/// アカウントが多い場合は、`pg_trgm`拡張のトライグラムインデックス(GIN)を`name`列と`email`列に作成すること。
+fn accounts_search_select(search: &AccountSearch) -> SelectTwo<Accounts, Prefectures> {
+    let mut select = Accounts::find().find_also_related(Prefectures);
+    if let Some(text) = search.text() {
+        select = select.filter(
+            Condition::any()
+                .add(Expr::col((Accounts, accounts::Column::Name)).ilike(contains_pattern(text)))
+                .add(Expr::col((Accounts, accounts::Column::Email)).ilike(contains_pattern(text))),
+        );
+    }
+    if let Some(prefecture_code) = search.prefecture_code {
+        select = select.filter(accounts::Column::PrefectureCode.eq(prefecture_code as i16));
+    }
+    if let Some(is_active) = search.is_active {
+        select = select.filter(accounts::Column::IsActive.eq(is_active));
+    }
+    select
+        .order_by_asc(accounts::Column::CreatedAt)
+        .order_by_asc(accounts::Column::Id)
+}
+
+/// アカウントIDを指定して、アカウントと都道府県を取得するクエリ。
+///
+/// # Arguments
+///
+/// * `id` - アカウントID。
+///
+/// # Returns
+///
+/// アカウントと都道府県を取得するクエリ。
+fn account_select(id: &AccountId) -> SelectTwo<Accounts, Prefectures> {
+    Accounts::find()
+        .find_also_related(Prefectures)
+        .filter(accounts::Column::Id.eq(id.to_string()))
+}
+
+/// アカウントに関連付けられた、有効期限が切れていないトークンを、最も新しく発行したものから順に取得するクエリ。
+///
+/// トークンを発行するたびに行が追加されるため、アカウントに複数のトークンが関連付けられている場合がある。
+/// リフレッシュトークンの有効期限が切れていないトークンを、アクセストークンの有効期限が遅い順に並べる。
+///
+/// # Arguments
+///
+/// * `id` - アカウントID。
+/// * `now` - 現在日時。
+///
+/// # Returns
+///
+/// トークンを取得するクエリ。
+fn latest_tokens_select(id: &AccountId, now: DateTimeWithTimeZone) -> Select<JwtTokens> {
+    JwtTokens::find()
+        .filter(jwt_tokens::Column::AccountId.eq(id.to_string()))
+        .filter(jwt_tokens::Column::RefreshExpiredAt.gt(now))
+        .order_by_desc(jwt_tokens::Column::AccessExpiredAt)
+        .order_by_desc(jwt_tokens::Column::Id)
+}
+
+impl PgAccountQueryService<'_> {
+    /// クエリを実行してアカウントを取得した後で、アカウントの最新のトークンを取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `select` - `account_select`で構築したクエリ。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: アカウントが見つかった場合はアカウントとトークン。アカウントが見つからなかった場合は`None`。
+    /// * `Err`: エラー。
+    async fn find_account_tokens(
+        &self,
+        select: SelectTwo<Accounts, Prefectures>,
+    ) -> anyhow::Result<Option<AccountTokens>> {
+        let (account, prefecture) = match select.one(self.txn).await? {
+            Some(result) => result,
+            None => return Ok(None),
+        };
+        let account = joined_model_to_account(&account, prefecture.as_ref())?;
+        let tokens = latest_tokens_select(&account.id(), local_now(None))
+            .one(self.txn)
+            .await?
+            .map(|tokens| db_to_model(&tokens))
+            .transpose()?;
+
+        Ok(Some(AccountTokens { account, tokens }))
+    }
 }
 
 #[async_trait]
@@ -51,56 +188,411 @@ impl AccountQueryService for PgAccountQueryService<'_> {
         &self,
         id: AccountId,
     ) -> anyhow::Result<Option<AccountTokens>> {
-        let select = Accounts::find()
-            .join(JoinType::InnerJoin, accounts::Relation::Prefectures.def())
-            .join(JoinType::LeftJoin, accounts::Relation::JwtTokens.def())
-            .column_as(prefectures::Column::Name, "prefecture_name")
-            .column_as(jwt_tokens::Column::Id, "tokens_id")
-            .column(jwt_tokens::Column::Access)
-            .column(jwt_tokens::Column::AccessExpiredAt)
-            .column(jwt_tokens::Column::Refresh)
-            .column(jwt_tokens::Column::RefreshExpiredAt)
-            .filter(accounts::Column::Id.eq(id.value.to_string()));
-        let result = select.into_model::<SelectResult>().one(self.txn).await?;
-        if result.is_none() {
-            return Ok(None);
-        }
-        let result = result.unwrap();
-        let account_id = AccountId::try_from(result.id.as_str()).unwrap();
-        let phone_numbers = FixedMobileNumbers::new(
-            optional_phone_number(result.fixed_number.as_deref()).unwrap(),
-            optional_phone_number(result.mobile_number.as_deref()).unwrap(),
+        let select = account_select(&id).filter(accounts::Column::IsActive.eq(true));
+        self.find_account_tokens(select).await
+    }
+
+    async fn find_account_by_id(&self, id: AccountId) -> anyhow::Result<Option<AccountTokens>> {
+        self.find_account_tokens(account_select(&id)).await
+    }
+
+    async fn list_by_prefecture(
+        &self,
+        prefecture_code: u8,
+        created_at: CreatedAtRange,
+        pagination: Pagination,
+    ) -> anyhow::Result<Page<Account>> {
+        let prefecture = Prefectures::find_by_id(prefecture_code as i16)
+            .one(self.txn)
+            .await?;
+        let prefecture = match prefecture {
+            Some(prefecture) => prefecture,
+            None => {
+                return Ok(Page {
+                    items: vec![],
+                    page: pagination.page,
+                    per_page: pagination.per_page,
+                    total: 0,
+                })
+            }
+        };
+        let select = accounts_by_prefecture_select(prefecture_code, &created_at);
+        let total = select.clone().count(self.txn).await?;
+        let models = select
+            .offset(pagination.offset())
+            .limit(pagination.per_page)
+            .all(self.txn)
+            .await?;
+        let items = models
+            .iter()
+            .map(|model| model_to_account(model, &prefecture))
+            .collect::<anyhow::Result<Vec<Account>>>()?;
+
+        Ok(Page {
+            items,
+            page: pagination.page,
+            per_page: pagination.per_page,
+            total,
+        })
+    }
+
+    async fn search(
+        &self,
+        search: AccountSearch,
+        pagination: Pagination,
+    ) -> anyhow::Result<Page<Account>> {
+        let select = accounts_search_select(&search);
+        let total = select.clone().count(self.txn).await?;
+        let models = select
+            .offset(pagination.offset())
+            .limit(pagination.per_page)
+            .all(self.txn)
+            .await?;
+        let items = models
+            .iter()
+            .map(|(account, prefecture)| joined_model_to_account(account, prefecture.as_ref()))
+            .collect::<anyhow::Result<Vec<Account>>>()?;
+
+        Ok(Page {
+            items,
+            page: pagination.page,
+            per_page: pagination.per_page,
+            total,
+        })
+    }
+}
+
+/// 都道府県別のアカウント数とアクティブなアカウント数を取得するクエリを構築する。
+///
+/// # Returns
+///
+/// 都道府県コード順に都道府県別アカウント統計を取得するクエリ。
+fn prefecture_stats_select() -> Select<Prefectures> {
+    // アクティブなアカウントのみを数えるため、非アクティブなアカウントをNULLにしてCOUNTする
+    let active_id = Expr::case(
+        Expr::col((accounts::Entity, accounts::Column::IsActive)).eq(true),
+        Expr::col((accounts::Entity, accounts::Column::Id)),
+    );
+    Prefectures::find()
+        .select_only()
+        .column(prefectures::Column::Code)
+        .column(prefectures::Column::Name)
+        .column_as(
+            Expr::col((accounts::Entity, accounts::Column::Id)).count(),
+            "account_count",
         )
-        .unwrap();
-        let prefecture = Prefecture::new(result.prefecture_code as u8, &result.prefecture_name);
-        let address_details = AddressDetails::new(&result.address_details).unwrap();
-        let account = Account::new_unchecked(
-            account_id.clone(),
-            EmailAddress::new(&result.email).unwrap(),
-            AccountName::new(&result.name).unwrap(),
-            HashedPassword::from_repository(&result.password),
-            result.is_active,
-            phone_numbers,
-            PostalCode::new(&result.postal_code).unwrap(),
-            Address::new(prefecture, address_details),
-            result.logged_in_at,
-            result.created_at,
-            result.updated_at,
+        .column_as(SimpleExpr::from(Func::count(active_id)), "active_count")
+        .join(JoinType::LeftJoin, prefectures::Relation::Accounts.def())
+        .group_by(prefectures::Column::Code)
+        .group_by(prefectures::Column::Name)
+        .order_by_asc(prefectures::Column::Code)
+}
+
+#[async_trait]
+impl PrefectureStatsQueryService for PgPrefectureStatsQueryService<'_> {
+    async fn account_counts(&self) -> anyhow::Result<Vec<PrefectureStats>> {
+        let results = prefecture_stats_select()
+            .into_model::<PrefectureStatsResult>()
+            .all(self.txn)
+            .await?;
+
+        results
+            .into_iter()
+            .map(|result| {
+                Ok(PrefectureStats {
+                    code: u8::try_from(result.code)
+                        .with_context(|| format!("都道府県コード({})が不正です。", result.code))?,
+                    name: result.name,
+                    account_count: result.account_count as u64,
+                    active_count: result.active_count as u64,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod prefecture_stats_query_tests {
+    use sea_orm::{DbBackend, QueryTrait};
+
+    use super::*;
+
+    /// アカウントが登録されていない都道府県も含めて、都道府県コード順に集計するクエリを構築することを確認する。
+    #[test]
+    fn test_prefecture_stats_select() {
+        let sql = prefecture_stats_select()
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(sql.contains(r#"LEFT JOIN "accounts""#), "{}", sql);
+        assert!(
+            sql.contains(r#"COUNT("accounts"."id") AS "account_count""#),
+            "{}",
+            sql
         );
-        let mut tokens: Option<JwtTokens> = None;
-        if result.access.is_some() {
-            let tokens_id = JwtTokensId::try_from(result.tokens_id.as_str()).unwrap();
-            let access = JwtTokenWithExpiredAt {
-                token: JwtToken::new(&result.access.unwrap()).unwrap(),
-                expired_at: result.access_expired_at.unwrap(),
-            };
-            let refresh = JwtTokenWithExpiredAt {
-                token: JwtToken::new(&result.refresh.unwrap()).unwrap(),
-                expired_at: result.refresh_expired_at.unwrap(),
+        assert!(sql.contains(r#""is_active" = TRUE"#), "{}", sql);
+        assert!(sql.contains(r#"END)) AS "active_count""#), "{}", sql);
+        assert!(
+            sql.contains(r#"GROUP BY "prefectures"."code", "prefectures"."name""#),
+            "{}",
+            sql
+        );
+        assert!(
+            sql.ends_with(r#"ORDER BY "prefectures"."code" ASC"#),
+            "{}",
+            sql
+        );
+    }
+}
+
+#[cfg(test)]
+mod accounts_by_prefecture_query_tests {
+    use sea_orm::{DbBackend, QueryTrait};
+
+    use super::*;
+
+    /// 都道府県コードで絞り込み、登録日時順にアカウントを取得するクエリを構築することを確認する。
+    #[test]
+    fn test_accounts_by_prefecture_select() {
+        let pagination = Pagination {
+            page: 3,
+            per_page: 10,
+        };
+        let sql = accounts_by_prefecture_select(13, &CreatedAtRange::default())
+            .offset(pagination.offset())
+            .limit(pagination.per_page)
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(
+            sql.contains(r#"WHERE "accounts"."prefecture_code" = 13"#),
+            "{}",
+            sql
+        );
+        assert!(
+            sql.contains(r#"ORDER BY "accounts"."created_at" ASC, "accounts"."id" ASC"#),
+            "{}",
+            sql
+        );
+        assert!(sql.ends_with("LIMIT 10 OFFSET 20"), "{}", sql);
+    }
+    /// 登録日時の範囲を指定した場合に、登録日時で絞り込むクエリを構築することを確認する。
+    #[test]
+    fn test_accounts_by_prefecture_select_created_at() {
+        let from = DateTimeWithTimeZone::parse_from_rfc3339("2022-04-01T00:00:00+09:00").unwrap();
+        let to = DateTimeWithTimeZone::parse_from_rfc3339("2022-04-30T23:59:59+09:00").unwrap();
+        for (created_from, created_to, expected) in [
+            (
+                Some(from),
+                Some(to),
+                r#""accounts"."created_at" BETWEEN '2022-04-01 00:00:00 +09:00' AND '2022-04-30 23:59:59 +09:00'"#,
+            ),
+            (
+                Some(from),
+                None,
+                r#""accounts"."created_at" >= '2022-04-01 00:00:00 +09:00'"#,
+            ),
+            (
+                None,
+                Some(to),
+                r#""accounts"."created_at" <= '2022-04-30 23:59:59 +09:00'"#,
+            ),
+        ] {
+            let range = CreatedAtRange {
+                created_from,
+                created_to,
             };
-            tokens = Some(JwtTokens::new(tokens_id, account_id, access, refresh));
+            let sql = accounts_by_prefecture_select(13, &range)
+                .build(DbBackend::Postgres)
+                .to_string();
+            assert!(
+                sql.contains(r#"WHERE "accounts"."prefecture_code" = 13"#),
+                "{}",
+                sql
+            );
+            assert!(sql.contains(expected), "{}", sql);
         }
+    }
+}
 
-        Ok(Some(AccountTokens { account, tokens }))
+#[cfg(test)]
+mod account_search_query_tests {
+    use std::collections::BTreeMap;
+
+    use sea_orm::{DatabaseBackend, DbBackend, MockDatabase, QueryTrait, TransactionTrait, Value};
+
+    use super::*;
+
+    fn search(q: Option<&str>) -> AccountSearch {
+        AccountSearch {
+            q: q.map(str::to_owned),
+            ..Default::default()
+        }
+    }
+
+    /// 検索する文字列を指定した場合に、アカウント名とEメールアドレスのいずれかに一致するクエリを構築することを確認する。
+    #[test]
+    fn test_accounts_search_select_or_condition() {
+        let sql = accounts_search_select(&search(Some("Foo")))
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(
+            sql.contains(
+                r#"WHERE ("accounts"."name" ILIKE ('%Foo%' ESCAPE E'\\')) OR ("accounts"."email" ILIKE ('%Foo%' ESCAPE E'\\'))"#
+            ),
+            "{}",
+            sql
+        );
+        assert!(
+            sql.contains(r#"ORDER BY "accounts"."created_at" ASC, "accounts"."id" ASC"#),
+            "{}",
+            sql
+        );
+    }
+
+    /// 検索する文字列とその他の検索条件を指定した場合に、すべての条件をANDで結合することを確認する。
+    #[test]
+    fn test_accounts_search_select_with_filters() {
+        let search = AccountSearch {
+            prefecture_code: Some(13),
+            is_active: Some(true),
+            ..search(Some("foo"))
+        };
+        let sql = accounts_search_select(&search)
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(
+            sql.contains(
+                r#"WHERE (("accounts"."name" ILIKE ('%foo%' ESCAPE E'\\')) OR ("accounts"."email" ILIKE ('%foo%' ESCAPE E'\\'))) AND "accounts"."prefecture_code" = 13 AND "accounts"."is_active" = TRUE"#
+            ),
+            "{}",
+            sql
+        );
+    }
+
+    /// 検索する文字列が空の場合に、文字列で絞り込まないことを確認する。
+    #[test]
+    fn test_accounts_search_select_empty_text() {
+        for q in [None, Some(""), Some("  ")] {
+            let sql = accounts_search_select(&search(q))
+                .build(DbBackend::Postgres)
+                .to_string();
+            assert!(!sql.contains("WHERE"), "{}", sql);
+        }
+    }
+
+    /// 検索する文字列に含まれるワイルドカードをエスケープすることを確認する。
+    #[test]
+    fn test_contains_pattern() {
+        let sql = accounts_search_select(&search(Some(r"50%_off\")))
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(
+            sql.contains(r#""accounts"."name" ILIKE (E'%50\\%\\_off\\\\%' ESCAPE E'\\')"#),
+            "{}",
+            sql
+        );
+    }
+
+    /// 検索を実行した場合に、アカウント名とEメールアドレスのいずれかに一致する条件で、
+    /// アカウントの総数とページのアカウントを取得することを確認する。
+    #[tokio::test]
+    async fn test_search() {
+        let count: BTreeMap<&str, Value> = [("num_items", Value::from(0i64))].into();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![count]])
+            .append_query_results([Vec::<BTreeMap<&str, Value>>::new()])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let page = PgAccountQueryService::new(&txn)
+            .search(search(Some("foo")), Pagination::default())
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert!(page.items.is_empty());
+        assert_eq!(page.total, 0);
+        let log = format!("{:?}", conn.into_transaction_log());
+        let condition = r#"WHERE (\"accounts\".\"name\" ILIKE ($1 ESCAPE E'\\\\')) OR (\"accounts\".\"email\" ILIKE ($2 ESCAPE E'\\\\'))"#;
+        assert_eq!(log.matches(condition).count(), 2, "{}", log);
+        assert!(log.contains(r#"String(Some("%foo%"))"#), "{}", log);
+    }
+}
+
+#[cfg(test)]
+mod account_tokens_query_tests {
+    use sea_orm::{DatabaseBackend, DbBackend, MockDatabase, QueryTrait, TransactionTrait};
+
+    use super::*;
+
+    const ACCOUNT_ID: &str = "01FVH5SD9H9Y4HNZ4J1Q3E3PFD";
+
+    fn account_id() -> AccountId {
+        AccountId::try_from(ACCOUNT_ID).unwrap()
+    }
+
+    /// アクティブなアカウントを取得するクエリが、アカウントIDとアクティブフラグで絞り込むことを確認する。
+    #[tokio::test]
+    async fn test_find_active_account_by_id() {
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([Vec::<accounts::Model>::new()])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let result = PgAccountQueryService::new(&txn)
+            .find_active_account_by_id(account_id())
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert!(result.is_none());
+        let log = format!("{:?}", conn.into_transaction_log());
+        assert!(
+            log.contains(r#"WHERE \"accounts\".\"id\" = $1 AND \"accounts\".\"is_active\" = $2"#),
+            "{}",
+            log
+        );
+        assert!(
+            log.contains(&format!(r#"String(Some("{}"))"#, ACCOUNT_ID)),
+            "{}",
+            log
+        );
+        assert!(log.contains("Bool(Some(true))"), "{}", log);
+    }
+
+    /// アカウントを取得するクエリが、アクティブフラグで絞り込まないことを確認する。
+    #[tokio::test]
+    async fn test_find_account_by_id() {
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([Vec::<accounts::Model>::new()])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let result = PgAccountQueryService::new(&txn)
+            .find_account_by_id(account_id())
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert!(result.is_none());
+        let log = format!("{:?}", conn.into_transaction_log());
+        assert!(log.contains(r#"WHERE \"accounts\".\"id\" = $1"#), "{}", log);
+        assert!(!log.contains("is_active\\\" ="), "{}", log);
+    }
+
+    /// 最新のトークンを取得するクエリが、有効期限が切れていないトークンを、アクセストークンの有効期限が遅い順に並べることを確認する。
+    #[test]
+    fn test_latest_tokens_select() {
+        let sql = latest_tokens_select(&account_id(), local_now(None))
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(
+            sql.contains(&format!(
+                r#"WHERE "jwt_tokens"."account_id" = '{}' AND "jwt_tokens"."refresh_expired_at" > "#,
+                ACCOUNT_ID
+            )),
+            "{}",
+            sql
+        );
+        assert!(
+            sql.ends_with(
+                r#"ORDER BY "jwt_tokens"."access_expired_at" DESC, "jwt_tokens"."id" DESC"#
+            ),
+            "{}",
+            sql
+        );
     }
 }
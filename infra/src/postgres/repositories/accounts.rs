@@ -1,12 +1,20 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context};
 use async_trait::async_trait;
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone,
+    sea_query::{Expr, Func},
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseTransaction, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select, Set, UpdateMany,
+};
 
 use domains::models::{
     accounts::{
-        optional_phone_number, optional_phone_number_string, Account, AccountId, AccountName,
-        FixedMobileNumbers, HashedPassword,
+        optional_phone_number, optional_phone_number_string, Account, AccountBuilder, AccountId,
+        AccountName, FixedMobileNumbers, HashedPassword, Role,
     },
-    common::{Address, AddressDetails, EmailAddress, PostalCode, Prefecture},
+    common::{Address, AddressDetails, EmailAddress, EmailNormalization, PostalCode, Prefecture},
 };
 use domains::repositories::accounts::AccountRepository;
 
@@ -14,10 +22,10 @@ use super::super::schema::{
     accounts, prefectures,
     prelude::{Accounts, Prefectures},
 };
-use super::common::PgRepository;
+use super::common::{to_local, PgRepository};
 
 /// アカウントリポジトリ型
-pub type PgAccountRepository<'a> = PgRepository<'a, Account>;
+pub type PgAccountRepository<'a, C = DatabaseTransaction> = PgRepository<'a, Account, C>;
 
 /// アカウントモデルと都道府県モデルからアカウントを構築して返却する。
 ///
@@ -28,29 +36,114 @@ pub type PgAccountRepository<'a> = PgRepository<'a, Account>;
 ///
 /// # Returns
 ///
-/// * アカウント。
-fn model_to_account(account: &accounts::Model, prefecture: &prefectures::Model) -> Account {
-    let phone_numbers = FixedMobileNumbers::new(
-        optional_phone_number(account.fixed_number.as_deref()).unwrap(),
-        optional_phone_number(account.mobile_number.as_deref()).unwrap(),
-    )
-    .unwrap();
-    let prefecture = Prefecture::new(prefecture.code as u8, &prefecture.name);
-    let address_details = AddressDetails::new(&account.address_details).unwrap();
-
-    Account::new_unchecked(
-        AccountId::try_from(account.id.as_str()).unwrap(),
-        EmailAddress::new(&account.email).unwrap(),
-        AccountName::new(&account.name).unwrap(),
-        HashedPassword::from_repository(&account.password),
-        account.is_active,
-        phone_numbers,
-        PostalCode::new(&account.postal_code).unwrap(),
-        Address::new(prefecture, address_details),
-        account.logged_in_at,
-        account.created_at,
-        account.updated_at,
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: アカウント。
+/// * `Err`: アカウントモデルに不正な値が記録されていた場合のエラー。
+pub(crate) fn model_to_account(
+    account: &accounts::Model,
+    prefecture: &prefectures::Model,
+) -> anyhow::Result<Account> {
+    let context = |column: &str| {
+        format!(
+            "アカウント({})の{}列に記録されている値が不正です。",
+            account.id, column
+        )
+    };
+    let phone_numbers = FixedMobileNumbers::new_lenient(
+        optional_phone_number(account.fixed_number.as_deref())
+            .with_context(|| context("fixed_number"))?,
+        optional_phone_number(account.mobile_number.as_deref())
+            .with_context(|| context("mobile_number"))?,
     )
+    .with_context(|| context("fixed_number/mobile_number"))?;
+    let prefecture =
+        Prefecture::try_from(prefecture.clone()).with_context(|| context("prefecture_code"))?;
+    let address_details = AddressDetails::new(&account.address_details)
+        .with_context(|| context("address_details"))?;
+
+    AccountBuilder::new()
+        .id(AccountId::try_from(account.id.as_str()).with_context(|| context("id"))?)
+        .email(EmailAddress::new(&account.email).with_context(|| context("email"))?)
+        .name(AccountName::new(&account.name).with_context(|| context("name"))?)
+        .password(HashedPassword::from_repository(&account.password))
+        .is_active(account.is_active)
+        .role(Role::from_str(&account.role).with_context(|| context("role"))?)
+        .phone_numbers(phone_numbers)
+        .postal_code(PostalCode::new(&account.postal_code).with_context(|| context("postal_code"))?)
+        .address(Address::new(prefecture, address_details))
+        .logged_in_at(account.logged_in_at.map(to_local))
+        .created_at(to_local(account.created_at))
+        .updated_at(to_local(account.updated_at))
+        .updated_by(
+            account
+                .updated_by
+                .as_deref()
+                .map(AccountId::try_from)
+                .transpose()
+                .with_context(|| context("updated_by"))?,
+        )
+        .build()
+}
+
+/// 都道府県を結合したアカウントモデルからアカウントを構築して返却する。
+///
+/// # Arguments
+///
+/// * `account` - アカウントモデル。
+/// * `prefecture` - アカウントモデルに結合した都道府県モデル。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: アカウント。
+/// * `Err`: アカウントの都道府県コードと一致する都道府県が存在しない場合や、アカウントモデルに不正な値が記録されていた場合のエラー。
+pub(crate) fn joined_model_to_account(
+    account: &accounts::Model,
+    prefecture: Option<&prefectures::Model>,
+) -> anyhow::Result<Account> {
+    match prefecture {
+        Some(prefecture) => model_to_account(account, prefecture),
+        None => Err(anyhow!(
+            "アカウント({})の都道府県コード({})と一致する都道府県が見つかりません。",
+            account.id,
+            account.prefecture_code
+        )),
+    }
+}
+
+/// 都道府県を結合したアカウントモデルのリストからアカウントのリストを構築して返却する。
+///
+/// 都道府県コードと一致する都道府県が存在しないアカウントは、警告をログに出力して除外する。
+///
+/// # Arguments
+///
+/// * `models` - アカウントモデルと結合した都道府県モデルのタプルのリスト。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: アカウントのリスト。
+/// * `Err`: アカウントモデルに不正な値が記録されていた場合のエラー。
+fn joined_models_to_accounts(
+    models: &[(accounts::Model, Option<prefectures::Model>)],
+) -> anyhow::Result<Vec<Account>> {
+    let mut result = Vec::with_capacity(models.len());
+    for (account, prefecture) in models {
+        if prefecture.is_none() {
+            log::warn!(
+                "アカウント({})の都道府県コード({})と一致する都道府県が見つからないため、アカウントを除外しました。",
+                account.id,
+                account.prefecture_code
+            );
+            continue;
+        }
+        result.push(joined_model_to_account(account, prefecture.as_ref())?);
+    }
+
+    Ok(result)
 }
 
 /// アカウントをアクティブモデルに変換する。
@@ -64,11 +157,12 @@ fn model_to_account(account: &accounts::Model, prefecture: &prefectures::Model)
 /// * アカウントのアクティブモデル。
 fn account_to_active_model(account: &Account) -> accounts::ActiveModel {
     accounts::ActiveModel {
-        id: Set(account.id().value.to_string()),
+        id: Set(account.id().to_string()),
         email: Set(account.email().value()),
         name: Set(account.name().value()),
         password: Set(account.password().value()),
         is_active: Set(account.is_active()),
+        role: Set(account.role().to_string()),
         fixed_number: Set(optional_phone_number_string(
             account.phone_numbers().fixed(),
         )),
@@ -81,14 +175,36 @@ fn account_to_active_model(account: &Account) -> accounts::ActiveModel {
         logged_in_at: Set(account.logged_in_at()),
         created_at: Set(account.created_at()),
         updated_at: Set(account.updated_at()),
+        updated_by: Set(account.updated_by().map(|id| id.to_string())),
     }
 }
 
+/// 記録されているアカウントの更新日時が、指定された更新日時と一致する場合のみアカウントを更新するクエリを構築する。
+///
+/// # Arguments
+///
+/// * `account` - アカウント。
+/// * `expected_updated_at` - 記録されていることを期待するアカウントの更新日時。
+///
+/// # Returns
+///
+/// アカウントを更新するクエリ。
+fn update_if_unmodified_query(
+    account: &Account,
+    expected_updated_at: DateTimeWithTimeZone,
+) -> UpdateMany<accounts::Entity> {
+    Accounts::update_many()
+        .set(account_to_active_model(account))
+        .filter(accounts::Column::Id.eq(account.id().to_string()))
+        .filter(accounts::Column::UpdatedAt.eq(to_local(expected_updated_at)))
+}
+
 #[cfg(test)]
 mod account_model_tests {
     use super::*;
+    use chrono::Duration;
     use domains::models::common::{local_now, PhoneNumber};
-    use sea_orm::ActiveValue;
+    use sea_orm::{ActiveValue, Database, DbBackend, QueryTrait, TransactionTrait};
     use ulid::Ulid;
 
     /// アカウントモデルと都道府県モデルから、アカウントを構築できることを確認する。
@@ -104,6 +220,7 @@ mod account_model_tests {
             name: String::from("taro"),
             password: String::from("this-is-hashed-password"),
             is_active: true,
+            role: String::from("user"),
             fixed_number: Some(String::from("012-345-6789")),
             mobile_number: Some(String::from("090-1234-5678")),
             postal_code: String::from("100-0014"),
@@ -112,13 +229,15 @@ mod account_model_tests {
             logged_in_at: Some(local_now(None)),
             created_at: local_now(None),
             updated_at: local_now(None),
+            updated_by: None,
         };
-        let account = model_to_account(&a, &p);
-        assert_eq!(account.id().value.to_string(), a.id);
+        let account = model_to_account(&a, &p).unwrap();
+        assert_eq!(account.id().to_string(), a.id);
         assert_eq!(account.email().value(), a.email);
         assert_eq!(account.name().value(), a.name);
         assert_eq!(account.password().value(), a.password);
         assert_eq!(account.is_active(), a.is_active);
+        assert_eq!(account.role(), Role::User);
         assert_eq!(
             account.phone_numbers().fixed().unwrap().value(),
             a.fixed_number.unwrap()
@@ -135,6 +254,72 @@ mod account_model_tests {
         assert_eq!(account.updated_at(), a.updated_at);
     }
 
+    /// 不正な値が記録されたアカウントモデルから、アカウントを構築できないことを確認する。
+    #[test]
+    fn test_model_to_account_invalid() {
+        let p = prefectures::Model {
+            code: 13,
+            name: String::from("東京都"),
+        };
+        let a = accounts::Model {
+            id: Ulid::new().to_string(),
+            email: String::from("taro@example.com"),
+            name: String::from("taro"),
+            password: String::from("this-is-hashed-password"),
+            is_active: true,
+            role: String::from("user"),
+            fixed_number: Some(String::from("012-345-6789")),
+            mobile_number: None,
+            postal_code: String::from("100-0014"),
+            prefecture_code: p.code,
+            address_details: String::from("千代田区永田町1-7-1"),
+            logged_in_at: None,
+            created_at: local_now(None),
+            updated_at: local_now(None),
+            updated_by: None,
+        };
+        let invalid_models = vec![
+            accounts::Model {
+                id: String::from("invalid-ulid-string"),
+                ..a.clone()
+            },
+            accounts::Model {
+                email: String::from("@example.com"),
+                ..a.clone()
+            },
+            accounts::Model {
+                name: String::from("t"),
+                ..a.clone()
+            },
+            accounts::Model {
+                fixed_number: Some(String::from("invalid-number")),
+                ..a.clone()
+            },
+            accounts::Model {
+                fixed_number: None,
+                mobile_number: None,
+                ..a.clone()
+            },
+            accounts::Model {
+                postal_code: String::from("00-0000"),
+                ..a.clone()
+            },
+            accounts::Model {
+                address_details: String::from("千"),
+                ..a.clone()
+            },
+            accounts::Model {
+                role: String::from("root"),
+                ..a.clone()
+            },
+        ];
+        for model in invalid_models {
+            let result = model_to_account(&model, &p);
+            assert!(result.is_err());
+            assert!(format!("{:?}", result.unwrap_err()).contains(&model.id));
+        }
+    }
+
     /// アカウントをアクティブモデルに変換できるか確認する。
     #[test]
     fn test_account_to_active_model() {
@@ -151,32 +336,35 @@ mod account_model_tests {
         let postal_code = PostalCode::new("012-3456").unwrap();
         let pref_code = 13;
         let pref_name = "東京都";
-        let prefecture = Prefecture::new(pref_code, pref_name);
+        let prefecture = Prefecture::new(pref_code, pref_name).unwrap();
         let address_details = AddressDetails::new("新宿区西新宿2-8-1").unwrap();
         let address = Address::new(prefecture.clone(), address_details.clone());
         let logged_in_at = Some(local_now(None));
         let created_at = local_now(None);
         let updated_at = local_now(None);
         // アカウントを構築
-        let account = Account::new_unchecked(
-            AccountId::new(id),
-            email.clone(),
-            name.clone(),
-            password.clone(),
-            is_active,
-            phone_numbers.clone(),
-            postal_code.clone(),
-            address.clone(),
-            logged_in_at,
-            created_at,
-            updated_at,
-        );
+        let account = AccountBuilder::new()
+            .id(AccountId::new(id))
+            .email(email.clone())
+            .name(name.clone())
+            .password(password.clone())
+            .is_active(is_active)
+            .role(Role::Admin)
+            .phone_numbers(phone_numbers.clone())
+            .postal_code(postal_code.clone())
+            .address(address.clone())
+            .logged_in_at(logged_in_at)
+            .created_at(created_at)
+            .updated_at(updated_at)
+            .build()
+            .unwrap();
         let model = account_to_active_model(&account);
         assert_eq!(model.id, ActiveValue::set(id.to_string()));
         assert_eq!(model.email, ActiveValue::set(email.value()));
         assert_eq!(model.name, ActiveValue::set(name.value()));
         assert_eq!(model.password, ActiveValue::set(password.value()));
         assert_eq!(model.is_active, ActiveValue::set(is_active));
+        assert_eq!(model.role, ActiveValue::set(String::from("admin")));
         assert_eq!(
             model.fixed_number,
             ActiveValue::set(Some(fixed_number.value()))
@@ -195,10 +383,181 @@ mod account_model_tests {
         assert_eq!(model.created_at, ActiveValue::set(created_at));
         assert_eq!(model.updated_at, ActiveValue::set(updated_at));
     }
+
+    /// 指定された都道府県コードのアカウントモデルを構築する。
+    fn account_model(prefecture_code: i16) -> accounts::Model {
+        accounts::Model {
+            id: Ulid::new().to_string(),
+            email: String::from("taro@example.com"),
+            name: String::from("taro"),
+            password: String::from("this-is-hashed-password"),
+            is_active: true,
+            role: String::from("user"),
+            fixed_number: None,
+            mobile_number: Some(String::from("090-1234-5678")),
+            postal_code: String::from("100-0014"),
+            prefecture_code,
+            address_details: String::from("千代田区永田町1-7-1"),
+            logged_in_at: None,
+            created_at: local_now(None),
+            updated_at: local_now(None),
+            updated_by: None,
+        }
+    }
+
+    /// 都道府県が結合されていないアカウントモデルから、パニックせずにエラーを返却することを確認する。
+    #[test]
+    fn test_joined_model_to_account_orphaned() {
+        let a = account_model(99);
+        let err = joined_model_to_account(&a, None).unwrap_err().to_string();
+        assert!(err.contains(&a.id), "{}", err);
+        assert!(err.contains("99"), "{}", err);
+    }
+
+    /// 都道府県が結合されていないアカウントモデルを、パニックせずにリストから除外することを確認する。
+    #[test]
+    fn test_joined_models_to_accounts_skips_orphaned() {
+        let tokyo = prefectures::Model {
+            code: 13,
+            name: String::from("東京都"),
+        };
+        let valid = account_model(13);
+        let orphaned = account_model(99);
+        let models = vec![(valid.clone(), Some(tokyo)), (orphaned, None)];
+        let accounts = joined_models_to_accounts(&models).unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].id().to_string(), valid.id);
+    }
+
+    /// 指定された更新日時のアカウントを構築する。
+    fn account(updated_at: DateTimeWithTimeZone) -> Account {
+        AccountBuilder::new()
+            .id(AccountId::gen())
+            .email(
+                EmailAddress::new(&format!("{}@example.com", Ulid::new()).to_lowercase()).unwrap(),
+            )
+            .name(AccountName::new("foo").unwrap())
+            .password(HashedPassword::from_repository("SHA-256$1$1$a$b"))
+            .is_active(true)
+            .phone_numbers(
+                FixedMobileNumbers::new(None, Some(PhoneNumber::new("090-1234-5678").unwrap()))
+                    .unwrap(),
+            )
+            .postal_code(PostalCode::new("100-0014").unwrap())
+            .address(Address::new(
+                Prefecture::new(13, "東京都").unwrap(),
+                AddressDetails::new("千代田区永田町1-7-1").unwrap(),
+            ))
+            .logged_in_at(None)
+            .created_at(updated_at)
+            .updated_at(updated_at)
+            .build()
+            .unwrap()
+    }
+
+    /// 更新日時が一致する場合のみアカウントを更新するクエリに、アカウントIDと更新日時の条件が含まれることを確認する。
+    #[test]
+    fn test_update_if_unmodified_query() {
+        let account = account(local_now(None));
+        let expected = local_now(None) - Duration::minutes(1);
+        let sql = update_if_unmodified_query(&account, expected)
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(sql.starts_with(r#"UPDATE "accounts" SET"#), "{}", sql);
+        assert!(
+            sql.contains(&format!(r#""accounts"."id" = '{}'"#, account.id())),
+            "{}",
+            sql
+        );
+        assert!(
+            sql.contains(r#"AND "accounts"."updated_at" = '"#),
+            "{}",
+            sql
+        );
+    }
+
+    /// アカウントを分割して取得するクエリが、指定されたアカウントIDより後のアカウントをアカウントIDの昇順で取得することを確認する。
+    #[test]
+    fn test_list_paged_select() {
+        let sql = list_paged_select(None, 100)
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(!sql.contains("WHERE"), "{}", sql);
+        assert!(
+            sql.ends_with(r#"ORDER BY "accounts"."id" ASC LIMIT 100"#),
+            "{}",
+            sql
+        );
+
+        let after = AccountId::gen();
+        let sql = list_paged_select(Some(after.clone()), 100)
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(
+            sql.contains(&format!(r#"WHERE "accounts"."id" > '{}'"#, after)),
+            "{}",
+            sql
+        );
+    }
+
+    /// 他のリクエストで更新された後に、古い更新日時を指定してアカウントを更新できないことを確認する。
+    ///
+    /// マイグレーションを適用したデータベースが必要なため、環境変数`DATABASE_URL`を設定して
+    /// `cargo test -- --ignored`で実行する。登録したデータはロールバックする。
+    #[tokio::test]
+    #[ignore]
+    async fn test_update_if_unmodified_stale() {
+        let url = std::env::var("DATABASE_URL").unwrap();
+        let conn = Database::connect(url.as_str()).await.unwrap();
+        let txn = conn.begin().await.unwrap();
+        let repo = PgAccountRepository::new(&txn);
+        let inserted = repo.insert(&account(local_now(None))).await.unwrap();
+        let loaded_at = inserted.updated_at();
+        // 1つ目のリクエストは、取得したときの更新日時が記録されているため更新できる
+        let mut first = inserted.clone();
+        first.set_name(AccountName::new("first").unwrap());
+        first.set_updated_at(loaded_at + Duration::seconds(1));
+        let first = repo.update_if_unmodified(&first, loaded_at).await.unwrap();
+        assert_eq!(first.unwrap().name().value(), "first");
+        // 2つ目のリクエストは、取得したときの更新日時が古くなっているため更新できない
+        let mut second = inserted.clone();
+        second.set_name(AccountName::new("second").unwrap());
+        second.set_updated_at(loaded_at + Duration::seconds(2));
+        assert!(repo
+            .update_if_unmodified(&second, loaded_at)
+            .await
+            .unwrap()
+            .is_none());
+        let stored = repo.find_by_id(inserted.id()).await.unwrap().unwrap();
+        assert_eq!(stored.name().value(), "first");
+        txn.rollback().await.unwrap();
+    }
+}
+
+/// アカウントIDの昇順で、指定されたアカウントIDより後のアカウントを取得するクエリを返却する。
+///
+/// アカウントIDはULIDであるため、オフセットではなく最後に取得したアカウントIDを条件にして、
+/// 後のページでも索引を使用して取得する。
+///
+/// # Arguments
+///
+/// * `after` - 前回取得した最後のアカウントのアカウントID。
+/// * `limit` - 取得するアカウントの最大数。
+///
+/// # Returns
+///
+/// アカウントを取得するクエリ。
+fn list_paged_select(after: Option<AccountId>, limit: u64) -> Select<Accounts> {
+    let mut select = Accounts::find();
+    if let Some(after) = after {
+        select = select.filter(accounts::Column::Id.gt(after.to_string()));
+    }
+
+    select.order_by_asc(accounts::Column::Id).limit(limit)
 }
 
 #[async_trait]
-impl AccountRepository for PgAccountRepository<'_> {
+impl<C: ConnectionTrait> AccountRepository for PgAccountRepository<'_, C> {
     /// アカウントIDを指定して、アカウントを検索する。
     ///
     /// # Arguments
@@ -212,7 +571,7 @@ impl AccountRepository for PgAccountRepository<'_> {
     /// * `Ok`: アカウントが見つかった場合はアカウント。アカウントが見つからなかった場合は`None`。
     /// * `Err`: エラーメッセージ。
     async fn find_by_id(&self, id: AccountId) -> anyhow::Result<Option<Account>> {
-        let result = Accounts::find_by_id(id.value.to_string())
+        let result = Accounts::find_by_id(id.to_string())
             .find_also_related(Prefectures)
             .one(self.txn)
             .await?;
@@ -221,7 +580,10 @@ impl AccountRepository for PgAccountRepository<'_> {
         }
         let (account, prefecture) = result.unwrap();
 
-        Ok(Some(model_to_account(&account, &prefecture.unwrap())))
+        Ok(Some(joined_model_to_account(
+            &account,
+            prefecture.as_ref(),
+        )?))
     }
 
     /// Eメールを指定して、アカウントを検索する。
@@ -237,21 +599,28 @@ impl AccountRepository for PgAccountRepository<'_> {
     /// * `Ok`: アカウントが見つかった場合はアカウント。アカウントが見つからなかった場合は`None`。
     /// * `Err`: エラーメッセージ。
     async fn find_by_email(&self, email: EmailAddress) -> anyhow::Result<Option<Account>> {
+        // 正規化する前に記録されたEメールアドレスも検索できるように、大文字と小文字を区別せずに検索
+        // (`accounts_lower_email_key`インデックスにより、一致するアカウントは1件以下)
         let result = Accounts::find()
-            .filter(accounts::Column::Email.eq(email.value()))
+            .filter(lower_email().eq(email.value().to_lowercase()))
             .find_also_related(Prefectures)
             .one(self.txn)
             .await?;
-        if result.is_none() {
-            return Ok(None);
-        }
-        let (account, prefecture) = result.unwrap();
+        let (account, prefecture) = match result {
+            Some(joined) => joined,
+            None => return Ok(None),
+        };
 
-        Ok(Some(model_to_account(&account, &prefecture.unwrap())))
+        Ok(Some(joined_model_to_account(
+            &account,
+            prefecture.as_ref(),
+        )?))
     }
 
     /// アカウントのリストを返却する。
     ///
+    /// 都道府県コードと一致する都道府県が存在しないアカウントは、リストから除外する。
+    ///
     /// # Returns
     ///
     /// `Result`。返却される`Result`の内容は以下の通り。
@@ -264,10 +633,35 @@ impl AccountRepository for PgAccountRepository<'_> {
             .all(self.txn)
             .await?;
 
-        Ok(result
-            .iter()
-            .map(|(a, p)| model_to_account(a, p.as_ref().unwrap()))
-            .collect())
+        joined_models_to_accounts(&result)
+    }
+
+    /// アカウントIDの昇順で、指定されたアカウントIDより後のアカウントを、指定された件数まで返却する。
+    ///
+    /// 都道府県コードと一致する都道府県が存在しないアカウントは、リストから除外する。
+    ///
+    /// # Arguments
+    ///
+    /// * `after` - 前回取得した最後のアカウントのアカウントID。最初のアカウントから取得する場合は`None`。
+    /// * `limit` - 取得するアカウントの最大数。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: アカウントを格納したベクタ。
+    /// * `Err`: エラーメッセージ。
+    async fn list_paged(
+        &self,
+        after: Option<AccountId>,
+        limit: u64,
+    ) -> anyhow::Result<Vec<Account>> {
+        let result = list_paged_select(after, limit)
+            .find_also_related(Prefectures)
+            .all(self.txn)
+            .await?;
+
+        joined_models_to_accounts(&result)
     }
 
     /// アカウントを登録する。
@@ -286,7 +680,9 @@ impl AccountRepository for PgAccountRepository<'_> {
         let active_model = account_to_active_model(account);
         let _ = active_model.insert(self.txn).await?;
 
-        Ok(self.find_by_id(account.id()).await?.unwrap())
+        self.find_by_id(account.id())
+            .await?
+            .ok_or_else(|| anyhow!("登録したアカウント({})が見つかりません。", account.id()))
     }
 
     /// アカウントを更新する。
@@ -308,6 +704,34 @@ impl AccountRepository for PgAccountRepository<'_> {
         Ok(self.find_by_id(account.id()).await?.unwrap())
     }
 
+    /// 記録されているアカウントの更新日時が、指定された更新日時と一致する場合のみアカウントを更新する。
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - アカウント。
+    /// * `expected_updated_at` - 記録されていることを期待するアカウントの更新日時。
+    ///
+    /// # Result
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 更新後のアカウント。更新日時が一致しないか、アカウントが見つからない場合は`None`。
+    /// * `Err`: エラーメッセージ。
+    async fn update_if_unmodified(
+        &self,
+        account: &Account,
+        expected_updated_at: DateTimeWithTimeZone,
+    ) -> anyhow::Result<Option<Account>> {
+        let result = update_if_unmodified_query(account, expected_updated_at)
+            .exec(self.txn)
+            .await?;
+        if result.rows_affected == 0 {
+            return Ok(None);
+        }
+
+        self.find_by_id(account.id()).await
+    }
+
     /// アカウントを削除する。
     ///
     /// # Arguments
@@ -322,7 +746,7 @@ impl AccountRepository for PgAccountRepository<'_> {
     /// * `Err`: エラーメッセージ。
     async fn delete(&self, id: AccountId) -> anyhow::Result<()> {
         let _ = accounts::Entity::delete_many()
-            .filter(accounts::Column::Id.eq(id.value.to_string()))
+            .filter(accounts::Column::Id.eq(id.to_string()))
             .exec(self.txn)
             .await?;
 
@@ -335,6 +759,7 @@ impl AccountRepository for PgAccountRepository<'_> {
     ///
     /// * `id` - パスワードを変更するアカウントのアカウントID。
     /// * `password` - 新たに設定するハッシュ化したパスワード。
+    /// * `updated_by` - パスワードを変更したアカウントのアカウントID。
     ///
     /// # Returns
     ///
@@ -346,17 +771,583 @@ impl AccountRepository for PgAccountRepository<'_> {
         &self,
         id: AccountId,
         new_password: HashedPassword,
+        updated_by: Option<AccountId>,
     ) -> anyhow::Result<bool> {
-        let result = Accounts::find_by_id(id.value.to_string())
-            .one(self.txn)
-            .await?;
+        let result = Accounts::find_by_id(id.to_string()).one(self.txn).await?;
         if result.is_none() {
             return Ok(false);
         }
         let mut active_model: accounts::ActiveModel = result.unwrap().into();
         active_model.password = Set(new_password.value());
+        if let Some(updated_by) = updated_by {
+            active_model.updated_by = Set(Some(updated_by.to_string()));
+        }
         let _ = active_model.update(self.txn).await?;
 
         Ok(true)
     }
+
+    /// 正規化されていないEメールアドレスで記録されているアカウントのEメールアドレスを、正規化したEメールアドレスに更新する。
+    ///
+    /// # Arguments
+    ///
+    /// * `normalization` - Eメールアドレス正規化方針。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: Eメールアドレスを更新したアカウントの数。
+    /// * `Err`: エラー。
+    async fn normalize_email_addresses(
+        &self,
+        normalization: EmailNormalization,
+    ) -> anyhow::Result<u64> {
+        // 大文字を含むEメールアドレスのみを正規化の対象とする
+        let models = Accounts::find()
+            .filter(Expr::col((Accounts, accounts::Column::Email)).ne(lower_email()))
+            .order_by_asc(accounts::Column::Id)
+            .all(self.txn)
+            .await?;
+        let mut updated = 0;
+        for model in models {
+            // 不正なEメールアドレスが記録されている場合は、他のアカウントを正規化できるように更新しない
+            let normalized = match EmailAddress::normalized(&model.email, normalization) {
+                Ok(email) => email.value(),
+                Err(err) => {
+                    log::warn!(
+                        "アカウント({})のEメールアドレスを正規化できないため更新しません。{}",
+                        model.id,
+                        err
+                    );
+                    continue;
+                }
+            };
+            if normalized == model.email {
+                continue;
+            }
+            let duplicated = Accounts::find()
+                .filter(accounts::Column::Email.eq(normalized.as_str()))
+                .count(self.txn)
+                .await?;
+            if 0 < duplicated {
+                log::warn!(
+                    "アカウント({})のEメールアドレス({})を正規化したEメールアドレスが、他のアカウントに記録されているため更新しません。",
+                    model.id,
+                    model.email
+                );
+                continue;
+            }
+            Accounts::update_many()
+                .col_expr(accounts::Column::Email, Expr::value(normalized))
+                .filter(accounts::Column::Id.eq(model.id))
+                .exec(self.txn)
+                .await?;
+            updated += 1;
+        }
+
+        Ok(updated)
+    }
+}
+
+/// アカウントのEメールアドレスを小文字に変換する式を返却する。
+///
+/// # Returns
+///
+/// `LOWER("accounts"."email")`。
+fn lower_email() -> sea_orm::sea_query::SimpleExpr {
+    Func::lower(Expr::col((Accounts, accounts::Column::Email))).into()
+}
+
+#[cfg(test)]
+mod pg_account_repository_tests {
+    use domains::models::common::local_now;
+    use sea_orm::{
+        DatabaseBackend, MockDatabase, MockExecResult, QueryTrait, Statement, Transaction,
+        TransactionTrait, Value,
+    };
+    use ulid::Ulid;
+
+    use super::*;
+
+    /// アカウントテーブルの列名のリスト。
+    const ACCOUNT_COLUMNS: [&str; 15] = [
+        "id",
+        "email",
+        "name",
+        "password",
+        "is_active",
+        "role",
+        "fixed_number",
+        "mobile_number",
+        "postal_code",
+        "prefecture_code",
+        "address_details",
+        "logged_in_at",
+        "created_at",
+        "updated_at",
+        "updated_by",
+    ];
+
+    fn tokyo_model() -> prefectures::Model {
+        prefectures::Model {
+            code: 13,
+            name: String::from("東京都"),
+        }
+    }
+
+    fn account_model() -> accounts::Model {
+        accounts::Model {
+            id: Ulid::new().to_string(),
+            email: String::from("taro@example.com"),
+            name: String::from("taro"),
+            password: String::from("this-is-hashed-password"),
+            is_active: true,
+            role: String::from("user"),
+            fixed_number: None,
+            mobile_number: Some(String::from("090-1234-5678")),
+            postal_code: String::from("100-0014"),
+            prefecture_code: 13,
+            address_details: String::from("千代田区永田町1-7-1"),
+            logged_in_at: None,
+            created_at: local_now(None),
+            updated_at: local_now(None),
+            updated_by: None,
+        }
+    }
+
+    /// アカウントモデルの値を、アカウントテーブルの列の順番で返却する。
+    fn account_values(model: &accounts::Model) -> Vec<Value> {
+        vec![
+            model.id.clone().into(),
+            model.email.clone().into(),
+            model.name.clone().into(),
+            model.password.clone().into(),
+            model.is_active.into(),
+            model.role.clone().into(),
+            model.fixed_number.clone().into(),
+            model.mobile_number.clone().into(),
+            model.postal_code.clone().into(),
+            model.prefecture_code.into(),
+            model.address_details.clone().into(),
+            model.logged_in_at.into(),
+            model.created_at.into(),
+            model.updated_at.into(),
+            model.updated_by.clone().into(),
+        ]
+    }
+
+    /// 都道府県を結合せずに、アカウントIDを指定してアカウントを1件検索するSQL文を返却する。
+    fn find_statement(id: &str) -> Statement {
+        let columns = ACCOUNT_COLUMNS
+            .iter()
+            .map(|c| format!(r#""accounts"."{}""#, c))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            format!(
+                r#"SELECT {} FROM "accounts" WHERE "accounts"."id" = $1 LIMIT $2"#,
+                columns
+            ),
+            [id.into(), 1u64.into()],
+        )
+    }
+
+    /// アカウントテーブルのすべての列を指定したRETURNING句を返却する。
+    fn returning_all() -> String {
+        let columns = ACCOUNT_COLUMNS
+            .iter()
+            .map(|c| format!(r#""{}""#, c))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        format!("RETURNING {}", columns)
+    }
+
+    /// 都道府県を結合して、アカウントを1件検索するSQL文を返却する。
+    fn find_joined_statement(column: &str, value: Value) -> Statement {
+        find_joined_statement_where(&format!(r#""accounts"."{}""#, column), value)
+    }
+
+    /// アカウントと都道府県を結合して、指定された式と値が一致するアカウントを検索するSQL文を返却する。
+    fn find_joined_statement_where(expr: &str, value: Value) -> Statement {
+        Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            format!(
+                r#"SELECT {} FROM "accounts" LEFT JOIN "prefectures" ON "accounts"."prefecture_code" = "prefectures"."code" WHERE {} = $1 LIMIT $2"#,
+                joined_columns(),
+                expr
+            ),
+            [value, 1u64.into()],
+        )
+    }
+
+    /// 大文字と小文字を区別せずにEメールアドレスで、都道府県を結合したアカウントを検索するSQL文を返却する。
+    fn find_by_email_statement(email: &str) -> Statement {
+        find_joined_statement_where(r#"LOWER("accounts"."email")"#, email.into())
+    }
+
+    /// 都道府県を結合したアカウントを検索するSQL文の列のリストを返却する。
+    fn joined_columns() -> String {
+        ACCOUNT_COLUMNS
+            .iter()
+            .map(|c| format!(r#""accounts"."{}" AS "A_{}""#, c, c))
+            .chain(
+                ["code", "name"]
+                    .iter()
+                    .map(|c| format!(r#""prefectures"."{}" AS "B_{}""#, c, c)),
+            )
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// トランザクション内で実行したSQL文の前後に、`BEGIN`と`COMMIT`を追加する。
+    fn committed(statements: Vec<Statement>) -> Vec<Transaction> {
+        let mut stmts = vec![Statement::from_string(DatabaseBackend::Postgres, "BEGIN")];
+        stmts.extend(statements);
+        stmts.push(Statement::from_string(DatabaseBackend::Postgres, "COMMIT"));
+
+        vec![Transaction::many(stmts)]
+    }
+
+    /// アカウントがアカウントモデルと都道府県モデルの値を持つことを確認する。
+    fn assert_account(account: &Account, model: &accounts::Model) {
+        assert_eq!(account.id().to_string(), model.id);
+        assert_eq!(account.email().value(), model.email);
+        assert_eq!(account.name().value(), model.name);
+        assert_eq!(account.password().value(), model.password);
+        assert_eq!(account.is_active(), model.is_active);
+        assert_eq!(account.role(), Role::User);
+        assert!(account.phone_numbers().fixed().is_none());
+        assert_eq!(
+            account.phone_numbers().mobile().unwrap().value(),
+            model.mobile_number.clone().unwrap()
+        );
+        assert_eq!(account.postal_code().value(), model.postal_code);
+        assert_eq!(account.address().prefecture().code(), 13);
+        assert_eq!(account.address().prefecture().name(), "東京都");
+        assert_eq!(account.address().details().value(), model.address_details);
+        assert_eq!(account.logged_in_at(), model.logged_in_at);
+        assert_eq!(account.created_at(), model.created_at);
+        assert_eq!(account.updated_at(), model.updated_at);
+    }
+
+    /// アカウントIDを指定して、都道府県を結合したアカウントを取得することを確認する。
+    #[tokio::test]
+    async fn test_find_by_id() {
+        let model = account_model();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[(model.clone(), tokyo_model())]])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let account = PgAccountRepository::new(&txn)
+            .find_by_id(AccountId::try_from(model.id.as_str()).unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert_account(&account, &model);
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![find_joined_statement("id", model.id.clone().into())])
+        );
+    }
+
+    /// アカウントIDと一致するアカウントが存在しない場合に、`None`を返却することを確認する。
+    #[tokio::test]
+    async fn test_find_by_id_not_found() {
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([Vec::<accounts::Model>::new()])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let result = PgAccountRepository::new(&txn)
+            .find_by_id(AccountId::gen())
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert!(result.is_none());
+    }
+
+    /// Eメールアドレスを指定して、都道府県を結合したアカウントを取得することを確認する。
+    #[tokio::test]
+    async fn test_find_by_email() {
+        let model = account_model();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[(model.clone(), tokyo_model())]])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let account = PgAccountRepository::new(&txn)
+            .find_by_email(EmailAddress::new(&model.email).unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert_account(&account, &model);
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![find_by_email_statement(&model.email)])
+        );
+    }
+
+    /// 大文字を含むEメールアドレスを指定しても、大文字と小文字を区別せずにアカウントを検索することを確認する。
+    #[tokio::test]
+    async fn test_find_by_email_ignores_case() {
+        let model = account_model();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[(model.clone(), tokyo_model())]])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let account = PgAccountRepository::new(&txn)
+            .find_by_email(EmailAddress::new("Taro@Example.COM").unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert_account(&account, &model);
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![find_by_email_statement("taro@example.com")])
+        );
+    }
+
+    /// アカウントのすべての列を登録した後に、登録したアカウントを取得することを確認する。
+    #[tokio::test]
+    async fn test_insert() {
+        let model = account_model();
+        let account = model_to_account(&model, &tokyo_model()).unwrap();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[model.clone()]])
+            .append_query_results([[(model.clone(), tokyo_model())]])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let inserted = PgAccountRepository::new(&txn)
+            .insert(&account)
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert_account(&inserted, &model);
+        let placeholders = (1..=ACCOUNT_COLUMNS.len())
+            .map(|i| format!("${}", i))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let insert = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            format!(
+                r#"INSERT INTO "accounts" ({}) VALUES ({}) {}"#,
+                ACCOUNT_COLUMNS
+                    .iter()
+                    .map(|c| format!(r#""{}""#, c))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                placeholders,
+                returning_all()
+            ),
+            account_values(&model),
+        );
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![
+                insert,
+                find_joined_statement("id", model.id.clone().into())
+            ])
+        );
+    }
+
+    /// アカウントID以外のすべての列を更新した後に、更新したアカウントを取得することを確認する。
+    #[tokio::test]
+    async fn test_update() {
+        let model = accounts::Model {
+            name: String::from("jiro"),
+            ..account_model()
+        };
+        let account = model_to_account(&model, &tokyo_model()).unwrap();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[model.clone()]])
+            .append_query_results([[(model.clone(), tokyo_model())]])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let updated = PgAccountRepository::new(&txn)
+            .update(&account)
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert_account(&updated, &model);
+        let assignments = ACCOUNT_COLUMNS[1..]
+            .iter()
+            .enumerate()
+            .map(|(i, c)| format!(r#""{}" = ${}"#, c, i + 1))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let mut values = account_values(&model);
+        let id = values.remove(0);
+        values.push(id);
+        let update = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            format!(
+                r#"UPDATE "accounts" SET {} WHERE "accounts"."id" = ${} {}"#,
+                assignments,
+                ACCOUNT_COLUMNS.len(),
+                returning_all()
+            ),
+            values,
+        );
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![
+                update,
+                find_joined_statement("id", model.id.clone().into())
+            ])
+        );
+    }
+
+    /// 更新日時が一致してアカウントを更新した場合に、更新したアカウントを取得することを確認する。
+    #[tokio::test]
+    async fn test_update_if_unmodified_matched() {
+        let model = account_model();
+        let account = model_to_account(&model, &tokyo_model()).unwrap();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }])
+            .append_query_results([[(model.clone(), tokyo_model())]])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let updated = PgAccountRepository::new(&txn)
+            .update_if_unmodified(&account, model.updated_at)
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert_account(&updated.unwrap(), &model);
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![
+                update_if_unmodified_query(&account, model.updated_at)
+                    .build(DatabaseBackend::Postgres),
+                find_joined_statement("id", model.id.clone().into())
+            ])
+        );
+    }
+
+    /// 他のリクエストで更新日時が変更されていて、更新した行が0件の場合に、
+    /// アカウントを取得せずに`None`を返却することを確認する。
+    #[tokio::test]
+    async fn test_update_if_unmodified_not_matched() {
+        let model = account_model();
+        let account = model_to_account(&model, &tokyo_model()).unwrap();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 0,
+            }])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let updated = PgAccountRepository::new(&txn)
+            .update_if_unmodified(&account, model.updated_at)
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert!(updated.is_none());
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![update_if_unmodified_query(&account, model.updated_at)
+                .build(DatabaseBackend::Postgres)])
+        );
+    }
+
+    /// アカウントIDで絞り込んでアカウントを削除することを確認する。
+    #[tokio::test]
+    async fn test_delete() {
+        let id = AccountId::gen();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        PgAccountRepository::new(&txn)
+            .delete(id.clone())
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"DELETE FROM "accounts" WHERE "accounts"."id" = $1"#,
+                [id.to_string().into()],
+            )])
+        );
+    }
+
+    /// パスワードと、パスワードを変更したアカウントの列のみを更新することを確認する。
+    #[tokio::test]
+    async fn test_change_password() {
+        let model = account_model();
+        let new_password = HashedPassword::from_repository("this-is-new-hashed-password");
+        let updated_by = AccountId::gen();
+        let changed = accounts::Model {
+            password: new_password.value(),
+            updated_by: Some(updated_by.to_string()),
+            ..model.clone()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[model.clone()]])
+            .append_query_results([[changed]])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let result = PgAccountRepository::new(&txn)
+            .change_password(
+                AccountId::try_from(model.id.as_str()).unwrap(),
+                new_password.clone(),
+                Some(updated_by.clone()),
+            )
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert!(result);
+        let update = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            format!(
+                r#"UPDATE "accounts" SET "password" = $1, "updated_by" = $2 WHERE "accounts"."id" = $3 {}"#,
+                returning_all()
+            ),
+            [
+                new_password.value().into(),
+                updated_by.to_string().into(),
+                model.id.clone().into(),
+            ],
+        );
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![find_statement(&model.id), update])
+        );
+    }
+
+    /// アカウントが見つからない場合に、パスワードを更新せずに`false`を返却することを確認する。
+    #[tokio::test]
+    async fn test_change_password_not_found() {
+        let id = AccountId::gen();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([Vec::<accounts::Model>::new()])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let result = PgAccountRepository::new(&txn)
+            .change_password(
+                id.clone(),
+                HashedPassword::from_repository("this-is-new-hashed-password"),
+                None,
+            )
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert!(!result);
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![find_statement(&id.to_string())])
+        );
+    }
 }
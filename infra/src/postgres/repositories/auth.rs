@@ -1,3 +1,4 @@
+use anyhow::Context;
 use async_trait::async_trait;
 use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
 
@@ -13,15 +14,15 @@ use crate::postgres::schema::jwt_tokens;
 
 use super::super::schema::jwt_tokens::{ActiveModel, Column, Entity, Model};
 use super::super::schema::prelude::JwtTokens;
-use super::common::PgRepository;
+use super::common::{to_local, PgRepository};
 
 /// 有効期限付きアクセス・リフレッシュトークンリポジトリ型
 pub type PgJwtTokensRepository<'a> = PgRepository<'a, auth::JwtTokens>;
 
 fn model_to_active_model(tokens: &auth::JwtTokens) -> ActiveModel {
     ActiveModel {
-        id: Set(tokens.id().value.to_string()),
-        account_id: Set(tokens.account_id().value.to_string()),
+        id: Set(tokens.id().to_string()),
+        account_id: Set(tokens.account_id().to_string()),
         access: Set(tokens.access().token.value()),
         access_expired_at: Set(tokens.access().expired_at),
         refresh: Set(tokens.refresh().token.value()),
@@ -29,21 +30,40 @@ fn model_to_active_model(tokens: &auth::JwtTokens) -> ActiveModel {
     }
 }
 
-fn db_to_model(db: &Model) -> auth::JwtTokens {
+/// JWTトークンモデルから有効期限付きアクセス・リフレッシュトークンを構築して返却する。
+///
+/// # Arguments
+///
+/// * `db` - JWTトークンモデル。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 有効期限付きアクセス・リフレッシュトークン。
+/// * `Err`: JWTトークンモデルに不正な値が記録されていた場合のエラー。
+pub(crate) fn db_to_model(db: &Model) -> anyhow::Result<auth::JwtTokens> {
+    let context = |column: &str| {
+        format!(
+            "トークン({})の{}列に記録されている値が不正です。",
+            db.id, column
+        )
+    };
     let access = JwtTokenWithExpiredAt {
-        token: JwtToken::new(&db.access).unwrap(),
-        expired_at: db.access_expired_at,
+        token: JwtToken::new(&db.access).with_context(|| context("access"))?,
+        expired_at: to_local(db.access_expired_at),
     };
     let refresh = JwtTokenWithExpiredAt {
-        token: JwtToken::new(&db.refresh).unwrap(),
-        expired_at: db.refresh_expired_at,
+        token: JwtToken::new(&db.refresh).with_context(|| context("refresh"))?,
+        expired_at: to_local(db.refresh_expired_at),
     };
-    auth::JwtTokens::new(
-        JwtTokensId::try_from(db.id.as_str()).unwrap(),
-        AccountId::try_from(db.account_id.as_str()).unwrap(),
+
+    Ok(auth::JwtTokens::new(
+        JwtTokensId::try_from(db.id.as_str()).with_context(|| context("id"))?,
+        AccountId::try_from(db.account_id.as_str()).with_context(|| context("account_id"))?,
         access,
         refresh,
-    )
+    ))
 }
 
 #[async_trait]
@@ -61,14 +81,12 @@ impl JwtTokensRepository for PgJwtTokensRepository<'_> {
     /// * `Ok`: 見つかった場合は有効期限付きアクセス・リフレッシュトークン。見つからなかった場合は`None`。
     /// * `Err`: エラー。
     async fn find_by_id(&self, id: JwtTokensId) -> anyhow::Result<Option<auth::JwtTokens>> {
-        let result = JwtTokens::find_by_id(id.value.to_string())
-            .one(self.txn)
-            .await?;
+        let result = JwtTokens::find_by_id(id.to_string()).one(self.txn).await?;
         if result.is_none() {
             return Ok(None);
         }
 
-        Ok(Some(db_to_model(&result.unwrap())))
+        Ok(Some(db_to_model(&result.unwrap())?))
     }
 
     /// アクセストークンを指定して、有効期限付きアクセス・リフレッシュトークンを検索する。
@@ -92,7 +110,7 @@ impl JwtTokensRepository for PgJwtTokensRepository<'_> {
             return Ok(None);
         }
 
-        Ok(Some(db_to_model(&result.unwrap())))
+        Ok(Some(db_to_model(&result.unwrap())?))
     }
 
     /// リフレッシュトークンを指定して、有効期限付きアクセス・リフレッシュトークンを検索する。
@@ -116,7 +134,7 @@ impl JwtTokensRepository for PgJwtTokensRepository<'_> {
             return Ok(None);
         }
 
-        Ok(Some(db_to_model(&result.unwrap())))
+        Ok(Some(db_to_model(&result.unwrap())?))
     }
 
     /// 有効期限付きアクセス・リフレッシュトークンを登録する。
@@ -140,11 +158,11 @@ impl JwtTokensRepository for PgJwtTokensRepository<'_> {
 
     /// 有効期限付きアクセス・リフレッシュトークンを削除する。
     ///
-    /// アカウントIDが一致するアクセス・リフレッシュトークンが登録されていない場合は`OK(())`を返却する。
+    /// トークンIDが一致するアクセス・リフレッシュトークンが登録されていない場合は`OK(())`を返却する。
     ///
     /// # Arguments
     ///
-    /// * `id` - 削除するアカウントのアカウントID。
+    /// * `id` - 削除するトークンのトークンID。
     ///
     /// # Returns
     ///
@@ -152,12 +170,368 @@ impl JwtTokensRepository for PgJwtTokensRepository<'_> {
     ///
     /// * `Ok`: `()`。
     /// * `Err`: エラー。
-    async fn delete(&self, id: AccountId) -> anyhow::Result<()> {
+    async fn delete(&self, id: JwtTokensId) -> anyhow::Result<()> {
         let _ = Entity::delete_many()
-            .filter(Column::Id.eq(id.value.to_string()))
+            .filter(Column::Id.eq(id.to_string()))
             .exec(self.txn)
             .await?;
 
         Ok(())
     }
+
+    /// アカウントに発行したすべての有効期限付きアクセス・リフレッシュトークンを削除する。
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - トークンを削除するアカウントのアカウントID。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 削除したトークンの数。
+    /// * `Err`: エラー。
+    async fn delete_by_account_id(&self, account_id: AccountId) -> anyhow::Result<u64> {
+        let result = Entity::delete_many()
+            .filter(Column::AccountId.eq(account_id.to_string()))
+            .exec(self.txn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+}
+
+#[cfg(test)]
+mod jwt_tokens_model_tests {
+    use super::*;
+    use domains::models::common::local_now;
+    use ulid::Ulid;
+
+    fn tokens_model() -> Model {
+        Model {
+            id: Ulid::new().to_string(),
+            account_id: Ulid::new().to_string(),
+            access: String::from("access-token"),
+            access_expired_at: local_now(None),
+            refresh: String::from("refresh-token"),
+            refresh_expired_at: local_now(None),
+        }
+    }
+
+    /// JWTトークンモデルから、有効期限付きアクセス・リフレッシュトークンを構築できることを確認する。
+    #[test]
+    fn test_db_to_model() {
+        let model = tokens_model();
+        let tokens = db_to_model(&model).unwrap();
+        assert_eq!(tokens.id().to_string(), model.id);
+        assert_eq!(tokens.account_id().to_string(), model.account_id);
+        assert_eq!(tokens.access().token.value(), model.access);
+        assert_eq!(tokens.refresh().token.value(), model.refresh);
+    }
+
+    /// 不正な値が記録されたJWTトークンモデルから、トークンを構築できないことを確認する。
+    #[test]
+    fn test_db_to_model_invalid() {
+        let model = tokens_model();
+        let invalid_models = vec![
+            Model {
+                account_id: String::from("invalid-ulid-string"),
+                ..model.clone()
+            },
+            Model {
+                access: String::new(),
+                ..model.clone()
+            },
+            Model {
+                refresh: String::new(),
+                ..model.clone()
+            },
+        ];
+        for model in invalid_models {
+            assert!(db_to_model(&model).is_err());
+        }
+    }
+}
+
+#[cfg(test)]
+mod pg_jwt_tokens_repository_tests {
+    use sea_orm::{
+        DatabaseBackend, MockDatabase, MockExecResult, Statement, Transaction, TransactionTrait,
+        Value,
+    };
+    use ulid::Ulid;
+
+    use domains::{
+        models::{
+            accounts::{Account, AccountName, FixedMobileNumbers, HashedPassword},
+            common::{
+                local_now, Address, AddressDetails, EmailAddress, PhoneNumber, PostalCode,
+                Prefecture,
+            },
+        },
+        repositories::{accounts::AccountRepository, common::PrefectureRepository},
+    };
+
+    use super::*;
+    use crate::{
+        postgres::repositories::{
+            accounts::PgAccountRepository, prefectures::PgPrefectureRepository,
+        },
+        sqlite::memory_connection,
+    };
+
+    /// 指定されたアカウントのトークンを構築する。
+    fn tokens(account_id: AccountId) -> auth::JwtTokens {
+        let token = |kind: &str| JwtTokenWithExpiredAt {
+            token: JwtToken::new(&format!("{}-{}", kind, Ulid::new())).unwrap(),
+            expired_at: local_now(None),
+        };
+        auth::JwtTokens::new(
+            JwtTokensId::gen(),
+            account_id,
+            token("access"),
+            token("refresh"),
+        )
+    }
+
+    /// アカウントに発行した複数のトークンを、すべて削除することを確認する。
+    #[tokio::test]
+    async fn test_delete_by_account_id() {
+        let conn = memory_connection().await;
+        let txn = conn.begin().await.unwrap();
+        PgPrefectureRepository::new(&txn)
+            .insert_missing(&Prefecture::all())
+            .await
+            .unwrap();
+        let account = Account::new(
+            EmailAddress::new(&format!("{}@example.com", Ulid::new()).to_lowercase()).unwrap(),
+            AccountName::new("foo").unwrap(),
+            HashedPassword::from_repository("SHA-256$1$1$a$b"),
+            true,
+            FixedMobileNumbers::new(None, Some(PhoneNumber::new("090-1234-5678").unwrap()))
+                .unwrap(),
+            PostalCode::new("100-0014").unwrap(),
+            Address::new(
+                Prefecture::new(13, "東京都").unwrap(),
+                AddressDetails::new("千代田区永田町1-7-1").unwrap(),
+            ),
+            local_now(None),
+        );
+        let account = PgAccountRepository::new(&txn)
+            .insert(&account)
+            .await
+            .unwrap();
+        let repo = PgJwtTokensRepository::new(&txn);
+        let first = repo.insert(&tokens(account.id())).await.unwrap();
+        let second = repo.insert(&tokens(account.id())).await.unwrap();
+        assert_eq!(repo.delete_by_account_id(account.id()).await.unwrap(), 2);
+        assert!(repo.find_by_id(first.id()).await.unwrap().is_none());
+        assert!(repo.find_by_id(second.id()).await.unwrap().is_none());
+        txn.rollback().await.unwrap();
+    }
+
+    /// JWTトークンテーブルの列名のリスト。
+    const TOKENS_COLUMNS: [&str; 6] = [
+        "id",
+        "account_id",
+        "access",
+        "access_expired_at",
+        "refresh",
+        "refresh_expired_at",
+    ];
+
+    fn tokens_model() -> Model {
+        Model {
+            id: Ulid::new().to_string(),
+            account_id: Ulid::new().to_string(),
+            access: format!("access-{}", Ulid::new()),
+            access_expired_at: local_now(None),
+            refresh: format!("refresh-{}", Ulid::new()),
+            refresh_expired_at: local_now(None),
+        }
+    }
+
+    /// 指定された列で絞り込んで、トークンを1件検索するSQL文を返却する。
+    fn find_statement(column: &str, value: &str) -> Statement {
+        let columns = TOKENS_COLUMNS
+            .iter()
+            .map(|c| format!(r#""jwt_tokens"."{}""#, c))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            format!(
+                r#"SELECT {} FROM "jwt_tokens" WHERE "jwt_tokens"."{}" = $1 LIMIT $2"#,
+                columns, column
+            ),
+            [value.into(), 1u64.into()],
+        )
+    }
+
+    /// トランザクション内で実行したSQL文の前後に、`BEGIN`と`COMMIT`を追加する。
+    fn committed(statements: Vec<Statement>) -> Vec<Transaction> {
+        let mut stmts = vec![Statement::from_string(DatabaseBackend::Postgres, "BEGIN")];
+        stmts.extend(statements);
+        stmts.push(Statement::from_string(DatabaseBackend::Postgres, "COMMIT"));
+
+        vec![Transaction::many(stmts)]
+    }
+
+    /// トークンがJWTトークンモデルの値を持つことを確認する。
+    fn assert_tokens(tokens: &auth::JwtTokens, model: &Model) {
+        assert_eq!(tokens.id().to_string(), model.id);
+        assert_eq!(tokens.account_id().to_string(), model.account_id);
+        assert_eq!(tokens.access().token.value(), model.access);
+        assert_eq!(tokens.access().expired_at, model.access_expired_at);
+        assert_eq!(tokens.refresh().token.value(), model.refresh);
+        assert_eq!(tokens.refresh().expired_at, model.refresh_expired_at);
+    }
+
+    /// トークンIDを指定して、トークンを取得することを確認する。
+    #[tokio::test]
+    async fn test_find_by_id() {
+        let model = tokens_model();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[model.clone()]])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let tokens = PgJwtTokensRepository::new(&txn)
+            .find_by_id(JwtTokensId::try_from(model.id.as_str()).unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert_tokens(&tokens, &model);
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![find_statement("id", &model.id)])
+        );
+    }
+
+    /// アクセストークンまたはリフレッシュトークンを指定して、トークンを取得することを確認する。
+    #[tokio::test]
+    async fn test_find_by_access_and_refresh_token() {
+        let model = tokens_model();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[model.clone()], [model.clone()]])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let repo = PgJwtTokensRepository::new(&txn);
+        let by_access = repo
+            .find_by_access_token(&model.access)
+            .await
+            .unwrap()
+            .unwrap();
+        let by_refresh = repo
+            .find_by_refresh_token(&model.refresh)
+            .await
+            .unwrap()
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert_tokens(&by_access, &model);
+        assert_tokens(&by_refresh, &model);
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![
+                find_statement("access", &model.access),
+                find_statement("refresh", &model.refresh),
+            ])
+        );
+    }
+
+    /// トークンのすべての列を登録した後に、登録したトークンを取得することを確認する。
+    #[tokio::test]
+    async fn test_insert() {
+        let model = tokens_model();
+        let tokens = db_to_model(&model).unwrap();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[model.clone()], [model.clone()]])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let inserted = PgJwtTokensRepository::new(&txn)
+            .insert(&tokens)
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert_tokens(&inserted, &model);
+        let columns = TOKENS_COLUMNS
+            .iter()
+            .map(|c| format!(r#""{}""#, c))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let values: Vec<Value> = vec![
+            model.id.clone().into(),
+            model.account_id.clone().into(),
+            model.access.clone().into(),
+            model.access_expired_at.into(),
+            model.refresh.clone().into(),
+            model.refresh_expired_at.into(),
+        ];
+        let insert = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            format!(
+                r#"INSERT INTO "jwt_tokens" ({}) VALUES ($1, $2, $3, $4, $5, $6) RETURNING {}"#,
+                columns, columns
+            ),
+            values,
+        );
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![insert, find_statement("id", &model.id)])
+        );
+    }
+
+    /// トークンIDで絞り込んでトークンを削除することを確認する。
+    #[tokio::test]
+    async fn test_delete() {
+        let id = JwtTokensId::gen();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        PgJwtTokensRepository::new(&txn)
+            .delete(id.clone())
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"DELETE FROM "jwt_tokens" WHERE "jwt_tokens"."id" = $1"#,
+                [id.to_string().into()],
+            )])
+        );
+    }
+
+    /// アカウントIDで絞り込んでトークンを削除して、削除した数を返却することを確認する。
+    #[tokio::test]
+    async fn test_delete_by_account_id_statement() {
+        let account_id = AccountId::gen();
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 2,
+            }])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let deleted = PgJwtTokensRepository::new(&txn)
+            .delete_by_account_id(account_id.clone())
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert_eq!(deleted, 2);
+        assert_eq!(
+            conn.into_transaction_log(),
+            committed(vec![Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"DELETE FROM "jwt_tokens" WHERE "jwt_tokens"."account_id" = $1"#,
+                [account_id.to_string().into()],
+            )])
+        );
+    }
 }
@@ -1,13 +1,38 @@
 use std::marker::PhantomData;
 
 use derive_new::new;
-use sea_orm::DatabaseTransaction;
+use sea_orm::{
+    prelude::{ChronoDateTimeUtc, DateTimeWithTimeZone},
+    DatabaseTransaction,
+};
+
+use domains::models::common::local_now;
 
 /// PostgreSQLリポジトリ構造体
+///
+/// 通常はデータベーストランザクションでクエリを実行する。
+/// 読み取り専用のクエリのみを実行するリポジトリは、トランザクションを開始せずに、
+/// データベースコネクションで直接クエリを実行できる。
 #[derive(new)]
-pub struct PgRepository<'a, T> {
-    /// データベースコネクション。
-    pub txn: &'a DatabaseTransaction,
+pub struct PgRepository<'a, T, C = DatabaseTransaction> {
+    /// データベーストランザクションまたはデータベースコネクション。
+    pub txn: &'a C,
     /// マーカー。
     _marker: PhantomData<T>,
 }
+
+/// データベースから取得した日時を、環境変数で指定されたタイムゾーンの日時に変換する。
+///
+/// データベースから取得した日時のオフセットはデータベースによって異なる(PostgreSQLやSQLiteはUTC)。
+/// SQLiteは日時を文字列で比較するため、記録する日時のオフセットを揃える必要がある。
+///
+/// # Arguments
+///
+/// * `dt` - データベースから取得した日時。
+///
+/// # Returns
+///
+/// 環境変数で指定されたタイムゾーンの日時。
+pub(crate) fn to_local(dt: DateTimeWithTimeZone) -> DateTimeWithTimeZone {
+    local_now(Some(ChronoDateTimeUtc::from(dt)))
+}
@@ -1,4 +1,5 @@
 pub mod accounts;
 pub mod auth;
 pub mod common;
+pub mod password_history;
 pub mod prefectures;
@@ -0,0 +1,151 @@
+use async_trait::async_trait;
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set,
+};
+use ulid::Ulid;
+
+use domains::models::accounts::{AccountId, HashedPassword};
+use domains::repositories::accounts::PasswordHistoryRepository;
+
+use super::super::schema::password_history::{ActiveModel, Column, Entity};
+use super::common::PgRepository;
+
+/// パスワード履歴リポジトリ型
+pub type PgPasswordHistoryRepository<'a> = PgRepository<'a, HashedPassword>;
+
+#[async_trait]
+impl PasswordHistoryRepository for PgPasswordHistoryRepository<'_> {
+    /// アカウントに過去に設定されていたパスワードを、新しい順に返却する。
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - アカウントID。
+    /// * `limit` - 返却するパスワードの最大数。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: ハッシュ化したパスワードを新しい順に格納したベクタ。
+    /// * `Err`: エラー。
+    async fn list(
+        &self,
+        account_id: AccountId,
+        limit: usize,
+    ) -> anyhow::Result<Vec<HashedPassword>> {
+        let models = Entity::find()
+            .filter(Column::AccountId.eq(account_id.to_string()))
+            .order_by_desc(Column::CreatedAt)
+            .order_by_desc(Column::Id)
+            .limit(limit as u64)
+            .all(self.txn)
+            .await?;
+
+        Ok(models
+            .iter()
+            .map(|m| HashedPassword::from_repository(&m.password))
+            .collect())
+    }
+
+    /// パスワードを履歴に追加して、新しい順に`size`個を超える古いパスワードを履歴から削除する。
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - アカウントID。
+    /// * `password` - 履歴に追加する、変更前のハッシュ化したパスワード。
+    /// * `created_at` - 履歴に追加した日時。
+    /// * `size` - 履歴に残すパスワードの数。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: `()`。
+    /// * `Err`: エラー。
+    async fn push(
+        &self,
+        account_id: AccountId,
+        password: HashedPassword,
+        created_at: DateTimeWithTimeZone,
+        size: usize,
+    ) -> anyhow::Result<()> {
+        let active_model = ActiveModel {
+            id: Set(Ulid::new().to_string()),
+            account_id: Set(account_id.to_string()),
+            password: Set(password.value()),
+            created_at: Set(created_at),
+        };
+        let _ = active_model.insert(self.txn).await?;
+        // 新しい順に`size`個を超える古いパスワードを削除
+        let expired: Vec<String> = Entity::find()
+            .filter(Column::AccountId.eq(account_id.to_string()))
+            .order_by_desc(Column::CreatedAt)
+            .order_by_desc(Column::Id)
+            .offset(size as u64)
+            .all(self.txn)
+            .await?
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        if !expired.is_empty() {
+            let _ = Entity::delete_many()
+                .filter(Column::Id.is_in(expired))
+                .exec(self.txn)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod pg_password_history_repository_tests {
+    use domains::models::common::local_now;
+    use sea_orm::{DatabaseBackend, MockDatabase, Statement, Transaction, TransactionTrait};
+
+    use super::super::super::schema::password_history::Model;
+    use super::*;
+
+    /// アカウントのパスワード履歴を、新しい順に指定された数まで取得することを確認する。
+    #[tokio::test]
+    async fn test_list() {
+        let account_id = AccountId::gen();
+        let model = |password: &str| Model {
+            id: Ulid::new().to_string(),
+            account_id: account_id.to_string(),
+            password: password.to_owned(),
+            created_at: local_now(None),
+        };
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[model("SHA-256$1$1$a$new"), model("SHA-256$1$1$a$old")]])
+            .into_connection();
+        let txn = conn.begin().await.unwrap();
+        let passwords = PgPasswordHistoryRepository::new(&txn)
+            .list(account_id.clone(), 5)
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        let passwords: Vec<String> = passwords.iter().map(|p| p.value()).collect();
+        assert_eq!(passwords, vec!["SHA-256$1$1$a$new", "SHA-256$1$1$a$old"]);
+        assert_eq!(
+            conn.into_transaction_log(),
+            vec![Transaction::many([
+                Statement::from_string(DatabaseBackend::Postgres, "BEGIN"),
+                Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    [
+                        r#"SELECT "password_history"."id", "password_history"."account_id","#,
+                        r#""password_history"."password", "password_history"."created_at""#,
+                        r#"FROM "password_history" WHERE "password_history"."account_id" = $1"#,
+                        r#"ORDER BY "password_history"."created_at" DESC, "password_history"."id" DESC"#,
+                        r#"LIMIT $2"#,
+                    ]
+                    .join(" "),
+                    [account_id.to_string().into(), 5u64.into()],
+                ),
+                Statement::from_string(DatabaseBackend::Postgres, "COMMIT"),
+            ])]
+        );
+    }
+}
@@ -1,5 +1,9 @@
+use anyhow::Context;
 use async_trait::async_trait;
-use sea_orm::{EntityTrait, QueryOrder};
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, ConnectionTrait, DatabaseTransaction, EntityTrait, Insert,
+    QueryFilter, QueryOrder, Select, Set,
+};
 
 use domains::models::common::Prefecture;
 use domains::repositories::common::PrefectureRepository;
@@ -9,16 +13,61 @@ use super::super::schema::prelude::Prefectures;
 use super::common::PgRepository;
 
 /// 都道府県リポジトリ型
-pub type PgPrefectureRepository<'a> = PgRepository<'a, Prefecture>;
+pub type PgPrefectureRepository<'a, C = DatabaseTransaction> = PgRepository<'a, Prefecture, C>;
 
-impl From<prefectures::Model> for Prefecture {
-    fn from(m: prefectures::Model) -> Self {
-        Self::new(m.code as u8, &m.name)
+impl TryFrom<prefectures::Model> for Prefecture {
+    type Error = anyhow::Error;
+
+    fn try_from(m: prefectures::Model) -> anyhow::Result<Self> {
+        let code = u8::try_from(m.code)
+            .with_context(|| format!("都道府県コード({})が不正です。", m.code))?;
+
+        Prefecture::new(code, &m.name)
     }
 }
 
+/// 都道府県コードのいずれかと一致する都道府県を、都道府県コードの順に検索するクエリを構築する。
+///
+/// # Arguments
+///
+/// * `codes` - 都道府県コードのスライス。
+///
+/// # Returns
+///
+/// `IN`で都道府県コードを絞り込むSELECTクエリ。
+fn find_by_codes_query(codes: &[u8]) -> Select<Prefectures> {
+    Prefectures::find()
+        .filter(prefectures::Column::Code.is_in(codes.iter().map(|&code| code as i16)))
+        .order_by_asc(prefectures::Column::Code)
+}
+
+/// 都道府県コードが登録されていない都道府県のみを登録するクエリを構築する。
+///
+/// # Arguments
+///
+/// * `prefectures` - 登録する都道府県のリスト。
+///
+/// # Returns
+///
+/// 都道府県コードが重複した場合に何もしないINSERTクエリ。
+fn insert_missing_query(prefectures: &[Prefecture]) -> Insert<prefectures::ActiveModel> {
+    Prefectures::insert_many(
+        prefectures
+            .iter()
+            .map(|prefecture| prefectures::ActiveModel {
+                code: Set(prefecture.code() as i16),
+                name: Set(prefecture.name()),
+            }),
+    )
+    .on_conflict(
+        OnConflict::column(prefectures::Column::Code)
+            .do_nothing()
+            .to_owned(),
+    )
+}
+
 #[async_trait]
-impl PrefectureRepository for PgPrefectureRepository<'_> {
+impl<C: ConnectionTrait> PrefectureRepository for PgPrefectureRepository<'_, C> {
     /// 都道府県コードを指定して、都道府県を検索する。
     ///
     /// # Arguments
@@ -35,11 +84,32 @@ impl PrefectureRepository for PgPrefectureRepository<'_> {
         let entity = Prefectures::find_by_id(code as i16).one(self.txn).await?;
 
         match entity {
-            Some(pref) => Ok(Some(pref.into())),
+            Some(pref) => Ok(Some(pref.try_into()?)),
             None => Ok(None),
         }
     }
 
+    /// 都道府県コードを複数指定して、都道府県を検索する。
+    ///
+    /// # Arguments
+    ///
+    /// * `codes` - 都道府県コードのスライス。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 見つかった都道府県を都道府県コードの順に格納したベクタ。見つからなかった都道府県コードは含まない。
+    /// * `Err`: エラーメッセージ。
+    async fn find_by_codes(&self, codes: &[u8]) -> anyhow::Result<Vec<Prefecture>> {
+        if codes.is_empty() {
+            return Ok(vec![]);
+        }
+        let entities = find_by_codes_query(codes).all(self.txn).await?;
+
+        entities.into_iter().map(Prefecture::try_from).collect()
+    }
+
     /// 都道府県のリストを返却する。
     ///
     /// # Returns
@@ -54,7 +124,29 @@ impl PrefectureRepository for PgPrefectureRepository<'_> {
             .all(self.txn)
             .await?;
 
-        Ok(entities.iter().map(|e| e.clone().into()).collect())
+        entities.into_iter().map(Prefecture::try_from).collect()
+    }
+
+    /// 都道府県コードが登録されていない都道府県を登録する。
+    ///
+    /// # Arguments
+    ///
+    /// * `prefectures` - 登録する都道府県のリスト。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 登録した都道府県の数。
+    /// * `Err`: エラーメッセージ。
+    async fn insert_missing(&self, prefectures: &[Prefecture]) -> anyhow::Result<u64> {
+        if prefectures.is_empty() {
+            return Ok(0);
+        }
+
+        Ok(insert_missing_query(prefectures)
+            .exec_without_returning(self.txn)
+            .await?)
     }
 }
 
@@ -63,6 +155,9 @@ mod pg_prefecture_repository_tests {
     use crate::postgres::schema::prefectures;
     // use crate::schema::prelude::Prefectures;
     use domains::models::common::Prefecture;
+    use sea_orm::{Database, DbBackend, PaginatorTrait, QueryTrait, TransactionTrait};
+
+    use super::*;
     // use sea_orm::{DatabaseBackend, EntityTrait, MockDatabase};
 
     fn tokyo_model() -> prefectures::Model {
@@ -83,11 +178,77 @@ mod pg_prefecture_repository_tests {
     #[test]
     fn test_prefecture_from_model() {
         let model = tokyo_model();
-        let prefecture = Prefecture::from(model);
+        let prefecture = Prefecture::try_from(model).unwrap();
         assert_eq!(prefecture.code(), 13);
         assert_eq!(prefecture.name(), "東京都");
     }
 
+    /// 都道府県コードが不正な都道府県モデルを都道府県に変換できないことを確認する。
+    #[test]
+    fn test_prefecture_from_model_invalid() {
+        for code in [0, 48, 300, -1] {
+            let model = prefectures::Model {
+                code,
+                name: "不明".to_owned(),
+            };
+            assert!(Prefecture::try_from(model).is_err());
+        }
+    }
+
+    /// 都道府県を登録するクエリが、都道府県コードが重複した場合に何もしないことを確認する。
+    #[test]
+    fn test_insert_missing_query() {
+        let sql = insert_missing_query(&Prefecture::all())
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(
+            sql.starts_with(r#"INSERT INTO "prefectures" ("code", "name") VALUES (1, '北海道')"#),
+            "{}",
+            sql
+        );
+        assert!(sql.contains("(47, '沖縄県')"), "{}", sql);
+        assert!(
+            sql.ends_with(r#"ON CONFLICT ("code") DO NOTHING"#),
+            "{}",
+            sql
+        );
+    }
+
+    /// 都道府県コードで絞り込むクエリが、`IN`で都道府県コードを指定することを確認する。
+    #[test]
+    fn test_find_by_codes_query() {
+        let sql = find_by_codes_query(&[13, 27, 1])
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(
+            sql.ends_with(
+                r#"WHERE "prefectures"."code" IN (13, 27, 1) ORDER BY "prefectures"."code" ASC"#
+            ),
+            "{}",
+            sql
+        );
+    }
+
+    /// すべての都道府県を登録した後に47件の都道府県が存在して、
+    /// 再度登録しても都道府県を重複して登録しないことを確認する。
+    ///
+    /// マイグレーションを適用したデータベースが必要なため、環境変数`DATABASE_URL`を設定して
+    /// `cargo test -- --ignored`で実行する。登録したデータはロールバックする。
+    #[tokio::test]
+    #[ignore]
+    async fn test_insert_missing() {
+        let url = std::env::var("DATABASE_URL").unwrap();
+        let conn = Database::connect(url.as_str()).await.unwrap();
+        let txn = conn.begin().await.unwrap();
+        let repo = PgPrefectureRepository::new(&txn);
+        repo.insert_missing(&Prefecture::all()).await.unwrap();
+        assert_eq!(Prefectures::find().count(&txn).await.unwrap(), 47);
+        assert_eq!(repo.insert_missing(&Prefecture::all()).await.unwrap(), 0);
+        assert_eq!(Prefectures::find().count(&txn).await.unwrap(), 47);
+        assert_eq!(repo.insert_missing(&[]).await.unwrap(), 0);
+        txn.rollback().await.unwrap();
+    }
+
     // /// 都道府県コードを指定して都道府県を取得できることを確認する。
     // #[async_std::test]
     // async fn test_find_prefecture() {
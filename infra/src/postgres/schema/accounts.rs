@@ -11,6 +11,7 @@ pub struct Model {
     pub name: String,
     pub password: String,
     pub is_active: bool,
+    pub role: String,
     pub fixed_number: Option<String>,
     pub mobile_number: Option<String>,
     pub postal_code: String,
@@ -19,6 +20,7 @@ pub struct Model {
     pub logged_in_at: Option<DateTimeWithTimeZone>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub updated_by: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -33,6 +35,8 @@ pub enum Relation {
     Prefectures,
     #[sea_orm(has_many = "super::jwt_tokens::Entity")]
     JwtTokens,
+    #[sea_orm(has_many = "super::password_history::Entity")]
+    PasswordHistory,
 }
 
 impl Related<super::prefectures::Entity> for Entity {
@@ -47,4 +51,10 @@ impl Related<super::jwt_tokens::Entity> for Entity {
     }
 }
 
+impl Related<super::password_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PasswordHistory.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
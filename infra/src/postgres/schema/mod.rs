@@ -4,4 +4,5 @@ pub mod prelude;
 
 pub mod accounts;
 pub mod jwt_tokens;
+pub mod password_history;
 pub mod prefectures;
@@ -2,4 +2,5 @@
 
 pub use super::accounts::Entity as Accounts;
 pub use super::jwt_tokens::Entity as JwtTokens;
+pub use super::password_history::Entity as PasswordHistory;
 pub use super::prefectures::Entity as Prefectures;
@@ -0,0 +1,122 @@
+//! SQLiteのスキーマ
+//!
+//! `migrations`ディレクトリのマイグレーションはPostgreSQLのSQLで記述されているため、SQLiteでは実行できない。
+//! SQLiteでは、エンティティの定義からテーブルを作成する。
+//!
+//! エンティティとリポジトリは、SeaORMのクエリビルダーで記述しているため、SQLiteでもそのまま使用できる。
+//! ただし、以下の点がPostgreSQLと異なる。
+//!
+//! * `TIMESTAMPTZ`の列は、オフセット付きのRFC 3339形式の文字列で記録される。
+//!   記録する日時のオフセットは`TIME_ZONE_OFFSET_SECONDS`で統一されるため、文字列の順序で日時を並べ替えられる。
+//! * 都道府県コード(`i16`)は`INTEGER`で記録される。
+//! * インデックスは作成しない。
+use sea_orm::{ConnectionTrait, DatabaseConnection, EntityTrait, Schema};
+
+use crate::postgres::schema::{accounts, jwt_tokens, password_history, prefectures};
+
+/// SQLiteのデータベースURLのスキーム。
+const SQLITE_SCHEME: &str = "sqlite:";
+
+/// データベースURLがSQLiteのデータベースを示すか確認する。
+///
+/// # Arguments
+///
+/// * `url` - データベースURL。
+///
+/// # Returns
+///
+/// SQLiteのデータベースURLの場合は`true`。
+pub fn is_sqlite_url(url: &str) -> bool {
+    url.starts_with(SQLITE_SCHEME)
+}
+
+/// データベースURLがSQLiteのインメモリデータベースを示すか確認する。
+///
+/// インメモリデータベースは、コネクションごとに別のデータベースになるため、
+/// コネクションプールのコネクション数を1にする必要がある。
+///
+/// # Arguments
+///
+/// * `url` - データベースURL。
+///
+/// # Returns
+///
+/// SQLiteのインメモリデータベースのURLの場合は`true`。
+pub fn is_sqlite_memory_url(url: &str) -> bool {
+    is_sqlite_url(url) && url.contains(":memory:")
+}
+
+/// エンティティの定義から、作成されていないテーブルを作成する。
+///
+/// 外部キーを参照するテーブルを後に作成するため、都道府県、アカウント、JWTトークン、パスワード履歴の順に作成する。
+///
+/// # Arguments
+///
+/// * `conn` - データベースコネクション。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: ()
+/// * `Err`: エラー。
+pub async fn create_tables(conn: &DatabaseConnection) -> anyhow::Result<()> {
+    create_table(conn, prefectures::Entity).await?;
+    create_table(conn, accounts::Entity).await?;
+    create_table(conn, jwt_tokens::Entity).await?;
+    create_table(conn, password_history::Entity).await?;
+
+    Ok(())
+}
+
+/// エンティティの定義から、作成されていないテーブルを作成する。
+///
+/// # Arguments
+///
+/// * `conn` - データベースコネクション。
+/// * `entity` - エンティティ。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: ()
+/// * `Err`: エラー。
+async fn create_table<E>(conn: &DatabaseConnection, entity: E) -> anyhow::Result<()>
+where
+    E: EntityTrait,
+{
+    let backend = conn.get_database_backend();
+    let mut stmt = Schema::new(backend).create_table_from_entity(entity);
+    conn.execute(backend.build(stmt.if_not_exists())).await?;
+
+    Ok(())
+}
+
+/// SQLiteのインメモリデータベースに接続して、テーブルを作成したデータベースコネクションを返却する。
+///
+/// インメモリデータベースはコネクションごとに作成されるため、コネクションプールのコネクションは1つのみとする。
+#[cfg(test)]
+pub(crate) async fn memory_connection() -> DatabaseConnection {
+    let mut options = sea_orm::ConnectOptions::new("sqlite::memory:".to_owned());
+    options.max_connections(1).sqlx_logging(false);
+    let conn = sea_orm::Database::connect(options).await.unwrap();
+    create_tables(&conn).await.unwrap();
+
+    conn
+}
+
+#[cfg(test)]
+mod sqlite_tests {
+    use super::*;
+
+    /// SQLiteのデータベースURLを判定できることを確認する。
+    #[test]
+    fn test_is_sqlite_url() {
+        assert!(is_sqlite_url("sqlite::memory:"));
+        assert!(is_sqlite_url("sqlite://data.db?mode=rwc"));
+        assert!(!is_sqlite_url("postgres://localhost/test"));
+        assert!(is_sqlite_memory_url("sqlite::memory:"));
+        assert!(!is_sqlite_memory_url("sqlite://data.db?mode=rwc"));
+    }
+}
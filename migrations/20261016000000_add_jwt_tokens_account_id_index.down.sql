@@ -0,0 +1 @@
+DROP INDEX IF EXISTS jwt_tokens_account_id_index;
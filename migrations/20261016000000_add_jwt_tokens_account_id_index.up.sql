@@ -0,0 +1,2 @@
+-- アカウントはログインするたびにトークンを発行するため、アカウントIDでトークンを検索及び削除するインデックスを作成
+CREATE INDEX IF NOT EXISTS jwt_tokens_account_id_index ON jwt_tokens (account_id);
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS accounts_prefecture_code_created_at_index;
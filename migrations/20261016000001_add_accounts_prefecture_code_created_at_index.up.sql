@@ -0,0 +1,2 @@
+-- 都道府県別のアカウントを登録日時の範囲で絞り込み、登録日時順に取得するインデックスを作成
+CREATE INDEX IF NOT EXISTS accounts_prefecture_code_created_at_index ON accounts (prefecture_code, created_at);
@@ -0,0 +1,3 @@
+-- アカウントの権限(user: 一般ユーザー、admin: 管理者)を記録する列を追加
+ALTER TABLE accounts
+ADD COLUMN IF NOT EXISTS role VARCHAR(10) NOT NULL DEFAULT 'user' CONSTRAINT accounts_role_check CHECK (role IN ('user', 'admin'));
@@ -0,0 +1,17 @@
+-- パスワード履歴テーブル作成。
+CREATE TABLE IF NOT EXISTS password_history (
+    -- ID。
+    id CHAR(26) NOT NULL,
+    -- アカウントID。
+    account_id CHAR(26) NOT NULL,
+    -- 過去にアカウントに設定されていた、ハッシュ化したパスワード。
+    password VARCHAR(512) NOT NULL,
+    -- 登録日時。
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    -- 主キー制約。
+    PRIMARY KEY (id),
+    -- アカウントID外部参照制約。
+    CONSTRAINT password_history_account_id_to_accounts FOREIGN KEY (account_id) REFERENCES accounts (id) ON DELETE CASCADE
+);
+-- アカウントのパスワード履歴を新しい順に検索するインデックスを作成
+CREATE INDEX IF NOT EXISTS password_history_account_id_created_at_index ON password_history (account_id, created_at DESC);
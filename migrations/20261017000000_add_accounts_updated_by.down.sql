@@ -0,0 +1 @@
+ALTER TABLE accounts DROP COLUMN IF EXISTS updated_by;
\ No newline at end of file
@@ -0,0 +1,4 @@
+-- 最後にアカウントを更新したアカウントのアカウントIDを記録する列を追加
+-- 更新したアカウントを削除しても記録を残すため、外部キー制約は設定しない
+ALTER TABLE accounts
+ADD COLUMN IF NOT EXISTS updated_by CHAR(26);
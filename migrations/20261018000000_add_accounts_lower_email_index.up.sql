@@ -0,0 +1,3 @@
+-- 大文字と小文字のみが異なるEメールアドレスを、複数のアカウントに登録できないようにするインデックスを作成
+-- 大文字と小文字のみが異なるEメールアドレスが既に登録されている場合は、作成する前に解消する必要がある
+CREATE UNIQUE INDEX IF NOT EXISTS accounts_lower_email_key ON accounts (LOWER(email));
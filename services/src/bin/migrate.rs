@@ -0,0 +1,209 @@
+use anyhow::anyhow;
+use sqlx::{migrate::Migrate, postgres::PgPoolOptions, PgPool};
+
+use adapters::MIGRATOR;
+use common::{EnvValues, ENV_VALUES};
+
+/// マイグレーションのコマンド
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    /// 実行されていないすべてのマイグレーションを実行する。
+    Up,
+    /// 最後に実行したマイグレーションを戻す。
+    Down,
+    /// 登録されていない都道府県を登録する。
+    Seed,
+    /// 正規化されていないアカウントのEメールアドレスを正規化する。
+    NormalizeEmails,
+}
+
+/// コマンドライン引数からマイグレーションのコマンドを取得する。
+///
+/// # Arguments
+///
+/// * `args` - プログラム名を除いたコマンドライン引数。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: マイグレーションのコマンド。引数が指定されていない場合は`Up`。
+/// * `Err`: エラー。
+fn parse_command(args: &[String]) -> anyhow::Result<Command> {
+    match args {
+        [] => Ok(Command::Up),
+        [command] if command == "up" => Ok(Command::Up),
+        [command] if command == "down" => Ok(Command::Down),
+        [command] if command == "seed" => Ok(Command::Seed),
+        [command] if command == "normalize-emails" => Ok(Command::NormalizeEmails),
+        _ => Err(anyhow!(
+            "コマンド({})が不正です。使用方法: migrate [up|down|seed|normalize-emails]",
+            args.join(" ")
+        )),
+    }
+}
+
+/// 最後に実行したマイグレーションを戻す。
+///
+/// # Arguments
+///
+/// * `pool` - コネクションプール。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 戻したマイグレーションのバージョン。実行したマイグレーションが存在しない場合は`None`。
+/// * `Err`: エラー。
+async fn revert_latest(pool: &PgPool) -> anyhow::Result<Option<i64>> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let mut versions: Vec<i64> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+    versions.sort_unstable();
+    let latest = match versions.pop() {
+        Some(latest) => latest,
+        None => return Ok(None),
+    };
+    // 指定したバージョンより後に実行したマイグレーションを戻す
+    MIGRATOR
+        .undo(&mut *conn, versions.last().copied().unwrap_or(0))
+        .await?;
+
+    Ok(Some(latest))
+}
+
+/// マイグレーションを実行する。
+///
+/// # Arguments
+///
+/// * `command` - マイグレーションのコマンド。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: ()
+/// * `Err`: エラー。
+async fn migrate(command: Command) -> anyhow::Result<()> {
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&ENV_VALUES.database_url)
+        .await
+        .map_err(|err| {
+            anyhow!(
+                "環境変数に設定されているDATABASE_URLで、データベースに接続できません。{}",
+                err
+            )
+        })?;
+    match command {
+        Command::Up => {
+            MIGRATOR.run(&pool).await?;
+            println!("すべてのマイグレーションを実行しました。");
+        }
+        Command::Down => match revert_latest(&pool).await? {
+            Some(version) => println!("マイグレーション({})を取り消しました。", version),
+            None => println!("取り消すマイグレーションがありません。"),
+        },
+        Command::Seed => {
+            let inserted = adapters::seed_prefectures().await?;
+            println!("{}件の都道府県を登録しました。", inserted);
+        }
+        Command::NormalizeEmails => {
+            let updated = adapters::normalize_email_addresses().await?;
+            println!("{}件のEメールアドレスを正規化しました。", updated);
+        }
+    }
+    pool.close().await;
+
+    Ok(())
+}
+
+/// マイグレーションのエントリポイント
+///
+/// ```bash
+/// # 実行されていないすべてのマイグレーションを実行
+/// cargo run --bin migrate
+/// # 最後に実行したマイグレーションを戻す
+/// cargo run --bin migrate down
+/// # 登録されていない都道府県を登録
+/// cargo run --bin migrate seed
+/// # 正規化されていないアカウントのEメールアドレスを正規化
+/// cargo run --bin migrate normalize-emails
+/// ```
+#[actix_web::main]
+async fn main() {
+    // 環境変数をロードして、不正な環境変数をすべて出力
+    dotenv::dotenv().ok();
+    if let Err(errors) = EnvValues::load() {
+        for err in errors {
+            eprintln!("{}", err);
+        }
+        std::process::exit(1);
+    }
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match parse_command(&args) {
+        Ok(command) => migrate(command).await,
+        Err(err) => Err(err),
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod migrate_tests {
+    use super::*;
+
+    /// コマンドライン引数からマイグレーションのコマンドを取得できることを確認する。
+    #[test]
+    fn test_parse_command() {
+        let args = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert_eq!(parse_command(&args(&[])).unwrap(), Command::Up);
+        assert_eq!(parse_command(&args(&["up"])).unwrap(), Command::Up);
+        assert_eq!(parse_command(&args(&["down"])).unwrap(), Command::Down);
+        assert_eq!(parse_command(&args(&["seed"])).unwrap(), Command::Seed);
+        assert_eq!(
+            parse_command(&args(&["normalize-emails"])).unwrap(),
+            Command::NormalizeEmails
+        );
+        assert!(parse_command(&args(&["reset"])).is_err());
+        assert!(parse_command(&args(&["up", "down"])).is_err());
+    }
+
+    /// マイグレーションを実行して、テーブルを作成し都道府県を登録することを確認する。
+    ///
+    /// 環境変数`DATABASE_URL`に設定されたデータベースにマイグレーションを実行するため、
+    /// テスト用のデータベースを準備して`cargo test -- --ignored`で実行する。
+    #[actix_web::test]
+    #[ignore]
+    async fn test_migrate_up() {
+        dotenv::dotenv().ok();
+        let pool = PgPoolOptions::new()
+            .max_connections(1)
+            .connect(&ENV_VALUES.database_url)
+            .await
+            .unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+        for table in ["accounts", "prefectures", "jwt_tokens"] {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM information_schema.tables WHERE table_name = $1)",
+            )
+            .bind(table)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            assert!(exists, "{}", table);
+        }
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM prefectures")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 47);
+    }
+}
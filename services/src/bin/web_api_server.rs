@@ -1,14 +1,13 @@
-use std::{
-    self,
-    net::{IpAddr, SocketAddr},
-};
+use std::{self, net::SocketAddr};
 
 use anyhow::anyhow;
 
-use common::ENV_VALUES;
+use common::{jwt_token::validate_jwt_keys, EnvValues, ENV_VALUES};
 
 /// ログの出力方法を設定する。
 ///
+/// ログの出力レベルは、環境変数`RUST_LOG`に設定されたレベルまでに制限する。
+///
 /// # Returns
 ///
 /// `Result`。返却される`Result`の内容は以下の通り。
@@ -17,17 +16,31 @@ use common::ENV_VALUES;
 /// * `Err`: エラー内容。
 fn init_logging() -> anyhow::Result<()> {
     // ロギング設定ファイルを開く。
-    match log4rs::init_file(&ENV_VALUES.log4rs_config, Default::default()) {
-        Ok(_) => Ok(()),
-        Err(err) => Err(anyhow!(
+    if let Err(err) = log4rs::init_file(&ENV_VALUES.log4rs_config, Default::default()) {
+        return Err(anyhow!(
             "ファイル({})からロギング設定を得られません。{:?}",
             ENV_VALUES.log4rs_config,
             err,
-        )),
+        ));
     }
+    // 環境変数に設定されたログレベルを設定
+    let level = ENV_VALUES
+        .log_level
+        .parse::<log::LevelFilter>()
+        .map_err(|_| {
+            anyhow!(
+                "環境変数に設定されているRUST_LOG({})が不正です。",
+                ENV_VALUES.log_level
+            )
+        })?;
+    log::set_max_level(level);
+
+    Ok(())
 }
 
-/// 環境変数からホスト名とポート番号を取得して、Webアプリケーションのソケットアドレスを返却する。
+/// 環境変数からバインドするホストとポート番号を取得して、Webアプリケーションのソケットアドレスを返却する。
+///
+/// 環境変数`WEB_SERVER_BIND`が設定されている場合は、`WEB_SERVER_ADDRESS`と`WEB_SERVER_PORT`より優先する。
 ///
 /// # Returns
 ///
@@ -36,17 +49,68 @@ fn init_logging() -> anyhow::Result<()> {
 /// * `Ok`: ソケットアドレス。
 /// * `Err`: エラー。
 fn server_socket_address() -> anyhow::Result<SocketAddr> {
-    Ok(SocketAddr::new(
-        IpAddr::V4(ENV_VALUES.web_server_address),
-        ENV_VALUES.web_server_port,
-    ))
+    ENV_VALUES
+        .bind_address()
+        .map_err(|err| anyhow!("Web APIサーバーのソケットアドレスを取得できません。{}", err))
+}
+
+/// コマンドライン引数から、起動時にマイグレーションを実行するかを取得する。
+///
+/// # Arguments
+///
+/// * `args` - プログラム名を除いたコマンドライン引数。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: `--migrate`が指定されている場合は`true`。
+/// * `Err`: エラー。
+fn parse_migrate_flag(args: &[String]) -> anyhow::Result<bool> {
+    match args {
+        [] => Ok(false),
+        [flag] if flag == "--migrate" => Ok(true),
+        _ => Err(anyhow!(
+            "コマンドライン引数({})が不正です。使用方法: web_api_server [--migrate]",
+            args.join(" ")
+        )),
+    }
 }
 
 /// Web APIサーバーのエントリポイント
+///
+/// ```bash
+/// # Web APIサーバーを起動
+/// cargo run --bin web_api_server
+/// # 実行されていないマイグレーションを実行してから、Web APIサーバーを起動
+/// cargo run --bin web_api_server -- --migrate
+/// ```
+///
+/// 環境変数`RUN_MIGRATIONS_ON_STARTUP`が`true`の場合は、`--migrate`を指定しなくてもマイグレーションを実行する。
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    // 環境変数をロード
+    // 環境変数をロードして、不正な環境変数をすべて出力
     dotenv::dotenv().ok();
+    if let Err(errors) = EnvValues::load() {
+        for err in errors {
+            eprintln!("{}", err);
+        }
+        std::process::exit(1);
+    }
+    // コマンドライン引数を解釈
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let migrate = match parse_migrate_flag(&args) {
+        Ok(migrate) => migrate || ENV_VALUES.run_migrations_on_startup,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    // JWTの署名と検証に使用する鍵を構築できない場合は起動しない
+    if let Err(err) = validate_jwt_keys() {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
     // 環境変数の内容でロギングを設定
     init_logging().unwrap();
 
@@ -54,7 +118,7 @@ async fn main() -> std::io::Result<()> {
     let address = server_socket_address().unwrap();
 
     // Web APIサーバーを起動
-    let result = adapters::run(&address).await;
+    let result = adapters::run(&address, migrate).await;
     if let Err(err) = result {
         log::error!("{}", err);
         std::process::exit(1);
@@ -62,3 +126,18 @@ async fn main() -> std::io::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod web_api_server_tests {
+    use super::*;
+
+    /// コマンドライン引数から、起動時にマイグレーションを実行するかを取得できることを確認する。
+    #[test]
+    fn test_parse_migrate_flag() {
+        let args = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert!(!parse_migrate_flag(&args(&[])).unwrap());
+        assert!(parse_migrate_flag(&args(&["--migrate"])).unwrap());
+        assert!(parse_migrate_flag(&args(&["--seed"])).is_err());
+        assert!(parse_migrate_flag(&args(&["--migrate", "--migrate"])).is_err());
+    }
+}
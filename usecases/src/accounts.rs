@@ -1,29 +1,53 @@
 use std::borrow::Cow;
 
-use chrono::{DateTime, FixedOffset};
-use sea_orm::{ConnectionTrait, DatabaseConnection, DatabaseTransaction};
+use chrono::{DateTime, Duration, FixedOffset};
+use sea_orm::{DatabaseTransaction, DbErr, IsolationLevel};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
+use common::{
+    jwt_token::{decode_email_change_token, gen_jwt_token, EmailChangeClaims},
+    ENV_VALUES,
+};
 use domains::{
     models::{
         accounts::{
-            optional_phone_number, optional_phone_number_string, Account, AccountId, AccountName,
-            FixedMobileNumbers, HashedPassword, RawPassword,
+            optional_phone_number, Account, AccountId, AccountName, FixedMobileNumbers,
+            FixedMobileNumbersError, HashedPassword, PasswordStrengthError, RawPassword, Role,
         },
+        auth::{JwtTokens, JwtTokensId},
         common::{
-            local_now, Address, AddressDetails, EmailAddress, PhoneNumber, PostalCode, Prefecture,
+            Address, AddressDetails, EmailAddress, EmailNormalization, PhoneNumber, PostalCode,
+            Prefecture,
         },
     },
-    services::auth::verify_password,
+    repositories::accounts::AccountRepository,
+    services::{clock::Clock, hashers::PasswordHasher},
+};
+
+use crate::{
+    database_service::DatabaseService,
+    events::AccountEventSink,
+    queries::{AccountSearch, AccountTokens, Page, Pagination},
+    transaction::{
+        begin_transaction, is_retryable_error, is_unique_violation, with_transaction_retry,
+        RetryableError,
+    },
 };
 
-use crate::database_service::DatabaseService;
+/// アカウントユースケースのトランザクションの分離レベル。
+///
+/// `None`の場合はデータベースの既定の分離レベル(PostgreSQLはREAD COMMITTED)で実行する。
+/// 環境変数`TRANSACTION_ISOLATION_LEVEL`が設定されている場合は、環境変数の分離レベルで実行する。
+const TRANSACTION_ISOLATION_LEVEL: Option<IsolationLevel> = None;
 
 /// アカウントユースケースエラー区分
 #[derive(Debug, Clone)]
 pub enum ErrorKind {
     /// サーバー内部エラー
     InternalServerError,
+    /// シリアライゼーションの失敗またはデッドロックで、トランザクションが中断された
+    TransactionAborted,
     /// アカウントが見つからない
     NotFound,
     /// 都道府県が見つからない
@@ -36,6 +60,14 @@ pub enum ErrorKind {
     InvalidName,
     /// パスワードが不正
     InvalidPassword,
+    /// パスワードがよく使用されるパスワード
+    CommonPassword,
+    /// パスワードがEメールアドレスを含んでいる
+    PasswordContainsEmail,
+    /// パスワードがアカウント名を含んでいる
+    PasswordContainsName,
+    /// パスワードに同じ文字が連続している
+    PasswordHasRepeatedCharacters,
     /// パスワードが間違っている
     WrongPassword,
     /// 固定電話番号が不正
@@ -52,6 +84,18 @@ pub enum ErrorKind {
     InvalidOldPassword,
     /// 新しいパスワードが不正
     InvalidNewPassword,
+    /// 新しいパスワードが、現在または最近設定されていたパスワード
+    PasswordReused,
+    /// Eメールアドレスが既に登録されている
+    EmailAddressAlreadyExists,
+    /// 一括登録するアカウントが多すぎる
+    TooManyAccounts,
+    /// Eメールアドレス変更トークンが不正、有効期限切れまたは使用済み
+    InvalidEmailChangeToken,
+    /// アカウントが他のリクエストで更新されている
+    Conflict,
+    /// インポートするCSVが不正
+    InvalidCsv,
 }
 
 /// アカウントユースケースエラー
@@ -61,77 +105,144 @@ pub struct Error {
     pub code: ErrorKind,
     /// エラーメッセージ。
     pub message: Cow<'static, str>,
+    /// フィールドの検証エラーのリスト。
+    ///
+    /// 複数のフィールドを検証した場合に、検証に失敗したすべてのフィールドのエラーを格納する。
+    /// このとき、`code`と`message`には、最初に検証に失敗したフィールドのエラーを格納する。
+    pub field_errors: Vec<FieldError>,
+}
+
+impl RetryableError for Error {
+    fn is_retryable(&self) -> bool {
+        matches!(self.code, ErrorKind::TransactionAborted)
+    }
+}
+
+impl From<DbErr> for Error {
+    fn from(err: DbErr) -> Self {
+        internal_error(Box::new(err))
+    }
+}
+
+/// フィールド検証エラー
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    /// 検証に失敗したフィールドの名前。
+    pub field: String,
+    /// エラーメッセージ。
+    pub message: String,
 }
 
 /// アカウントデータトランスファーオブジェクト
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AccountDto {
     /// アカウントID。
-    pub id: String,
+    #[schema(value_type = String)]
+    pub id: AccountId,
     /// Eメールアドレス。
-    pub email: String,
+    #[schema(value_type = String)]
+    pub email: EmailAddress,
     /// アカウント名。
-    pub name: String,
+    #[schema(value_type = String)]
+    pub name: AccountName,
     /// アクティブフラグ。
     pub is_active: bool,
+    /// 権限。
+    pub role: Role,
     /// 固定電話番号。
-    pub fixed_number: Option<String>,
+    #[schema(value_type = Option<String>)]
+    pub fixed_number: Option<PhoneNumber>,
     /// 携帯電話番号。
-    pub mobile_number: Option<String>,
+    #[schema(value_type = Option<String>)]
+    pub mobile_number: Option<PhoneNumber>,
     /// 郵便番号。
-    pub postal_code: String,
+    #[schema(value_type = String)]
+    pub postal_code: PostalCode,
     /// 都道府県コード。
     pub prefecture_code: u8,
+    /// 都道府県名。
+    pub prefecture_name: String,
     /// 市区町村以下住所。
-    pub address_details: String,
+    #[schema(value_type = String)]
+    pub address_details: AddressDetails,
     /// 最終ログイン日時。
     pub logged_in_at: Option<DateTime<FixedOffset>>,
     /// 登録日時。
     pub created_at: DateTime<FixedOffset>,
     /// 更新日時。
     pub updated_at: DateTime<FixedOffset>,
+    /// 最後にアカウントを更新したアカウントのアカウントID。
+    #[schema(value_type = Option<String>)]
+    pub updated_by: Option<AccountId>,
 }
 
 #[allow(clippy::from_over_into)]
 impl Into<AccountDto> for Account {
     fn into(self) -> AccountDto {
         AccountDto {
-            id: self.id().value.to_string(),
-            email: self.email().value(),
-            name: self.name().value(),
+            id: self.id(),
+            email: self.email(),
+            name: self.name(),
             is_active: self.is_active(),
-            fixed_number: optional_phone_number_string(self.phone_numbers().fixed()),
-            mobile_number: optional_phone_number_string(self.phone_numbers().mobile()),
-            postal_code: self.postal_code().value(),
+            role: self.role(),
+            fixed_number: self.phone_numbers().fixed(),
+            mobile_number: self.phone_numbers().mobile(),
+            postal_code: self.postal_code(),
             prefecture_code: self.address().prefecture().code(),
-            address_details: self.address().details().value(),
+            prefecture_name: self.address().prefecture().name(),
+            address_details: self.address().details(),
             logged_in_at: self.logged_in_at(),
             created_at: self.created_at(),
             updated_at: self.updated_at(),
+            updated_by: self.updated_by(),
         }
     }
 }
 
-/// トランザクションを開始する。
-///
-/// # Arguments
-///
-/// * `conn` - データベースコネクション。
+/// セッションデータトランスファーオブジェクト
 ///
-/// # Returns
-///
-/// `Result`。返却される`Result`の内容は以下の通り。
-///
-/// * `Ok`: データベーストランザクション。
-/// * `Err`: エラー。
-async fn begin_transaction(conn: &DatabaseConnection) -> Result<DatabaseTransaction, Error> {
-    let txn = conn.begin().await;
-    if let Err(err) = txn {
-        return Err(internal_error(Box::new(err)));
+/// アカウントに発行したトークンの識別子と有効期限を格納して、トークンの文字列は格納しない。
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDto {
+    /// トークンID。
+    #[schema(value_type = String)]
+    pub id: JwtTokensId,
+    /// アクセストークン有効期限。
+    pub access_expired_at: DateTime<FixedOffset>,
+    /// リフレッシュトークン有効期限。
+    pub refresh_expired_at: DateTime<FixedOffset>,
+}
+
+impl From<&JwtTokens> for SessionDto {
+    fn from(tokens: &JwtTokens) -> Self {
+        Self {
+            id: tokens.id(),
+            access_expired_at: tokens.access().expired_at,
+            refresh_expired_at: tokens.refresh().expired_at,
+        }
     }
+}
+
+/// アカウントとセッションデータトランスファーオブジェクト
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSessionsDto {
+    /// アカウント。
+    pub account: AccountDto,
+    /// 最も新しく発行した有効期限が切れていないトークン。トークンがない場合は`None`。
+    pub session: Option<SessionDto>,
+}
 
-    Ok(txn.unwrap())
+impl From<AccountTokens> for AccountSessionsDto {
+    fn from(value: AccountTokens) -> Self {
+        Self {
+            session: value.tokens.as_ref().map(SessionDto::from),
+            account: value.account.into(),
+        }
+    }
 }
 
 /// 都道府県を取得する。
@@ -182,9 +293,26 @@ async fn retrieve_prefecture(
 ///
 /// 内部サーバーエラー。
 fn internal_error(err: Box<dyn std::error::Error>) -> Error {
+    // 事前の確認をすり抜けた同時登録などで一意制約に違反した場合は、データベースのエラーを返却せずに競合にする
+    if is_unique_violation(err.as_ref()) {
+        log::warn!("{}", err);
+        return usecases_error(
+            ErrorKind::Conflict,
+            "同じEメールアドレスまたはIDのアカウントが既に登録されています。".into(),
+        );
+    }
+    // シリアライゼーションの失敗またはデッドロックの場合は、トランザクションを再試行できるエラーにする
+    let code = if is_retryable_error(err.as_ref()) {
+        log::warn!("{}", err);
+        ErrorKind::TransactionAborted
+    } else {
+        log::error!("{}", err);
+        ErrorKind::InternalServerError
+    };
     Error {
-        code: ErrorKind::InternalServerError,
+        code,
         message: format!("{}", err).into(),
+        field_errors: vec![],
     }
 }
 
@@ -199,7 +327,61 @@ fn internal_error(err: Box<dyn std::error::Error>) -> Error {
 ///
 /// ユースケースエラー。
 fn usecases_error(code: ErrorKind, message: Cow<'static, str>) -> Error {
-    Error { code, message }
+    Error {
+        code,
+        message,
+        field_errors: vec![],
+    }
+}
+
+/// フィールドの検証結果を確認して、検証に失敗した場合はエラーを記録する。
+///
+/// # Arguments
+///
+/// * `errors` - フィールド名と検証エラーのリスト。
+/// * `field` - 検証したフィールドの名前。
+/// * `result` - フィールドの検証結果。
+///
+/// # Returns
+///
+/// 検証に成功した場合は検証した値。失敗した場合は`None`。
+fn collect_field_error<T>(
+    errors: &mut Vec<(&'static str, Error)>,
+    field: &'static str,
+    result: Result<T, Error>,
+) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(err) => {
+            errors.push((field, err));
+            None
+        }
+    }
+}
+
+/// 記録したフィールドの検証エラーから、ユースケースエラーを生成する。
+///
+/// # Arguments
+///
+/// * `errors` - フィールド名と検証エラーのリスト。少なくとも1つの検証エラーを含んでいなければならない。
+///
+/// # Returns
+///
+/// 最初に検証に失敗したフィールドのエラー区分とエラーメッセージに、すべてのフィールドの検証エラーを格納したユースケースエラー。
+fn field_validation_error(errors: Vec<(&'static str, Error)>) -> Error {
+    let field_errors = errors
+        .iter()
+        .map(|(field, err)| FieldError {
+            field: field.to_string(),
+            message: err.message.to_string(),
+        })
+        .collect();
+    let (_, first) = errors.into_iter().next().unwrap();
+
+    Error {
+        field_errors,
+        ..first
+    }
 }
 
 /// アカウントを検索する。
@@ -221,8 +403,25 @@ async fn find_account(
     txn: &DatabaseTransaction,
     id: AccountId,
 ) -> Result<Account, Error> {
+    find_account_in(&*db_service.account(txn), id).await
+}
+
+/// 指定されたアカウントリポジトリで、アカウントを検索する。
+///
+/// # Arguments
+///
+/// * `repo` - アカウントリポジトリ。
+/// * `id` - アカウントID。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: アカウント。
+/// * `Err`: エラー。アカウントが見つからない場合のエラー区分は`NotFound`。
+async fn find_account_in(repo: &dyn AccountRepository, id: AccountId) -> Result<Account, Error> {
     // アカウントを検索
-    let result = db_service.account(txn).find_by_id(id.clone()).await;
+    let result = repo.find_by_id(id.clone()).await;
     if let Err(err) = result {
         return Err(internal_error(err.into()));
     }
@@ -231,11 +430,7 @@ async fn find_account(
     if result.is_none() {
         return Err(usecases_error(
             ErrorKind::NotFound,
-            format!(
-                "アカウントID({})と一致するアカウントが見つかりません。",
-                id.value.to_string()
-            )
-            .into(),
+            format!("アカウントID({})と一致するアカウントが見つかりません。", id).into(),
         ));
     }
 
@@ -259,29 +454,176 @@ pub async fn find_by_id(
     db_service: &dyn DatabaseService,
     id: AccountId,
 ) -> Result<AccountDto, Error> {
+    // 読み取りのみのため、トランザクションを開始せずにアカウントを取得
+    let account = find_account_in(&*db_service.account_reader(), id).await?;
+
+    Ok(account.into())
+}
+
+/// 指定されたEメールアドレスと一致するアカウントを返却する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `email` - Eメールアドレス。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: アカウント。
+/// * `Err`: エラー。Eメールアドレスが不正な場合のエラー区分は`InvalidEmailAddress`、
+///   アカウントが見つからない場合のエラー区分は`NotFound`。
+pub async fn find_by_email(
+    db_service: &dyn DatabaseService,
+    email: &str,
+) -> Result<AccountDto, Error> {
+    let email = to_email(email)?;
+    // 読み取りのみのため、トランザクションを開始せずにアカウントを取得
+    match db_service
+        .account_reader()
+        .find_by_email(email.clone())
+        .await
+    {
+        Ok(Some(account)) => Ok(account.into()),
+        Ok(None) => Err(usecases_error(
+            ErrorKind::NotFound,
+            format!(
+                "Eメールアドレス({})と一致するアカウントが見つかりません。",
+                email.value()
+            )
+            .into(),
+        )),
+        Err(err) => Err(internal_error(err.into())),
+    }
+}
+
+/// 正規化されていないEメールアドレスで記録されているアカウントのEメールアドレスを、正規化したEメールアドレスに更新する。
+///
+/// Eメールアドレスを正規化する前に登録されたアカウントを揃えるために実行する。
+/// 更新するEメールアドレスはないため、繰り返し実行できる。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: Eメールアドレスを更新したアカウントの数。
+/// * `Err`: エラー。
+pub async fn normalize_email_addresses(db_service: &dyn DatabaseService) -> anyhow::Result<u64> {
+    let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL).await?;
+    let updated = db_service
+        .account(&txn)
+        .normalize_email_addresses(email_normalization())
+        .await?;
+    txn.commit().await?;
+
+    Ok(updated)
+}
+
+/// 指定されたアカウントIDと一致するアカウントと、アカウントのセッションを返却する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `id` - アカウントID。
+/// * `include_inactive` - 非アクティブなアカウントも返却する場合は`true`。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: アカウントとセッション。
+/// * `Err`: エラー。アカウントが見つからない場合のエラー区分は`NotFound`。
+pub async fn find_with_tokens(
+    db_service: &dyn DatabaseService,
+    id: AccountId,
+    include_inactive: bool,
+) -> Result<AccountSessionsDto, Error> {
     // トランザクションを開始
-    let txn = begin_transaction(&db_service.connection()).await?;
-    // アカウントを取得
-    let account = find_account(db_service, &txn, id.clone()).await?;
+    let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL).await?;
+    // アカウントとトークンを取得
+    let result = {
+        let service = db_service.account_service(&txn);
+        if include_inactive {
+            service.find_account_by_id(id.clone()).await
+        } else {
+            service.find_active_account_by_id(id.clone()).await
+        }
+    };
+    let account_tokens = match result {
+        Ok(Some(account_tokens)) => account_tokens,
+        Ok(None) => {
+            return Err(usecases_error(
+                ErrorKind::NotFound,
+                format!("アカウントID({})と一致するアカウントが見つかりません。", id).into(),
+            ))
+        }
+        Err(err) => return Err(internal_error(err.into())),
+    };
     // トランザクションをコミット
     match txn.commit().await {
-        Ok(_) => Ok(account.into()),
+        Ok(_) => Ok(account_tokens.into()),
         Err(err) => Err(internal_error(err.into())),
     }
 }
 
-fn to_account_id(value: &str) -> Result<AccountId, Error> {
-    match AccountId::try_from(value) {
-        Ok(value) => Ok(value),
-        Err(e) => Err(usecases_error(
-            ErrorKind::InvalidAccountId,
-            format!("{}", e).into(),
-        )),
+/// 検索条件に一致するアカウントを、登録日時順に返却する。
+///
+/// アカウント名とEメールアドレスを検索する文字列のみを指定した場合は、すべてのアカウントを走査する可能性があるため、
+/// 警告をログに出力する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `search` - 検索条件。
+/// * `pagination` - ページ指定。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: アカウントのページ。
+/// * `Err`: エラー。
+pub async fn search(
+    db_service: &dyn DatabaseService,
+    search: AccountSearch,
+    pagination: Pagination,
+) -> anyhow::Result<Page<AccountDto>> {
+    if let Some(text) = search.text() {
+        if !search.has_filters() {
+            log::warn!(
+                "アカウント名とEメールアドレスを、他の検索条件を指定せずに検索しています(q: {})。",
+                text
+            );
+        }
+    }
+    let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL).await?;
+    let result = db_service
+        .account_service(&txn)
+        .search(search, pagination)
+        .await?;
+    txn.commit().await?;
+
+    Ok(result.map(|account| account.into()))
+}
+
+/// 環境変数`EMAIL_LOWERCASE_LOCAL_PART`から、Eメールアドレス正規化方針を構築する。
+///
+/// # Returns
+///
+/// Eメールアドレス正規化方針。
+pub fn email_normalization() -> EmailNormalization {
+    EmailNormalization {
+        lowercase_local_part: ENV_VALUES.email_lowercase_local_part,
     }
 }
 
 fn to_email(value: &str) -> Result<EmailAddress, Error> {
-    match EmailAddress::new(value) {
+    match EmailAddress::normalized(value, email_normalization()) {
         Ok(value) => Ok(value),
         Err(e) => Err(usecases_error(
             ErrorKind::InvalidEmailAddress,
@@ -300,13 +642,46 @@ fn to_name(value: &str) -> Result<AccountName, Error> {
     }
 }
 
-fn to_raw_password(value: &str) -> Result<RawPassword, Error> {
-    match RawPassword::new(value) {
+fn to_raw_password(
+    value: &str,
+    email: Option<&EmailAddress>,
+    name: Option<&AccountName>,
+    code: ErrorKind,
+) -> Result<RawPassword, Error> {
+    match RawPassword::new_with_context(value, email, name) {
         Ok(value) => Ok(value),
-        Err(err) => Err(usecases_error(
-            ErrorKind::InvalidPassword,
-            format!("{}", err).into(),
-        )),
+        Err(err) => {
+            let code = match err.downcast_ref::<PasswordStrengthError>() {
+                Some(PasswordStrengthError::Common) => ErrorKind::CommonPassword,
+                Some(PasswordStrengthError::ContainsEmail) => ErrorKind::PasswordContainsEmail,
+                Some(PasswordStrengthError::ContainsName) => ErrorKind::PasswordContainsName,
+                Some(PasswordStrengthError::RepeatedCharacters) => {
+                    ErrorKind::PasswordHasRepeatedCharacters
+                }
+                None => code,
+            };
+            Err(usecases_error(code, format!("{}", err).into()))
+        }
+    }
+}
+
+/// パスワードをハッシュ化する。
+///
+/// # Arguments
+///
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `raw` - パスワード。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: ハッシュ化したパスワード。
+/// * `Err`: パスワードのハッシュ化に失敗した場合は、サーバー内部エラー。
+fn hash_password(hasher: &dyn PasswordHasher, raw: &RawPassword) -> Result<HashedPassword, Error> {
+    match HashedPassword::new(raw.clone(), hasher) {
+        Ok(value) => Ok(value),
+        Err(err) => Err(internal_error(err.into())),
     }
 }
 
@@ -330,10 +705,14 @@ fn to_phone_numbers(
 ) -> Result<FixedMobileNumbers, Error> {
     match FixedMobileNumbers::new(fixed, mobile) {
         Ok(value) => Ok(value),
-        Err(err) => Err(usecases_error(
-            ErrorKind::InvalidPhoneNumbers,
-            format!("{}", err).into(),
-        )),
+        Err(err) => {
+            let code = match err.downcast_ref::<FixedMobileNumbersError>() {
+                Some(FixedMobileNumbersError::MobileInFixed) => ErrorKind::InvalidFixedNumber,
+                Some(FixedMobileNumbersError::LandlineInMobile) => ErrorKind::InvalidMobileNumber,
+                _ => ErrorKind::InvalidPhoneNumbers,
+            };
+            Err(usecases_error(code, format!("{}", err).into()))
+        }
     }
 }
 
@@ -347,8 +726,32 @@ fn to_postal_code(value: &str) -> Result<PostalCode, Error> {
     }
 }
 
-fn to_address_details(value: &str) -> Result<AddressDetails, Error> {
-    match AddressDetails::new(value) {
+/// 市区町村以下住所を構築する。
+///
+/// # Arguments
+///
+/// * `value` - 市区町村以下住所。
+/// * `prefecture_code` - 都道府県コード。
+/// * `strict` - 市区町村以下住所が、都道府県コードと矛盾する都道府県名で始まっていないか確認する場合は`true`。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 市区町村以下住所。
+/// * `Err`: エラー。
+fn to_address_details(
+    value: &str,
+    prefecture_code: u8,
+    strict: bool,
+) -> Result<AddressDetails, Error> {
+    let result = AddressDetails::new(value).and_then(|details| {
+        if strict {
+            details.check_prefecture(prefecture_code)?;
+        }
+        Ok(details)
+    });
+    match result {
         Ok(value) => Ok(value),
         Err(err) => Err(usecases_error(
             ErrorKind::InvalidAddressDetails,
@@ -358,7 +761,7 @@ fn to_address_details(value: &str) -> Result<AddressDetails, Error> {
 }
 
 /// 新規アカウント
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct NewAccount {
     /// Eメールアドレス。
@@ -381,255 +784,2218 @@ pub struct NewAccount {
     pub address_details: String,
 }
 
-/// アカウントを登録する。
+impl NewAccount {
+    /// 新規アカウントを検証する。
+    ///
+    /// アカウントの登録と一括登録で実施する検証と同じ検証を、データベースにアクセスせずに実施する。
+    /// 最初に検証に失敗したフィールドで検証を中断せずに、すべてのフィールドを検証する。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: ()
+    /// * `Err`: 検証に失敗したすべてのフィールドの検証エラーのリスト。
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        validate_new_account(self)
+            .map(|_| ())
+            .map_err(|err| err.field_errors)
+    }
+}
+
+/// 検証済み新規アカウント
+#[derive(Clone)]
+struct ValidatedNewAccount {
+    /// Eメールアドレス。
+    email: EmailAddress,
+    /// アカウント名。
+    name: AccountName,
+    /// パスワード。
+    raw_password: RawPassword,
+    /// アクティブフラグ。
+    is_active: bool,
+    /// 固定携帯電話番号。
+    phone_numbers: FixedMobileNumbers,
+    /// 郵便番号。
+    postal_code: PostalCode,
+    /// 都道府県コード。
+    prefecture_code: u8,
+    /// 市区町村以下住所。
+    address_details: AddressDetails,
+}
+
+/// 新規アカウントを検証する。
+///
+/// 最初に検証に失敗したフィールドで検証を中断せずに、すべてのフィールドを検証する。
+/// 都道府県の存在など、データベースにアクセスする検証は、この関数では実施しない。
 ///
 /// # Arguments
 ///
-/// * `db_service` - データベースサービス。
-/// * `new` - 登録するアカウント。
+/// * `new` - 新規アカウント。
 ///
 /// # Returns
 ///
 /// `Result`。返却される`Result`の内容は以下の通り。
 ///
-/// * `Ok`: 登録したアカウント。
-/// * `Err`: エラー。
-pub async fn insert(
-    db_service: &dyn DatabaseService,
-    new: NewAccount,
-) -> Result<AccountDto, Error> {
-    // 返却するアカウント
-    let new_account: Account;
-    // アカウントに設定する値を生成
-    let email = to_email(&new.email)?;
-    let name = to_name(&new.name)?;
-    let raw_password = to_raw_password(&new.password)?;
-    let fixed_number = to_phone_number(new.fixed_number.as_deref(), "fixed")?;
-    let mobile_number = to_phone_number(new.mobile_number.as_deref(), "mobile")?;
-    let phone_numbers = to_phone_numbers(fixed_number, mobile_number)?;
-    let postal_code = to_postal_code(&new.postal_code)?;
-    let address_details = to_address_details(&new.address_details)?;
-    // トランザクションを開始
-    let txn = begin_transaction(&db_service.connection()).await?;
-    {
-        // アカウントに記録されていた都道府県コードから都道府県を取得
-        let prefecture = retrieve_prefecture(db_service, &txn, new.prefecture_code).await?;
-        // 登録するアカウントを生成
-        let account = Account::new(
+/// * `Ok`: 検証済み新規アカウント。
+/// * `Err`: 検証に失敗したすべてのフィールドの検証エラーを格納したエラー。
+fn validate_new_account(new: &NewAccount) -> Result<ValidatedNewAccount, Error> {
+    let mut errors = vec![];
+    let fixed_number = collect_field_error(
+        &mut errors,
+        "fixedNumber",
+        to_phone_number(new.fixed_number.as_deref(), "fixed"),
+    );
+    let mobile_number = collect_field_error(
+        &mut errors,
+        "mobileNumber",
+        to_phone_number(new.mobile_number.as_deref(), "mobile"),
+    );
+    let email = collect_field_error(&mut errors, "email", to_email(&new.email));
+    let name = collect_field_error(&mut errors, "name", to_name(&new.name));
+    let raw_password = collect_field_error(
+        &mut errors,
+        "password",
+        to_raw_password(
+            &new.password,
+            email.as_ref(),
+            name.as_ref(),
+            ErrorKind::InvalidPassword,
+        ),
+    );
+    // 固定電話番号と携帯電話番号の組み合わせは、両方の電話番号の検証に成功した場合のみ検証
+    let phone_numbers = match (fixed_number, mobile_number) {
+        (Some(fixed_number), Some(mobile_number)) => {
+            let result = to_phone_numbers(fixed_number, mobile_number);
+            let field = match result.as_ref().map_err(|err| &err.code) {
+                Err(ErrorKind::InvalidFixedNumber) => "fixedNumber",
+                Err(ErrorKind::InvalidMobileNumber) => "mobileNumber",
+                _ => "phoneNumbers",
+            };
+            collect_field_error(&mut errors, field, result)
+        }
+        _ => None,
+    };
+    let postal_code =
+        collect_field_error(&mut errors, "postalCode", to_postal_code(&new.postal_code));
+    let address_details = collect_field_error(
+        &mut errors,
+        "addressDetails",
+        to_address_details(
+            &new.address_details,
+            new.prefecture_code,
+            ENV_VALUES.strict_address_check,
+        ),
+    );
+
+    match (
+        email,
+        name,
+        raw_password,
+        phone_numbers,
+        postal_code,
+        address_details,
+    ) {
+        (
+            Some(email),
+            Some(name),
+            Some(raw_password),
+            Some(phone_numbers),
+            Some(postal_code),
+            Some(address_details),
+        ) => Ok(ValidatedNewAccount {
             email,
             name,
             raw_password,
-            new.is_active,
+            is_active: new.is_active,
             phone_numbers,
             postal_code,
-            Address::new(prefecture, address_details),
-        );
-        // アカウントを登録
-        let account_repo = db_service.account(&txn);
-        let result = account_repo.insert(&account).await;
-        if let Err(err) = result {
-            return Err(internal_error(err.into()));
-        }
-        new_account = result.unwrap();
-    }
-    // トランザクションをコミット
-    match txn.commit().await {
-        Ok(_) => Ok(new_account.into()),
-        Err(err) => Err(internal_error(err.into())),
+            prefecture_code: new.prefecture_code,
+            address_details,
+        }),
+        _ => Err(field_validation_error(errors)),
     }
 }
 
-/// 更新アカウント
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct UpdateAccount {
-    /// アカウントID。
-    pub id: String,
-    /// アカウント名。
-    pub name: String,
-    /// アクティブフラグ。
-    pub is_active: bool,
-    /// 固定電話番号。
-    pub fixed_number: Option<String>,
-    /// 携帯電話番号。
-    pub mobile_number: Option<String>,
-    /// 郵便番号。
-    pub postal_code: String,
-    /// 都道府県コード。
-    pub prefecture_code: u8,
-    /// 市区町村以下住所。
-    pub address_details: String,
-}
-
-/// アカウントを更新する。
+/// 検証済み新規アカウントをアカウントとして登録する。
 ///
 /// # Arguments
 ///
-/// * `db_service`: データベースサービス。
-/// * `account`: 更新するアカウント。
+/// * `db_service` - データベースサービス。
+/// * `txn` - データベーストランザクション。
+/// * `new` - 検証済み新規アカウント。
+/// * `password` - 検証済み新規アカウントのパスワードをハッシュ化したパスワード。
+/// * `now` - 登録日時に記録する現在日時。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 登録したアカウント。
+/// * `Err`: エラー。
+async fn insert_account(
+    db_service: &dyn DatabaseService,
+    txn: &DatabaseTransaction,
+    new: ValidatedNewAccount,
+    password: HashedPassword,
+    now: DateTime<FixedOffset>,
+) -> Result<Account, Error> {
+    // アカウントに記録されていた都道府県コードから都道府県を取得
+    let prefecture = retrieve_prefecture(db_service, txn, new.prefecture_code).await?;
+    // 登録するアカウントを生成
+    let account = Account::new(
+        new.email,
+        new.name,
+        password,
+        new.is_active,
+        new.phone_numbers,
+        new.postal_code,
+        Address::new(prefecture, new.address_details),
+        now,
+    );
+    // アカウントを登録
+    let account_repo = db_service.account(txn);
+    match account_repo.insert(&account).await {
+        Ok(account) => Ok(account),
+        Err(err) => Err(internal_error(err.into())),
+    }
+}
+
+/// アカウントを登録する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `event_sink` - アカウントイベントシンク。
+/// * `new` - 登録するアカウント。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 登録したアカウント。
+/// * `Err`: エラー。
+pub async fn insert(
+    db_service: &dyn DatabaseService,
+    clock: &dyn Clock,
+    hasher: &dyn PasswordHasher,
+    event_sink: &dyn AccountEventSink,
+    new: NewAccount,
+) -> Result<AccountDto, Error> {
+    // アカウントに設定する値を検証
+    let new = validate_new_account(&new)?;
+    // パスワードをハッシュ化
+    let password = hash_password(hasher, &new.raw_password)?;
+    // アカウントを登録して、トランザクションが中断された場合は再試行
+    let now = clock.now();
+    let new_account = with_transaction_retry(
+        db_service.connection(),
+        TRANSACTION_ISOLATION_LEVEL,
+        ENV_VALUES.transaction_max_retries,
+        |txn| {
+            let (new, password) = (new.clone(), password.clone());
+            Box::pin(async move {
+                // 正規化する前に記録されたEメールアドレスを含めて、既に登録されていないか確認
+                // (大文字と小文字のみが異なるEメールアドレスは、データベースの一意インデックスでも拒否する)
+                ensure_email_available(db_service, &txn, &new.email).await?;
+                insert_account(db_service, &txn, new, password, now).await
+            })
+        },
+    )
+    .await?;
+    // アカウントの登録を通知
+    let new_account: AccountDto = new_account.into();
+    event_sink.on_account_created(&new_account);
+
+    Ok(new_account)
+}
+
+/// 呼び出し元が開始したトランザクションで、アカウントを登録する。
+///
+/// トランザクションのコミットとロールバックは呼び出し元が行う。
+/// コミットする前にアカウントの登録を通知しないように、アカウントイベントシンクには通知しないため、
+/// 必要な場合は呼び出し元がコミットした後に通知する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `txn` - データベーストランザクション。
+/// * `clock` - 時計。
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `new` - 登録するアカウント。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 登録したアカウント。
+/// * `Err`: エラー。
+pub async fn insert_in_txn(
+    db_service: &dyn DatabaseService,
+    txn: &DatabaseTransaction,
+    clock: &dyn Clock,
+    hasher: &dyn PasswordHasher,
+    new: NewAccount,
+) -> Result<AccountDto, Error> {
+    let new = validate_new_account(&new)?;
+    let password = hash_password(hasher, &new.raw_password)?;
+    ensure_email_available(db_service, txn, &new.email).await?;
+
+    insert_account(db_service, txn, new, password, clock.now())
+        .await
+        .map(Into::into)
+}
+
+/// 一括登録できるアカウントの最大数。
+pub const MAX_BULK_INSERT_ACCOUNTS: usize = 1000;
+
+/// アカウント一括登録エラー
+#[derive(Debug, Clone)]
+pub struct BulkInsertError {
+    /// エラーが発生したアカウントのインデックス。特定のアカウントに起因しない場合は`None`。
+    pub index: Option<usize>,
+    /// エラー。
+    pub error: Error,
+}
+
+impl BulkInsertError {
+    /// 指定されたインデックスのアカウントで発生したエラーを生成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - エラーが発生したアカウントのインデックス。
+    ///
+    /// # Returns
+    ///
+    /// エラーを変換するクロージャー。
+    fn at(index: usize) -> impl FnOnce(Error) -> Self {
+        move |error| Self {
+            index: Some(index),
+            error,
+        }
+    }
+}
+
+impl From<Error> for BulkInsertError {
+    fn from(error: Error) -> Self {
+        Self { index: None, error }
+    }
+}
+
+/// 一括登録したアカウント
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkInsertedAccounts {
+    /// 登録したアカウントの数。
+    pub inserted: usize,
+    /// 登録したアカウントのアカウントIDのリスト。
+    #[schema(value_type = Vec<String>)]
+    pub ids: Vec<AccountId>,
+}
+
+/// 複数のアカウントを単一のトランザクションで一括登録する。
+///
+/// いずれかのアカウントの検証または登録に失敗した場合は、すべてのアカウントを登録しない。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `event_sink` - アカウントイベントシンク。
+/// * `accounts` - 登録するアカウントのリスト。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 一括登録したアカウント。
+/// * `Err`: エラーと、エラーが発生したアカウントのインデックス。
+pub async fn bulk_insert(
+    db_service: &dyn DatabaseService,
+    clock: &dyn Clock,
+    hasher: &dyn PasswordHasher,
+    event_sink: &dyn AccountEventSink,
+    accounts: Vec<NewAccount>,
+) -> Result<BulkInsertedAccounts, BulkInsertError> {
+    // 登録するアカウントの数を検証
+    if MAX_BULK_INSERT_ACCOUNTS < accounts.len() {
+        return Err(usecases_error(
+            ErrorKind::TooManyAccounts,
+            format!(
+                "一括登録できるアカウントは{}件までです(指定された件数: {})。",
+                MAX_BULK_INSERT_ACCOUNTS,
+                accounts.len()
+            )
+            .into(),
+        )
+        .into());
+    }
+    // すべてのアカウントに設定する値を検証
+    let mut validated = Vec::with_capacity(accounts.len());
+    for (index, new) in accounts.iter().enumerate() {
+        let new = validate_new_account(new).map_err(BulkInsertError::at(index))?;
+        // 一括登録するアカウントの間でEメールアドレスが重複していないか確認
+        if validated
+            .iter()
+            .any(|v: &ValidatedNewAccount| v.email == new.email)
+        {
+            return Err(BulkInsertError::at(index)(email_already_exists(&new.email)));
+        }
+        validated.push(new);
+    }
+
+    insert_validated_accounts(db_service, clock, hasher, event_sink, validated).await
+}
+
+/// 検証済みの複数のアカウントを単一のトランザクションで登録する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `event_sink` - アカウントイベントシンク。
+/// * `validated` - 登録する検証済み新規アカウントのリスト。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 一括登録したアカウント。
+/// * `Err`: エラーと、エラーが発生したアカウントのインデックス。
+async fn insert_validated_accounts(
+    db_service: &dyn DatabaseService,
+    clock: &dyn Clock,
+    hasher: &dyn PasswordHasher,
+    event_sink: &dyn AccountEventSink,
+    validated: Vec<ValidatedNewAccount>,
+) -> Result<BulkInsertedAccounts, BulkInsertError> {
+    // すべてのアカウントのパスワードをハッシュ化
+    let mut passwords = Vec::with_capacity(validated.len());
+    for (index, new) in validated.iter().enumerate() {
+        passwords
+            .push(hash_password(hasher, &new.raw_password).map_err(BulkInsertError::at(index))?);
+    }
+    // 一括登録するアカウントの登録日時を揃える
+    let now = clock.now();
+    // トランザクションを開始
+    let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL)
+        .await
+        .map_err(Error::from)?;
+    // アカウントを登録
+    let mut new_accounts: Vec<AccountDto> = Vec::with_capacity(validated.len());
+    for (index, (new, password)) in validated.into_iter().zip(passwords).enumerate() {
+        // Eメールアドレスが既に登録されていないか確認
+        match db_service
+            .account(&txn)
+            .find_by_email(new.email.clone())
+            .await
+        {
+            Ok(None) => {}
+            Ok(Some(_)) => {
+                return Err(BulkInsertError::at(index)(email_already_exists(&new.email)))
+            }
+            Err(err) => return Err(BulkInsertError::at(index)(internal_error(err.into()))),
+        }
+        let account = insert_account(db_service, &txn, new, password, now)
+            .await
+            .map_err(BulkInsertError::at(index))?;
+        new_accounts.push(account.into());
+    }
+    // トランザクションをコミット
+    if let Err(err) = txn.commit().await {
+        return Err(internal_error(err.into()).into());
+    }
+    // アカウントの登録を通知
+    for account in new_accounts.iter() {
+        event_sink.on_account_created(account);
+    }
+
+    Ok(BulkInsertedAccounts {
+        inserted: new_accounts.len(),
+        ids: new_accounts.into_iter().map(|a| a.id).collect(),
+    })
+}
+
+/// Eメールアドレスが既に登録されていることを示すエラーを生成する。
+///
+/// # Arguments
+///
+/// * `email` - Eメールアドレス。
+///
+/// # Returns
+///
+/// ユースケースエラー。
+fn email_already_exists(email: &EmailAddress) -> Error {
+    usecases_error(
+        ErrorKind::EmailAddressAlreadyExists,
+        format!("Eメールアドレス({})は既に登録されています。", email.value()).into(),
+    )
+}
+
+/// CSVの行のインポートエラー
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportRowError {
+    /// エラーが発生したCSVの行番号。ヘッダ行を1行目とする。
+    pub line: u64,
+    /// 検証に失敗したフィールドの名前。特定のフィールドに起因しない場合は`None`。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// エラーメッセージ。
+    pub message: String,
+}
+
+/// アカウントインポートエラー
+#[derive(Debug, Clone)]
+pub struct ImportError {
+    /// エラー。
+    pub error: Error,
+    /// エラーが発生したCSVの行のエラーのリスト。特定の行に起因しない場合は空。
+    pub rows: Vec<ImportRowError>,
+}
+
+impl From<Error> for ImportError {
+    fn from(error: Error) -> Self {
+        Self {
+            error,
+            rows: vec![],
+        }
+    }
+}
+
+/// CSVの行のエラーを生成する。
+///
+/// # Arguments
+///
+/// * `line` - CSVの行番号。
+/// * `field` - 検証に失敗したフィールドの名前。
+/// * `message` - エラーメッセージ。
+///
+/// # Returns
+///
+/// CSVの行のインポートエラー。
+fn row_error(line: u64, field: Option<&str>, message: impl ToString) -> ImportRowError {
+    ImportRowError {
+        line,
+        field: field.map(str::to_owned),
+        message: message.to_string(),
+    }
+}
+
+/// CSVの行の読み込みまたはデシリアライズのエラーを、CSVの行のエラーに変換する。
+///
+/// # Arguments
+///
+/// * `err` - CSVのエラー。
+/// * `headers` - CSVのヘッダ行。
+///
+/// # Returns
+///
+/// CSVの行のインポートエラー。行を特定できない場合は`None`。
+fn csv_row_error(err: &csv::Error, headers: &csv::StringRecord) -> Option<ImportRowError> {
+    let (line, field) = match err.kind() {
+        csv::ErrorKind::Deserialize { pos, err } => (
+            pos.as_ref()?.line(),
+            err.field()
+                .and_then(|index| headers.get(index as usize))
+                .map(str::to_owned),
+        ),
+        _ => (err.position()?.line(), None),
+    };
+
+    Some(ImportRowError {
+        line,
+        field,
+        message: match err.kind() {
+            csv::ErrorKind::Deserialize { err, .. } => format!("{}", err.kind()),
+            _ => format!("{}", err),
+        },
+    })
+}
+
+/// CSVからアカウントを単一のトランザクションでインポートする。
+///
+/// CSVの1行目はヘッダ行で、新規アカウントのフィールド名(`email`、`postalCode`など)を列名に指定する。
+/// すべての行を検証して、いずれかの行の検証または登録に失敗した場合は、すべてのアカウントを登録しない。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `event_sink` - アカウントイベントシンク。
+/// * `csv` - インポートするCSV。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: インポートしたアカウント。
+/// * `Err`: エラーと、エラーが発生したすべての行のエラー。
+pub async fn import_csv(
+    db_service: &dyn DatabaseService,
+    clock: &dyn Clock,
+    hasher: &dyn PasswordHasher,
+    event_sink: &dyn AccountEventSink,
+    csv: &[u8],
+) -> Result<BulkInsertedAccounts, ImportError> {
+    let invalid_csv = |message: String| usecases_error(ErrorKind::InvalidCsv, message.into());
+    let max_rows = ENV_VALUES.account_import_max_rows;
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(csv);
+    let headers = reader
+        .headers()
+        .map_err(|err| invalid_csv(format!("CSVのヘッダ行を読み込めません。{}", err)))?
+        .clone();
+    // すべての行を検証
+    let mut rows = vec![];
+    let mut lines = vec![];
+    let mut validated: Vec<ValidatedNewAccount> = vec![];
+    for result in reader.records() {
+        if max_rows <= lines.len() {
+            return Err(usecases_error(
+                ErrorKind::TooManyAccounts,
+                format!("インポートできるアカウントは{}件までです。", max_rows).into(),
+            )
+            .into());
+        }
+        let record = match result {
+            Ok(record) => record,
+            Err(err) => match csv_row_error(&err, &headers) {
+                Some(row) => {
+                    rows.push(row);
+                    continue;
+                }
+                None => return Err(invalid_csv(format!("CSVを読み込めません。{}", err)).into()),
+            },
+        };
+        let line = record.position().map(|pos| pos.line()).unwrap_or_default();
+        lines.push(line);
+        let new = match record.deserialize::<NewAccount>(Some(&headers)) {
+            Ok(new) => new,
+            Err(err) => {
+                rows.extend(csv_row_error(&err, &headers));
+                continue;
+            }
+        };
+        match validate_new_account(&new) {
+            Ok(new) => {
+                // インポートするアカウントの間でEメールアドレスが重複していないか確認
+                if validated.iter().any(|v| v.email == new.email) {
+                    let err = email_already_exists(&new.email);
+                    rows.push(row_error(line, Some("email"), err.message));
+                }
+                validated.push(new);
+            }
+            Err(err) if err.field_errors.is_empty() => {
+                rows.push(row_error(line, None, err.message));
+            }
+            Err(err) => rows.extend(
+                err.field_errors
+                    .into_iter()
+                    .map(|e| row_error(line, Some(&e.field), e.message)),
+            ),
+        }
+    }
+    if lines.is_empty() {
+        return Err(invalid_csv("インポートするアカウントがありません。".to_owned()).into());
+    }
+    if !rows.is_empty() {
+        return Err(ImportError {
+            error: invalid_csv(format!("{}件のエラーがあります。", rows.len())),
+            rows,
+        });
+    }
+
+    // すべての行の検証に成功した場合のみ、アカウントを登録
+    insert_validated_accounts(db_service, clock, hasher, event_sink, validated)
+        .await
+        .map_err(|err| match (err.index, &err.error.code) {
+            (_, ErrorKind::InternalServerError) | (None, _) => err.error.into(),
+            (Some(index), _) => {
+                let field = match err.error.code {
+                    ErrorKind::EmailAddressAlreadyExists => Some("email"),
+                    ErrorKind::PrefectureNotFound => Some("prefectureCode"),
+                    _ => None,
+                };
+                let row = row_error(lines[index], field, &err.error.message);
+                ImportError {
+                    error: invalid_csv("1件のエラーがあります。".to_owned()),
+                    rows: vec![row],
+                }
+            }
+        })
+}
+
+/// エクスポートするCSVの列名のリスト。
+///
+/// パスワードはエクスポートしない。
+pub const EXPORT_CSV_COLUMNS: [&str; 12] = [
+    "id",
+    "email",
+    "name",
+    "isActive",
+    "fixedNumber",
+    "mobileNumber",
+    "postalCode",
+    "prefectureCode",
+    "prefectureName",
+    "addressDetails",
+    "createdAt",
+    "updatedAt",
+];
+
+/// エクスポートするCSVの行
+#[derive(Serialize)]
+struct AccountCsvRow {
+    id: String,
+    email: String,
+    name: String,
+    is_active: bool,
+    fixed_number: Option<String>,
+    mobile_number: Option<String>,
+    postal_code: String,
+    prefecture_code: u8,
+    prefecture_name: String,
+    address_details: String,
+    created_at: String,
+    updated_at: String,
+}
+
+impl From<&AccountDto> for AccountCsvRow {
+    fn from(account: &AccountDto) -> Self {
+        Self {
+            id: account.id.to_string(),
+            email: account.email.value(),
+            name: account.name.value(),
+            is_active: account.is_active,
+            fixed_number: account.fixed_number.as_ref().map(PhoneNumber::value),
+            mobile_number: account.mobile_number.as_ref().map(PhoneNumber::value),
+            postal_code: account.postal_code.value(),
+            prefecture_code: account.prefecture_code,
+            prefecture_name: account.prefecture_name.clone(),
+            address_details: account.address_details.value(),
+            created_at: account.created_at.to_rfc3339(),
+            updated_at: account.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// アカウントIDの昇順で、指定されたアカウントIDより後のアカウントを、指定された件数まで返却する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `after` - 前回取得した最後のアカウントのアカウントID。最初のアカウントから取得する場合は`None`。
+/// * `limit` - 取得するアカウントの最大数。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: アカウントのリスト。これ以上アカウントが存在しない場合は空のリスト。
+/// * `Err`: エラー。
+pub async fn list_paged(
+    db_service: &dyn DatabaseService,
+    after: Option<AccountId>,
+    limit: u64,
+) -> Result<Vec<AccountDto>, Error> {
+    // トランザクションを開始
+    let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL).await?;
+    // アカウントを取得
+    let accounts = match db_service.account(&txn).list_paged(after, limit).await {
+        Ok(accounts) => accounts,
+        Err(err) => return Err(internal_error(err.into())),
+    };
+    // トランザクションをコミット
+    match txn.commit().await {
+        Ok(_) => Ok(accounts.into_iter().map(Into::into).collect()),
+        Err(err) => Err(internal_error(err.into())),
+    }
+}
+
+/// アカウントのリストをCSVに変換する。
+///
+/// # Arguments
+///
+/// * `accounts` - アカウントのリスト。
+/// * `with_header` - ヘッダ行を出力する場合は`true`。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: CSV。
+/// * `Err`: エラー。
+pub fn accounts_to_csv(accounts: &[AccountDto], with_header: bool) -> Result<Vec<u8>, Error> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(vec![]);
+    if with_header {
+        writer
+            .write_record(EXPORT_CSV_COLUMNS)
+            .map_err(|err| internal_error(err.into()))?;
+    }
+    for account in accounts {
+        writer
+            .serialize(AccountCsvRow::from(account))
+            .map_err(|err| internal_error(err.into()))?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|err| internal_error(err.into()))
+}
+
+/// 更新アカウント
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateAccount {
+    /// アカウントID。ULIDの書式でない場合は、デシリアライズに失敗する。
+    #[schema(value_type = String)]
+    pub id: AccountId,
+    /// アカウント名。
+    pub name: String,
+    /// アクティブフラグ。
+    pub is_active: bool,
+    /// 固定電話番号。
+    pub fixed_number: Option<String>,
+    /// 携帯電話番号。
+    pub mobile_number: Option<String>,
+    /// 郵便番号。
+    pub postal_code: String,
+    /// 都道府県コード。
+    pub prefecture_code: u8,
+    /// 市区町村以下住所。
+    pub address_details: String,
+    /// クライアントがアカウントを取得したときの更新日時。
+    ///
+    /// 記録されているアカウントの更新日時と一致しない場合は、他のリクエストで更新されたと判断して更新しない。
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+/// 検証済み更新アカウント
+#[derive(Clone)]
+struct ValidatedUpdateAccount {
+    /// アカウントID。
+    id: AccountId,
+    /// アカウント名。
+    name: AccountName,
+    /// アクティブフラグ。
+    is_active: bool,
+    /// 固定携帯電話番号。
+    phone_numbers: FixedMobileNumbers,
+    /// 郵便番号。
+    postal_code: PostalCode,
+    /// 都道府県コード。
+    prefecture_code: u8,
+    /// 市区町村以下住所。
+    address_details: AddressDetails,
+    /// クライアントがアカウントを取得したときの更新日時。
+    updated_at: DateTime<FixedOffset>,
+}
+
+/// 更新アカウントに設定された値を検証する。
+///
+/// # Arguments
+///
+/// * `account` - 更新アカウント。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 検証済み更新アカウント。
+/// * `Err`: エラー。
+fn validate_update_account(account: &UpdateAccount) -> Result<ValidatedUpdateAccount, Error> {
+    let id = account.id.clone();
+    let name = to_name(&account.name)?;
+    let fixed_number = to_phone_number(account.fixed_number.as_deref(), "fixed")?;
+    let mobile_number = to_phone_number(account.mobile_number.as_deref(), "mobile")?;
+    let phone_numbers = to_phone_numbers(fixed_number, mobile_number)?;
+    let postal_code = to_postal_code(&account.postal_code)?;
+    let address_details = to_address_details(
+        &account.address_details,
+        account.prefecture_code,
+        ENV_VALUES.strict_address_check,
+    )?;
+
+    Ok(ValidatedUpdateAccount {
+        id,
+        name,
+        is_active: account.is_active,
+        phone_numbers,
+        postal_code,
+        prefecture_code: account.prefecture_code,
+        address_details,
+        updated_at: account.updated_at,
+    })
+}
+
+/// 検証済み更新アカウントでアカウントを更新する。
+///
+/// アカウントを無効化した場合は、無効化したアカウントが発行済みのトークンを使用し続けられないように、
+/// 同じトランザクションでアカウントのトークンを削除する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `txn` - データベーストランザクション。
+/// * `account` - 検証済み更新アカウント。
+/// * `now` - 更新日時に記録する現在日時。
+/// * `updated_by` - アカウントを更新するアカウントのアカウントID。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 更新後のアカウント。
+/// * `Err`: エラー。
+async fn update_account(
+    db_service: &dyn DatabaseService,
+    txn: &DatabaseTransaction,
+    account: ValidatedUpdateAccount,
+    now: DateTime<FixedOffset>,
+    updated_by: AccountId,
+) -> Result<Account, Error> {
+    // アカウントに記録されていた都道府県コードから都道府県を取得
+    let prefecture = retrieve_prefecture(db_service, txn, account.prefecture_code).await?;
+    // 更新するアカウントを取得
+    let mut target = find_account(db_service, txn, account.id).await?;
+    // 更新するアカウントに値を設定
+    target.set_name(account.name);
+    target.set_is_active(account.is_active);
+    target.set_phone_numbers(account.phone_numbers);
+    target.set_postal_code(account.postal_code);
+    target.set_address(Address::new(prefecture, account.address_details));
+    target.set_updated_at(now);
+    target.set_updated_by(Some(updated_by));
+    // クライアントがアカウントを取得した後に、他のリクエストで更新されていない場合のみアカウントを更新
+    let updated = match db_service
+        .account(txn)
+        .update_if_unmodified(&target, account.updated_at)
+        .await
+    {
+        Ok(Some(updated)) => updated,
+        Ok(None) => return Err(usecases_error(
+            ErrorKind::Conflict,
+            format!(
+                "アカウント({})は他のリクエストで更新されています。アカウントを取得し直してから更新してください。",
+                target.id()
+            )
+            .into(),
+        )),
+        Err(err) => return Err(internal_error(err.into())),
+    };
+    // アカウントを無効化した場合は、`set_active`と同様にアカウントのトークンを削除
+    if !account.is_active {
+        if let Err(err) = db_service
+            .jwt_tokens(txn)
+            .delete_by_account_id(updated.id())
+            .await
+        {
+            return Err(internal_error(err.into()));
+        }
+    }
+
+    Ok(updated)
+}
+
+/// アカウントを更新する。
+///
+/// アカウントを無効化した場合は、アカウントのトークンを削除する。
+///
+/// # Arguments
+///
+/// * `db_service`: データベースサービス。
+/// * `clock`: 時計。
+/// * `account`: 更新するアカウント。
+/// * `updated_by`: アカウントを更新するアカウントのアカウントID。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 更新後のアカウント。アカウントが見つからなかった場合、都道府県コードが不正な場合はNone。
+/// * `Err`: エラー。
+pub async fn update(
+    db_service: &dyn DatabaseService,
+    clock: &dyn Clock,
+    account: UpdateAccount,
+    updated_by: AccountId,
+) -> Result<AccountDto, Error> {
+    // 更新する値を検証
+    let account = validate_update_account(&account)?;
+    // アカウントを更新して、トランザクションが中断された場合は再試行
+    let now = clock.now();
+    let updated_account = with_transaction_retry(
+        db_service.connection(),
+        TRANSACTION_ISOLATION_LEVEL,
+        ENV_VALUES.transaction_max_retries,
+        |txn| {
+            let (account, updated_by) = (account.clone(), updated_by.clone());
+            Box::pin(
+                async move { update_account(db_service, &txn, account, now, updated_by).await },
+            )
+        },
+    )
+    .await?;
+
+    Ok(updated_account.into())
+}
+
+/// 呼び出し元が開始したトランザクションで、アカウントを更新する。
+///
+/// トランザクションのコミットとロールバックは呼び出し元が行う。
+///
+/// # Arguments
+///
+/// * `db_service`: データベースサービス。
+/// * `txn`: データベーストランザクション。
+/// * `clock`: 時計。
+/// * `account`: 更新するアカウント。
+/// * `updated_by`: アカウントを更新するアカウントのアカウントID。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 更新後のアカウント。
+/// * `Err`: エラー。
+pub async fn update_in_txn(
+    db_service: &dyn DatabaseService,
+    txn: &DatabaseTransaction,
+    clock: &dyn Clock,
+    account: UpdateAccount,
+    updated_by: AccountId,
+) -> Result<AccountDto, Error> {
+    let account = validate_update_account(&account)?;
+
+    update_account(db_service, txn, account, clock.now(), updated_by)
+        .await
+        .map(Into::into)
+}
+
+/// アカウント削除
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteAccount {
+    /// 削除するアカウントのパスワード。
+    pub password: String,
+}
+
+/// アカウントを削除する。
+///
+/// 削除したアカウントのトークンが残らないように、同じトランザクションでアカウントのトークンも削除する。
+/// パスワードが指定された場合は、アカウントのパスワードと一致する場合のみ削除する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `event_sink` - アカウントイベントシンク。
+/// * `id` - 削除するアカウントのID。
+/// * `password` - 削除するアカウントのパスワード。`None`の場合は確認しない。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 削除したアカウント。
+/// * `Err`: エラー。パスワードが一致しない場合のエラー区分は`WrongPassword`。
+pub async fn delete(
+    db_service: &dyn DatabaseService,
+    hasher: &dyn PasswordHasher,
+    event_sink: &dyn AccountEventSink,
+    id: AccountId,
+    password: Option<&str>,
+) -> Result<(), Error> {
+    // アカウントを削除して、トランザクションが中断された場合は再試行
+    with_transaction_retry(
+        db_service.connection(),
+        TRANSACTION_ISOLATION_LEVEL,
+        ENV_VALUES.transaction_max_retries,
+        |txn| {
+            let id = id.clone();
+            Box::pin(async move {
+                // アカウントを取得
+                let account = find_account(db_service, &txn, id.clone()).await?;
+                // パスワードが指定された場合は、パスワードが一致することを確認
+                if let Some(password) = password {
+                    let result = hasher.verify(password, &account.password().value());
+                    if let Err(err) = result {
+                        return Err(internal_error(err.into()));
+                    }
+                    if !result.unwrap() {
+                        return Err(usecases_error(
+                            ErrorKind::WrongPassword,
+                            "パスワードが間違っています。".into(),
+                        ));
+                    }
+                }
+                // アカウントのトークンを削除
+                let result = db_service
+                    .jwt_tokens(&txn)
+                    .delete_by_account_id(id.clone())
+                    .await;
+                if let Err(err) = result {
+                    return Err(internal_error(err.into()));
+                }
+                // アカウントを削除
+                match db_service.account(&txn).delete(id).await {
+                    Ok(_) => Ok(()),
+                    Err(err) => Err(internal_error(err.into())),
+                }
+            })
+        },
+    )
+    .await?;
+    // アカウントの削除を通知
+    event_sink.on_account_deleted(&id);
+
+    Ok(())
+}
+
+/// アカウントを有効化または無効化する。
+///
+/// アカウントを無効化する場合は、無効化したアカウントが発行済みのトークンを使用できないように、
+/// 同じトランザクションでアカウントのトークンを削除する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `id` - アカウントID。
+/// * `is_active` - アカウントを有効化する場合は`true`、無効化する場合は`false`。
+/// * `updated_by` - アカウントを有効化または無効化するアカウントのアカウントID。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 更新後のアカウント。
+/// * `Err`: エラー。
+pub async fn set_active(
+    db_service: &dyn DatabaseService,
+    clock: &dyn Clock,
+    id: AccountId,
+    is_active: bool,
+    updated_by: AccountId,
+) -> Result<AccountDto, Error> {
+    // 返却するアカウント
+    let updated_account: Account;
+    // トランザクションを開始
+    let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL).await?;
+    {
+        // 更新するアカウントを取得
+        let mut target = find_account(db_service, &txn, id.clone()).await?;
+        // アクティブフラグを設定
+        target.set_is_active(is_active);
+        target.set_updated_at(clock.now());
+        target.set_updated_by(Some(updated_by));
+        // アカウントを更新
+        let result = db_service.account(&txn).update(&target).await;
+        if let Err(err) = result {
+            return Err(internal_error(err.into()));
+        }
+        updated_account = result.unwrap();
+        // アカウントを無効化した場合は、アカウントのトークンを削除
+        if !is_active {
+            if let Err(err) = db_service.jwt_tokens(&txn).delete_by_account_id(id).await {
+                return Err(internal_error(err.into()));
+            }
+        }
+    }
+    // トランザクションをコミット
+    match txn.commit().await {
+        Ok(_) => Ok(updated_account.into()),
+        Err(err) => Err(internal_error(err.into())),
+    }
+}
+
+/// 権限変更
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeRole {
+    /// 変更後の権限。
+    pub role: Role,
+}
+
+/// アカウントを管理者に昇格、または一般ユーザーに降格する。
+///
+/// 発行済みのトークンには変更前の権限が記録されているため、権限を変更した場合は、
+/// 同じトランザクションでアカウントのトークンを削除して、再度トークンを取得させる。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `id` - アカウントID。
+/// * `role` - 変更後の権限。
+/// * `updated_by` - 権限を変更する管理者のアカウントID。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 更新後のアカウント。
+/// * `Err`: エラー。
+pub async fn change_role(
+    db_service: &dyn DatabaseService,
+    clock: &dyn Clock,
+    id: AccountId,
+    role: Role,
+    updated_by: AccountId,
+) -> Result<AccountDto, Error> {
+    // 返却するアカウント
+    let updated_account: Account;
+    // トランザクションを開始
+    let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL).await?;
+    {
+        // 更新するアカウントを取得
+        let mut target = find_account(db_service, &txn, id.clone()).await?;
+        if target.role() == role {
+            updated_account = target;
+        } else {
+            // 権限を設定して、アカウントを更新
+            target.set_role(role);
+            target.set_updated_at(clock.now());
+            target.set_updated_by(Some(updated_by));
+            let result = db_service.account(&txn).update(&target).await;
+            if let Err(err) = result {
+                return Err(internal_error(err.into()));
+            }
+            updated_account = result.unwrap();
+            // 変更前の権限を記録したトークンを削除
+            if let Err(err) = db_service.jwt_tokens(&txn).delete_by_account_id(id).await {
+                return Err(internal_error(err.into()));
+            }
+        }
+    }
+    // トランザクションをコミット
+    match txn.commit().await {
+        Ok(_) => Ok(updated_account.into()),
+        Err(err) => Err(internal_error(err.into())),
+    }
+}
+
+/// パスワード変更
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePassword {
+    /// アカウントID。ULIDの書式でない場合は、デシリアライズに失敗する。
+    #[schema(value_type = String)]
+    pub id: AccountId,
+    /// 古いパスワード。
+    pub old_password: String,
+    /// 新しいパスワード。
+    pub new_password: String,
+}
+
+/// 新しいパスワードが、現在のパスワードまたはパスワード履歴に記録されているパスワードと一致しないことを確認する。
+///
+/// 環境変数`PASSWORD_HISTORY_SIZE`が0の場合は確認しない。
+///
+/// # Arguments
+///
+/// * `db_service` - リポジトリエクステンション。
+/// * `txn` - データベーストランザクション。
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `account` - パスワードを変更するアカウント。
+/// * `new_password` - 変更後のパスワード。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 新しいパスワードが再利用されていない場合は`()`。
+/// * `Err`: 新しいパスワードが再利用されている場合は`PasswordReused`エラー。
+async fn ensure_password_not_reused(
+    db_service: &dyn DatabaseService,
+    txn: &DatabaseTransaction,
+    hasher: &dyn PasswordHasher,
+    account: &Account,
+    new_password: &RawPassword,
+) -> Result<(), Error> {
+    let size = ENV_VALUES.password_history_size;
+    if size == 0 {
+        return Ok(());
+    }
+    let history = db_service
+        .password_history(txn)
+        .list(account.id(), size)
+        .await
+        .map_err(|err| internal_error(err.into()))?;
+    for password in std::iter::once(account.password()).chain(history) {
+        let reused = hasher
+            .verify(&new_password.value(), &password.value())
+            .map_err(|err| internal_error(err.into()))?;
+        if reused {
+            return Err(usecases_error(
+                ErrorKind::PasswordReused,
+                format!(
+                    "新しいパスワードに、現在または最近{}回以内に設定したパスワードは使用できません。",
+                    size
+                )
+                .into(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// パスワードを変更する。
+///
+/// 環境変数`PASSWORD_HISTORY_SIZE`が1以上の場合は、変更前のパスワードをパスワード履歴に記録して、
+/// 現在のパスワードとパスワード履歴に記録されているパスワードへの変更を拒否する。
+///
+/// # Arguments
+///
+/// * `db_service` - リポジトリエクステンション。
+/// * `clock` - 時計。
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `id` - パスワードを変更するアカウントのアカウントID。
+/// * `old_password` - 変更前のパスワード。
+/// * `new_password` - 変更後のパスワード。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: パスワードの変更に成功した場合は`()`。
+/// * `Err`: エラー。
+pub async fn change_password<'a>(
+    db_service: &dyn DatabaseService,
+    clock: &dyn Clock,
+    hasher: &dyn PasswordHasher,
+    id: AccountId,
+    old_password: &'a str,
+    new_password: &'a str,
+) -> Result<(), Error> {
+    // 古いパスワードを検証
+    let old_password = RawPassword::new(old_password);
+    if old_password.is_err() {
+        return Err(usecases_error(
+            ErrorKind::InvalidOldPassword,
+            "古いパスワードが不正です。".into(),
+        ));
+    }
+    let old_password = old_password.unwrap();
+    // 新しいパスワードを検証
+    if RawPassword::new(new_password).is_err() {
+        return Err(usecases_error(
+            ErrorKind::InvalidNewPassword,
+            "新しいパスワードが不正です。".into(),
+        ));
+    }
+    // パスワードを変更して、トランザクションが中断された場合は再試行
+    with_transaction_retry(
+        db_service.connection(),
+        TRANSACTION_ISOLATION_LEVEL,
+        ENV_VALUES.transaction_max_retries,
+        |txn| {
+            let (id, old_password) = (id.clone(), old_password.clone());
+            Box::pin(async move {
+                // パスワードを変更するアカウントを取得
+                let account = find_account(db_service, &txn, id.clone()).await?;
+                // パスワードが一致することを確認
+                let result = hasher.verify(&old_password.value(), &account.password().value());
+                if let Err(err) = result {
+                    return Err(internal_error(err.into()));
+                }
+                if !result.unwrap() {
+                    return Err(usecases_error(
+                        ErrorKind::WrongPassword,
+                        "古いパスワードが間違っています。".into(),
+                    ));
+                }
+                // 新しいパスワードの強度を検証
+                let new_password = to_raw_password(
+                    new_password,
+                    Some(&account.email()),
+                    Some(&account.name()),
+                    ErrorKind::InvalidNewPassword,
+                )?;
+                // 新しいパスワードが再利用されていないことを確認
+                ensure_password_not_reused(db_service, &txn, hasher, &account, &new_password)
+                    .await?;
+                // パスワードをハッシュ化
+                let hashed_password = hash_password(hasher, &new_password)?;
+                // パスワードを変更(パスワードはアカウント本人のみ変更できるため、本人を更新したアカウントとして記録)
+                let result = db_service
+                    .account(&txn)
+                    .change_password(id.clone(), hashed_password, Some(id.clone()))
+                    .await;
+                if let Err(err) = result {
+                    return Err(internal_error(err.into()));
+                }
+                // 変更前のパスワードをパスワード履歴に記録
+                let size = ENV_VALUES.password_history_size;
+                if size > 0 {
+                    let result = db_service
+                        .password_history(&txn)
+                        .push(id, account.password(), clock.now(), size)
+                        .await;
+                    if let Err(err) = result {
+                        return Err(internal_error(err.into()));
+                    }
+                }
+
+                Ok(())
+            })
+        },
+    )
+    .await
+}
+
+/// パスワード再設定
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetPassword {
+    /// 新しいパスワード。
+    pub new_password: String,
+}
+
+/// 管理者がアカウントのパスワードを再設定する。
+///
+/// アカウント本人によるパスワードの変更と異なり、古いパスワードを要求しない。
+/// 再設定する前のパスワードで取得したトークンを使用させないため、同じトランザクションでアカウントのトークンを削除する。
+/// 環境変数`PASSWORD_HISTORY_SIZE`が1以上の場合は、変更前のパスワードをパスワード履歴に記録する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `hasher` - パスワードをハッシュ化する構造体。
+/// * `id` - パスワードを再設定するアカウントのアカウントID。
+/// * `new_password` - 再設定するパスワード。
+/// * `updated_by` - パスワードを再設定する管理者のアカウントID。
 ///
 /// # Returns
 ///
 /// `Result`。返却される`Result`の内容は以下の通り。
 ///
-/// * `Ok`: 更新後のアカウント。アカウントが見つからなかった場合、都道府県コードが不正な場合はNone。
+/// * `Ok`: パスワードの再設定に成功した場合は`()`。
 /// * `Err`: エラー。
-pub async fn update(
+pub async fn reset_password(
     db_service: &dyn DatabaseService,
-    account: UpdateAccount,
-) -> Result<AccountDto, Error> {
-    // 返却するアカウント
-    let updated_account: Account;
-    // アカウントIDを生成
-    let account_id = to_account_id(&account.id)?;
-    // 更新する値を生成
-    let name = to_name(&account.name)?;
-    let fixed_number = to_phone_number(account.fixed_number.as_deref(), "fixed")?;
-    let mobile_number = to_phone_number(account.mobile_number.as_deref(), "mobile")?;
-    let phone_numbers = to_phone_numbers(fixed_number, mobile_number)?;
-    let postal_code = to_postal_code(&account.postal_code)?;
-    let address_details = to_address_details(&account.address_details)?;
-    // トランザクションを開始
-    let txn = begin_transaction(&db_service.connection()).await?;
-    {
-        // アカウントに記録されていた都道府県コードから都道府県を取得
-        let prefecture = retrieve_prefecture(db_service, &txn, account.prefecture_code).await?;
-        // 更新するアカウントを取得
-        let mut target = find_account(db_service, &txn, account_id).await?;
-        // 更新するアカウントに値を設定
-        target.set_name(name);
-        target.set_is_active(account.is_active);
-        target.set_phone_numbers(phone_numbers);
-        target.set_postal_code(postal_code);
-        target.set_address(Address::new(prefecture, address_details));
-        target.set_updated_at(local_now(None));
-        // アカウントを更新
-        let result = db_service.account(&txn).update(&target).await;
-        if let Err(err) = result {
-            return Err(internal_error(err.into()));
-        }
-        updated_account = result.unwrap();
+    clock: &dyn Clock,
+    hasher: &dyn PasswordHasher,
+    id: AccountId,
+    new_password: &str,
+    updated_by: AccountId,
+) -> Result<(), Error> {
+    // 新しいパスワードを検証
+    if RawPassword::new(new_password).is_err() {
+        return Err(usecases_error(
+            ErrorKind::InvalidNewPassword,
+            "新しいパスワードが不正です。".into(),
+        ));
     }
-    // トランザクションをコミット
-    match txn.commit().await {
-        Ok(_) => Ok(updated_account.into()),
+    // パスワードを再設定して、トランザクションが中断された場合は再試行
+    with_transaction_retry(
+        db_service.connection(),
+        TRANSACTION_ISOLATION_LEVEL,
+        ENV_VALUES.transaction_max_retries,
+        |txn| {
+            let (id, updated_by) = (id.clone(), updated_by.clone());
+            Box::pin(async move {
+                // パスワードを再設定するアカウントを取得
+                let account = find_account(db_service, &txn, id.clone()).await?;
+                // 新しいパスワードの強度を検証
+                let new_password = to_raw_password(
+                    new_password,
+                    Some(&account.email()),
+                    Some(&account.name()),
+                    ErrorKind::InvalidNewPassword,
+                )?;
+                // パスワードをハッシュ化
+                let hashed_password = hash_password(hasher, &new_password)?;
+                // パスワードを変更
+                let result = db_service
+                    .account(&txn)
+                    .change_password(id.clone(), hashed_password, Some(updated_by))
+                    .await;
+                if let Err(err) = result {
+                    return Err(internal_error(err.into()));
+                }
+                // 変更前のパスワードをパスワード履歴に記録
+                let size = ENV_VALUES.password_history_size;
+                if size > 0 {
+                    let result = db_service
+                        .password_history(&txn)
+                        .push(id.clone(), account.password(), clock.now(), size)
+                        .await;
+                    if let Err(err) = result {
+                        return Err(internal_error(err.into()));
+                    }
+                }
+                // 再設定する前のパスワードで取得したトークンを削除
+                if let Err(err) = db_service.jwt_tokens(&txn).delete_by_account_id(id).await {
+                    return Err(internal_error(err.into()));
+                }
+
+                Ok(())
+            })
+        },
+    )
+    .await
+}
+
+/// Eメールアドレス変更リクエスト
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestEmailChange {
+    /// 変更後のEメールアドレス。
+    pub new_email: String,
+}
+
+/// Eメールアドレス変更トークン
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailChangeToken {
+    /// Eメールアドレス変更トークン。
+    pub token: String,
+    /// トークンの有効期限。
+    pub expired_at: DateTime<FixedOffset>,
+}
+
+/// Eメールアドレス変更確認
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmEmailChange {
+    /// Eメールアドレス変更トークン。
+    pub token: String,
+}
+
+/// Eメールアドレス変更トークンから取り出した、Eメールアドレスの変更内容
+struct EmailChange {
+    /// Eメールアドレスを変更するアカウントのアカウントID。
+    id: AccountId,
+    /// トークンを発行したときのアカウントのEメールアドレス。
+    current_email: EmailAddress,
+    /// 変更後のEメールアドレス。
+    new_email: EmailAddress,
+}
+
+/// Eメールアドレス変更トークンが不正であることを示すエラーを生成する。
+///
+/// # Arguments
+///
+/// * `message` - エラーメッセージ。
+///
+/// # Returns
+///
+/// ユースケースエラー。
+fn invalid_email_change_token(message: Cow<'static, str>) -> Error {
+    usecases_error(ErrorKind::InvalidEmailChangeToken, message)
+}
+
+/// Eメールアドレス変更トークンを生成する。
+///
+/// # Arguments
+///
+/// * `id` - Eメールアドレスを変更するアカウントのアカウントID。
+/// * `current_email` - アカウントの現在のEメールアドレス。
+/// * `new_email` - 変更後のEメールアドレス。
+/// * `now` - トークンの有効期限の起点となる現在日時。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: Eメールアドレス変更トークン。
+/// * `Err`: エラー。
+fn gen_email_change_token(
+    id: &AccountId,
+    current_email: &EmailAddress,
+    new_email: &EmailAddress,
+    now: DateTime<FixedOffset>,
+) -> Result<EmailChangeToken, Error> {
+    let expired_at = now + Duration::seconds(ENV_VALUES.email_change_token_seconds);
+    let claims = EmailChangeClaims {
+        sub: id.to_string(),
+        current_email: current_email.value(),
+        new_email: new_email.value(),
+        exp: expired_at.timestamp(),
+        ..Default::default()
+    };
+    match gen_jwt_token(&claims) {
+        Ok(token) => Ok(EmailChangeToken { token, expired_at }),
         Err(err) => Err(internal_error(err.into())),
     }
 }
 
-/// アカウントを削除する。
+/// Eメールアドレス変更トークンを検証して、Eメールアドレスの変更内容を取り出す。
+///
+/// # Arguments
+///
+/// * `token` - Eメールアドレス変更トークン。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: Eメールアドレスの変更内容。
+/// * `Err`: トークンの署名が不正な場合、または有効期限が切れている場合のエラー。
+fn decode_email_change(token: &str) -> Result<EmailChange, Error> {
+    let claims = decode_email_change_token(token)
+        .map_err(|err| invalid_email_change_token(format!("{}", err).into()))?;
+    let invalid = || invalid_email_change_token("Eメールアドレス変更トークンが不正です。".into());
+
+    Ok(EmailChange {
+        id: AccountId::try_from(claims.sub.as_str()).map_err(|_| invalid())?,
+        current_email: EmailAddress::new(&claims.current_email).map_err(|_| invalid())?,
+        new_email: EmailAddress::new(&claims.new_email).map_err(|_| invalid())?,
+    })
+}
+
+/// Eメールアドレスが他のアカウントに登録されていないことを確認する。
 ///
 /// # Arguments
 ///
 /// * `db_service` - データベースサービス。
-/// * `id` - 削除するアカウントのID。
+/// * `txn` - データベーストランザクション。
+/// * `email` - 確認するEメールアドレス。
 ///
 /// # Returns
 ///
 /// `Result`。返却される`Result`の内容は以下の通り。
 ///
-/// * `Ok`: 削除したアカウント。
+/// * `Ok`: Eメールアドレスが登録されていない場合は`()`。
+/// * `Err`: エラー。
+async fn ensure_email_available(
+    db_service: &dyn DatabaseService,
+    txn: &DatabaseTransaction,
+    email: &EmailAddress,
+) -> Result<(), Error> {
+    match db_service.account(txn).find_by_email(email.clone()).await {
+        Ok(None) => Ok(()),
+        Ok(Some(_)) => Err(email_already_exists(email)),
+        Err(err) => Err(internal_error(err.into())),
+    }
+}
+
+/// Eメールアドレスの変更をリクエストして、Eメールアドレス変更トークンを発行する。
+///
+/// Eメールアドレスは、発行したトークンで`confirm_email_change`を呼び出すまで変更しない。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `id` - Eメールアドレスを変更するアカウントのアカウントID。
+/// * `new_email` - 変更後のEメールアドレス。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: Eメールアドレス変更トークン。
 /// * `Err`: エラー。
-pub async fn delete(db_service: &dyn DatabaseService, id: AccountId) -> Result<(), Error> {
+pub async fn request_email_change(
+    db_service: &dyn DatabaseService,
+    clock: &dyn Clock,
+    id: AccountId,
+    new_email: &str,
+) -> Result<EmailChangeToken, Error> {
+    // 変更後のEメールアドレスを検証
+    let new_email = to_email(new_email)?;
     // トランザクションを開始
-    let txn = begin_transaction(&db_service.connection()).await?;
+    let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL).await?;
+    let token: EmailChangeToken;
     {
-        // アカウントを取得
-        let _ = find_account(db_service, &txn, id.clone()).await?;
-        // アカウントを削除
-        let result = db_service.account(&txn).delete(id).await;
-        if let Err(err) = result {
-            return Err(internal_error(err.into()));
+        // Eメールアドレスを変更するアカウントを取得
+        let account = find_account(db_service, &txn, id.clone()).await?;
+        if account.email() == new_email {
+            return Err(usecases_error(
+                ErrorKind::InvalidEmailAddress,
+                "変更後のEメールアドレスが、現在のEメールアドレスと同じです。".into(),
+            ));
         }
+        // 変更後のEメールアドレスが既に登録されていないか確認
+        ensure_email_available(db_service, &txn, &new_email).await?;
+        // Eメールアドレス変更トークンを生成
+        token = gen_email_change_token(&id, &account.email(), &new_email, clock.now())?;
     }
     // トランザクションをコミット
     match txn.commit().await {
-        Ok(_) => Ok(()),
+        Ok(_) => Ok(token),
         Err(err) => Err(internal_error(err.into())),
     }
 }
 
-/// パスワード変更
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ChangePassword {
-    /// アカウントID。
-    pub id: String,
-    /// 古いパスワード。
-    pub old_password: String,
-    /// 新しいパスワード。
-    pub new_password: String,
-}
-
-/// パスワードを変更する。
+/// Eメールアドレス変更トークンを検証して、アカウントのEメールアドレスを変更する。
+///
+/// トークンを発行したときのEメールアドレスとアカウントのEメールアドレスが一致しない場合は、
+/// トークンが使用済みとして変更しない。
 ///
 /// # Arguments
 ///
-/// * `db_service` - リポジトリエクステンション。
-/// * `id` - パスワードを変更するアカウントのアカウントID。
-/// * `old_password` - 変更前のパスワード。
-/// * `new_password` - 変更後のパスワード。
+/// * `db_service` - データベースサービス。
+/// * `clock` - 時計。
+/// * `token` - Eメールアドレス変更トークン。
 ///
 /// # Returns
 ///
 /// `Result`。返却される`Result`の内容は以下の通り。
 ///
-/// * `Ok`: パスワードの変更に成功した場合は`()`。
+/// * `Ok`: 更新後のアカウント。
 /// * `Err`: エラー。
-pub async fn change_password<'a>(
+pub async fn confirm_email_change(
     db_service: &dyn DatabaseService,
-    id: AccountId,
-    old_password: &'a str,
-    new_password: &'a str,
-) -> Result<(), Error> {
-    // 古いパスワードを検証
-    let old_password = RawPassword::new(old_password);
-    if old_password.is_err() {
-        return Err(usecases_error(
-            ErrorKind::InvalidOldPassword,
-            "古いパスワードが不正です。".into(),
-        ));
-    }
-    let old_password = old_password.unwrap();
-    // 新しいパスワードを検証
-    let new_password = RawPassword::new(new_password);
-    if new_password.is_err() {
-        return Err(usecases_error(
-            ErrorKind::InvalidNewPassword,
-            "新しいパスワードが不正です。".into(),
-        ));
-    }
-    let new_password = new_password.unwrap();
+    clock: &dyn Clock,
+    token: &str,
+) -> Result<AccountDto, Error> {
+    // トークンを検証
+    let change = decode_email_change(token)?;
+    // 返却するアカウント
+    let updated_account: Account;
     // トランザクションを開始
-    let txn = begin_transaction(&db_service.connection()).await?;
+    let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL).await?;
     {
-        // パスワードを変更するアカウントを取得
-        let account = find_account(&*db_service, &txn, id.clone()).await?;
-        // パスワードが一致することを確認
-        let result = verify_password(&old_password.value(), &account.password().value());
-        if let Err(err) = result {
-            return Err(internal_error(err.into()));
-        }
-        if !result.unwrap() {
-            return Err(Error {
-                code: ErrorKind::WrongPassword,
-                message: "古いパスワードが間違っています。".into(),
-            });
+        // Eメールアドレスを変更するアカウントを取得
+        let mut target = find_account(db_service, &txn, change.id).await?;
+        // トークンを発行した後でEメールアドレスが変更されている場合は、トークンを使用済みとする
+        if target.email() != change.current_email {
+            return Err(invalid_email_change_token(
+                "Eメールアドレス変更トークンは使用済みです。".into(),
+            ));
         }
-        // パスワードをハッシュ化
-        let hashed_password = HashedPassword::new(new_password);
-        // パスワードを変更
-        let result = db_service
-            .account(&txn)
-            .change_password(id, hashed_password)
-            .await;
+        // トークンを発行した後で、変更後のEメールアドレスが登録されていないか確認
+        ensure_email_available(db_service, &txn, &change.new_email).await?;
+        // Eメールアドレスを変更
+        target.set_email(change.new_email);
+        target.set_updated_at(clock.now());
+        let result = db_service.account(&txn).update(&target).await;
         if let Err(err) = result {
             return Err(internal_error(err.into()));
         }
+        updated_account = result.unwrap();
     }
     // トランザクションをコミット
     match txn.commit().await {
-        Ok(_) => Ok(()),
+        Ok(_) => Ok(updated_account.into()),
         Err(err) => Err(internal_error(err.into())),
     }
 }
+
+#[cfg(test)]
+mod insert_tests {
+    use common::hashing::HashingConfig;
+    use domains::{
+        repositories::{
+            accounts::{AccountRepository, PasswordHistoryRepository},
+            auth::JwtTokensRepository,
+            common::PrefectureRepository,
+        },
+        services::{clock::SystemClock, hashers::PasswordHasherImpl},
+    };
+    use sea_orm::DatabaseConnection;
+
+    use super::*;
+    use crate::{
+        events::NoopAccountEventSink,
+        queries::{AccountQueryService, PrefectureStatsQueryService},
+    };
+
+    /// データベースにアクセスしないことを確認するためのデータベースサービス
+    struct UnreachableDatabaseService;
+
+    impl DatabaseService for UnreachableDatabaseService {
+        fn connection(&self) -> &DatabaseConnection {
+            unreachable!()
+        }
+
+        fn prefecture<'a>(
+            &self,
+            _txn: &'a DatabaseTransaction,
+        ) -> Box<dyn PrefectureRepository + 'a> {
+            unreachable!()
+        }
+
+        fn account<'a>(&self, _txn: &'a DatabaseTransaction) -> Box<dyn AccountRepository + 'a> {
+            unreachable!()
+        }
+
+        fn prefecture_reader(&self) -> Box<dyn PrefectureRepository + '_> {
+            unreachable!()
+        }
+
+        fn account_reader(&self) -> Box<dyn AccountRepository + '_> {
+            unreachable!()
+        }
+
+        fn password_history<'a>(
+            &self,
+            _txn: &'a DatabaseTransaction,
+        ) -> Box<dyn PasswordHistoryRepository + 'a> {
+            unreachable!()
+        }
+
+        fn jwt_tokens<'a>(
+            &self,
+            _txn: &'a DatabaseTransaction,
+        ) -> Box<dyn JwtTokensRepository + 'a> {
+            unreachable!()
+        }
+
+        fn account_service<'a>(
+            &self,
+            _txn: &'a DatabaseTransaction,
+        ) -> Box<dyn AccountQueryService + 'a> {
+            unreachable!()
+        }
+
+        fn prefecture_stats_service<'a>(
+            &self,
+            _txn: &'a DatabaseTransaction,
+        ) -> Box<dyn PrefectureStatsQueryService + 'a> {
+            unreachable!()
+        }
+    }
+
+    fn new_account(email: &str) -> NewAccount {
+        NewAccount {
+            email: email.to_owned(),
+            name: "foo".to_owned(),
+            password: "012abcEFG=+".to_owned(),
+            is_active: true,
+            fixed_number: None,
+            mobile_number: Some("090-1234-5678".to_owned()),
+            postal_code: "100-0014".to_owned(),
+            prefecture_code: 13,
+            address_details: "千代田区永田町1-7-1".to_owned(),
+        }
+    }
+
+    /// 複数のフィールドが不正な場合に、データベースにアクセスせずに、すべてのフィールドの検証エラーを返却することを確認する。
+    #[tokio::test]
+    async fn test_insert_invalid_fields() {
+        let mut account = new_account("invalid-email");
+        account.password = "012abcEFG".to_owned();
+        account.postal_code = "100-001".to_owned();
+        let err = insert(
+            &UnreachableDatabaseService,
+            &SystemClock,
+            &PasswordHasherImpl::new(HashingConfig::from_env()),
+            &NoopAccountEventSink,
+            account,
+        )
+        .await
+        .unwrap_err();
+        // 最初に検証に失敗したフィールドのエラー
+        assert!(matches!(err.code, ErrorKind::InvalidEmailAddress));
+        assert_eq!(err.message, err.field_errors[0].message);
+        let fields: Vec<&str> = err.field_errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["email", "password", "postalCode"]);
+        assert_eq!(
+            err.field_errors[1].message,
+            "パスワードに記号が含まれていません。"
+        );
+    }
+
+    /// 新規アカウントの検証で、検証に失敗したすべてのフィールドの検証エラーを返却することを確認する。
+    #[test]
+    fn test_new_account_validate() {
+        assert!(new_account("foo@example.com").validate().is_ok());
+
+        let mut account = new_account("foo@example.com");
+        account.name = "f".to_owned();
+        account.mobile_number = Some("090-1234".to_owned());
+        account.postal_code = "100-001".to_owned();
+        account.address_details = "".to_owned();
+        let errors = account.validate().unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec!["mobileNumber", "name", "postalCode", "addressDetails"]
+        );
+        assert!(errors.iter().all(|e| !e.message.is_empty()));
+    }
+
+    /// パスワードのハッシュ化に失敗した場合に、パニックせずにデータベースにアクセスする前にサーバー内部エラーを返却することを確認する。
+    #[tokio::test]
+    async fn test_insert_invalid_hash_func() {
+        let hasher = PasswordHasherImpl::new(HashingConfig {
+            func: "MD5".to_owned(),
+            ..HashingConfig::from_env()
+        });
+        let err = insert(
+            &UnreachableDatabaseService,
+            &SystemClock,
+            &hasher,
+            &NoopAccountEventSink,
+            new_account("foo@example.com"),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err.code, ErrorKind::InternalServerError));
+    }
+
+    /// 一括登録できるアカウントの最大数を超えた場合に、データベースにアクセスせずにエラーを返却することを確認する。
+    #[tokio::test]
+    async fn test_bulk_insert_too_many_accounts() {
+        let accounts = (0..=MAX_BULK_INSERT_ACCOUNTS)
+            .map(|i| new_account(&format!("foo{}@example.com", i)))
+            .collect();
+        let err = bulk_insert(
+            &UnreachableDatabaseService,
+            &SystemClock,
+            &PasswordHasherImpl::new(HashingConfig::from_env()),
+            &NoopAccountEventSink,
+            accounts,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err.error.code, ErrorKind::TooManyAccounts));
+        assert!(err.index.is_none());
+    }
+
+    /// 不正なアカウントが含まれている場合に、そのアカウントのインデックスを返却することを確認する。
+    #[tokio::test]
+    async fn test_bulk_insert_invalid_account() {
+        let accounts = vec![
+            new_account("foo@example.com"),
+            new_account("bar@example.com"),
+            new_account("invalid-email"),
+        ];
+        let err = bulk_insert(
+            &UnreachableDatabaseService,
+            &SystemClock,
+            &PasswordHasherImpl::new(HashingConfig::from_env()),
+            &NoopAccountEventSink,
+            accounts,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err.error.code, ErrorKind::InvalidEmailAddress));
+        assert_eq!(err.index, Some(2));
+    }
+
+    /// Eメールアドレスが重複したアカウントが含まれている場合に、そのアカウントのインデックスを返却することを確認する。
+    #[tokio::test]
+    async fn test_bulk_insert_duplicated_email() {
+        let accounts = vec![
+            new_account("foo@example.com"),
+            new_account("bar@example.com"),
+            new_account("foo@example.com"),
+        ];
+        let err = bulk_insert(
+            &UnreachableDatabaseService,
+            &SystemClock,
+            &PasswordHasherImpl::new(HashingConfig::from_env()),
+            &NoopAccountEventSink,
+            accounts,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err.error.code,
+            ErrorKind::EmailAddressAlreadyExists
+        ));
+        assert_eq!(err.index, Some(2));
+    }
+
+    /// 市区町村以下住所の都道府県名の確認を、指定された場合のみ実施することを確認する。
+    #[test]
+    fn test_to_address_details_strict() {
+        let details = "大阪府大阪市北区梅田1-1-1";
+        assert!(to_address_details(details, 13, false).is_ok());
+        let err = to_address_details(details, 13, true).unwrap_err();
+        assert!(matches!(err.code, ErrorKind::InvalidAddressDetails));
+        assert!(err.message.contains("大阪府"), "{}", err.message);
+        assert!(to_address_details(details, 27, true).is_ok());
+        assert!(to_address_details("北区梅田1-1-1", 13, true).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod change_password_tests {
+    use common::hashing::HashingConfig;
+    use domains::{
+        models::accounts::FixedMobileNumbers,
+        repositories::{
+            accounts::{
+                AccountRepository, MockAccountRepository, MockPasswordHistoryRepository,
+                PasswordHistoryRepository,
+            },
+            auth::JwtTokensRepository,
+            common::PrefectureRepository,
+        },
+        services::{clock::SystemClock, hashers::PasswordHasherImpl},
+    };
+    use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase};
+
+    use super::*;
+    use crate::queries::{AccountQueryService, PrefectureStatsQueryService};
+
+    /// パスワードを変更するアカウントと、そのパスワード履歴を返却するデータベースサービス
+    struct PasswordHistoryDatabaseService {
+        /// トランザクションを開始するモックデータベースのコネクション。
+        conn: DatabaseConnection,
+        /// パスワードを変更するアカウント。
+        account: Account,
+        /// パスワード履歴に記録されているパスワード。
+        history: Vec<HashedPassword>,
+    }
+
+    impl DatabaseService for PasswordHistoryDatabaseService {
+        fn connection(&self) -> &DatabaseConnection {
+            &self.conn
+        }
+
+        fn prefecture<'a>(
+            &self,
+            _txn: &'a DatabaseTransaction,
+        ) -> Box<dyn PrefectureRepository + 'a> {
+            unreachable!()
+        }
+
+        fn account<'a>(&self, _txn: &'a DatabaseTransaction) -> Box<dyn AccountRepository + 'a> {
+            let mut mock = MockAccountRepository::new();
+            let account = self.account.clone();
+            mock.expect_find_by_id()
+                .returning(move |_| Ok(Some(account.clone())));
+            mock.expect_change_password().returning(|_, _, _| Ok(true));
+            Box::new(mock)
+        }
+
+        fn prefecture_reader(&self) -> Box<dyn PrefectureRepository + '_> {
+            unreachable!()
+        }
+
+        fn account_reader(&self) -> Box<dyn AccountRepository + '_> {
+            unreachable!()
+        }
+
+        fn password_history<'a>(
+            &self,
+            _txn: &'a DatabaseTransaction,
+        ) -> Box<dyn PasswordHistoryRepository + 'a> {
+            let mut mock = MockPasswordHistoryRepository::new();
+            let history = self.history.clone();
+            mock.expect_list()
+                .returning(move |_, limit| Ok(history.iter().take(limit).cloned().collect()));
+            mock.expect_push().returning(|_, _, _, _| Ok(()));
+            Box::new(mock)
+        }
+
+        fn jwt_tokens<'a>(
+            &self,
+            _txn: &'a DatabaseTransaction,
+        ) -> Box<dyn JwtTokensRepository + 'a> {
+            unreachable!()
+        }
+
+        fn account_service<'a>(
+            &self,
+            _txn: &'a DatabaseTransaction,
+        ) -> Box<dyn AccountQueryService + 'a> {
+            unreachable!()
+        }
+
+        fn prefecture_stats_service<'a>(
+            &self,
+            _txn: &'a DatabaseTransaction,
+        ) -> Box<dyn PrefectureStatsQueryService + 'a> {
+            unreachable!()
+        }
+    }
+
+    fn hasher() -> PasswordHasherImpl {
+        PasswordHasherImpl::new(HashingConfig::from_env())
+    }
+
+    fn hashed(password: &str) -> HashedPassword {
+        HashedPassword::new(RawPassword::new(password).unwrap(), &hasher()).unwrap()
+    }
+
+    /// 現在のパスワードが`current`で、パスワード履歴に`history`が記録されているデータベースサービスを構築する。
+    fn db_service(current: &str, history: &[&str]) -> PasswordHistoryDatabaseService {
+        let account = Account::new(
+            EmailAddress::new("foo@example.com").unwrap(),
+            AccountName::new("foo").unwrap(),
+            hashed(current),
+            true,
+            FixedMobileNumbers::new(None, Some(PhoneNumber::new("090-1234-5678").unwrap()))
+                .unwrap(),
+            PostalCode::new("100-0014").unwrap(),
+            Address::new(
+                Prefecture::from_code(13).unwrap(),
+                AddressDetails::new("千代田区永田町1-7-1").unwrap(),
+            ),
+            SystemClock.now(),
+        );
+
+        PasswordHistoryDatabaseService {
+            conn: MockDatabase::new(DatabaseBackend::Postgres).into_connection(),
+            account,
+            history: history.iter().map(|password| hashed(password)).collect(),
+        }
+    }
+
+    /// 現在のパスワードと、最近設定したパスワードへの変更を拒否することを確認する。
+    #[tokio::test]
+    async fn test_change_password_reused() {
+        let db_service = db_service("012abcEFG=+", &["345xyzKLM#-", "678hijNOP$%"]);
+        for new_password in ["012abcEFG=+", "345xyzKLM#-", "678hijNOP$%"] {
+            let err = change_password(
+                &db_service,
+                &SystemClock,
+                &hasher(),
+                db_service.account.id(),
+                "012abcEFG=+",
+                new_password,
+            )
+            .await
+            .unwrap_err();
+            assert!(
+                matches!(err.code, ErrorKind::PasswordReused),
+                "{}",
+                new_password
+            );
+        }
+    }
+
+    /// 現在のパスワードとパスワード履歴に記録されていないパスワードに変更できることを確認する。
+    #[tokio::test]
+    async fn test_change_password_not_reused() {
+        let db_service = db_service("012abcEFG=+", &["345xyzKLM#-"]);
+        change_password(
+            &db_service,
+            &SystemClock,
+            &hasher(),
+            db_service.account.id(),
+            "012abcEFG=+",
+            "901qrsTUV&*",
+        )
+        .await
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod email_change_tests {
+    use chrono::TimeZone;
+    use domains::services::clock::SystemClock;
+
+    use super::*;
+
+    /// 発行したEメールアドレス変更トークンから、Eメールアドレスの変更内容を取り出せることを確認する。
+    #[test]
+    fn test_email_change_token() {
+        let id = AccountId::gen();
+        let current_email = EmailAddress::new("foo@example.com").unwrap();
+        let new_email = EmailAddress::new("bar@example.com").unwrap();
+        let now = SystemClock.now();
+        let token = gen_email_change_token(&id, &current_email, &new_email, now).unwrap();
+        assert_eq!(
+            token.expired_at,
+            now + Duration::seconds(ENV_VALUES.email_change_token_seconds)
+        );
+        let change = decode_email_change(&token.token).unwrap();
+        assert_eq!(change.id, id);
+        assert_eq!(change.current_email, current_email);
+        assert_eq!(change.new_email, new_email);
+    }
+
+    /// 有効期限が切れたEメールアドレス変更トークンを拒否することを確認する。
+    #[test]
+    fn test_expired_email_change_token() {
+        let now = FixedOffset::east_opt(9 * 60 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2022, 4, 1, 9, 0, 0)
+            .unwrap();
+        let token = gen_email_change_token(
+            &AccountId::gen(),
+            &EmailAddress::new("foo@example.com").unwrap(),
+            &EmailAddress::new("bar@example.com").unwrap(),
+            now,
+        )
+        .unwrap();
+        let err = decode_email_change(&token.token).err().unwrap();
+        assert!(matches!(err.code, ErrorKind::InvalidEmailChangeToken));
+        assert!(decode_email_change("invalid-token").is_err());
+    }
+}
+
+#[cfg(test)]
+mod account_dto_tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    /// アカウントデータトランスファーオブジェクトをシリアライズしたJSONに、都道府県名が含まれることを確認する。
+    #[test]
+    fn test_serialize_prefecture_name() {
+        let dt = FixedOffset::east_opt(9 * 60 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2022, 4, 1, 9, 0, 0)
+            .unwrap();
+        let account = Account::new(
+            EmailAddress::new("foo@example.com").unwrap(),
+            AccountName::new("foo").unwrap(),
+            HashedPassword::from_repository("SHA-256$1$1$a$b"),
+            true,
+            FixedMobileNumbers::new(None, Some(PhoneNumber::new("090-1234-5678").unwrap()))
+                .unwrap(),
+            PostalCode::new("100-0014").unwrap(),
+            Address::new(
+                Prefecture::new(13, "東京都").unwrap(),
+                AddressDetails::new("千代田区永田町1-7-1").unwrap(),
+            ),
+            dt,
+        );
+        let dto: AccountDto = account.into();
+        let value = serde_json::to_value(&dto).unwrap();
+        assert_eq!(value["prefectureCode"], 13);
+        assert_eq!(value["prefectureName"], "東京都");
+        assert_eq!(value["id"], dto.id.to_string());
+    }
+
+    /// 更新アカウントとパスワード変更のアカウントIDを、ULID文字列からデシリアライズできることを確認する。
+    #[test]
+    fn test_deserialize_account_id() {
+        let id = "01FVH5SD9H9Y4HNZ4J1Q3E3PFD";
+        let update: UpdateAccount = serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": "foo",
+            "isActive": true,
+            "fixedNumber": null,
+            "mobileNumber": "090-1234-5678",
+            "postalCode": "100-0014",
+            "prefectureCode": 13,
+            "addressDetails": "千代田区永田町1-7-1",
+            "updatedAt": "2022-04-01T09:00:00+09:00",
+        }))
+        .unwrap();
+        assert_eq!(update.id.to_string(), id);
+        let change: ChangePassword = serde_json::from_value(serde_json::json!({
+            "id": id,
+            "oldPassword": "old",
+            "newPassword": "new",
+        }))
+        .unwrap();
+        assert_eq!(change.id.to_string(), id);
+    }
+
+    /// アカウントIDがULIDの書式でない場合に、デシリアライズに失敗することを確認する。
+    #[test]
+    fn test_deserialize_malformed_account_id() {
+        for id in ["", "invalid-ulid-string", "01FVH5SD9H9Y4HNZ4J1Q3E3PF"] {
+            let result = serde_json::from_value::<ChangePassword>(serde_json::json!({
+                "id": id,
+                "oldPassword": "old",
+                "newPassword": "new",
+            }));
+            assert!(result.is_err(), "{}", id);
+        }
+    }
+}
@@ -1,30 +1,49 @@
 use std::borrow::Cow;
 
 use chrono::{DateTime, Duration, FixedOffset};
-use sea_orm::{ConnectionTrait, DatabaseConnection, DatabaseTransaction};
+use sea_orm::{DbErr, IsolationLevel};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use common::{
-    jwt_token::{gen_jwt_token, Claims},
+    jwt_token::{decode_jwt_token, gen_jwt_token, Claims},
     ENV_VALUES,
 };
 use domains::{
     models::{
-        accounts::{Account, AccountId, RawPassword},
+        accounts::{Account, AccountId, RawPassword, Role},
         auth::{JwtToken, JwtTokenWithExpiredAt, JwtTokens, JwtTokensId},
-        common::{local_now, EmailAddress},
+        common::EmailAddress,
     },
     repositories::{accounts::AccountRepository, auth::JwtTokensRepository},
-    services::auth::authenticate,
+    services::{auth::authenticate, clock::Clock, hashers::PasswordHasher},
 };
 
-use crate::database_service::DatabaseService;
+use crate::{
+    accounts::email_normalization,
+    database_service::DatabaseService,
+    transaction::{begin_transaction, is_retryable_error, with_transaction_retry, RetryableError},
+};
+
+/// 認証ユースケースのトランザクションの分離レベル。
+///
+/// `None`の場合はデータベースの既定の分離レベル(PostgreSQLはREAD COMMITTED)で実行する。
+/// 環境変数`TRANSACTION_ISOLATION_LEVEL`が設定されている場合は、環境変数の分離レベルで実行する。
+const TRANSACTION_ISOLATION_LEVEL: Option<IsolationLevel> = None;
+
+/// トークンを発行するトランザクションの分離レベル。
+///
+/// アカウントの認証から最終ログイン日時の更新及びトークンの登録までを、同じスナップショットで実行する。
+/// 同時に更新された場合はシリアライゼーションの失敗でトランザクションが中断されるため、再試行する。
+const OBTAIN_TOKENS_ISOLATION_LEVEL: Option<IsolationLevel> = Some(IsolationLevel::RepeatableRead);
 
 /// 認証ユースケースエラー区分
 #[derive(Debug, Clone)]
 pub enum ErrorKind {
     /// サーバー内部エラー
     InternalServerError,
+    /// シリアライゼーションの失敗またはデッドロックで、トランザクションが中断された
+    TransactionAborted,
     /// アカウントに登録したEメールアドレス、またはパスワードが異なる。
     InvalidCredential,
     /// Eメールアドレスが不正
@@ -42,8 +61,20 @@ pub struct Error {
     pub message: Cow<'static, str>,
 }
 
+impl RetryableError for Error {
+    fn is_retryable(&self) -> bool {
+        matches!(self.code, ErrorKind::TransactionAborted)
+    }
+}
+
+impl From<DbErr> for Error {
+    fn from(err: DbErr) -> Self {
+        internal_server_error(Box::new(err))
+    }
+}
+
 /// クレデンシャル
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Credential {
     /// Eメールアドレス。
@@ -52,14 +83,59 @@ pub struct Credential {
     pub password: String,
 }
 
+/// パスワードチェック
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckPassword {
+    /// 確認するパスワード。
+    pub password: String,
+}
+
+/// パスワードチェック結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordCheckResult {
+    /// パスワードが規則を満たしているかを示すフラグ。
+    pub valid: bool,
+    /// パスワードが違反している規則を示すエラーメッセージのリスト。規則を満たしている場合は省略する。
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
+}
+
+/// トークンイントロスペクション要求
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IntrospectToken {
+    /// 検証するトークン。
+    pub token: String,
+}
+
+/// トークンイントロスペクション結果
+///
+/// RFC 7662に従い、トークンが有効な場合のみアカウントIDと有効期限を含める。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IntrospectionResult {
+    /// トークンが有効かを示すフラグ。
+    pub active: bool,
+    /// アカウントID。トークンが無効な場合は省略する。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    /// 有効期限を示すUnixエポック(1970-01-01(UTC)からの経過秒数)。トークンが無効な場合は省略する。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+}
+
 /// 有効期限付きアクセス・リフレッシュトークンデータトランスファーオブジェクト
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct JwtTokensDto {
     /// トークンID。
-    pub id: String,
+    #[schema(value_type = String)]
+    pub id: JwtTokensId,
     /// アカウントID。
-    pub account_id: String,
+    #[schema(value_type = String)]
+    pub account_id: AccountId,
     /// アクセストークン。
     pub access: String,
     /// アクセストークン有効期限。
@@ -71,7 +147,7 @@ pub struct JwtTokensDto {
 }
 
 fn to_email(value: &str) -> Result<EmailAddress, Error> {
-    match EmailAddress::new(value) {
+    match EmailAddress::normalized(value, email_normalization()) {
         Ok(value) => Ok(value),
         Err(e) => Err(Error {
             code: ErrorKind::InvalidEmailAddress,
@@ -100,41 +176,30 @@ fn to_raw_password(value: &str) -> Result<RawPassword, Error> {
 ///
 /// インターナルエラー。
 fn internal_server_error(err: Box<dyn std::error::Error>) -> Error {
+    // シリアライゼーションの失敗またはデッドロックの場合は、トランザクションを再試行できるエラーにする
+    let code = if is_retryable_error(err.as_ref()) {
+        log::warn!("{}", err);
+        ErrorKind::TransactionAborted
+    } else {
+        log::error!("{}", err);
+        ErrorKind::InternalServerError
+    };
     Error {
-        code: ErrorKind::InternalServerError,
+        code,
         message: format!("{}", err).into(),
     }
 }
 
-/// トランザクションを開始する。
-///
-/// # Arguments
-///
-/// * `conn` - データベースコネクション。
-///
-/// # Returns
-///
-/// `Result`。返却される`Result`の内容は以下の通り。
-///
-/// * `Ok`: データベーストランザクション。
-/// * `Err`: エラー。
-async fn begin_transaction(conn: &DatabaseConnection) -> Result<DatabaseTransaction, Error> {
-    let txn = conn.begin().await;
-    if let Err(err) = txn {
-        return Err(internal_server_error(Box::new(err)));
-    }
-
-    Ok(txn.unwrap())
-}
-
 /// アカウントを認証する。
 ///
 /// # Arguments
 ///
 /// * `repos` - リポジトリエクステンション。
 /// * `txn` - データベーストランザクション。
+/// * `hasher` - パスワードをハッシュ化し直すときに使用する構造体。
 /// * `email` - 認証するアカウントのEメールアドレス。
 /// * `password` - 認証するアカウントのパスワード。
+/// * `now` - 最終ログイン日時に記録する現在日時。
 ///
 /// # Returns
 ///
@@ -144,10 +209,12 @@ async fn begin_transaction(conn: &DatabaseConnection) -> Result<DatabaseTransact
 /// * `Err`: エラー。`
 async fn authenticate_account(
     repo: &dyn AccountRepository,
+    hasher: &dyn PasswordHasher,
     email: EmailAddress,
     password: RawPassword,
+    now: DateTime<FixedOffset>,
 ) -> Result<Account, Error> {
-    let result = authenticate(repo, email, password).await;
+    let result = authenticate(repo, hasher, email, password, now).await;
     if let Err(err) = result {
         return Err(internal_server_error(err.into()));
     }
@@ -168,6 +235,8 @@ async fn authenticate_account(
 /// # Arguments
 ///
 /// * `account_id` - アカウントID。
+/// * `role` - トークンに記録するアカウントの権限。
+/// * `now` - トークンの有効期限の起点となる現在日時。
 ///
 /// # Returns
 ///
@@ -175,15 +244,20 @@ async fn authenticate_account(
 ///
 /// * `Ok`: 有効期限付きアクセス・リフレッシュトークン。
 /// * `Err`: エラー。
-fn gen_jwt_tokens(account_id: AccountId) -> Result<JwtTokens, Error> {
+fn gen_jwt_tokens(
+    account_id: AccountId,
+    role: Role,
+    now: DateTime<FixedOffset>,
+) -> Result<JwtTokens, Error> {
     // 有効期限を設定
-    let now = local_now(None);
     let access_expired_at = now + Duration::seconds(ENV_VALUES.access_token_seconds);
     let refresh_expired_at = now + Duration::seconds(ENV_VALUES.refresh_token_seconds);
     // トークンを生成
     let mut claims = Claims {
-        sub: account_id.value.to_string(),
+        sub: account_id.to_string(),
         exp: access_expired_at.timestamp(),
+        role: Some(role.to_string()),
+        ..Default::default()
     };
     let access = gen_jwt_token(&claims);
     if let Err(err) = access {
@@ -241,6 +315,8 @@ async fn save_jwt_tokens(
 /// # Arguments
 ///
 /// * `db_service` - リポジトリエクステンション。
+/// * `clock` - 時計。
+/// * `hasher` - パスワードをハッシュ化し直すときに使用する構造体。
 /// * `credential` - アカウントクレデンシャル。
 ///
 /// # Returns
@@ -251,34 +327,212 @@ async fn save_jwt_tokens(
 /// * `Err`: エラー。
 pub async fn obtain_tokens(
     db_service: &dyn DatabaseService,
+    clock: &dyn Clock,
+    hasher: &dyn PasswordHasher,
     credential: Credential,
 ) -> Result<JwtTokensDto, Error> {
-    let tokens;
     let email = to_email(&credential.email)?;
     let password = to_raw_password(&credential.password)?;
 
-    // トランザクションを開始
-    let txn = begin_transaction(&db_service.connection()).await?;
-    {
-        let account_repo = db_service.account(&txn);
-        let jwt_repo = db_service.jwt_tokens(&txn);
-        // アカウントを認証
-        let account = authenticate_account(&*account_repo, email, password).await?;
-        // トークンを生成
-        let result = gen_jwt_tokens(account.id())?;
-        // トークンを保存
-        tokens = save_jwt_tokens(&*jwt_repo, &result).await?;
+    // トークンを発行して、トランザクションが中断された場合は再試行
+    let tokens = with_transaction_retry(
+        db_service.connection(),
+        OBTAIN_TOKENS_ISOLATION_LEVEL,
+        ENV_VALUES.transaction_max_retries,
+        |txn| {
+            let (email, password) = (email.clone(), password.clone());
+            Box::pin(async move {
+                let account_repo = db_service.account(&txn);
+                let jwt_repo = db_service.jwt_tokens(&txn);
+                // アカウントを認証して、最終ログイン日時を更新
+                // パスワードのハッシュ化の設定が変更されている場合は、同じトランザクションでパスワードをハッシュ化し直す
+                let now = clock.now();
+                let account =
+                    authenticate_account(&*account_repo, hasher, email, password, now).await?;
+                // トークンを生成
+                let result = gen_jwt_tokens(account.id(), account.role(), now)?;
+                // トークンを保存
+                save_jwt_tokens(&*jwt_repo, &result).await
+            })
+        },
+    )
+    .await?;
+
+    Ok(JwtTokensDto {
+        id: tokens.id(),
+        account_id: tokens.account_id(),
+        access: tokens.access().token.value(),
+        access_expired_at: tokens.access().expired_at,
+        refresh: tokens.refresh().token.value(),
+        refresh_expired_at: tokens.refresh().expired_at,
+    })
+}
+
+/// アクセストークンがデータベースに記録されているか確認する。
+///
+/// アカウントを無効化したときなどにトークンは削除されるため、
+/// 有効期限内のアクセストークンであっても、データベースに記録されていない場合は無効として扱う。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `token` - アクセストークン。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: アクセストークンが記録されている場合は`true`、記録されていない場合は`false`。
+/// * `Err`: エラー。
+pub async fn is_access_token_stored(
+    db_service: &dyn DatabaseService,
+    token: &str,
+) -> Result<bool, Error> {
+    let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL).await?;
+    let result = db_service
+        .jwt_tokens(&txn)
+        .find_by_access_token(token)
+        .await;
+    let tokens = match result {
+        Ok(tokens) => tokens,
+        Err(err) => return Err(internal_server_error(err.into())),
+    };
+    match txn.commit().await {
+        Ok(_) => Ok(tokens.is_some()),
+        Err(err) => Err(internal_server_error(err.into())),
     }
-    // トランザクションをコミット
+}
+
+/// アカウントに発行したすべての有効期限付きアクセス・リフレッシュトークンを削除する。
+///
+/// アカウントがログインしているすべてのセッションからログアウトするために使用する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `account_id` - トークンを削除するアカウントのアカウントID。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 削除したトークンの数。
+/// * `Err`: エラー。
+pub async fn revoke_tokens(
+    db_service: &dyn DatabaseService,
+    account_id: &str,
+) -> Result<u64, Error> {
+    let account_id = match AccountId::try_from(account_id) {
+        Ok(account_id) => account_id,
+        Err(_) => {
+            return Err(Error {
+                code: ErrorKind::InvalidCredential,
+                message: format!("アクセストークンのアカウントID({})が不正です。", account_id)
+                    .into(),
+            })
+        }
+    };
+    let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL).await?;
+    let result = db_service
+        .jwt_tokens(&txn)
+        .delete_by_account_id(account_id)
+        .await;
+    let count = match result {
+        Ok(count) => count,
+        Err(err) => return Err(internal_server_error(err.into())),
+    };
     match txn.commit().await {
-        Ok(_) => Ok(JwtTokensDto {
-            id: tokens.id().value.to_string(),
-            account_id: tokens.account_id().value.to_string(),
-            access: tokens.access().token.value(),
-            access_expired_at: tokens.access().expired_at,
-            refresh: tokens.refresh().token.value(),
-            refresh_expired_at: tokens.refresh().expired_at,
-        }),
+        Ok(_) => Ok(count),
         Err(err) => Err(internal_server_error(err.into())),
     }
 }
+
+/// パスワードが規則を満たしているか確認する。
+///
+/// # Arguments
+///
+/// * `data` - 確認するパスワード。
+///
+/// # Returns
+///
+/// パスワードチェック結果。
+pub fn check_password(data: CheckPassword) -> PasswordCheckResult {
+    let reasons = RawPassword::validate_all(&data.password);
+
+    PasswordCheckResult {
+        valid: reasons.is_empty(),
+        reasons,
+    }
+}
+
+/// トークンを検証して、トークンのクレームを返却する。
+///
+/// トークンの署名と有効期限のみを検証して、データベースに記録されているかは確認しない。
+/// 署名が不正なトークン、有効期限が切れたトークン及びトークンとして解釈できない文字列は、無効なトークンとする。
+///
+/// # Arguments
+///
+/// * `data` - 検証するトークン。
+///
+/// # Returns
+///
+/// トークンイントロスペクション結果。
+pub fn introspect(data: IntrospectToken) -> IntrospectionResult {
+    match decode_jwt_token(&data.token) {
+        Ok(claims) => IntrospectionResult {
+            active: true,
+            sub: Some(claims.sub),
+            exp: Some(claims.exp),
+        },
+        Err(_) => IntrospectionResult {
+            active: false,
+            sub: None,
+            exp: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod gen_jwt_tokens_tests {
+    use chrono::TimeZone;
+    use domains::services::clock::FixedClock;
+
+    use super::*;
+
+    /// 注入された時計の日時を起点に、トークンの有効期限を設定することを確認する。
+    #[test]
+    fn test_gen_jwt_tokens_expired_at() {
+        let now = FixedOffset::east_opt(9 * 60 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2099, 4, 1, 9, 0, 0)
+            .unwrap();
+        let clock: &dyn Clock = &FixedClock(now);
+        let account_id = AccountId::gen();
+        let tokens = gen_jwt_tokens(account_id.clone(), Role::User, clock.now()).unwrap();
+        assert_eq!(tokens.account_id(), account_id);
+        assert_eq!(
+            tokens.access().expired_at,
+            now + Duration::seconds(ENV_VALUES.access_token_seconds)
+        );
+        assert_eq!(
+            tokens.refresh().expired_at,
+            now + Duration::seconds(ENV_VALUES.refresh_token_seconds)
+        );
+    }
+
+    /// アカウントの権限をアクセストークンとリフレッシュトークンに記録することを確認する。
+    #[test]
+    fn test_gen_jwt_tokens_role() {
+        let now = FixedOffset::east_opt(9 * 60 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2099, 4, 1, 9, 0, 0)
+            .unwrap();
+        for role in [Role::User, Role::Admin] {
+            let tokens = gen_jwt_tokens(AccountId::gen(), role, now).unwrap();
+            for token in [tokens.access(), tokens.refresh()] {
+                let claims = decode_jwt_token(&token.token.value()).unwrap();
+                assert_eq!(claims.role, Some(role.to_string()));
+            }
+        }
+    }
+}
@@ -1,10 +1,12 @@
 use sea_orm::{DatabaseConnection, DatabaseTransaction};
 
 use domains::repositories::{
-    accounts::AccountRepository, auth::JwtTokensRepository, common::PrefectureRepository,
+    accounts::{AccountRepository, PasswordHistoryRepository},
+    auth::JwtTokensRepository,
+    common::PrefectureRepository,
 };
 
-use crate::queries::AccountQueryService;
+use crate::queries::{AccountQueryService, PrefectureStatsQueryService};
 
 /// データベースサービス
 pub trait DatabaseService: Send + Sync {
@@ -13,7 +15,7 @@ pub trait DatabaseService: Send + Sync {
     /// # Returns
     ///
     /// データベースコネクション。
-    fn connection(&self) -> DatabaseConnection;
+    fn connection(&self) -> &DatabaseConnection;
 
     /// 都道府県リポジトリを返却する。
     ///
@@ -28,6 +30,35 @@ pub trait DatabaseService: Send + Sync {
     /// アカウントリポジトリ。
     fn account<'a>(&self, txn: &'a DatabaseTransaction) -> Box<dyn AccountRepository + 'a>;
 
+    /// トランザクションを開始せずに、データベースコネクションでクエリを実行する都道府県リポジトリを返却する。
+    ///
+    /// 読み取り専用のユースケースで使用する。都道府県を登録する場合は、`prefecture`で取得したリポジトリを使用する。
+    ///
+    /// # Returns
+    ///
+    /// 都道府県リポジトリ。
+    fn prefecture_reader(&self) -> Box<dyn PrefectureRepository + '_>;
+
+    /// トランザクションを開始せずに、データベースコネクションでクエリを実行するアカウントリポジトリを返却する。
+    ///
+    /// 読み取り専用のユースケースで使用する。アカウントを登録、更新または削除する場合は、
+    /// `account`で取得したリポジトリを使用する。
+    ///
+    /// # Returns
+    ///
+    /// アカウントリポジトリ。
+    fn account_reader(&self) -> Box<dyn AccountRepository + '_>;
+
+    /// パスワード履歴リポジトリを返却する。
+    ///
+    /// # Returns
+    ///
+    /// パスワード履歴リポジトリ。
+    fn password_history<'a>(
+        &self,
+        txn: &'a DatabaseTransaction,
+    ) -> Box<dyn PasswordHistoryRepository + 'a>;
+
     /// JWTトークンリポジトリを返却する。
     ///
     /// # Returns
@@ -44,4 +75,14 @@ pub trait DatabaseService: Send + Sync {
         &self,
         txn: &'a DatabaseTransaction,
     ) -> Box<dyn AccountQueryService + 'a>;
+
+    /// 都道府県統計クエリサービスを返却する。
+    ///
+    /// # Returns
+    ///
+    /// 都道府県統計クエリサービス。
+    fn prefecture_stats_service<'a>(
+        &self,
+        txn: &'a DatabaseTransaction,
+    ) -> Box<dyn PrefectureStatsQueryService + 'a>;
 }
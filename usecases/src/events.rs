@@ -0,0 +1,79 @@
+use domains::models::accounts::AccountId;
+
+use crate::accounts::AccountDto;
+
+/// アカウントイベントシンクトレイト
+///
+/// アカウントの登録や削除を、ウェルカムメールの送信や分析などの外部の処理に通知する。
+/// イベントは、アカウントを登録または削除したトランザクションをコミットした後に通知される。
+pub trait AccountEventSink: Send + Sync {
+    /// アカウントが登録されたときに呼び出される。
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - 登録されたアカウント。
+    fn on_account_created(&self, account: &AccountDto);
+
+    /// アカウントが削除されたときに呼び出される。
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - 削除されたアカウントのアカウントID。
+    fn on_account_deleted(&self, id: &AccountId);
+}
+
+/// 何もしないアカウントイベントシンク
+#[derive(Debug, Clone, Default)]
+pub struct NoopAccountEventSink;
+
+impl AccountEventSink for NoopAccountEventSink {
+    fn on_account_created(&self, _account: &AccountDto) {}
+
+    fn on_account_deleted(&self, _id: &AccountId) {}
+}
+
+#[cfg(test)]
+mod account_event_sink_tests {
+    use std::sync::Arc;
+
+    use chrono::{FixedOffset, TimeZone};
+    use domains::models::{
+        accounts::{AccountName, Role},
+        common::{AddressDetails, EmailAddress, PhoneNumber, PostalCode},
+    };
+
+    use super::*;
+
+    fn account_dto(id: &AccountId) -> AccountDto {
+        let dt = FixedOffset::east_opt(9 * 60 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2022, 4, 1, 9, 0, 0)
+            .unwrap();
+        AccountDto {
+            id: id.clone(),
+            email: EmailAddress::new("foo@example.com").unwrap(),
+            name: AccountName::new("foo").unwrap(),
+            is_active: true,
+            role: Role::User,
+            fixed_number: None,
+            mobile_number: Some(PhoneNumber::new("090-1234-5678").unwrap()),
+            postal_code: PostalCode::new("100-0014").unwrap(),
+            prefecture_code: 13,
+            prefecture_name: "東京都".to_owned(),
+            address_details: AddressDetails::new("千代田区永田町1-7-1").unwrap(),
+            logged_in_at: None,
+            created_at: dt,
+            updated_at: dt,
+            updated_by: None,
+        }
+    }
+
+    /// 何もしないアカウントイベントシンクを呼び出せることを確認する。
+    #[test]
+    fn test_noop_account_event_sink() {
+        let sink: Arc<dyn AccountEventSink> = Arc::new(NoopAccountEventSink);
+        let id = AccountId::gen();
+        sink.on_account_created(&account_dto(&id));
+        sink.on_account_deleted(&id);
+    }
+}
@@ -1,5 +1,8 @@
 pub mod accounts;
 pub mod auth;
 pub mod database_service;
+pub mod events;
+pub mod metrics;
 pub mod prefectures;
 pub mod queries;
+pub mod transaction;
@@ -0,0 +1,111 @@
+use std::ops::Deref;
+
+use once_cell::sync::Lazy;
+use prometheus::{Histogram, HistogramOpts, HistogramTimer};
+use sea_orm::{
+    ConnectionTrait, DatabaseBackend, DatabaseConnection, DatabaseTransaction, DbErr,
+    IsolationLevel, Statement, TransactionTrait,
+};
+
+/// データベーストランザクションの開始から終了までの時間(秒)を記録するヒストグラム。
+///
+/// メトリクスを公開するレジストリに登録して使用する。
+pub static DB_TRANSACTION_DURATION_SECONDS: Lazy<Histogram> = Lazy::new(|| {
+    Histogram::with_opts(HistogramOpts::new(
+        "db_transaction_duration_seconds",
+        "データベーストランザクションの開始から終了までの時間(秒)。",
+    ))
+    .expect("データベーストランザクションのヒストグラムを構築できません。")
+});
+
+/// 処理時間を計測するデータベーストランザクション
+///
+/// トランザクションをコミットしたとき、またはコミットせずに破棄(ロールバック)したときに、
+/// トランザクションの開始からの経過時間を`DB_TRANSACTION_DURATION_SECONDS`に記録する。
+pub struct TimedTransaction {
+    /// データベーストランザクション。
+    txn: DatabaseTransaction,
+    /// トランザクションの処理時間を計測するタイマー。
+    timer: HistogramTimer,
+}
+
+impl TimedTransaction {
+    /// 分離レベルを指定して、トランザクションを開始する。
+    ///
+    /// 環境変数の分離レベルを反映するため、クレートの外部からは`transaction::begin_transaction`で開始する。
+    ///
+    /// PostgreSQLでは、トランザクションを開始した後に`SET TRANSACTION`で分離レベルを設定する。
+    /// SeaORMのモックは`begin_with_config`に指定した分離レベルを無視するため、
+    /// `SET TRANSACTION`を明示的に実行して、モックのトランザクションログに記録されるようにしている。
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - データベースコネクション。
+    /// * `isolation_level` - 分離レベル。`None`の場合はデータベースの既定の分離レベル。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 処理時間を計測するデータベーストランザクション。
+    /// * `Err`: エラー。
+    pub(crate) async fn begin_with_isolation_level(
+        conn: &DatabaseConnection,
+        isolation_level: Option<IsolationLevel>,
+    ) -> Result<Self, DbErr> {
+        let timer = DB_TRANSACTION_DURATION_SECONDS.start_timer();
+        let txn = match isolation_level {
+            None => conn.begin().await?,
+            Some(level) if conn.get_database_backend() == DatabaseBackend::Postgres => {
+                let txn = conn.begin().await?;
+                txn.execute(Statement::from_string(
+                    DatabaseBackend::Postgres,
+                    format!("SET TRANSACTION ISOLATION LEVEL {}", level),
+                ))
+                .await?;
+                txn
+            }
+            Some(level) => conn.begin_with_config(Some(level), None).await?,
+        };
+
+        Ok(Self { txn, timer })
+    }
+
+    /// トランザクションをコミットして、処理時間を記録する。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: ()
+    /// * `Err`: エラー。
+    pub async fn commit(self) -> Result<(), DbErr> {
+        let result = self.txn.commit().await;
+        self.timer.observe_duration();
+
+        result
+    }
+
+    /// トランザクションをロールバックして、処理時間を記録する。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: ()
+    /// * `Err`: エラー。
+    pub async fn rollback(self) -> Result<(), DbErr> {
+        let result = self.txn.rollback().await;
+        self.timer.observe_duration();
+
+        result
+    }
+}
+
+impl Deref for TimedTransaction {
+    type Target = DatabaseTransaction;
+
+    fn deref(&self) -> &Self::Target {
+        &self.txn
+    }
+}
@@ -1,8 +1,21 @@
-use sea_orm::ConnectionTrait;
+use std::collections::BTreeMap;
 
-use domains::models::common::Prefecture;
+use sea_orm::IsolationLevel;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
+use domains::models::common::{Prefecture, Region};
+
+use crate::accounts::AccountDto;
 use crate::database_service::DatabaseService;
+use crate::queries::{CreatedAtRange, Page, Pagination, PrefectureStats};
+use crate::transaction::begin_transaction;
+
+/// 都道府県ユースケースのトランザクションの分離レベル。
+///
+/// `None`の場合はデータベースの既定の分離レベル(PostgreSQLはREAD COMMITTED)で実行する。
+/// 環境変数`TRANSACTION_ISOLATION_LEVEL`が設定されている場合は、環境変数の分離レベルで実行する。
+const TRANSACTION_ISOLATION_LEVEL: Option<IsolationLevel> = None;
 
 /// 都道府県のリストを返却する。
 ///
@@ -17,11 +30,80 @@ use crate::database_service::DatabaseService;
 /// * `Ok`: 都道府県のリスト。
 /// * `Err`: エラー。
 pub async fn list(db_service: &dyn DatabaseService) -> anyhow::Result<Vec<Prefecture>> {
-    let txn = db_service.connection().begin().await?;
-    let result = db_service.prefecture(&txn).list().await?;
+    // 読み取りのみのため、トランザクションを開始せずに都道府県を取得
+    db_service.prefecture_reader().list().await
+}
+
+/// 都道府県コードが登録されていない都道府県を登録して、47都道府県を揃える。
+///
+/// 登録されている都道府県はスキップするため、繰り返し実行できる。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 登録した都道府県の数。
+/// * `Err`: エラー。
+pub async fn seed_prefectures(db_service: &dyn DatabaseService) -> anyhow::Result<u64> {
+    let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL).await?;
+    let inserted = db_service
+        .prefecture(&txn)
+        .insert_missing(&Prefecture::all())
+        .await?;
     txn.commit().await?;
 
-    Ok(result)
+    Ok(inserted)
+}
+
+/// 地方別都道府県
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionWithPrefectures {
+    /// 地方。
+    pub region: Region,
+    /// 地方に属する都道府県のリスト。
+    pub prefectures: Vec<Prefecture>,
+}
+
+/// 都道府県のリストを地方別に分類する。
+///
+/// 地方別都道府県は地方の順に並べ、地方に属する都道府県は都道府県コードの順に並べる。
+/// 地方に属さない都道府県は、警告をログに出力して除外する。
+///
+/// # Arguments
+///
+/// * `prefectures` - 都道府県のリスト。
+///
+/// # Returns
+///
+/// 地方別都道府県のリスト。
+pub fn group_by_region(prefectures: Vec<Prefecture>) -> Vec<RegionWithPrefectures> {
+    let mut regions: BTreeMap<Region, Vec<Prefecture>> = BTreeMap::new();
+    for prefecture in prefectures {
+        match prefecture.region() {
+            Some(region) => regions.entry(region).or_default().push(prefecture),
+            None => log::warn!(
+                "都道府県({}: {})が属する地方がありません。",
+                prefecture.code(),
+                prefecture.name()
+            ),
+        }
+    }
+
+    regions
+        .into_iter()
+        .map(|(region, mut prefectures)| {
+            prefectures.sort_by_key(|p| p.code());
+            RegionWithPrefectures {
+                region,
+                prefectures,
+            }
+        })
+        .collect()
 }
 
 /// 指定された都道府県コードと一致する都道府県を検索して返却する。
@@ -39,9 +121,175 @@ pub async fn find_by_code(
     db_service: &dyn DatabaseService,
     code: u8,
 ) -> anyhow::Result<Option<Prefecture>> {
-    let txn = db_service.connection().begin().await?;
-    let result = db_service.prefecture(&txn).find_by_code(code).await?;
+    // 読み取りのみのため、トランザクションを開始せずに都道府県を取得
+    db_service.prefecture_reader().find_by_code(code).await
+}
+
+/// 複数の都道府県コード
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PrefectureCodes {
+    /// 都道府県コードのリスト。
+    pub codes: Vec<u8>,
+}
+
+/// 指定された都道府県コードと一致する都道府県を、指定された都道府県コードの順に返却する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `codes` - 都道府県コードのスライス。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 都道府県のリスト。都道府県が見つからなかった都道府県コードの要素は`None`。
+/// * `Err`: エラー。
+pub async fn find_by_codes(
+    db_service: &dyn DatabaseService,
+    codes: &[u8],
+) -> anyhow::Result<Vec<Option<Prefecture>>> {
+    let mut unique = codes.to_vec();
+    unique.sort_unstable();
+    unique.dedup();
+    // 読み取りのみのため、トランザクションを開始せずに都道府県を取得
+    let found = db_service
+        .prefecture_reader()
+        .find_by_codes(&unique)
+        .await?;
+
+    Ok(in_code_order(codes, found))
+}
+
+/// 都道府県のリストを、指定された都道府県コードの順に並べる。
+///
+/// # Arguments
+///
+/// * `codes` - 都道府県コードのスライス。
+/// * `prefectures` - 都道府県のリスト。
+///
+/// # Returns
+///
+/// 都道府県コードと同じ数の都道府県のリスト。都道府県が見つからなかった都道府県コードの要素は`None`。
+fn in_code_order(codes: &[u8], prefectures: Vec<Prefecture>) -> Vec<Option<Prefecture>> {
+    let prefectures: BTreeMap<u8, Prefecture> =
+        prefectures.into_iter().map(|p| (p.code(), p)).collect();
+
+    codes
+        .iter()
+        .map(|code| prefectures.get(code).cloned())
+        .collect()
+}
+
+/// 都道府県に住所を登録しているアカウントを、登録日時順に返却する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+/// * `code` - 都道府県コード。
+/// * `created_at` - 登録日時の範囲指定。
+/// * `pagination` - ページ指定。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: アカウントのページ。都道府県が見つからなかった場合は`None`。
+/// * `Err`: エラー。
+pub async fn list_accounts(
+    db_service: &dyn DatabaseService,
+    code: u8,
+    created_at: CreatedAtRange,
+    pagination: Pagination,
+) -> anyhow::Result<Option<Page<AccountDto>>> {
+    let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL).await?;
+    if db_service
+        .prefecture(&txn)
+        .find_by_code(code)
+        .await?
+        .is_none()
+    {
+        return Ok(None);
+    }
+    let result = db_service
+        .account_service(&txn)
+        .list_by_prefecture(code, created_at, pagination)
+        .await?;
+    txn.commit().await?;
+
+    Ok(Some(result.map(|account| account.into())))
+}
+
+/// 都道府県別のアカウント数とアクティブなアカウント数を返却する。
+///
+/// # Arguments
+///
+/// * `db_service` - データベースサービス。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 都道府県コード順に並べた都道府県別アカウント統計のリスト。
+/// * `Err`: エラー。
+pub async fn stats(db_service: &dyn DatabaseService) -> anyhow::Result<Vec<PrefectureStats>> {
+    let txn = begin_transaction(db_service.connection(), TRANSACTION_ISOLATION_LEVEL).await?;
+    let result = db_service
+        .prefecture_stats_service(&txn)
+        .account_counts()
+        .await?;
     txn.commit().await?;
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod group_by_region_tests {
+    use super::*;
+
+    /// 都道府県のリストを地方別に分類できることを確認する。
+    #[test]
+    fn test_group_by_region() {
+        let prefectures = [27, 13, 1, 8]
+            .into_iter()
+            .map(|code| Prefecture::from_code(code).unwrap())
+            .collect();
+        let regions = group_by_region(prefectures);
+        let actual: Vec<(Region, Vec<u8>)> = regions
+            .iter()
+            .map(|r| (r.region, r.prefectures.iter().map(|p| p.code()).collect()))
+            .collect();
+        assert_eq!(
+            actual,
+            vec![
+                (Region::HokkaidoTohoku, vec![1]),
+                (Region::Kanto, vec![8, 13]),
+                (Region::Kinki, vec![27]),
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+mod in_code_order_tests {
+    use super::*;
+
+    /// 都道府県を指定された都道府県コードの順に並べて、見つからなかった都道府県コードを`None`にすることを確認する。
+    #[test]
+    fn test_in_code_order() {
+        let prefectures = [1, 13, 27]
+            .into_iter()
+            .map(|code| Prefecture::from_code(code).unwrap())
+            .collect();
+        let ordered = in_code_order(&[13, 99, 27, 1, 13, 0], prefectures);
+        let codes: Vec<Option<u8>> = ordered
+            .iter()
+            .map(|p| p.as_ref().map(|p| p.code()))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![Some(13), None, Some(27), Some(1), Some(13), None]
+        );
+        assert!(in_code_order(&[], vec![]).is_empty());
+    }
+}
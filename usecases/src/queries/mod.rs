@@ -1,18 +1,228 @@
+use anyhow::anyhow;
 use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use domains::models::{
     accounts::{Account, AccountId},
     auth::JwtTokens,
 };
 
+/// 1ページに含める要素の既定の数。
+const DEFAULT_PER_PAGE: u64 = 20;
+/// 1ページに含める要素の最大数。
+pub const MAX_PER_PAGE: u64 = 100;
+
+/// ページ指定
+///
+/// ページ番号は1から始まる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct Pagination {
+    /// ページ番号(省略時: 1)。
+    #[serde(default = "default_page")]
+    pub page: u64,
+    /// 1ページに含める要素の数(省略時: 20、最大: 100)。
+    #[serde(default = "default_per_page")]
+    pub per_page: u64,
+}
+
+fn default_page() -> u64 {
+    1
+}
+
+fn default_per_page() -> u64 {
+    DEFAULT_PER_PAGE
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self {
+            page: default_page(),
+            per_page: default_per_page(),
+        }
+    }
+}
+
+impl Pagination {
+    /// ページ指定を検証する。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: ページ指定が妥当な場合。
+    /// * `Err`: エラーメッセージ。
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.page == 0 {
+            return Err(anyhow!("ページ番号は1以上を指定してください。"));
+        }
+        if !(1..=MAX_PER_PAGE).contains(&self.per_page) {
+            return Err(anyhow!(
+                "1ページに含める要素の数は1以上{}以下を指定してください。",
+                MAX_PER_PAGE
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// ページの先頭の要素の位置を返却する。
+    ///
+    /// # Returns
+    ///
+    /// ページの先頭の要素の、0から始まる位置。
+    pub fn offset(&self) -> u64 {
+        (self.page - 1) * self.per_page
+    }
+}
+
+/// 登録日時の範囲指定
+///
+/// 日時はISO 8601形式(例: `2022-04-01T00:00:00+09:00`)で指定する。
+/// 下限と上限はそれぞれ省略でき、省略した側は範囲を制限しない。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct CreatedAtRange {
+    /// 登録日時の下限(この日時を含む)。
+    #[param(value_type = Option<String>, format = DateTime)]
+    pub created_from: Option<DateTime<FixedOffset>>,
+    /// 登録日時の上限(この日時を含む)。
+    #[param(value_type = Option<String>, format = DateTime)]
+    pub created_to: Option<DateTime<FixedOffset>>,
+}
+
+impl CreatedAtRange {
+    /// 登録日時の範囲指定を検証する。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 範囲指定が妥当な場合。
+    /// * `Err`: エラーメッセージ。
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let (Some(from), Some(to)) = (self.created_from, self.created_to) {
+            if to < from {
+                return Err(anyhow!(
+                    "登録日時の上限(createdTo)は、下限(createdFrom)以降の日時を指定してください。"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// アカウントの検索条件
+///
+/// 指定された条件をすべて満たすアカウントを検索する。省略した条件では絞り込まない。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct AccountSearch {
+    /// アカウント名またはEメールアドレスに含まれる文字列(大文字と小文字を区別しない)。
+    pub q: Option<String>,
+    /// 都道府県コード。
+    pub prefecture_code: Option<u8>,
+    /// アクティブフラグ。
+    pub is_active: Option<bool>,
+}
+
+impl AccountSearch {
+    /// アカウント名とEメールアドレスを検索する文字列を返却する。
+    ///
+    /// # Returns
+    ///
+    /// 前後の空白を除いた検索する文字列。文字列が指定されていない、または空の場合は`None`。
+    pub fn text(&self) -> Option<&str> {
+        self.q.as_deref().map(str::trim).filter(|q| !q.is_empty())
+    }
+
+    /// 文字列以外の検索条件が指定されているか確認する。
+    ///
+    /// # Returns
+    ///
+    /// 都道府県コードまたはアクティブフラグが指定されている場合は`true`。
+    pub fn has_filters(&self) -> bool {
+        self.prefecture_code.is_some() || self.is_active.is_some()
+    }
+}
+
+/// Eメールアドレスによるアカウントの検索条件
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EmailAddressSearch {
+    /// Eメールアドレス。
+    pub email: String,
+}
+
+/// ページ
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    /// ページに含まれる要素のリスト。
+    pub items: Vec<T>,
+    /// ページ番号。
+    pub page: u64,
+    /// 1ページに含める要素の数。
+    pub per_page: u64,
+    /// 全ページの要素の総数。
+    pub total: u64,
+}
+
+impl<T> Page<T> {
+    /// ページに含まれる要素を変換する。
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - 要素を変換する関数。
+    ///
+    /// # Returns
+    ///
+    /// 要素を変換したページ。
+    pub fn map<U, F: FnMut(T) -> U>(self, f: F) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            page: self.page,
+            per_page: self.per_page,
+            total: self.total,
+        }
+    }
+}
+
+/// アカウントとトークン
 pub struct AccountTokens {
+    /// アカウント。
     pub account: Account,
+    /// アカウントに関連付けられたトークンのうち、最も新しく発行した有効期限が切れていないトークン。
+    /// 有効期限が切れていないトークンがない場合は`None`。
     pub tokens: Option<JwtTokens>,
 }
 
+/// 都道府県別アカウント統計
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefectureStats {
+    /// 都道府県コード。
+    pub code: u8,
+    /// 都道府県名。
+    pub name: String,
+    /// 都道府県に住所を登録しているアカウントの数。
+    pub account_count: u64,
+    /// 都道府県に住所を登録しているアクティブなアカウントの数。
+    pub active_count: u64,
+}
+
+#[cfg_attr(feature = "test-util", mockall::automock)]
 #[async_trait]
 pub trait AccountQueryService {
-    /// アカウントとトークンを取得する。
+    /// アクティブなアカウントとトークンを取得する。
+    ///
+    /// 非アクティブなアカウントは、見つからなかったものとして扱う。
     ///
     /// # Arguments
     ///
@@ -22,10 +232,177 @@ pub trait AccountQueryService {
     ///
     /// `Result`。返却される`Result`の内容は以下の通り。
     ///
-    /// * `Ok`: アカウント道買った場合はアカウントとトークン。アカウントが見つからなかった場合は`None`。
+    /// * `Ok`: アクティブなアカウントが見つかった場合はアカウントと最新のトークン。
+    ///   アカウントが見つからなかった場合、またはアカウントが非アクティブな場合は`None`。
     /// * `Err`: エラー。
     async fn find_active_account_by_id(
         &self,
         id: AccountId,
     ) -> anyhow::Result<Option<AccountTokens>>;
+
+    /// アクティブフラグに関わらず、アカウントとトークンを取得する。
+    ///
+    /// 非アクティブなアカウントも取得するため、管理者向けの処理で使用する。
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - アカウントID。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: アカウントが見つかった場合はアカウントと最新のトークン。アカウントが見つからなかった場合は`None`。
+    /// * `Err`: エラー。
+    async fn find_account_by_id(&self, id: AccountId) -> anyhow::Result<Option<AccountTokens>>;
+
+    /// 都道府県に住所を登録しているアカウントを、登録日時順に取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `prefecture_code` - 都道府県コード。
+    /// * `created_at` - 登録日時の範囲指定。
+    /// * `pagination` - ページ指定。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: アカウントのページ。
+    /// * `Err`: エラー。
+    async fn list_by_prefecture(
+        &self,
+        prefecture_code: u8,
+        created_at: CreatedAtRange,
+        pagination: Pagination,
+    ) -> anyhow::Result<Page<Account>>;
+
+    /// 検索条件に一致するアカウントを、登録日時順に取得する。
+    ///
+    /// アカウント名とEメールアドレスを検索する文字列は、アカウント名またはEメールアドレスのいずれかに
+    /// 部分一致(大文字と小文字を区別しない)するアカウントを検索する。
+    ///
+    /// # Arguments
+    ///
+    /// * `search` - 検索条件。
+    /// * `pagination` - ページ指定。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: アカウントのページ。
+    /// * `Err`: エラー。
+    async fn search(
+        &self,
+        search: AccountSearch,
+        pagination: Pagination,
+    ) -> anyhow::Result<Page<Account>>;
+}
+
+#[cfg_attr(feature = "test-util", mockall::automock)]
+#[async_trait]
+pub trait PrefectureStatsQueryService {
+    /// 都道府県別のアカウント数とアクティブなアカウント数を取得する。
+    ///
+    /// アカウントが登録されていない都道府県も、アカウント数を0として含める。
+    ///
+    /// # Returns
+    ///
+    /// `Result`。返却される`Result`の内容は以下の通り。
+    ///
+    /// * `Ok`: 都道府県コード順に並べた都道府県別アカウント統計のベクタ。
+    /// * `Err`: エラー。
+    async fn account_counts(&self) -> anyhow::Result<Vec<PrefectureStats>>;
+}
+
+#[cfg(test)]
+mod pagination_tests {
+    use super::*;
+
+    /// ページ指定を省略した場合に、既定のページ指定になることを確認する。
+    #[test]
+    fn test_pagination_default() {
+        let pagination: Pagination = serde_json::from_str("{}").unwrap();
+        assert_eq!(pagination, Pagination::default());
+        assert_eq!(pagination.page, 1);
+        assert_eq!(pagination.per_page, DEFAULT_PER_PAGE);
+        assert_eq!(pagination.offset(), 0);
+        let pagination: Pagination = serde_json::from_str(r#"{"page": 3, "perPage": 10}"#).unwrap();
+        assert_eq!(pagination.offset(), 20);
+    }
+
+    /// 不正なページ指定を検証できることを確認する。
+    #[test]
+    fn test_pagination_validate() {
+        let valid = [(1, 1), (1, MAX_PER_PAGE), (100, 20)];
+        for (page, per_page) in valid {
+            assert!(Pagination { page, per_page }.validate().is_ok());
+        }
+        let invalid = [(0, 20), (1, 0), (1, MAX_PER_PAGE + 1)];
+        for (page, per_page) in invalid {
+            assert!(Pagination { page, per_page }.validate().is_err());
+        }
+    }
+
+    /// ページに含まれる要素を変換できることを確認する。
+    #[test]
+    fn test_page_map() {
+        let page = Page {
+            items: vec![1, 2, 3],
+            page: 2,
+            per_page: 3,
+            total: 8,
+        };
+        let page = page.map(|item| item.to_string());
+        assert_eq!(page.items, vec!["1", "2", "3"]);
+        assert_eq!((page.page, page.per_page, page.total), (2, 3, 8));
+        let json = serde_json::to_value(&page).unwrap();
+        assert_eq!(json["perPage"], 3);
+    }
+
+    /// 登録日時の範囲指定をISO 8601形式で指定できて、下限と上限をそれぞれ省略できることを確認する。
+    #[test]
+    fn test_created_at_range() {
+        let range: CreatedAtRange = serde_json::from_str("{}").unwrap();
+        assert_eq!(range, CreatedAtRange::default());
+        let range: CreatedAtRange =
+            serde_json::from_str(r#"{"createdFrom": "2022-04-01T00:00:00+09:00"}"#).unwrap();
+        assert_eq!(
+            range.created_from.unwrap().to_rfc3339(),
+            "2022-04-01T00:00:00+09:00"
+        );
+        assert!(range.created_to.is_none());
+        assert!(range.validate().is_ok());
+        let range: CreatedAtRange = serde_json::from_str(
+            r#"{"createdFrom": "2022-04-02T00:00:00+09:00", "createdTo": "2022-04-01T00:00:00+09:00"}"#,
+        )
+        .unwrap();
+        assert!(range.validate().is_err());
+        assert!(serde_json::from_str::<CreatedAtRange>(r#"{"createdTo": "2022-04-01"}"#).is_err());
+    }
+}
+
+#[cfg(test)]
+mod account_search_tests {
+    use super::*;
+
+    /// 検索する文字列の前後の空白を除き、空の場合は文字列で絞り込まないことを確認する。
+    #[test]
+    fn test_account_search_text() {
+        let search = |q: Option<&str>| AccountSearch {
+            q: q.map(str::to_owned),
+            ..Default::default()
+        };
+        assert_eq!(search(Some(" foo ")).text(), Some("foo"));
+        assert_eq!(search(Some("  ")).text(), None);
+        assert_eq!(search(Some("")).text(), None);
+        assert_eq!(search(None).text(), None);
+        assert!(!search(Some("foo")).has_filters());
+        let search = AccountSearch {
+            is_active: Some(true),
+            ..search(Some("foo"))
+        };
+        assert!(search.has_filters());
+    }
 }
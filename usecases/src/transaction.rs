@@ -0,0 +1,483 @@
+use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
+
+use sea_orm::{DatabaseConnection, DbErr, IsolationLevel, RuntimeErr};
+
+use common::{TransactionIsolationLevel, ENV_VALUES};
+
+use crate::metrics::TimedTransaction;
+
+/// 再試行するデータベースのエラーコード(SQLSTATE)。
+///
+/// * `40001`: シリアライゼーションの失敗(serialization_failure)。
+/// * `40P01`: デッドロックの検出(deadlock_detected)。
+const RETRYABLE_SQLSTATES: [&str; 2] = ["40001", "40P01"];
+
+/// トランザクションを再試行するまでの待機時間の基準(ミリ秒)。
+const RETRY_BACKOFF_BASE_MILLIS: u64 = 10;
+
+/// 再試行するトランザクションで実行する処理が返却するフューチャー。
+///
+/// 処理はトランザクションの参照ではなく`Arc`を受け取るため、フューチャーはトランザクションを借用せずに、
+/// 処理が借用しているデータベースサービスなどの値のライフタイムのみを持つ。
+pub type TransactionFuture<'c, T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + 'c>>;
+
+/// 再試行できるエラー
+///
+/// トランザクションを最初から実行し直せば成功する可能性があるエラーかを判定する。
+pub trait RetryableError {
+    /// 再試行できるエラーかを返却する。
+    ///
+    /// # Returns
+    ///
+    /// 再試行できるエラーの場合は`true`。
+    fn is_retryable(&self) -> bool;
+}
+
+/// データベースのエラーが、シリアライゼーションの失敗またはデッドロックによるものかを返却する。
+///
+/// # Arguments
+///
+/// * `err` - データベースのエラー。
+///
+/// # Returns
+///
+/// シリアライゼーションの失敗またはデッドロックによるエラーの場合は`true`。
+pub fn is_retryable_db_err(err: &DbErr) -> bool {
+    is_retryable_error(err)
+}
+
+/// エラーまたはエラーの原因から、データベースが返却したエラーを取得する。
+///
+/// リポジトリは`anyhow::Error`でデータベースのエラーを返却するため、エラーの原因を辿って取得する。
+///
+/// # Arguments
+///
+/// * `err` - エラー。
+///
+/// # Returns
+///
+/// データベースが返却したエラー。データベースが返却したエラーが含まれない場合は`None`。
+fn find_database_error<'a>(
+    err: &'a (dyn std::error::Error + 'static),
+) -> Option<&'a dyn sqlx::error::DatabaseError> {
+    let mut current = Some(err);
+    while let Some(err) = current {
+        let sqlx_err = match err.downcast_ref::<DbErr>() {
+            Some(
+                DbErr::Conn(RuntimeErr::SqlxError(err))
+                | DbErr::Exec(RuntimeErr::SqlxError(err))
+                | DbErr::Query(RuntimeErr::SqlxError(err)),
+            ) => Some(err),
+            Some(_) => return None,
+            None => err.downcast_ref::<sqlx::Error>(),
+        };
+        if let Some(sqlx_err) = sqlx_err {
+            return match sqlx_err {
+                sqlx::Error::Database(err) => Some(err.as_ref()),
+                _ => None,
+            };
+        }
+        current = err.source();
+    }
+
+    None
+}
+
+/// エラーまたはエラーの原因に、シリアライゼーションの失敗またはデッドロックによるデータベースのエラーが含まれるかを返却する。
+///
+/// # Arguments
+///
+/// * `err` - エラー。
+///
+/// # Returns
+///
+/// シリアライゼーションの失敗またはデッドロックによるエラーが含まれる場合は`true`。
+pub fn is_retryable_error(err: &(dyn std::error::Error + 'static)) -> bool {
+    find_database_error(err)
+        .and_then(|err| err.code())
+        .is_some_and(|code| RETRYABLE_SQLSTATES.contains(&code.as_ref()))
+}
+
+/// エラーまたはエラーの原因に、一意制約違反(PostgreSQLのSQLSTATEは`23505`)によるデータベースのエラーが含まれるかを返却する。
+///
+/// # Arguments
+///
+/// * `err` - エラー。
+///
+/// # Returns
+///
+/// 一意制約違反によるエラーが含まれる場合は`true`。
+pub fn is_unique_violation(err: &(dyn std::error::Error + 'static)) -> bool {
+    find_database_error(err).is_some_and(|err| err.is_unique_violation())
+}
+
+/// SQLSTATEを指定できるデータベースのエラー
+///
+/// データベースに接続せずに、データベースのエラーを処理するテストで使用する。
+#[cfg(any(test, feature = "test-util"))]
+#[derive(Debug)]
+struct TestDatabaseError(&'static str);
+
+#[cfg(any(test, feature = "test-util"))]
+impl std::fmt::Display for TestDatabaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "database error: {}", self.0)
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl std::error::Error for TestDatabaseError {}
+
+#[cfg(any(test, feature = "test-util"))]
+impl sqlx::error::DatabaseError for TestDatabaseError {
+    fn message(&self) -> &str {
+        "database error"
+    }
+
+    fn code(&self) -> Option<std::borrow::Cow<'_, str>> {
+        Some(std::borrow::Cow::Borrowed(self.0))
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn kind(&self) -> sqlx::error::ErrorKind {
+        match self.0 {
+            "23505" => sqlx::error::ErrorKind::UniqueViolation,
+            _ => sqlx::error::ErrorKind::Other,
+        }
+    }
+}
+
+/// 指定されたSQLSTATEのデータベースのエラーを返却する。
+///
+/// データベースに接続せずに、データベースのエラーを処理するテストで使用する。
+///
+/// # Arguments
+///
+/// * `code` - SQLSTATE。
+///
+/// # Returns
+///
+/// SQLを実行したときに発生したデータベースのエラー。
+#[cfg(any(test, feature = "test-util"))]
+pub fn test_db_err(code: &'static str) -> DbErr {
+    DbErr::Exec(RuntimeErr::SqlxError(sqlx::Error::Database(Box::new(
+        TestDatabaseError(code),
+    ))))
+}
+
+/// ユースケースが定めた分離レベルを、環境変数`TRANSACTION_ISOLATION_LEVEL`で上書きする。
+///
+/// # Arguments
+///
+/// * `isolation_level` - ユースケースが定めた分離レベル。
+///
+/// # Returns
+///
+/// 環境変数が設定されている場合は環境変数の分離レベル、設定されていない場合はユースケースが定めた分離レベル。
+fn effective_isolation_level(isolation_level: Option<IsolationLevel>) -> Option<IsolationLevel> {
+    match ENV_VALUES.transaction_isolation_level {
+        Some(TransactionIsolationLevel::ReadUncommitted) => Some(IsolationLevel::ReadUncommitted),
+        Some(TransactionIsolationLevel::ReadCommitted) => Some(IsolationLevel::ReadCommitted),
+        Some(TransactionIsolationLevel::RepeatableRead) => Some(IsolationLevel::RepeatableRead),
+        Some(TransactionIsolationLevel::Serializable) => Some(IsolationLevel::Serializable),
+        None => isolation_level,
+    }
+}
+
+/// 分離レベルを指定して、トランザクションを開始する。
+///
+/// 環境変数`TRANSACTION_ISOLATION_LEVEL`が設定されている場合は、指定された分離レベルに関わらず、
+/// 環境変数の分離レベルでトランザクションを開始する。
+///
+/// # Arguments
+///
+/// * `conn` - データベースコネクション。
+/// * `isolation_level` - ユースケースが定めた分離レベル。`None`の場合はデータベースの既定の分離レベル。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 処理時間を計測するデータベーストランザクション。
+/// * `Err`: エラー。
+pub async fn begin_transaction(
+    conn: &DatabaseConnection,
+    isolation_level: Option<IsolationLevel>,
+) -> Result<TimedTransaction, DbErr> {
+    let isolation_level = effective_isolation_level(isolation_level);
+
+    TimedTransaction::begin_with_isolation_level(conn, isolation_level).await
+}
+
+/// トランザクションを再試行するまでの待機時間を返却する。
+///
+/// 同時に中断されたトランザクションが同じ時刻に再試行しないように、待機時間をランダムにずらす。
+///
+/// # Arguments
+///
+/// * `retries` - 再試行する回数目(1から開始)。
+///
+/// # Returns
+///
+/// 待機時間。
+fn retry_backoff(retries: u32) -> Duration {
+    let base = RETRY_BACKOFF_BASE_MILLIS << retries.min(6);
+
+    Duration::from_millis(base / 2 + fastrand::u64(0..=base / 2))
+}
+
+/// トランザクションを開始して処理を実行した後にコミットする。
+///
+/// 処理が失敗した場合は、コミットせずにトランザクションを破棄してロールバックする。
+///
+/// # Arguments
+///
+/// * `conn` - データベースコネクション。
+/// * `isolation_level` - 分離レベル。
+/// * `f` - トランザクションで実行する処理。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 処理の結果。
+/// * `Err`: エラー。
+async fn run_in_transaction<'c, T, E, F>(
+    conn: &DatabaseConnection,
+    isolation_level: Option<IsolationLevel>,
+    f: &mut F,
+) -> Result<T, E>
+where
+    E: From<DbErr>,
+    F: FnMut(Arc<TimedTransaction>) -> TransactionFuture<'c, T, E>,
+{
+    let txn = Arc::new(begin_transaction(conn, isolation_level).await?);
+    let value = f(Arc::clone(&txn)).await?;
+    // 処理がトランザクションを保持し続けている場合はコミットできない
+    match Arc::try_unwrap(txn) {
+        Ok(txn) => txn.commit().await?,
+        Err(_) => {
+            return Err(DbErr::Custom(
+                "トランザクションで実行した処理が、トランザクションを保持しています。".to_owned(),
+            )
+            .into())
+        }
+    }
+
+    Ok(value)
+}
+
+/// トランザクションで処理を実行して、シリアライゼーションの失敗またはデッドロックで中断された場合は再試行する。
+///
+/// 再試行するたびに新しいトランザクションを開始して、処理を最初から実行し直す。
+/// 再試行できないエラーは、再試行せずにすぐに返却する。
+///
+/// # Arguments
+///
+/// * `conn` - データベースコネクション。
+/// * `isolation_level` - ユースケースが定めた分離レベル。
+/// * `max_retries` - 再試行する最大回数。処理は最大で`max_retries + 1`回実行する。
+/// * `f` - トランザクションで実行する処理。
+///
+/// # Returns
+///
+/// `Result`。返却される`Result`の内容は以下の通り。
+///
+/// * `Ok`: 処理の結果。
+/// * `Err`: 再試行できないエラー、または最後に再試行したときのエラー。
+pub async fn with_transaction_retry<'c, T, E, F>(
+    conn: &DatabaseConnection,
+    isolation_level: Option<IsolationLevel>,
+    max_retries: u32,
+    mut f: F,
+) -> Result<T, E>
+where
+    E: RetryableError + From<DbErr>,
+    F: FnMut(Arc<TimedTransaction>) -> TransactionFuture<'c, T, E>,
+{
+    let mut retries = 0;
+    loop {
+        match run_in_transaction(conn, isolation_level, &mut f).await {
+            Err(err) if err.is_retryable() && retries < max_retries => {
+                retries += 1;
+                log::warn!(
+                    "トランザクションが中断されたため再試行します({}/{})。",
+                    retries,
+                    max_retries
+                );
+                tokio::time::sleep(retry_backoff(retries)).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod transaction_tests {
+    use std::cell::Cell;
+
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Statement, Transaction};
+
+    use super::*;
+
+    /// テスト用のエラー。
+    #[derive(Debug)]
+    struct TestError(bool);
+
+    impl RetryableError for TestError {
+        fn is_retryable(&self) -> bool {
+            self.0
+        }
+    }
+
+    impl From<DbErr> for TestError {
+        fn from(err: DbErr) -> Self {
+            Self(is_retryable_db_err(&err))
+        }
+    }
+
+    /// シリアライゼーションの失敗とデッドロックのエラーのみ、再試行できると判定することを確認する。
+    #[test]
+    fn test_is_retryable_db_err() {
+        assert!(is_retryable_db_err(&test_db_err("40001")));
+        assert!(is_retryable_db_err(&test_db_err("40P01")));
+        assert!(is_retryable_db_err(&DbErr::Query(RuntimeErr::SqlxError(
+            sqlx::Error::Database(Box::new(TestDatabaseError("40001")))
+        ))));
+        assert!(!is_retryable_db_err(&test_db_err("23505")));
+        assert!(!is_retryable_db_err(&DbErr::Exec(RuntimeErr::Internal(
+            "40001".to_owned()
+        ))));
+        assert!(!is_retryable_db_err(&DbErr::RecordNotFound(
+            "40001".to_owned()
+        )));
+    }
+
+    /// リポジトリが返却した`anyhow::Error`から、再試行できるエラーを判定できることを確認する。
+    #[test]
+    fn test_is_retryable_error() {
+        let err: Box<dyn std::error::Error> = anyhow::Error::from(test_db_err("40P01")).into();
+        assert!(is_retryable_error(err.as_ref()));
+        let err: Box<dyn std::error::Error> = anyhow::Error::from(test_db_err("23505")).into();
+        assert!(!is_retryable_error(err.as_ref()));
+        let err: Box<dyn std::error::Error> = Box::new(test_db_err("40001"));
+        assert!(is_retryable_error(err.as_ref()));
+        let err: Box<dyn std::error::Error> = anyhow::anyhow!("40001").into();
+        assert!(!is_retryable_error(err.as_ref()));
+    }
+
+    /// 一意制約違反のエラーのみ、一意制約違反と判定することを確認する。
+    #[test]
+    fn test_is_unique_violation() {
+        let err: Box<dyn std::error::Error> = anyhow::Error::from(test_db_err("23505")).into();
+        assert!(is_unique_violation(err.as_ref()));
+        assert!(is_unique_violation(&test_db_err("23505")));
+        assert!(!is_unique_violation(&test_db_err("40001")));
+        assert!(!is_unique_violation(&DbErr::RecordNotInserted));
+        let err: Box<dyn std::error::Error> = anyhow::anyhow!("23505").into();
+        assert!(!is_unique_violation(err.as_ref()));
+    }
+
+    /// 再試行できるエラーが続く場合に、最大回数まで再試行して最後のエラーを返却することを確認する。
+    #[tokio::test]
+    async fn test_with_transaction_retry_gives_up() {
+        let conn = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+        let attempts = Cell::new(0);
+        let result: Result<(), TestError> = with_transaction_retry(&conn, None, 2, |_| {
+            attempts.set(attempts.get() + 1);
+            Box::pin(async { Err(TestError(true)) })
+        })
+        .await;
+        assert!(result.unwrap_err().is_retryable());
+        assert_eq!(attempts.get(), 3);
+    }
+
+    /// 再試行した処理が成功した場合に、処理の結果を返却することを確認する。
+    #[tokio::test]
+    async fn test_with_transaction_retry_succeeds() {
+        let conn = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+        let attempts = Cell::new(0);
+        let result = with_transaction_retry(&conn, None, 3, |_| {
+            attempts.set(attempts.get() + 1);
+            let attempt = attempts.get();
+            Box::pin(async move {
+                if attempt < 2 {
+                    Err(TestError::from(test_db_err("40001")))
+                } else {
+                    Ok(attempt)
+                }
+            })
+        })
+        .await;
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(attempts.get(), 2);
+    }
+
+    /// 再試行できないエラーの場合は、再試行せずにすぐに返却することを確認する。
+    #[tokio::test]
+    async fn test_with_transaction_retry_not_retryable() {
+        let conn = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+        let attempts = Cell::new(0);
+        let result: Result<(), TestError> = with_transaction_retry(&conn, None, 3, |_| {
+            attempts.set(attempts.get() + 1);
+            Box::pin(async { Err(TestError::from(test_db_err("23505"))) })
+        })
+        .await;
+        assert!(!result.unwrap_err().is_retryable());
+        assert_eq!(attempts.get(), 1);
+    }
+
+    /// 分離レベルを指定した場合に、トランザクションを開始した後に分離レベルを設定することを確認する。
+    #[tokio::test]
+    async fn test_begin_transaction_with_isolation_level() {
+        let conn = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([MockExecResult::default()])
+            .into_connection();
+        let txn = begin_transaction(&conn, Some(IsolationLevel::RepeatableRead))
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert_eq!(
+            conn.into_transaction_log(),
+            vec![Transaction::many([
+                Statement::from_string(DatabaseBackend::Postgres, "BEGIN"),
+                Statement::from_string(
+                    DatabaseBackend::Postgres,
+                    "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ"
+                ),
+                Statement::from_string(DatabaseBackend::Postgres, "COMMIT"),
+            ])]
+        );
+    }
+
+    /// 分離レベルを指定しない場合は、分離レベルを設定しないことを確認する。
+    #[tokio::test]
+    async fn test_begin_transaction_without_isolation_level() {
+        let conn = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+        let txn = begin_transaction(&conn, None).await.unwrap();
+        txn.commit().await.unwrap();
+        let log = format!("{:?}", conn.into_transaction_log());
+        assert!(!log.contains("SET TRANSACTION"), "{}", log);
+    }
+
+    /// 再試行する待機時間が、基準の待機時間の半分から基準の待機時間までの範囲であることを確認する。
+    #[test]
+    fn test_retry_backoff() {
+        for retries in 1..=10 {
+            let base = RETRY_BACKOFF_BASE_MILLIS << retries.min(6);
+            let backoff = retry_backoff(retries).as_millis() as u64;
+            assert!(base / 2 <= backoff && backoff <= base, "{}", retries);
+        }
+    }
+}